@@ -0,0 +1,3 @@
+-- Record where a session was created from so users can tell their sessions apart
+ALTER TABLE user_sessions ADD COLUMN user_agent TEXT;
+ALTER TABLE user_sessions ADD COLUMN ip_address TEXT;
@@ -0,0 +1,2 @@
+-- Soft delete: deactivated users keep their row so authored data stays linked
+ALTER TABLE users ADD COLUMN deleted_at TEXT;
@@ -0,0 +1,100 @@
+-- Emails are matched case-insensitively and without surrounding whitespace,
+-- so accounts whose emails only differ by case or whitespace belong to the
+-- same person. Merge each such group into one account: the oldest active
+-- one, or the oldest one if all are deactivated. Sessions, team memberships
+-- and audit entries of the other accounts move to it before they are removed.
+CREATE TEMP TABLE user_merges (
+    duplicate_id,
+    duplicate_key,
+    survivor_id,
+    survivor_key,
+    cf_access_id,
+    updated_at
+);
+
+INSERT INTO user_merges (duplicate_id, survivor_id, cf_access_id, updated_at)
+SELECT u.id,
+       (SELECT s.id FROM users AS s
+        WHERE lower(trim(s.email)) = lower(trim(u.email))
+        ORDER BY s.deleted_at IS NOT NULL, s.created_at, s.id
+        LIMIT 1),
+       u.cf_access_id,
+       u.updated_at
+FROM users AS u;
+
+DELETE FROM user_merges WHERE duplicate_id = survivor_id;
+
+-- workspace_members stores user IDs as hyphenated text
+UPDATE user_merges
+SET duplicate_key = CASE typeof(duplicate_id)
+        WHEN 'blob' THEN lower(substr(hex(duplicate_id), 1, 8) || '-' || substr(hex(duplicate_id), 9, 4) || '-'
+            || substr(hex(duplicate_id), 13, 4) || '-' || substr(hex(duplicate_id), 17, 4) || '-'
+            || substr(hex(duplicate_id), 21))
+        ELSE duplicate_id
+    END,
+    survivor_key = CASE typeof(survivor_id)
+        WHEN 'blob' THEN lower(substr(hex(survivor_id), 1, 8) || '-' || substr(hex(survivor_id), 9, 4) || '-'
+            || substr(hex(survivor_id), 13, 4) || '-' || substr(hex(survivor_id), 17, 4) || '-'
+            || substr(hex(survivor_id), 21))
+        ELSE survivor_id
+    END;
+
+-- cf_access_id is unique, so free it on the duplicates before the survivor
+-- takes the most recently used one
+UPDATE users SET cf_access_id = NULL
+WHERE id IN (SELECT duplicate_id FROM user_merges);
+
+UPDATE users
+SET cf_access_id = (
+    SELECT m.cf_access_id FROM user_merges AS m
+    WHERE m.survivor_id = users.id AND m.cf_access_id IS NOT NULL
+    ORDER BY m.updated_at DESC
+    LIMIT 1
+)
+WHERE cf_access_id IS NULL
+  AND id IN (SELECT survivor_id FROM user_merges);
+
+UPDATE user_sessions
+SET user_id = (SELECT survivor_id FROM user_merges WHERE duplicate_id = user_sessions.user_id)
+WHERE user_id IN (SELECT duplicate_id FROM user_merges);
+
+UPDATE audit_log
+SET user_id = (SELECT survivor_id FROM user_merges WHERE duplicate_id = audit_log.user_id)
+WHERE user_id IN (SELECT duplicate_id FROM user_merges);
+
+-- One membership per team survives: the survivor's own, otherwise the
+-- earliest-joined duplicate's
+DELETE FROM workspace_members
+WHERE EXISTS (
+    SELECT 1
+    FROM user_merges AS m
+    JOIN workspace_members AS other
+      ON other.workspace_team_id = workspace_members.workspace_team_id
+     AND other.id != workspace_members.id
+    LEFT JOIN user_merges AS other_merge ON other_merge.duplicate_key = other.user_id
+    WHERE m.duplicate_key = workspace_members.user_id
+      AND (other.user_id = m.survivor_key
+           OR (other_merge.survivor_id = m.survivor_id
+               AND (other.joined_at, other.id) < (workspace_members.joined_at, workspace_members.id)))
+);
+
+UPDATE workspace_members
+SET user_id = (SELECT survivor_key FROM user_merges WHERE duplicate_key = workspace_members.user_id)
+WHERE user_id IN (SELECT duplicate_key FROM user_merges);
+
+UPDATE workspace_members
+SET invited_by = (SELECT survivor_key FROM user_merges WHERE duplicate_key = workspace_members.invited_by)
+WHERE invited_by IN (SELECT duplicate_key FROM user_merges);
+
+DELETE FROM users WHERE id IN (SELECT duplicate_id FROM user_merges);
+
+DROP TABLE user_merges;
+
+-- One account per address is left, so every email can be normalized
+UPDATE users
+SET email = lower(trim(email))
+WHERE email != lower(trim(email));
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_email_nocase
+    ON users(email COLLATE NOCASE)
+    WHERE deleted_at IS NULL;
@@ -0,0 +1,3 @@
+-- Login activity: when the user last started a session, and how many times
+ALTER TABLE users ADD COLUMN last_login_at TEXT;
+ALTER TABLE users ADD COLUMN login_count INTEGER NOT NULL DEFAULT 0;
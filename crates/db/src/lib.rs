@@ -8,6 +8,7 @@ use sqlx::{
 use utils::assets::asset_dir;
 
 pub mod models;
+pub mod rbac_seed;
 
 async fn run_migrations(pool: &Pool<Sqlite>) -> Result<(), Error> {
     use std::collections::HashSet;
@@ -86,6 +87,17 @@ impl DBService {
         Ok(DBService { pool })
     }
 
+    /// Open the existing database read-only, without running migrations.
+    pub async fn new_read_only() -> Result<DBService, Error> {
+        let database_url = format!(
+            "sqlite://{}",
+            asset_dir().join("db.sqlite").to_string_lossy()
+        );
+        let options = SqliteConnectOptions::from_str(&database_url)?.read_only(true);
+        let pool = SqlitePool::connect_with(options).await?;
+        Ok(DBService { pool })
+    }
+
     pub async fn new_with_after_connect<F>(after_connect: F) -> Result<DBService, Error>
     where
         F: for<'a> Fn(
@@ -0,0 +1,85 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Event type recorded when an authorization check rejects a request
+pub const EVENT_PERMISSION_DENIED: &str = "permission_denied";
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct AuditEvent {
+    pub id: Uuid,
+    pub event_type: String,
+    pub user_id: Option<Uuid>,
+    pub permission: String,
+    pub route: String,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateAuditEvent {
+    pub event_type: String,
+    pub user_id: Option<Uuid>,
+    pub permission: String,
+    pub route: String,
+}
+
+impl CreateAuditEvent {
+    pub fn permission_denied(user_id: Option<Uuid>, permission: String, route: String) -> Self {
+        Self {
+            event_type: EVENT_PERMISSION_DENIED.to_string(),
+            user_id,
+            permission,
+            route,
+        }
+    }
+}
+
+impl AuditEvent {
+    /// Persist an audit event
+    pub async fn record(pool: &SqlitePool, data: &CreateAuditEvent) -> Result<Self, sqlx::Error> {
+        let id = Uuid::new_v4();
+        sqlx::query_as!(
+            AuditEvent,
+            r#"INSERT INTO audit_log (id, event_type, user_id, permission, route)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING
+                id as "id!: Uuid",
+                event_type,
+                user_id as "user_id: Uuid",
+                permission,
+                route,
+                created_at as "created_at!: DateTime<Utc>""#,
+            id,
+            data.event_type,
+            data.user_id,
+            data.permission,
+            data.route
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// List the most recent audit events, newest first
+    pub async fn list_recent(pool: &SqlitePool, limit: i64) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as!(
+            AuditEvent,
+            r#"SELECT
+                id as "id!: Uuid",
+                event_type,
+                user_id as "user_id: Uuid",
+                permission,
+                route,
+                created_at as "created_at!: DateTime<Utc>"
+            FROM audit_log
+            ORDER BY created_at DESC
+            LIMIT $1"#,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
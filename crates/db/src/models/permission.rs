@@ -13,7 +13,19 @@ pub struct Permission {
     pub created_at: DateTime<Utc>,
 }
 
+/// Permissions sharing a key prefix, e.g. `task` for `task.view` and `task.edit`
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct PermissionCategory {
+    pub name: String,
+    pub permissions: Vec<Permission>,
+}
+
 impl Permission {
+    /// Category of a permission key: the part before the first `.`
+    pub fn category(&self) -> &str {
+        self.key.split('.').next().unwrap_or_default()
+    }
+
     pub async fn find_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
         sqlx::query_as!(
             Permission,
@@ -167,6 +167,44 @@ impl Role {
         Ok(records.into_iter().map(|r| r.key).collect())
     }
 
+    /// All roles, in `find_all` order, each with its sorted permission keys
+    pub async fn find_all_with_permissions(
+        pool: &SqlitePool,
+    ) -> Result<Vec<(Self, Vec<String>)>, sqlx::Error> {
+        let records = sqlx::query!(
+            r#"SELECT r.id as "id!: Uuid",
+                      r.name,
+                      r.description,
+                      r.is_system as "is_system!: bool",
+                      r.created_at as "created_at!: DateTime<Utc>",
+                      r.updated_at as "updated_at!: DateTime<Utc>",
+                      GROUP_CONCAT(p.key, ',') as "permission_keys?: String"
+               FROM roles r
+               LEFT JOIN role_permissions rp ON rp.role_id = r.id
+               LEFT JOIN permissions p ON p.id = rp.permission_id
+               GROUP BY r.id
+               ORDER BY r.is_system DESC, r.name ASC"#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|r| {
+                let keys = split_permission_keys(r.permission_keys.as_deref());
+                let role = Role {
+                    id: r.id,
+                    name: r.name,
+                    description: r.description,
+                    is_system: r.is_system,
+                    created_at: r.created_at,
+                    updated_at: r.updated_at,
+                };
+                (role, keys)
+            })
+            .collect())
+    }
+
     /// Check if a role has a specific permission
     pub async fn has_permission(
         pool: &SqlitePool,
@@ -188,4 +226,79 @@ impl Role {
 
         Ok(result.exists)
     }
+
+    /// Attach a permission to a role. Returns false if it was already attached.
+    pub async fn add_permission(
+        pool: &SqlitePool,
+        role_id: Uuid,
+        permission_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "INSERT OR IGNORE INTO role_permissions (role_id, permission_id) VALUES ($1, $2)",
+            role_id,
+            permission_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Detach a permission from a role. Returns false if it was not attached.
+    pub async fn remove_permission(
+        pool: &SqlitePool,
+        role_id: Uuid,
+        permission_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "DELETE FROM role_permissions WHERE role_id = $1 AND permission_id = $2",
+            role_id,
+            permission_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Attach and detach permissions of a role in a single transaction
+    pub async fn update_permissions(
+        pool: &SqlitePool,
+        role_id: Uuid,
+        add_ids: &[Uuid],
+        remove_ids: &[Uuid],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        for permission_id in remove_ids {
+            sqlx::query!(
+                "DELETE FROM role_permissions WHERE role_id = $1 AND permission_id = $2",
+                role_id,
+                permission_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for permission_id in add_ids {
+            sqlx::query!(
+                "INSERT OR IGNORE INTO role_permissions (role_id, permission_id) VALUES ($1, $2)",
+                role_id,
+                permission_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await
+    }
+}
+
+/// Sorted permission keys from a `GROUP_CONCAT(p.key, ',')` column, which is
+/// NULL for a role without permissions
+pub(crate) fn split_permission_keys(concat: Option<&str>) -> Vec<String> {
+    // GROUP_CONCAT order is unspecified; keys never contain ','
+    let mut keys: Vec<String> = concat
+        .map(|keys| keys.split(',').map(str::to_string).collect())
+        .unwrap_or_default();
+    keys.sort();
+    keys
 }
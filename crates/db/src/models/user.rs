@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, SqlitePool};
 use thiserror::Error;
 use ts_rs::TS;
+use utils::api::users::{UserProfile, split_name};
 use uuid::Uuid;
 
 #[derive(Debug, Error)]
@@ -11,6 +12,8 @@ pub enum UserError {
     Database(#[from] sqlx::Error),
     #[error("User not found")]
     NotFound,
+    #[error("User account is deactivated")]
+    Deactivated,
 }
 
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
@@ -25,6 +28,13 @@ pub struct User {
     pub created_at: DateTime<Utc>,
     #[ts(type = "Date")]
     pub updated_at: DateTime<Utc>,
+    /// Set when the account is deactivated; such users are hidden from lookups
+    #[ts(type = "Date | null")]
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// When the user last started a new session
+    #[ts(type = "Date | null")]
+    pub last_login_at: Option<DateTime<Utc>>,
+    pub login_count: i64,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -35,9 +45,55 @@ pub struct UpsertUser {
     pub cf_access_id: Option<String>,
 }
 
+/// See `utils::api::users` for how local fields map to the remote model.
+impl From<User> for UserProfile {
+    fn from(user: User) -> Self {
+        let (first_name, last_name) = split_name(&user.name);
+        Self {
+            id: user.id,
+            email: user.email,
+            first_name,
+            last_name,
+            username: None,
+            avatar_url: user.avatar_url,
+        }
+    }
+}
+
+impl From<UserProfile> for UpsertUser {
+    /// The profile carries no Cloudflare Access identity, so `cf_access_id`
+    /// is left unset.
+    fn from(profile: UserProfile) -> Self {
+        Self {
+            name: profile.display_name(),
+            email: profile.email,
+            avatar_url: profile.avatar_url,
+            cf_access_id: None,
+        }
+    }
+}
+
+/// Most results `User::search` returns, whatever limit is requested
+pub const MAX_USER_SEARCH_LIMIT: i64 = 50;
+
+/// Canonical form of an email address: surrounding whitespace removed and
+/// lowercased, so differently typed forms of one address match one account.
+pub fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
 impl User {
-    /// Find a user by ID
+    /// Find an active user by ID
     pub async fn find_by_id(pool: &SqlitePool, id: Uuid) -> Result<Option<Self>, UserError> {
+        Self::find_by_id_including_deleted(pool, id, false).await
+    }
+
+    /// Find a user by ID, optionally including deactivated accounts
+    pub async fn find_by_id_including_deleted(
+        pool: &SqlitePool,
+        id: Uuid,
+        include_deleted: bool,
+    ) -> Result<Option<Self>, UserError> {
         sqlx::query_as!(
             User,
             r#"SELECT
@@ -47,18 +103,32 @@ impl User {
                 avatar_url,
                 cf_access_id,
                 created_at as "created_at!: DateTime<Utc>",
-                updated_at as "updated_at!: DateTime<Utc>"
+                updated_at as "updated_at!: DateTime<Utc>",
+                deleted_at as "deleted_at: DateTime<Utc>",
+                last_login_at as "last_login_at: DateTime<Utc>",
+                login_count
             FROM users
-            WHERE id = $1"#,
-            id
+            WHERE id = $1 AND ($2 OR deleted_at IS NULL)"#,
+            id,
+            include_deleted
         )
         .fetch_optional(pool)
         .await
         .map_err(UserError::from)
     }
 
-    /// Find a user by email
+    /// Find an active user by email, ignoring case and surrounding whitespace
     pub async fn find_by_email(pool: &SqlitePool, email: &str) -> Result<Option<Self>, UserError> {
+        Self::find_by_email_including_deleted(pool, email, false).await
+    }
+
+    /// Find a user by email, optionally including deactivated accounts
+    pub async fn find_by_email_including_deleted(
+        pool: &SqlitePool,
+        email: &str,
+        include_deleted: bool,
+    ) -> Result<Option<Self>, UserError> {
+        let email = normalize_email(email);
         sqlx::query_as!(
             User,
             r#"SELECT
@@ -68,20 +138,34 @@ impl User {
                 avatar_url,
                 cf_access_id,
                 created_at as "created_at!: DateTime<Utc>",
-                updated_at as "updated_at!: DateTime<Utc>"
+                updated_at as "updated_at!: DateTime<Utc>",
+                deleted_at as "deleted_at: DateTime<Utc>",
+                last_login_at as "last_login_at: DateTime<Utc>",
+                login_count
             FROM users
-            WHERE email = $1"#,
-            email
+            WHERE email = $1 COLLATE NOCASE AND ($2 OR deleted_at IS NULL)"#,
+            email,
+            include_deleted
         )
         .fetch_optional(pool)
         .await
         .map_err(UserError::from)
     }
 
-    /// Find a user by Cloudflare Access ID
+    /// Find an active user by Cloudflare Access ID
     pub async fn find_by_cf_access_id(
         pool: &SqlitePool,
         cf_access_id: &str,
+    ) -> Result<Option<Self>, UserError> {
+        Self::find_by_cf_access_id_including_deleted(pool, cf_access_id, false).await
+    }
+
+    /// Find a user by Cloudflare Access ID, optionally including deactivated
+    /// accounts
+    pub async fn find_by_cf_access_id_including_deleted(
+        pool: &SqlitePool,
+        cf_access_id: &str,
+        include_deleted: bool,
     ) -> Result<Option<Self>, UserError> {
         sqlx::query_as!(
             User,
@@ -92,19 +176,82 @@ impl User {
                 avatar_url,
                 cf_access_id,
                 created_at as "created_at!: DateTime<Utc>",
-                updated_at as "updated_at!: DateTime<Utc>"
+                updated_at as "updated_at!: DateTime<Utc>",
+                deleted_at as "deleted_at: DateTime<Utc>",
+                last_login_at as "last_login_at: DateTime<Utc>",
+                login_count
             FROM users
-            WHERE cf_access_id = $1"#,
-            cf_access_id
+            WHERE cf_access_id = $1 AND ($2 OR deleted_at IS NULL)"#,
+            cf_access_id,
+            include_deleted
         )
         .fetch_optional(pool)
         .await
         .map_err(UserError::from)
     }
 
-    /// Upsert a user (create or update by email)
+    /// Find active users whose name or email contains `query`, ignoring case.
+    /// Exact matches come first, then prefix matches, then other substring
+    /// matches. At most `MAX_USER_SEARCH_LIMIT` users are returned.
+    pub async fn search(
+        pool: &SqlitePool,
+        query: &str,
+        limit: i64,
+    ) -> Result<Vec<Self>, UserError> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let escaped = query
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        let contains = format!("%{escaped}%");
+        let prefix = format!("{escaped}%");
+        let limit = limit.clamp(1, MAX_USER_SEARCH_LIMIT);
+
+        sqlx::query_as!(
+            User,
+            r#"SELECT
+                id as "id!: Uuid",
+                email,
+                name,
+                avatar_url,
+                cf_access_id,
+                created_at as "created_at!: DateTime<Utc>",
+                updated_at as "updated_at!: DateTime<Utc>",
+                deleted_at as "deleted_at: DateTime<Utc>",
+                last_login_at as "last_login_at: DateTime<Utc>",
+                login_count
+            FROM users
+            WHERE deleted_at IS NULL
+              AND (name LIKE $1 ESCAPE '\' OR email LIKE $1 ESCAPE '\')
+            ORDER BY
+                CASE
+                    WHEN lower(name) = $2 OR lower(email) = $2 THEN 0
+                    WHEN name LIKE $3 ESCAPE '\' OR email LIKE $3 ESCAPE '\' THEN 1
+                    ELSE 2
+                END,
+                name COLLATE NOCASE,
+                email
+            LIMIT $4"#,
+            contains,
+            query,
+            prefix,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(UserError::from)
+    }
+
+    /// Upsert a user (create or update by normalized email). A deactivated
+    /// account is left untouched and reported as `Deactivated`; it must be
+    /// restored explicitly before it can sign in again.
     pub async fn upsert(pool: &SqlitePool, data: &UpsertUser) -> Result<Self, UserError> {
         let id = Uuid::new_v4();
+        let email = normalize_email(&data.email);
         sqlx::query_as!(
             User,
             r#"INSERT INTO users (id, email, name, avatar_url, cf_access_id)
@@ -114,6 +261,7 @@ impl User {
                 avatar_url = COALESCE(excluded.avatar_url, users.avatar_url),
                 cf_access_id = COALESCE(excluded.cf_access_id, users.cf_access_id),
                 updated_at = datetime('now', 'subsec')
+            WHERE users.deleted_at IS NULL
             RETURNING
                 id as "id!: Uuid",
                 email,
@@ -121,16 +269,19 @@ impl User {
                 avatar_url,
                 cf_access_id,
                 created_at as "created_at!: DateTime<Utc>",
-                updated_at as "updated_at!: DateTime<Utc>""#,
+                updated_at as "updated_at!: DateTime<Utc>",
+                deleted_at as "deleted_at: DateTime<Utc>",
+                last_login_at as "last_login_at: DateTime<Utc>",
+                login_count"#,
             id,
-            data.email,
+            email,
             data.name,
             data.avatar_url,
             data.cf_access_id
         )
-        .fetch_one(pool)
-        .await
-        .map_err(UserError::from)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(UserError::Deactivated)
     }
 
     /// Update user profile
@@ -151,7 +302,7 @@ impl User {
             User,
             r#"UPDATE users
             SET name = $2, avatar_url = $3, updated_at = datetime('now', 'subsec')
-            WHERE id = $1
+            WHERE id = $1 AND deleted_at IS NULL
             RETURNING
                 id as "id!: Uuid",
                 email,
@@ -159,13 +310,472 @@ impl User {
                 avatar_url,
                 cf_access_id,
                 created_at as "created_at!: DateTime<Utc>",
-                updated_at as "updated_at!: DateTime<Utc>""#,
+                updated_at as "updated_at!: DateTime<Utc>",
+                deleted_at as "deleted_at: DateTime<Utc>",
+                last_login_at as "last_login_at: DateTime<Utc>",
+                login_count"#,
             id,
             name,
             avatar_url
         )
-        .fetch_one(pool)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(UserError::NotFound)
+    }
+
+    /// Deactivate a user. Their row, memberships and authored data are kept,
+    /// but they are hidden from lookups and their sessions are ended.
+    pub async fn soft_delete(pool: &SqlitePool, id: Uuid) -> Result<(), UserError> {
+        let mut tx = pool.begin().await?;
+
+        let result = sqlx::query!(
+            r#"UPDATE users
+            SET deleted_at = datetime('now', 'subsec'), updated_at = datetime('now', 'subsec')
+            WHERE id = $1 AND deleted_at IS NULL"#,
+            id
+        )
+        .execute(&mut *tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(UserError::NotFound);
+        }
+
+        sqlx::query!(r#"DELETE FROM user_sessions WHERE user_id = $1"#, id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Count a sign-in that started a new session. Requests that reuse an
+    /// existing session are not logins.
+    pub async fn record_login(pool: &SqlitePool, id: Uuid) -> Result<Self, UserError> {
+        sqlx::query_as!(
+            User,
+            r#"UPDATE users
+            SET last_login_at = datetime('now', 'subsec'), login_count = login_count + 1
+            WHERE id = $1 AND deleted_at IS NULL
+            RETURNING
+                id as "id!: Uuid",
+                email,
+                name,
+                avatar_url,
+                cf_access_id,
+                created_at as "created_at!: DateTime<Utc>",
+                updated_at as "updated_at!: DateTime<Utc>",
+                deleted_at as "deleted_at: DateTime<Utc>",
+                last_login_at as "last_login_at: DateTime<Utc>",
+                login_count"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or(UserError::NotFound)
+    }
+
+    /// Reactivate a soft-deleted user
+    pub async fn restore(pool: &SqlitePool, id: Uuid) -> Result<Self, UserError> {
+        sqlx::query_as!(
+            User,
+            r#"UPDATE users
+            SET deleted_at = NULL, updated_at = datetime('now', 'subsec')
+            WHERE id = $1 AND deleted_at IS NOT NULL
+            RETURNING
+                id as "id!: Uuid",
+                email,
+                name,
+                avatar_url,
+                cf_access_id,
+                created_at as "created_at!: DateTime<Utc>",
+                updated_at as "updated_at!: DateTime<Utc>",
+                deleted_at as "deleted_at: DateTime<Utc>",
+                last_login_at as "last_login_at: DateTime<Utc>",
+                login_count"#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or(UserError::NotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::{migrate::Migrate, sqlite::SqlitePoolOptions};
+
+    use super::*;
+
+    async fn test_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        pool
+    }
+
+    fn cf_user(name: &str) -> UpsertUser {
+        UpsertUser {
+            email: "dev@example.com".to_string(),
+            name: name.to_string(),
+            avatar_url: None,
+            cf_access_id: Some("sub-1".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_profile_conversion_splits_and_joins_name() {
+        let user = User {
+            id: Uuid::new_v4(),
+            email: "dana@example.com".to_string(),
+            name: "Dana Katherine Scully".to_string(),
+            avatar_url: Some("https://cdn.example.com/a.png".to_string()),
+            cf_access_id: Some("sub-1".to_string()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            deleted_at: None,
+            last_login_at: None,
+            login_count: 0,
+        };
+
+        let profile = UserProfile::from(user.clone());
+        assert_eq!(profile.id, user.id);
+        assert_eq!(profile.first_name.as_deref(), Some("Dana"));
+        assert_eq!(profile.last_name.as_deref(), Some("Katherine Scully"));
+        assert_eq!(profile.avatar_url, user.avatar_url);
+
+        let upsert = UpsertUser::from(profile);
+        assert_eq!(upsert.name, "Dana Katherine Scully");
+        assert_eq!(upsert.email, "dana@example.com");
+        assert_eq!(upsert.avatar_url, user.avatar_url);
+        assert_eq!(upsert.cf_access_id, None);
+    }
+
+    #[test]
+    fn test_normalize_email() {
+        assert_eq!(
+            normalize_email("  Alice@Example.COM \n"),
+            "alice@example.com"
+        );
+        assert_eq!(normalize_email("bob@example.com"), "bob@example.com");
+    }
+
+    #[tokio::test]
+    async fn test_upsert_merges_email_case_and_whitespace() {
+        let pool = test_pool().await;
+        let upsert = |email: &str| UpsertUser {
+            email: email.to_string(),
+            name: "Alice".to_string(),
+            avatar_url: None,
+            cf_access_id: None,
+        };
+
+        let first = User::upsert(&pool, &upsert("Alice@Example.com "))
+            .await
+            .unwrap();
+        let second = User::upsert(&pool, &upsert("alice@example.com"))
+            .await
+            .unwrap();
+
+        assert_eq!(first.id, second.id);
+        assert_eq!(second.email, "alice@example.com");
+        let found = User::find_by_email(&pool, " ALICE@example.com")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.id, first.id);
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    async fn insert_legacy_user(
+        pool: &SqlitePool,
+        email: &str,
+        created_at: &str,
+        deleted_at: Option<&str>,
+        cf_access_id: Option<&str>,
+    ) -> Uuid {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO users (id, email, name, created_at, deleted_at, cf_access_id)
+             VALUES ($1, $2, 'Legacy', $3, $4, $5)",
+        )
+        .bind(id)
+        .bind(email)
+        .bind(created_at)
+        .bind(deleted_at)
+        .bind(cf_access_id)
+        .execute(pool)
         .await
-        .map_err(UserError::from)
+        .unwrap();
+        id
+    }
+
+    async fn insert_legacy_member(pool: &SqlitePool, team_id: Uuid, user_id: Uuid) {
+        sqlx::query(
+            "INSERT INTO workspace_members (id, workspace_team_id, user_id, role_id)
+             VALUES ($1, $2, $3, (SELECT id FROM roles WHERE name = 'Member'))",
+        )
+        .bind(Uuid::new_v4())
+        .bind(team_id)
+        .bind(user_id.to_string())
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_email_normalization_merges_duplicate_accounts() {
+        const NORMALIZE_EMAILS: i64 = 20260117000000;
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        // Seed the schema as it was before the migration, then run the rest
+        let migrator = sqlx::migrate!("./migrations");
+        let mut conn = pool.acquire().await.unwrap();
+        conn.ensure_migrations_table().await.unwrap();
+        for migration in migrator
+            .iter()
+            .filter(|migration| migration.version < NORMALIZE_EMAILS)
+        {
+            conn.apply(migration).await.unwrap();
+        }
+        drop(conn);
+
+        let alice =
+            insert_legacy_user(&pool, " Alice@Example.com ", "2026-01-01", None, None).await;
+        let alice_later = insert_legacy_user(
+            &pool,
+            "alice@example.com",
+            "2026-01-02",
+            None,
+            Some("sub-a"),
+        )
+        .await;
+        let alice_deleted = insert_legacy_user(
+            &pool,
+            "ALICE@example.com",
+            "2025-12-01",
+            Some("2026-01-03"),
+            None,
+        )
+        .await;
+        // A deactivated account already holds the normalized address
+        insert_legacy_user(
+            &pool,
+            "bob@example.com",
+            "2025-12-01",
+            Some("2026-01-03"),
+            None,
+        )
+        .await;
+        let bob = insert_legacy_user(&pool, " Bob@example.com", "2026-01-02", None, None).await;
+
+        sqlx::query("INSERT INTO user_sessions (id, user_id, expires_at) VALUES ($1, $2, $3)")
+            .bind(Uuid::new_v4())
+            .bind(alice_later)
+            .bind("2030-01-01")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let shared_team = Uuid::new_v4();
+        let other_team = Uuid::new_v4();
+        for team_id in [shared_team, other_team] {
+            sqlx::query("INSERT INTO workspace_teams (id, name) VALUES ($1, 'Team')")
+                .bind(team_id)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        insert_legacy_member(&pool, shared_team, alice).await;
+        insert_legacy_member(&pool, shared_team, alice_later).await;
+        insert_legacy_member(&pool, other_team, alice_later).await;
+        insert_legacy_member(&pool, other_team, alice_deleted).await;
+
+        migrator.run(&pool).await.unwrap();
+
+        let merged = User::find_by_email(&pool, "alice@example.com")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(merged.id, alice);
+        assert_eq!(merged.email, "alice@example.com");
+        assert_eq!(merged.cf_access_id.as_deref(), Some("sub-a"));
+        for id in [alice_later, alice_deleted] {
+            assert!(
+                User::find_by_id_including_deleted(&pool, id, true)
+                    .await
+                    .unwrap()
+                    .is_none()
+            );
+        }
+
+        let sessions: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM user_sessions WHERE user_id = $1")
+                .bind(alice)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(sessions, 1);
+        let memberships: Vec<(Uuid, String)> =
+            sqlx::query_as("SELECT workspace_team_id, user_id FROM workspace_members")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(memberships.len(), 2);
+        assert!(
+            memberships
+                .iter()
+                .all(|(_, user_id)| user_id == &alice.to_string())
+        );
+
+        let found = User::find_by_email(&pool, "bob@example.com")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.id, bob);
+        assert_eq!(found.email, "bob@example.com");
+    }
+
+    async fn named_user(pool: &SqlitePool, name: &str, email: &str) -> User {
+        User::upsert(
+            pool,
+            &UpsertUser {
+                email: email.to_string(),
+                name: name.to_string(),
+                avatar_url: None,
+                cf_access_id: None,
+            },
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_search_ranks_prefix_matches_first() {
+        let pool = test_pool().await;
+        let substring = named_user(&pool, "Jo Dana", "jo@example.com").await;
+        let prefix = named_user(&pool, "Dana Scully", "scully@example.com").await;
+        let email_prefix = named_user(&pool, "Fox Mulder", "dana.m@example.com").await;
+        named_user(&pool, "Walter Skinner", "skinner@example.com").await;
+
+        let results = User::search(&pool, "DANA", 10).await.unwrap();
+
+        let ids: Vec<Uuid> = results.iter().map(|user| user.id).collect();
+        assert_eq!(ids, vec![prefix.id, email_prefix.id, substring.id]);
+        assert!(User::search(&pool, "  ", 10).await.unwrap().is_empty());
+        assert!(User::search(&pool, "%", 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_search_caps_limit() {
+        let pool = test_pool().await;
+        for i in 0..(MAX_USER_SEARCH_LIMIT + 5) {
+            named_user(&pool, &format!("User {i}"), &format!("user{i}@example.com")).await;
+        }
+
+        assert_eq!(User::search(&pool, "user", 3).await.unwrap().len(), 3);
+        assert_eq!(
+            User::search(&pool, "user", 10_000).await.unwrap().len() as i64,
+            MAX_USER_SEARCH_LIMIT
+        );
+    }
+
+    #[tokio::test]
+    async fn test_soft_deleted_user_is_hidden_from_lookups() {
+        let pool = test_pool().await;
+        let user = User::upsert(&pool, &cf_user("Dev")).await.unwrap();
+
+        User::soft_delete(&pool, user.id).await.unwrap();
+
+        assert!(User::find_by_id(&pool, user.id).await.unwrap().is_none());
+        assert!(
+            User::find_by_email(&pool, &user.email)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            User::find_by_cf_access_id(&pool, "sub-1")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let deleted = User::find_by_id_including_deleted(&pool, user.id, true)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(deleted.deleted_at.is_some());
+        assert!(matches!(
+            User::soft_delete(&pool, user.id).await,
+            Err(UserError::NotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_upsert_does_not_reactivate_deleted_user() {
+        let pool = test_pool().await;
+        let user = User::upsert(&pool, &cf_user("Dev")).await.unwrap();
+        User::soft_delete(&pool, user.id).await.unwrap();
+
+        let result = User::upsert(&pool, &cf_user("Renamed")).await;
+
+        assert!(matches!(result, Err(UserError::Deactivated)));
+        let stored = User::find_by_id_including_deleted(&pool, user.id, true)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.name, "Dev");
+        assert!(stored.deleted_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_record_login_counts_and_advances_timestamp() {
+        let pool = test_pool().await;
+        let user = User::upsert(&pool, &cf_user("Dev")).await.unwrap();
+        assert_eq!(user.login_count, 0);
+        assert!(user.last_login_at.is_none());
+
+        let first = User::record_login(&pool, user.id).await.unwrap();
+        assert_eq!(first.login_count, 1);
+        let first_login = first.last_login_at.unwrap();
+
+        // SQLite timestamps have millisecond resolution
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        let second = User::record_login(&pool, user.id).await.unwrap();
+        assert_eq!(second.login_count, 2);
+        assert!(second.last_login_at.unwrap() > first_login);
+
+        User::soft_delete(&pool, user.id).await.unwrap();
+        assert!(matches!(
+            User::record_login(&pool, user.id).await,
+            Err(UserError::NotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_restore_reactivates_user() {
+        let pool = test_pool().await;
+        let user = User::upsert(&pool, &cf_user("Dev")).await.unwrap();
+        User::soft_delete(&pool, user.id).await.unwrap();
+
+        let restored = User::restore(&pool, user.id).await.unwrap();
+
+        assert!(restored.deleted_at.is_none());
+        assert!(User::find_by_id(&pool, user.id).await.unwrap().is_some());
+        assert!(User::upsert(&pool, &cf_user("Dev")).await.is_ok());
+        assert!(matches!(
+            User::restore(&pool, user.id).await,
+            Err(UserError::NotFound)
+        ));
     }
 }
@@ -8,6 +8,10 @@ use uuid::Uuid;
 /// Default session duration (7 days)
 pub const DEFAULT_SESSION_DURATION: Duration = Duration::days(7);
 
+/// Absolute session lifetime; sliding extensions never push `expires_at` past
+/// `created_at + MAX_SESSION_LIFETIME`
+pub const MAX_SESSION_LIFETIME: Duration = Duration::days(30);
+
 /// Maximum session inactivity before expiration (24 hours)
 pub const MAX_SESSION_INACTIVITY: Duration = Duration::hours(24);
 
@@ -27,6 +31,8 @@ pub struct UserSession {
     pub id: Uuid,
     pub user_id: Uuid,
     pub cf_access_jwt_id: Option<String>,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
     #[ts(type = "Date")]
     pub expires_at: DateTime<Utc>,
     #[ts(type = "Date")]
@@ -42,24 +48,30 @@ impl UserSession {
         user_id: Uuid,
         cf_access_jwt_id: Option<&str>,
         duration: Option<Duration>,
+        user_agent: Option<&str>,
+        ip_address: Option<&str>,
     ) -> Result<Self, UserSessionError> {
         let id = Uuid::new_v4();
         let expires_at = Utc::now() + duration.unwrap_or(DEFAULT_SESSION_DURATION);
 
         sqlx::query_as!(
             UserSession,
-            r#"INSERT INTO user_sessions (id, user_id, cf_access_jwt_id, expires_at)
-            VALUES ($1, $2, $3, $4)
+            r#"INSERT INTO user_sessions (id, user_id, cf_access_jwt_id, user_agent, ip_address, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
             RETURNING
                 id as "id!: Uuid",
                 user_id as "user_id!: Uuid",
                 cf_access_jwt_id,
+                user_agent,
+                ip_address,
                 expires_at as "expires_at!: DateTime<Utc>",
                 created_at as "created_at!: DateTime<Utc>",
                 last_used_at as "last_used_at!: DateTime<Utc>""#,
             id,
             user_id,
             cf_access_jwt_id,
+            user_agent,
+            ip_address,
             expires_at
         )
         .fetch_one(pool)
@@ -75,6 +87,8 @@ impl UserSession {
                 id as "id!: Uuid",
                 user_id as "user_id!: Uuid",
                 cf_access_jwt_id,
+                user_agent,
+                ip_address,
                 expires_at as "expires_at!: DateTime<Utc>",
                 created_at as "created_at!: DateTime<Utc>",
                 last_used_at as "last_used_at!: DateTime<Utc>"
@@ -96,6 +110,8 @@ impl UserSession {
                 id as "id!: Uuid",
                 user_id as "user_id!: Uuid",
                 cf_access_jwt_id,
+                user_agent,
+                ip_address,
                 expires_at as "expires_at!: DateTime<Utc>",
                 created_at as "created_at!: DateTime<Utc>",
                 last_used_at as "last_used_at!: DateTime<Utc>"
@@ -109,6 +125,83 @@ impl UserSession {
         .map_err(UserSessionError::from)
     }
 
+    /// Find the most recently used valid session for a user, CF Access identity
+    /// and client user-agent
+    pub async fn find_latest_valid_for_user(
+        pool: &SqlitePool,
+        user_id: Uuid,
+        cf_access_jwt_id: Option<&str>,
+        user_agent: Option<&str>,
+    ) -> Result<Option<Self>, UserSessionError> {
+        let now = Utc::now();
+        sqlx::query_as!(
+            UserSession,
+            r#"SELECT
+                id as "id!: Uuid",
+                user_id as "user_id!: Uuid",
+                cf_access_jwt_id,
+                user_agent,
+                ip_address,
+                expires_at as "expires_at!: DateTime<Utc>",
+                created_at as "created_at!: DateTime<Utc>",
+                last_used_at as "last_used_at!: DateTime<Utc>"
+            FROM user_sessions
+            WHERE user_id = $1
+              AND cf_access_jwt_id IS $2
+              AND user_agent IS $3
+              AND expires_at > $4
+            ORDER BY last_used_at DESC
+            LIMIT 1"#,
+            user_id,
+            cf_access_jwt_id,
+            user_agent,
+            now
+        )
+        .fetch_optional(pool)
+        .await
+        .map_err(UserSessionError::from)
+    }
+
+    /// List a user's non-expired sessions, most recently used first
+    pub async fn list_active_for_user(
+        pool: &SqlitePool,
+        user_id: Uuid,
+    ) -> Result<Vec<Self>, UserSessionError> {
+        let now = Utc::now();
+        sqlx::query_as!(
+            UserSession,
+            r#"SELECT
+                id as "id!: Uuid",
+                user_id as "user_id!: Uuid",
+                cf_access_jwt_id,
+                user_agent,
+                ip_address,
+                expires_at as "expires_at!: DateTime<Utc>",
+                created_at as "created_at!: DateTime<Utc>",
+                last_used_at as "last_used_at!: DateTime<Utc>"
+            FROM user_sessions
+            WHERE user_id = $1 AND expires_at > $2
+            ORDER BY last_used_at DESC"#,
+            user_id,
+            now
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(UserSessionError::from)
+    }
+
+    /// Count non-expired sessions across all users
+    pub async fn count_active(pool: &SqlitePool) -> Result<i64, UserSessionError> {
+        let now = Utc::now();
+        let count = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!: i64" FROM user_sessions WHERE expires_at > $1"#,
+            now
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(count)
+    }
+
     /// Update the last_used_at timestamp (touch session)
     pub async fn touch(pool: &SqlitePool, id: Uuid) -> Result<(), UserSessionError> {
         let now = Utc::now();
@@ -128,6 +221,42 @@ impl UserSession {
         Ok(())
     }
 
+    /// Touch the session and slide `expires_at` forward to `now + extend_by`,
+    /// capped at the session's absolute lifetime
+    pub async fn touch_and_extend(
+        pool: &SqlitePool,
+        id: Uuid,
+        extend_by: Duration,
+    ) -> Result<Self, UserSessionError> {
+        let session = Self::find_by_id(pool, id)
+            .await?
+            .ok_or(UserSessionError::NotFound)?;
+        let now = Utc::now();
+        let expires_at = session.extended_expiry(now, extend_by);
+
+        sqlx::query_as!(
+            UserSession,
+            r#"UPDATE user_sessions
+            SET last_used_at = $2, expires_at = $3
+            WHERE id = $1
+            RETURNING
+                id as "id!: Uuid",
+                user_id as "user_id!: Uuid",
+                cf_access_jwt_id,
+                user_agent,
+                ip_address,
+                expires_at as "expires_at!: DateTime<Utc>",
+                created_at as "created_at!: DateTime<Utc>",
+                last_used_at as "last_used_at!: DateTime<Utc>""#,
+            id,
+            now,
+            expires_at
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or(UserSessionError::NotFound)
+    }
+
     /// Delete a session (logout)
     pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<(), UserSessionError> {
         sqlx::query!(r#"DELETE FROM user_sessions WHERE id = $1"#, id)
@@ -136,6 +265,23 @@ impl UserSession {
         Ok(())
     }
 
+    /// Delete a session only if it belongs to the given user.
+    /// Returns false if no such session exists for that user.
+    pub async fn delete_for_user(
+        pool: &SqlitePool,
+        id: Uuid,
+        user_id: Uuid,
+    ) -> Result<bool, UserSessionError> {
+        let result = sqlx::query!(
+            r#"DELETE FROM user_sessions WHERE id = $1 AND user_id = $2"#,
+            id,
+            user_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
     /// Delete all sessions for a user
     pub async fn delete_all_for_user(
         pool: &SqlitePool,
@@ -170,4 +316,313 @@ impl UserSession {
     pub fn is_valid(&self) -> bool {
         !self.is_expired() && !self.is_inactive()
     }
+
+    /// Expiry after a sliding extension at `now`. Never moves backwards and
+    /// never passes the absolute lifetime cap.
+    pub fn extended_expiry(&self, now: DateTime<Utc>, extend_by: Duration) -> DateTime<Utc> {
+        let cap = self.created_at + MAX_SESSION_LIFETIME;
+        (now + extend_by).min(cap).max(self.expires_at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+    use crate::models::user::{UpsertUser, User};
+
+    async fn test_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        pool
+    }
+
+    async fn test_user(pool: &SqlitePool) -> User {
+        user_with_email(pool, "dev@example.com").await
+    }
+
+    async fn user_with_email(pool: &SqlitePool, email: &str) -> User {
+        User::upsert(
+            pool,
+            &UpsertUser {
+                email: email.to_string(),
+                name: "Dev".to_string(),
+                avatar_url: None,
+                cf_access_id: Some("sub-1".to_string()),
+            },
+        )
+        .await
+        .unwrap()
+    }
+
+    fn session_at(created_at: DateTime<Utc>, expires_at: DateTime<Utc>) -> UserSession {
+        UserSession {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            cf_access_jwt_id: None,
+            user_agent: None,
+            ip_address: None,
+            expires_at,
+            created_at,
+            last_used_at: created_at,
+        }
+    }
+
+    #[test]
+    fn extended_expiry_slides_forward() {
+        let created = Utc::now();
+        let session = session_at(created, created + DEFAULT_SESSION_DURATION);
+        let now = created + Duration::days(3);
+
+        assert_eq!(
+            session.extended_expiry(now, DEFAULT_SESSION_DURATION),
+            now + DEFAULT_SESSION_DURATION
+        );
+    }
+
+    #[test]
+    fn extended_expiry_is_capped_at_max_lifetime() {
+        let created = Utc::now();
+        let session = session_at(created, created + DEFAULT_SESSION_DURATION);
+        let now = created + MAX_SESSION_LIFETIME - Duration::days(1);
+
+        assert_eq!(
+            session.extended_expiry(now, DEFAULT_SESSION_DURATION),
+            created + MAX_SESSION_LIFETIME
+        );
+    }
+
+    #[test]
+    fn extended_expiry_never_moves_backwards() {
+        let created = Utc::now();
+        let session = session_at(created, created + DEFAULT_SESSION_DURATION);
+
+        assert_eq!(
+            session.extended_expiry(created, Duration::hours(1)),
+            session.expires_at
+        );
+    }
+
+    #[tokio::test]
+    async fn touch_and_extend_moves_expiry_forward() {
+        let pool = test_pool().await;
+        let user = test_user(&pool).await;
+        let session = UserSession::create(
+            &pool,
+            user.id,
+            Some("sub-1"),
+            Some(Duration::hours(1)),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let touched = UserSession::touch_and_extend(&pool, session.id, DEFAULT_SESSION_DURATION)
+            .await
+            .unwrap();
+
+        assert!(touched.expires_at > session.expires_at);
+        assert!(touched.expires_at <= touched.created_at + MAX_SESSION_LIFETIME);
+        assert!(touched.last_used_at >= session.last_used_at);
+    }
+
+    #[tokio::test]
+    async fn touch_and_extend_missing_session() {
+        let pool = test_pool().await;
+
+        let err = UserSession::touch_and_extend(&pool, Uuid::new_v4(), DEFAULT_SESSION_DURATION)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, UserSessionError::NotFound));
+    }
+
+    #[tokio::test]
+    async fn latest_valid_session_is_found_for_reuse() {
+        let pool = test_pool().await;
+        let user = test_user(&pool).await;
+        let session = UserSession::create(&pool, user.id, Some("sub-1"), None, None, None)
+            .await
+            .unwrap();
+
+        let found = UserSession::find_latest_valid_for_user(&pool, user.id, Some("sub-1"), None)
+            .await
+            .unwrap();
+        assert_eq!(found.map(|s| s.id), Some(session.id));
+
+        let other = UserSession::find_latest_valid_for_user(&pool, user.id, Some("sub-2"), None)
+            .await
+            .unwrap();
+        assert!(other.is_none());
+    }
+
+    #[tokio::test]
+    async fn list_active_skips_expired_and_orders_by_last_use() {
+        let pool = test_pool().await;
+        let user = test_user(&pool).await;
+        let older = UserSession::create(&pool, user.id, Some("sub-1"), None, None, None)
+            .await
+            .unwrap();
+        let newer = UserSession::create(&pool, user.id, Some("sub-1"), None, None, None)
+            .await
+            .unwrap();
+        UserSession::create(
+            &pool,
+            user.id,
+            Some("sub-1"),
+            Some(Duration::hours(-1)),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        UserSession::touch(&pool, newer.id).await.unwrap();
+
+        let sessions = UserSession::list_active_for_user(&pool, user.id)
+            .await
+            .unwrap();
+
+        let ids: Vec<Uuid> = sessions.iter().map(|s| s.id).collect();
+        assert_eq!(ids, vec![newer.id, older.id]);
+    }
+
+    #[tokio::test]
+    async fn delete_for_user_rejects_other_users_session() {
+        let pool = test_pool().await;
+        let owner = test_user(&pool).await;
+        let other = user_with_email(&pool, "other@example.com").await;
+        let session = UserSession::create(&pool, owner.id, Some("sub-1"), None, None, None)
+            .await
+            .unwrap();
+
+        assert!(
+            !UserSession::delete_for_user(&pool, session.id, other.id)
+                .await
+                .unwrap()
+        );
+        assert!(
+            UserSession::find_by_id(&pool, session.id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        assert!(
+            UserSession::delete_for_user(&pool, session.id, owner.id)
+                .await
+                .unwrap()
+        );
+        assert!(
+            UserSession::find_by_id(&pool, session.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn provenance_round_trips() {
+        let pool = test_pool().await;
+        let user = test_user(&pool).await;
+        let user_agent = "Mozilla/5.0 (Macintosh; Intel Mac OS X 14_5) Chrome/126.0";
+
+        let session = UserSession::create(
+            &pool,
+            user.id,
+            Some("sub-1"),
+            None,
+            Some(user_agent),
+            Some("203.0.113.7"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(session.user_agent.as_deref(), Some(user_agent));
+
+        let listed = UserSession::list_active_for_user(&pool, user.id)
+            .await
+            .unwrap();
+        assert_eq!(listed[0].user_agent.as_deref(), Some(user_agent));
+        assert_eq!(listed[0].ip_address.as_deref(), Some("203.0.113.7"));
+
+        // Sessions are only reused for the same client
+        let reused = UserSession::find_latest_valid_for_user(
+            &pool,
+            user.id,
+            Some("sub-1"),
+            Some(user_agent),
+        )
+        .await
+        .unwrap();
+        assert_eq!(reused.map(|s| s.id), Some(session.id));
+        let other_client = UserSession::find_latest_valid_for_user(
+            &pool,
+            user.id,
+            Some("sub-1"),
+            Some("curl/8.7"),
+        )
+        .await
+        .unwrap();
+        assert!(other_client.is_none());
+    }
+
+    #[tokio::test]
+    async fn cleanup_expired_removes_only_expired_sessions() {
+        let pool = test_pool().await;
+        let user = test_user(&pool).await;
+        let active = UserSession::create(&pool, user.id, Some("sub-1"), None, None, None)
+            .await
+            .unwrap();
+        let expired = UserSession::create(
+            &pool,
+            user.id,
+            Some("sub-1"),
+            Some(Duration::seconds(-1)),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let removed = UserSession::cleanup_expired(&pool).await.unwrap();
+
+        assert_eq!(removed, 1);
+        assert!(
+            UserSession::find_by_id(&pool, expired.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            UserSession::find_by_id(&pool, active.id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn count_active_skips_expired_sessions() {
+        let pool = test_pool().await;
+        let user = test_user(&pool).await;
+        UserSession::create(&pool, user.id, Some("sub-1"), None, None, None)
+            .await
+            .unwrap();
+        UserSession::create(
+            &pool,
+            user.id,
+            Some("sub-1"),
+            Some(Duration::seconds(-1)),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(UserSession::count_active(&pool).await.unwrap(), 1);
+    }
 }
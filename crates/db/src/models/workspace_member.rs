@@ -1,6 +1,8 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool};
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool};
 use ts_rs::TS;
 use uuid::Uuid;
 
@@ -209,6 +211,23 @@ impl WorkspaceMember {
         Ok(result.rows_affected())
     }
 
+    /// Remove every membership of a team
+    pub async fn delete_all_for_team<'e, E>(
+        executor: E,
+        workspace_team_id: Uuid,
+    ) -> Result<u64, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let result = sqlx::query!(
+            "DELETE FROM workspace_members WHERE workspace_team_id = $1",
+            workspace_team_id
+        )
+        .execute(executor)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
     pub async fn delete_by_team_and_user(
         pool: &SqlitePool,
         workspace_team_id: Uuid,
@@ -311,4 +330,23 @@ impl WorkspaceMember {
         .fetch_one(pool)
         .await
     }
+
+    /// Member count for every role in a team, including roles nobody holds
+    pub async fn count_all_roles(
+        pool: &SqlitePool,
+        workspace_team_id: Uuid,
+    ) -> Result<HashMap<Uuid, i64>, sqlx::Error> {
+        let records = sqlx::query!(
+            r#"SELECT r.id as "role_id!: Uuid", COUNT(wm.id) as "count!: i64"
+               FROM roles r
+               LEFT JOIN workspace_members wm
+                   ON wm.role_id = r.id AND wm.workspace_team_id = $1
+               GROUP BY r.id"#,
+            workspace_team_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(records.into_iter().map(|r| (r.role_id, r.count)).collect())
+    }
 }
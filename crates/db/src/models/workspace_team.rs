@@ -1,9 +1,11 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool};
+use sqlx::{Executor, FromRow, Sqlite, SqlitePool};
 use ts_rs::TS;
 use uuid::Uuid;
 
+use super::role::{Role, split_permission_keys};
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, TS)]
 pub struct WorkspaceTeam {
     pub id: Uuid,
@@ -108,9 +110,12 @@ impl WorkspaceTeam {
         .await
     }
 
-    pub async fn delete(pool: &SqlitePool, id: Uuid) -> Result<u64, sqlx::Error> {
+    pub async fn delete<'e, E>(executor: E, id: Uuid) -> Result<u64, sqlx::Error>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
         let result = sqlx::query!("DELETE FROM workspace_teams WHERE id = $1", id)
-            .execute(pool)
+            .execute(executor)
             .await?;
         Ok(result.rows_affected())
     }
@@ -133,4 +138,60 @@ impl WorkspaceTeam {
         .fetch_all(pool)
         .await
     }
+
+    /// Every team a user belongs to with their role there and its sorted
+    /// permission keys, in `find_by_user_id` order, from a single query
+    pub async fn find_teams_with_role_for_user(
+        pool: &SqlitePool,
+        user_id: &str,
+    ) -> Result<Vec<(Self, Role, Vec<String>)>, sqlx::Error> {
+        let records = sqlx::query!(
+            r#"SELECT wt.id as "id!: Uuid",
+                      wt.name,
+                      wt.description,
+                      wt.created_at as "created_at!: DateTime<Utc>",
+                      wt.updated_at as "updated_at!: DateTime<Utc>",
+                      r.id as "role_id!: Uuid",
+                      r.name as role_name,
+                      r.description as role_description,
+                      r.is_system as "role_is_system!: bool",
+                      r.created_at as "role_created_at!: DateTime<Utc>",
+                      r.updated_at as "role_updated_at!: DateTime<Utc>",
+                      GROUP_CONCAT(p.key, ',') as "permission_keys?: String"
+               FROM workspace_teams wt
+               INNER JOIN workspace_members wm ON wt.id = wm.workspace_team_id
+               INNER JOIN roles r ON r.id = wm.role_id
+               LEFT JOIN role_permissions rp ON rp.role_id = r.id
+               LEFT JOIN permissions p ON p.id = rp.permission_id
+               WHERE wm.user_id = $1
+               GROUP BY wt.id
+               ORDER BY wt.created_at DESC"#,
+            user_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|r| {
+                let keys = split_permission_keys(r.permission_keys.as_deref());
+                let team = WorkspaceTeam {
+                    id: r.id,
+                    name: r.name,
+                    description: r.description,
+                    created_at: r.created_at,
+                    updated_at: r.updated_at,
+                };
+                let role = Role {
+                    id: r.role_id,
+                    name: r.role_name,
+                    description: r.role_description,
+                    is_system: r.role_is_system,
+                    created_at: r.role_created_at,
+                    updated_at: r.role_updated_at,
+                };
+                (team, role, keys)
+            })
+            .collect())
+    }
 }
@@ -0,0 +1,462 @@
+//! Startup sync of system roles, permissions and their role assignments.
+//!
+//! The canonical definitions below mirror the RBAC migration. `plan` diffs
+//! them against the database; `apply` inserts whatever is missing. Check
+//! mode only computes the plan, so operators and CI can see what would
+//! change without mutating anything.
+
+use std::collections::HashSet;
+
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::models::{permission::keys, role::system_roles};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PermissionDef {
+    pub id: Uuid,
+    pub key: &'static str,
+    pub description: &'static str,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoleDef {
+    pub id: Uuid,
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+pub const PERMISSIONS: &[PermissionDef] = &[
+    PermissionDef {
+        id: Uuid::from_u128(0x10000000_0000_0000_0000_000000000001),
+        key: keys::WORKSPACE_VIEW,
+        description: "View workspace details",
+    },
+    PermissionDef {
+        id: Uuid::from_u128(0x10000000_0000_0000_0000_000000000002),
+        key: keys::WORKSPACE_EDIT,
+        description: "Edit workspace settings",
+    },
+    PermissionDef {
+        id: Uuid::from_u128(0x10000000_0000_0000_0000_000000000003),
+        key: keys::WORKSPACE_DELETE,
+        description: "Delete workspace",
+    },
+    PermissionDef {
+        id: Uuid::from_u128(0x10000000_0000_0000_0000_000000000004),
+        key: keys::WORKSPACE_TRANSFER,
+        description: "Transfer workspace ownership",
+    },
+    PermissionDef {
+        id: Uuid::from_u128(0x20000000_0000_0000_0000_000000000001),
+        key: keys::MEMBER_VIEW,
+        description: "View workspace members",
+    },
+    PermissionDef {
+        id: Uuid::from_u128(0x20000000_0000_0000_0000_000000000002),
+        key: keys::MEMBER_INVITE,
+        description: "Invite new members",
+    },
+    PermissionDef {
+        id: Uuid::from_u128(0x20000000_0000_0000_0000_000000000003),
+        key: keys::MEMBER_REMOVE,
+        description: "Remove members",
+    },
+    PermissionDef {
+        id: Uuid::from_u128(0x20000000_0000_0000_0000_000000000004),
+        key: keys::MEMBER_ROLE_ASSIGN,
+        description: "Assign roles to members",
+    },
+    PermissionDef {
+        id: Uuid::from_u128(0x30000000_0000_0000_0000_000000000001),
+        key: keys::TASK_VIEW,
+        description: "View tasks",
+    },
+    PermissionDef {
+        id: Uuid::from_u128(0x30000000_0000_0000_0000_000000000002),
+        key: keys::TASK_CREATE,
+        description: "Create new tasks",
+    },
+    PermissionDef {
+        id: Uuid::from_u128(0x30000000_0000_0000_0000_000000000003),
+        key: keys::TASK_EDIT,
+        description: "Edit tasks",
+    },
+    PermissionDef {
+        id: Uuid::from_u128(0x30000000_0000_0000_0000_000000000004),
+        key: keys::TASK_DELETE,
+        description: "Delete tasks",
+    },
+    PermissionDef {
+        id: Uuid::from_u128(0x30000000_0000_0000_0000_000000000005),
+        key: keys::TASK_ASSIGN,
+        description: "Assign tasks to members",
+    },
+    PermissionDef {
+        id: Uuid::from_u128(0x30000000_0000_0000_0000_000000000006),
+        key: keys::TASK_STATUS_CHANGE,
+        description: "Change task status",
+    },
+    PermissionDef {
+        id: Uuid::from_u128(0x40000000_0000_0000_0000_000000000001),
+        key: keys::PROJECT_VIEW,
+        description: "View projects",
+    },
+    PermissionDef {
+        id: Uuid::from_u128(0x40000000_0000_0000_0000_000000000002),
+        key: keys::PROJECT_CREATE,
+        description: "Create new projects",
+    },
+    PermissionDef {
+        id: Uuid::from_u128(0x40000000_0000_0000_0000_000000000003),
+        key: keys::PROJECT_EDIT,
+        description: "Edit projects",
+    },
+    PermissionDef {
+        id: Uuid::from_u128(0x40000000_0000_0000_0000_000000000004),
+        key: keys::PROJECT_DELETE,
+        description: "Delete projects",
+    },
+];
+
+pub const SYSTEM_ROLES: &[RoleDef] = &[
+    RoleDef {
+        id: system_roles::OWNER,
+        name: "Owner",
+        description: "Full access to workspace including deletion and ownership transfer",
+    },
+    RoleDef {
+        id: system_roles::ADMIN,
+        name: "Admin",
+        description: "Administrative access to workspace settings and member management",
+    },
+    RoleDef {
+        id: system_roles::MEMBER,
+        name: "Member",
+        description: "Standard access to create and manage tasks",
+    },
+    RoleDef {
+        id: system_roles::VIEWER,
+        name: "Viewer",
+        description: "Read-only access to workspace",
+    },
+];
+
+/// Permission keys granted to a system role by default.
+pub fn default_role_permissions(role_id: Uuid) -> Vec<&'static str> {
+    let all = PERMISSIONS.iter().map(|p| p.key);
+    match role_id {
+        system_roles::OWNER => all.collect(),
+        system_roles::ADMIN => all
+            .filter(|key| *key != keys::WORKSPACE_DELETE && *key != keys::WORKSPACE_TRANSFER)
+            .collect(),
+        system_roles::MEMBER => vec![
+            keys::WORKSPACE_VIEW,
+            keys::MEMBER_VIEW,
+            keys::TASK_VIEW,
+            keys::TASK_CREATE,
+            keys::TASK_EDIT,
+            keys::TASK_STATUS_CHANGE,
+            keys::PROJECT_VIEW,
+        ],
+        system_roles::VIEWER => vec![
+            keys::WORKSPACE_VIEW,
+            keys::MEMBER_VIEW,
+            keys::TASK_VIEW,
+            keys::PROJECT_VIEW,
+        ],
+        _ => Vec::new(),
+    }
+}
+
+/// Rows that are missing from the database.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SeedPlan {
+    pub missing_permissions: Vec<PermissionDef>,
+    pub missing_roles: Vec<RoleDef>,
+    pub missing_role_permissions: Vec<(Uuid, &'static str)>,
+}
+
+impl SeedPlan {
+    pub fn is_empty(&self) -> bool {
+        self.missing_permissions.is_empty()
+            && self.missing_roles.is_empty()
+            && self.missing_role_permissions.is_empty()
+    }
+
+    /// Human-readable list of the rows this plan would insert.
+    pub fn describe(&self) -> Vec<String> {
+        let role_name = |id: Uuid| {
+            SYSTEM_ROLES
+                .iter()
+                .find(|r| r.id == id)
+                .map_or_else(|| id.to_string(), |r| r.name.to_string())
+        };
+
+        let mut lines = Vec::new();
+        for permission in &self.missing_permissions {
+            lines.push(format!("permission {}", permission.key));
+        }
+        for role in &self.missing_roles {
+            lines.push(format!("role {}", role.name));
+        }
+        for (role_id, key) in &self.missing_role_permissions {
+            lines.push(format!(
+                "role_permission {} -> {}",
+                role_name(*role_id),
+                key
+            ));
+        }
+        lines
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeedMode {
+    /// Insert missing rows
+    Apply,
+    /// Report missing rows without writing
+    Check,
+}
+
+/// Compute which canonical rows are missing from the database.
+pub async fn plan(pool: &SqlitePool) -> Result<SeedPlan, sqlx::Error> {
+    let existing_permissions: HashSet<String> =
+        sqlx::query_scalar!(r#"SELECT key FROM permissions"#)
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .collect();
+
+    let existing_roles: HashSet<Uuid> =
+        sqlx::query_scalar!(r#"SELECT id as "id!: Uuid" FROM roles"#)
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .collect();
+
+    let existing_role_permissions: HashSet<(Uuid, String)> = sqlx::query!(
+        r#"SELECT rp.role_id as "role_id!: Uuid", p.key
+           FROM role_permissions rp
+           INNER JOIN permissions p ON p.id = rp.permission_id"#
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| (r.role_id, r.key))
+    .collect();
+
+    let missing_permissions = PERMISSIONS
+        .iter()
+        .filter(|p| !existing_permissions.contains(p.key))
+        .copied()
+        .collect();
+
+    let missing_roles = SYSTEM_ROLES
+        .iter()
+        .filter(|r| !existing_roles.contains(&r.id))
+        .copied()
+        .collect();
+
+    let missing_role_permissions = SYSTEM_ROLES
+        .iter()
+        .flat_map(|role| {
+            default_role_permissions(role.id)
+                .into_iter()
+                .map(move |key| (role.id, key))
+        })
+        .filter(|(role_id, key)| !existing_role_permissions.contains(&(*role_id, key.to_string())))
+        .collect();
+
+    Ok(SeedPlan {
+        missing_permissions,
+        missing_roles,
+        missing_role_permissions,
+    })
+}
+
+/// Insert every row in the plan inside a single transaction.
+pub async fn apply(pool: &SqlitePool, plan: &SeedPlan) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    for permission in &plan.missing_permissions {
+        sqlx::query!(
+            r#"INSERT INTO permissions (id, key, description) VALUES ($1, $2, $3)"#,
+            permission.id,
+            permission.key,
+            permission.description
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    for role in &plan.missing_roles {
+        sqlx::query!(
+            r#"INSERT INTO roles (id, name, description, is_system) VALUES ($1, $2, $3, 1)"#,
+            role.id,
+            role.name,
+            role.description
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    for (role_id, key) in &plan.missing_role_permissions {
+        sqlx::query!(
+            r#"INSERT INTO role_permissions (role_id, permission_id)
+               SELECT $1, id FROM permissions WHERE key = $2"#,
+            role_id,
+            key
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await
+}
+
+/// Diff the canonical RBAC rows against the database and, in apply mode,
+/// insert whatever is missing. Returns the computed plan either way.
+pub async fn sync(pool: &SqlitePool, mode: SeedMode) -> Result<SeedPlan, sqlx::Error> {
+    let plan = plan(pool).await?;
+    if mode == SeedMode::Apply && !plan.is_empty() {
+        apply(pool, &plan).await?;
+    }
+    Ok(plan)
+}
+
+/// Make sure every system role holds its default permissions, inserting any
+/// missing permission, role or grant. Safe to run on every startup.
+pub async fn seed_system_role_permissions(pool: &SqlitePool) -> Result<SeedPlan, sqlx::Error> {
+    sync(pool, SeedMode::Apply).await
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+    use crate::models::role::Role;
+
+    /// RBAC tables without the migration's seed rows.
+    async fn unseeded_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        for statement in [
+            "CREATE TABLE roles (
+                id BLOB PRIMARY KEY,
+                name TEXT NOT NULL UNIQUE,
+                description TEXT,
+                is_system INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+            )",
+            "CREATE TABLE permissions (
+                id BLOB PRIMARY KEY,
+                key TEXT NOT NULL UNIQUE,
+                description TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now', 'subsec'))
+            )",
+            "CREATE TABLE role_permissions (
+                role_id BLOB NOT NULL,
+                permission_id BLOB NOT NULL,
+                created_at TEXT NOT NULL DEFAULT (datetime('now', 'subsec')),
+                PRIMARY KEY (role_id, permission_id)
+            )",
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+
+        pool
+    }
+
+    async fn row_count(pool: &SqlitePool, table: &str) -> i64 {
+        sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table}"))
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    fn expected_role_permission_count() -> usize {
+        SYSTEM_ROLES
+            .iter()
+            .map(|r| default_role_permissions(r.id).len())
+            .sum()
+    }
+
+    #[tokio::test]
+    async fn test_check_mode_reports_missing_rows_without_writing() {
+        let pool = unseeded_pool().await;
+
+        let plan = sync(&pool, SeedMode::Check).await.unwrap();
+
+        assert_eq!(plan.missing_permissions.len(), PERMISSIONS.len());
+        assert_eq!(plan.missing_roles.len(), SYSTEM_ROLES.len());
+        assert_eq!(
+            plan.missing_role_permissions.len(),
+            expected_role_permission_count()
+        );
+        assert!(
+            plan.missing_role_permissions
+                .contains(&(system_roles::VIEWER, keys::TASK_VIEW))
+        );
+
+        assert_eq!(row_count(&pool, "permissions").await, 0);
+        assert_eq!(row_count(&pool, "roles").await, 0);
+        assert_eq!(row_count(&pool, "role_permissions").await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_apply_mode_seeds_and_converges() {
+        let pool = unseeded_pool().await;
+
+        let plan = sync(&pool, SeedMode::Apply).await.unwrap();
+        assert!(!plan.is_empty());
+
+        assert_eq!(
+            row_count(&pool, "permissions").await,
+            PERMISSIONS.len() as i64
+        );
+        assert_eq!(row_count(&pool, "roles").await, SYSTEM_ROLES.len() as i64);
+        assert_eq!(
+            row_count(&pool, "role_permissions").await,
+            expected_role_permission_count() as i64
+        );
+
+        assert!(sync(&pool, SeedMode::Check).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_seeded_owner_has_every_permission() {
+        let pool = unseeded_pool().await;
+
+        seed_system_role_permissions(&pool).await.unwrap();
+        // Running again must not fail on existing rows
+        let second = seed_system_role_permissions(&pool).await.unwrap();
+        assert!(second.is_empty());
+
+        let owner = Role::get_permissions(&pool, system_roles::OWNER)
+            .await
+            .unwrap();
+        let mut expected: Vec<String> = PERMISSIONS.iter().map(|p| p.key.to_string()).collect();
+        expected.sort();
+        assert_eq!(owner, expected);
+
+        let viewer = Role::get_permissions(&pool, system_roles::VIEWER)
+            .await
+            .unwrap();
+        assert!(viewer.iter().all(|key| key.ends_with(".view")));
+    }
+
+    #[test]
+    fn test_admin_lacks_owner_only_permissions() {
+        let admin = default_role_permissions(system_roles::ADMIN);
+        assert!(!admin.contains(&keys::WORKSPACE_DELETE));
+        assert!(!admin.contains(&keys::WORKSPACE_TRANSFER));
+        assert_eq!(admin.len(), PERMISSIONS.len() - 2);
+    }
+}
@@ -27,6 +27,7 @@ use services::services::{
     filesystem_watcher::FilesystemWatcherError,
     git::{GitService, GitServiceError},
     image::{ImageError, ImageService},
+    metrics::MetricsService,
     pr_monitor::PrMonitorService,
     project::ProjectService,
     queued_message::QueuedMessageService,
@@ -111,6 +112,8 @@ pub trait Deployment: Clone + Send + Sync + 'static {
 
     fn auth_context(&self) -> &AuthContext;
 
+    fn metrics(&self) -> &MetricsService;
+
     fn share_publisher(&self) -> Result<SharePublisher, RemoteClientNotConfigured>;
 
     async fn update_sentry_scope(&self) -> Result<(), DeploymentError> {
@@ -177,6 +177,41 @@ impl CodingAgent {
             Self::QaMock(_) => vec![], // QA mock doesn't need special capabilities
         }
     }
+
+    /// The model this configuration pins, or `None` to use the agent's own default
+    pub fn model(&self) -> Option<&str> {
+        match self {
+            Self::ClaudeCode(agent) => agent.model.as_deref(),
+            Self::Gemini(agent) => agent.model.as_deref(),
+            Self::Codex(agent) => agent.model.as_deref(),
+            Self::Opencode(agent) => agent.model.as_deref(),
+            Self::CursorAgent(agent) => agent.model.as_deref(),
+            Self::Copilot(agent) => agent.model.as_deref(),
+            Self::Droid(agent) => agent.model.as_deref(),
+            Self::Amp(_) | Self::QwenCode(_) => None,
+            #[cfg(feature = "qa-mode")]
+            Self::QaMock(_) => None,
+        }
+    }
+}
+
+impl BaseCodingAgent {
+    /// Human-readable executor name for pickers and labels
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Self::ClaudeCode => "Claude Code",
+            Self::Amp => "Amp",
+            Self::Gemini => "Gemini CLI",
+            Self::Codex => "Codex",
+            Self::Opencode => "OpenCode",
+            Self::CursorAgent => "Cursor Agent",
+            Self::QwenCode => "Qwen Code",
+            Self::Copilot => "GitHub Copilot",
+            Self::Droid => "Droid",
+            #[cfg(feature = "qa-mode")]
+            Self::QaMock => "QA Mock",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -11,7 +11,8 @@ use thiserror::Error;
 use ts_rs::TS;
 
 use crate::executors::{
-    AvailabilityInfo, BaseCodingAgent, CodingAgent, StandardCodingAgentExecutor,
+    AvailabilityInfo, BaseAgentCapability, BaseCodingAgent, CodingAgent,
+    StandardCodingAgentExecutor,
 };
 
 /// Return the canonical form for variant keys.
@@ -170,6 +171,25 @@ impl ExecutorConfig {
     }
 }
 
+/// One configured variant of an executor
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ExecutorVariantInfo {
+    pub name: String,
+    /// Model the variant pins, or `None` for the agent's own default
+    pub model: Option<String>,
+}
+
+/// An executor this deployment can run, with its configured variants
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ExecutorInfo {
+    pub executor: BaseCodingAgent,
+    pub display_name: String,
+    /// `DEFAULT` first, then the other variants by name
+    pub variants: Vec<ExecutorVariantInfo>,
+    /// Capabilities of the default variant
+    pub capabilities: Vec<BaseAgentCapability>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
 pub struct ExecutorConfigs {
     pub executors: HashMap<BaseCodingAgent, ExecutorConfig>,
@@ -387,6 +407,39 @@ impl ExecutorConfigs {
         })
     }
 
+    /// Every configured executor with its variants, ordered by executor id
+    pub fn executor_infos(&self) -> Vec<ExecutorInfo> {
+        let mut infos: Vec<ExecutorInfo> = self
+            .executors
+            .iter()
+            .map(|(&executor, config)| {
+                let mut variants: Vec<ExecutorVariantInfo> = config
+                    .configurations
+                    .iter()
+                    .map(|(name, agent)| ExecutorVariantInfo {
+                        name: name.clone(),
+                        model: agent.model().map(str::to_string),
+                    })
+                    .collect();
+                variants.sort_by(|a, b| {
+                    (a.name != "DEFAULT", &a.name).cmp(&(b.name != "DEFAULT", &b.name))
+                });
+
+                ExecutorInfo {
+                    executor,
+                    display_name: executor.display_name().to_string(),
+                    variants,
+                    capabilities: config
+                        .get_default()
+                        .map(CodingAgent::capabilities)
+                        .unwrap_or_default(),
+                }
+            })
+            .collect();
+        infos.sort_by_key(|info| info.executor.to_string());
+        infos
+    }
+
     pub fn get_coding_agent(&self, executor_profile_id: &ExecutorProfileId) -> Option<CodingAgent> {
         self.executors
             .get(&executor_profile_id.executor)
@@ -482,3 +535,49 @@ pub fn to_default_variant(id: &ExecutorProfileId) -> ExecutorProfileId {
         variant: None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_executor_infos_list_default_executors() {
+        let infos = ExecutorConfigs::from_defaults().executor_infos();
+
+        for executor in [
+            BaseCodingAgent::ClaudeCode,
+            BaseCodingAgent::Amp,
+            BaseCodingAgent::Gemini,
+            BaseCodingAgent::Codex,
+            BaseCodingAgent::Opencode,
+            BaseCodingAgent::CursorAgent,
+            BaseCodingAgent::QwenCode,
+            BaseCodingAgent::Copilot,
+            BaseCodingAgent::Droid,
+        ] {
+            let info = infos
+                .iter()
+                .find(|info| info.executor == executor)
+                .unwrap_or_else(|| panic!("{executor} missing"));
+            assert_eq!(info.display_name, executor.display_name());
+            assert_eq!(info.variants[0].name, "DEFAULT");
+        }
+
+        let codex = infos
+            .iter()
+            .find(|info| info.executor == BaseCodingAgent::Codex)
+            .unwrap();
+        assert_eq!(codex.variants[0].model.as_deref(), Some("gpt-5.2"));
+        assert!(
+            codex
+                .variants
+                .iter()
+                .any(|variant| variant.model.as_deref() == Some("gpt-5.1-codex-max"))
+        );
+        assert!(
+            codex
+                .capabilities
+                .contains(&BaseAgentCapability::SetupHelper)
+        );
+    }
+}
@@ -15,6 +15,7 @@ use services::services::{
     filesystem::FilesystemService,
     git::GitService,
     image::ImageService,
+    metrics::MetricsService,
     oauth_credentials::OAuthCredentials,
     project::ProjectService,
     queued_message::QueuedMessageService,
@@ -56,6 +57,7 @@ pub struct LocalDeployment {
     remote_client: Result<RemoteClient, RemoteClientNotConfigured>,
     auth_context: AuthContext,
     oauth_handoffs: Arc<RwLock<HashMap<Uuid, PendingHandoff>>>,
+    metrics: MetricsService,
 }
 
 #[derive(Debug, Clone)]
@@ -209,6 +211,7 @@ impl Deployment for LocalDeployment {
             remote_client,
             auth_context,
             oauth_handoffs,
+            metrics: MetricsService::new(),
         };
 
         Ok(deployment)
@@ -277,6 +280,10 @@ impl Deployment for LocalDeployment {
     fn auth_context(&self) -> &AuthContext {
         &self.auth_context
     }
+
+    fn metrics(&self) -> &MetricsService {
+        &self.metrics
+    }
 }
 
 impl LocalDeployment {
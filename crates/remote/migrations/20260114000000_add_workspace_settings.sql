@@ -0,0 +1,13 @@
+-- Per-workspace settings
+CREATE TABLE workspace_settings (
+    workspace_id UUID PRIMARY KEY,
+    invitation_activation_delay_secs BIGINT NOT NULL DEFAULT 0
+        CHECK (invitation_activation_delay_secs >= 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TRIGGER workspace_settings_updated_at
+    BEFORE UPDATE ON workspace_settings
+    FOR EACH ROW
+    EXECUTE FUNCTION update_workspace_member_updated_at();
@@ -0,0 +1,2 @@
+-- Baseline permission every workspace member holds by default
+ALTER TYPE workspace_permission ADD VALUE IF NOT EXISTS 'member.view';
@@ -0,0 +1,8 @@
+-- Only one pending invitation per address; accepted, declined and expired
+-- ones no longer stop the same address from being invited again
+ALTER TABLE workspace_invitations
+    DROP CONSTRAINT IF EXISTS workspace_invitations_workspace_id_email_status_key;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_workspace_invitation_pending_email
+    ON workspace_invitations(workspace_id, email)
+    WHERE status = 'pending';
@@ -0,0 +1,3 @@
+-- Invitations cancelled by an admin, e.g. because their inviter left; kept
+-- for auditing like expired ones, but their tokens no longer work
+ALTER TYPE invitation_status ADD VALUE IF NOT EXISTS 'revoked';
@@ -0,0 +1,5 @@
+-- Record who revoked an invitation and when, now that revoked invitations
+-- are kept instead of deleted
+ALTER TABLE workspace_invitations
+    ADD COLUMN IF NOT EXISTS revoked_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS revoked_by_user_id UUID REFERENCES users(id) ON DELETE SET NULL;
@@ -0,0 +1,11 @@
+-- Attachment objects are keyed by uploader so storage can be summed per user.
+-- This records which users uploaded to each task, so a task's attachments
+-- can be listed without scanning every uploader's files.
+CREATE TABLE IF NOT EXISTS task_attachments (
+    object_key          TEXT PRIMARY KEY,
+    task_id             UUID NOT NULL REFERENCES shared_tasks(id) ON DELETE CASCADE,
+    uploaded_by_user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at          TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_attachments_task_id ON task_attachments (task_id);
@@ -3,6 +3,7 @@ use std::{net::SocketAddr, sync::Arc};
 use anyhow::{Context, bail};
 use secrecy::ExposeSecret;
 use tracing::instrument;
+use utils::api::workspaces::WorkspacePermission;
 
 use crate::{
     AppState,
@@ -14,9 +15,11 @@ use crate::{
     db,
     files::FilesService,
     github_app::GitHubAppService,
-    mail::LoopsMailer,
+    invitation_cleanup::InvitationCleanupService,
+    mail,
     r2::R2Service,
     routes,
+    webhooks::WebhookService,
 };
 
 pub struct Server;
@@ -36,12 +39,27 @@ impl Server {
             .await
             .context("failed to run database migrations")?;
 
+        let permission_labels = db::enum_labels(&pool, "workspace_permission")
+            .await
+            .context("failed to read workspace_permission enum")?;
+        let permission_keys: Vec<&str> = WorkspacePermission::ALL
+            .into_iter()
+            .map(WorkspacePermission::as_str)
+            .collect();
+        if let Some(mismatch) =
+            db::enum_label_mismatch("workspace_permission", &permission_keys, &permission_labels)
+        {
+            bail!(mismatch);
+        }
+
         if let Some(password) = config.electric_role_password.as_ref() {
             db::ensure_electric_role_password(&pool, password.expose_secret())
                 .await
                 .context("failed to set electric role password")?;
         }
 
+        InvitationCleanupService::spawn(pool.clone(), config.workspace_invitations.clone());
+
         let auth_config = config.auth.clone();
         let jwt = Arc::new(JwtService::new(auth_config.jwt_secret().clone()));
 
@@ -77,9 +95,7 @@ impl Server {
         let oauth_token_validator =
             Arc::new(OAuthTokenValidator::new(pool.clone(), registry.clone()));
 
-        let api_key = std::env::var("LOOPS_EMAIL_API_KEY")
-            .context("LOOPS_EMAIL_API_KEY environment variable is required")?;
-        let mailer = Arc::new(LoopsMailer::new(api_key));
+        let mailer = mail::build_mailer(&config.mail)?;
 
         let server_public_base_url = config.server_public_base_url.clone().ok_or_else(|| {
             anyhow::anyhow!(
@@ -134,6 +150,17 @@ impl Server {
             }
         };
 
+        let webhooks = config
+            .webhooks
+            .clone()
+            .map(|webhook_config| WebhookService::spawn(webhook_config, http_client.clone()));
+        if let Some(webhook_config) = &config.webhooks {
+            tracing::info!(
+                urls = webhook_config.urls.len(),
+                "Workspace membership webhooks enabled"
+            );
+        }
+
         let state = AppState::new(
             pool.clone(),
             config.clone(),
@@ -146,6 +173,7 @@ impl Server {
             r2,
             files,
             github_app,
+            webhooks,
         );
 
         let router = routes::router(state);
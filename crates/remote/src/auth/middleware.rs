@@ -7,6 +7,7 @@ use axum::{
 };
 use axum_extra::headers::{Authorization, HeaderMapExt, authorization::Bearer};
 use chrono::{DateTime, Utc};
+use tower_http::request_id::RequestId;
 use tracing::warn;
 use uuid::Uuid;
 
@@ -24,6 +25,9 @@ pub struct RequestContext {
     pub user: User,
     pub session_id: Uuid,
     pub access_token_expires_at: DateTime<Utc>,
+    /// `X-Request-Id` of this request, for tagging side effects that are
+    /// logged outside the request span
+    pub request_id: Option<String>,
 }
 
 pub async fn require_session(
@@ -98,10 +102,16 @@ pub async fn require_session(
 
     configure_user_scope(user.id, user.username.as_deref(), Some(user.email.as_str()));
 
+    let request_id = req
+        .extensions()
+        .get::<RequestId>()
+        .and_then(|id| id.header_value().to_str().ok())
+        .map(str::to_string);
     req.extensions_mut().insert(RequestContext {
         user,
         session_id: session.id,
         access_token_expires_at: identity.expires_at,
+        request_id,
     });
 
     match session_repo.touch(session.id).await {
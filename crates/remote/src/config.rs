@@ -4,6 +4,8 @@ use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64_STANDARD};
 use secrecy::SecretString;
 use thiserror::Error;
 
+use crate::mail::Locale;
+
 #[derive(Debug, Clone)]
 pub struct RemoteServerConfig {
     pub database_url: String,
@@ -17,6 +19,19 @@ pub struct RemoteServerConfig {
     pub files_r2: Option<FilesR2Config>,
     pub review_worker_base_url: Option<String>,
     pub github_app: Option<GitHubAppConfig>,
+    pub workspace_invitations: WorkspaceInvitationConfig,
+    pub webhooks: Option<WebhookConfig>,
+    /// Hosts besides the files bucket that avatar URLs may point at, e.g.
+    /// `gravatar.com` for orgs that use Gravatar
+    pub avatar_allowed_hosts: Vec<String>,
+    pub mail: MailConfig,
+    /// Locale for emails when the request doesn't name a supported one
+    pub mail_default_locale: Locale,
+    /// Bearer token for operator-only `/admin` endpoints; they are disabled
+    /// when unset
+    pub admin_token: Option<SecretString>,
+    /// How long browsers may cache `GET /files/config`
+    pub files_config_max_age_secs: u64,
 }
 
 #[derive(Debug, Clone)]
@@ -74,7 +89,17 @@ pub struct FilesR2Config {
     pub bucket: String,
     pub public_url: String,
     pub presign_expiry_secs: u64,
+    pub presign_min_expiry_secs: u64,
+    pub presign_max_expiry_secs: u64,
     pub max_file_size_bytes: u64,
+    /// MIME types accepted for task attachments
+    pub attachment_allowed_types: Vec<String>,
+    /// Largest width or height accepted for uploaded images, in pixels
+    pub max_image_dimension_px: u32,
+    pub user_quota_bytes: u64,
+    /// Prepended to every object key, e.g. `staging/`, so environments can
+    /// share a bucket. Empty, or ends with `/`.
+    pub key_prefix: String,
 }
 
 impl FilesR2Config {
@@ -106,16 +131,61 @@ impl FilesR2Config {
             .and_then(|v| v.parse().ok())
             .unwrap_or(300); // 5 minutes default for uploads
 
+        let presign_min_expiry_secs = env::var("R2_FILES_PRESIGN_MIN_EXPIRY_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(60);
+
+        let presign_max_expiry_secs = env::var("R2_FILES_PRESIGN_MAX_EXPIRY_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(3600);
+
         let max_file_size_bytes = env::var("R2_FILES_MAX_SIZE_BYTES")
             .ok()
             .and_then(|v| v.parse().ok())
             .unwrap_or(5 * 1024 * 1024); // 5MB default
 
+        let max_image_dimension_px = env::var("R2_FILES_MAX_IMAGE_DIMENSION")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(4096);
+
+        let user_quota_bytes = env::var("R2_FILES_USER_QUOTA_BYTES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(100 * 1024 * 1024); // 100MB default
+
+        let key_prefix = match env::var("R2_FILES_KEY_PREFIX") {
+            Ok(v) => {
+                normalize_key_prefix(&v).ok_or(ConfigError::InvalidVar("R2_FILES_KEY_PREFIX"))?
+            }
+            Err(_) => String::new(),
+        };
+
+        let attachment_allowed_types: Vec<String> = env::var("R2_FILES_ATTACHMENT_TYPES")
+            .map(|v| {
+                v.split(',')
+                    .map(|content_type| content_type.trim().to_ascii_lowercase())
+                    .filter(|content_type| !content_type.is_empty())
+                    .collect()
+            })
+            .unwrap_or_else(|_| {
+                crate::files::DEFAULT_ATTACHMENT_TYPES
+                    .iter()
+                    .map(|content_type| content_type.to_string())
+                    .collect()
+            });
+
         tracing::info!(
             endpoint = %endpoint,
             bucket = %bucket,
             public_url = %public_url,
+            key_prefix = %key_prefix,
             max_file_size_bytes = %max_file_size_bytes,
+            attachment_allowed_types = %attachment_allowed_types.join(","),
+            max_image_dimension_px = %max_image_dimension_px,
+            user_quota_bytes = %user_quota_bytes,
             "Files R2 config loaded successfully"
         );
 
@@ -126,7 +196,13 @@ impl FilesR2Config {
             bucket,
             public_url,
             presign_expiry_secs,
+            attachment_allowed_types,
+            presign_min_expiry_secs,
+            presign_max_expiry_secs,
             max_file_size_bytes,
+            max_image_dimension_px,
+            user_quota_bytes,
+            key_prefix,
         }))
     }
 }
@@ -180,6 +256,293 @@ impl GitHubAppConfig {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct WorkspaceInvitationConfig {
+    /// Reject acceptance into a workspace that no longer has an admin
+    pub require_active_workspace: bool,
+    /// Only let the invited email address accept; disable for orgs that
+    /// deliberately share invitation links
+    pub require_matching_email: bool,
+    /// Give accounts that accept as their first workspace the invited
+    /// spelling of their address, unless their current one was verified by
+    /// an OAuth provider. Only applies with `require_matching_email`.
+    pub sync_invited_email: bool,
+    /// Refuse invitations granting a role above the inviter's own. Admins
+    /// hold the highest role, so this only limits members granted
+    /// `member.invite`.
+    pub limit_role_to_inviter: bool,
+    /// Lifetime of an invitation link when the inviter doesn't choose one
+    pub invitation_expiry_days: i64,
+    /// Longest lifetime an inviter may request for an invitation link
+    pub max_invitation_expiry_days: i64,
+    /// How often stale invitations are expired and old ones deleted
+    pub cleanup_interval_secs: u64,
+    /// How long expired and revoked invitations are kept before deletion
+    pub cleanup_retention_days: i64,
+    /// Invitations a workspace may send per hour; 0 disables the limit
+    pub invites_per_hour: u32,
+    /// Lowercase email domains (and their subdomains) that can't be invited,
+    /// such as disposable mail providers. Empty by default.
+    pub blocked_email_domains: Vec<String>,
+}
+
+impl WorkspaceInvitationConfig {
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let require_active_workspace = match env::var("WORKSPACE_INVITE_REQUIRE_ACTIVE_WORKSPACE") {
+            Ok(v) => v.parse().map_err(|_| {
+                ConfigError::InvalidVar("WORKSPACE_INVITE_REQUIRE_ACTIVE_WORKSPACE")
+            })?,
+            Err(_) => true,
+        };
+
+        let require_matching_email = match env::var("WORKSPACE_INVITE_REQUIRE_MATCHING_EMAIL") {
+            Ok(v) => v
+                .parse()
+                .map_err(|_| ConfigError::InvalidVar("WORKSPACE_INVITE_REQUIRE_MATCHING_EMAIL"))?,
+            Err(_) => true,
+        };
+
+        let sync_invited_email = match env::var("WORKSPACE_INVITE_SYNC_EMAIL") {
+            Ok(v) => v
+                .parse()
+                .map_err(|_| ConfigError::InvalidVar("WORKSPACE_INVITE_SYNC_EMAIL"))?,
+            Err(_) => true,
+        };
+
+        let limit_role_to_inviter = match env::var("WORKSPACE_INVITE_LIMIT_ROLE_TO_INVITER") {
+            Ok(v) => v
+                .parse()
+                .map_err(|_| ConfigError::InvalidVar("WORKSPACE_INVITE_LIMIT_ROLE_TO_INVITER"))?,
+            Err(_) => true,
+        };
+
+        let max_invitation_expiry_days = match env::var("WORKSPACE_INVITE_MAX_EXPIRY_DAYS") {
+            Ok(v) => v
+                .parse()
+                .ok()
+                .filter(|days| *days >= 1)
+                .ok_or(ConfigError::InvalidVar("WORKSPACE_INVITE_MAX_EXPIRY_DAYS"))?,
+            Err(_) => 30,
+        };
+
+        let invitation_expiry_days = match env::var("WORKSPACE_INVITE_EXPIRY_DAYS") {
+            Ok(v) => v
+                .parse()
+                .ok()
+                .filter(|days| (1..=max_invitation_expiry_days).contains(days))
+                .ok_or(ConfigError::InvalidVar("WORKSPACE_INVITE_EXPIRY_DAYS"))?,
+            Err(_) => 7.min(max_invitation_expiry_days),
+        };
+
+        let cleanup_interval_secs = match env::var("WORKSPACE_INVITE_CLEANUP_INTERVAL_SECS") {
+            Ok(v) => v
+                .parse()
+                .ok()
+                .filter(|secs| *secs > 0)
+                .ok_or(ConfigError::InvalidVar(
+                    "WORKSPACE_INVITE_CLEANUP_INTERVAL_SECS",
+                ))?,
+            Err(_) => 3600,
+        };
+
+        let cleanup_retention_days = match env::var("WORKSPACE_INVITE_RETENTION_DAYS") {
+            Ok(v) => v
+                .parse()
+                .ok()
+                .filter(|days| *days >= 0)
+                .ok_or(ConfigError::InvalidVar("WORKSPACE_INVITE_RETENTION_DAYS"))?,
+            Err(_) => 30,
+        };
+
+        let invites_per_hour = match env::var("WORKSPACE_INVITE_RATE_LIMIT_PER_HOUR") {
+            Ok(v) => v
+                .parse()
+                .map_err(|_| ConfigError::InvalidVar("WORKSPACE_INVITE_RATE_LIMIT_PER_HOUR"))?,
+            Err(_) => 100,
+        };
+
+        let blocked_email_domains = env::var("WORKSPACE_INVITE_BLOCKED_DOMAINS")
+            .map(|v| {
+                v.split(',')
+                    .map(|domain| domain.trim().trim_start_matches('@').to_lowercase())
+                    .filter(|domain| !domain.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(Self {
+            require_active_workspace,
+            require_matching_email,
+            sync_invited_email,
+            limit_role_to_inviter,
+            invitation_expiry_days,
+            max_invitation_expiry_days,
+            cleanup_interval_secs,
+            cleanup_retention_days,
+            invites_per_hour,
+            blocked_email_domains,
+        })
+    }
+
+    pub fn cleanup_retention(&self) -> chrono::Duration {
+        chrono::Duration::days(self.cleanup_retention_days)
+    }
+}
+
+/// Endpoints notified of workspace membership events
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    pub urls: Vec<String>,
+    /// HMAC key for the `X-Vibe-Signature` header
+    pub secret: SecretString,
+}
+
+impl WebhookConfig {
+    pub fn from_env() -> Result<Option<Self>, ConfigError> {
+        let urls: Vec<String> = match env::var("WORKSPACE_WEBHOOK_URLS") {
+            Ok(v) => v
+                .split(',')
+                .map(str::trim)
+                .filter(|url| !url.is_empty())
+                .map(str::to_string)
+                .collect(),
+            Err(_) => return Ok(None),
+        };
+        if urls.is_empty() {
+            return Ok(None);
+        }
+
+        let secret = env::var("WORKSPACE_WEBHOOK_SECRET")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .ok_or(ConfigError::MissingVar("WORKSPACE_WEBHOOK_SECRET"))?;
+
+        Ok(Some(Self {
+            urls,
+            secret: SecretString::new(secret.into()),
+        }))
+    }
+}
+
+/// Transport for outgoing email, picked by `MAIL_PROVIDER`
+#[derive(Debug, Clone)]
+pub enum MailConfig {
+    /// Loops transactional templates (the default)
+    Loops {
+        api_key: SecretString,
+    },
+    Smtp(SmtpConfig),
+    /// Log messages instead of sending them; for local development
+    Log,
+}
+
+impl MailConfig {
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let provider = env::var("MAIL_PROVIDER").unwrap_or_else(|_| "loops".to_string());
+        match provider.trim().to_ascii_lowercase().as_str() {
+            "loops" => {
+                let api_key = env::var("LOOPS_EMAIL_API_KEY")
+                    .ok()
+                    .filter(|s| !s.is_empty())
+                    .ok_or(ConfigError::MissingVar("LOOPS_EMAIL_API_KEY"))?;
+                Ok(Self::Loops {
+                    api_key: SecretString::new(api_key.into()),
+                })
+            }
+            "smtp" => Ok(Self::Smtp(SmtpConfig::from_env()?)),
+            "log" | "none" => Ok(Self::Log),
+            _ => Err(ConfigError::InvalidVar("MAIL_PROVIDER")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmtpTls {
+    /// Upgrade a plain connection with STARTTLS (port 587 by default)
+    StartTls,
+    /// TLS from the first byte (port 465 by default)
+    Implicit,
+    /// Unencrypted; only for local relays such as Mailpit
+    None,
+}
+
+#[derive(Debug, Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: Option<u16>,
+    pub username: Option<String>,
+    pub password: Option<SecretString>,
+    /// Sender mailbox, e.g. `Vibe Kanban <noreply@example.com>`
+    pub from: String,
+    pub tls: SmtpTls,
+}
+
+impl SmtpConfig {
+    fn from_env() -> Result<Self, ConfigError> {
+        let host = env::var("SMTP_HOST").map_err(|_| ConfigError::MissingVar("SMTP_HOST"))?;
+
+        let port = match env::var("SMTP_PORT") {
+            Ok(v) => Some(
+                v.parse()
+                    .map_err(|_| ConfigError::InvalidVar("SMTP_PORT"))?,
+            ),
+            Err(_) => None,
+        };
+
+        let username = env::var("SMTP_USERNAME").ok().filter(|s| !s.is_empty());
+        let password = env::var("SMTP_PASSWORD")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(|s| SecretString::new(s.into()));
+        if username.is_some() != password.is_some() {
+            return Err(ConfigError::MissingVar(if username.is_some() {
+                "SMTP_PASSWORD"
+            } else {
+                "SMTP_USERNAME"
+            }));
+        }
+
+        let from = env::var("SMTP_FROM").map_err(|_| ConfigError::MissingVar("SMTP_FROM"))?;
+
+        let tls = match env::var("SMTP_TLS")
+            .unwrap_or_else(|_| "starttls".to_string())
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "starttls" => SmtpTls::StartTls,
+            "tls" => SmtpTls::Implicit,
+            "none" => SmtpTls::None,
+            _ => return Err(ConfigError::InvalidVar("SMTP_TLS")),
+        };
+
+        Ok(Self {
+            host,
+            port,
+            username,
+            password,
+            from,
+            tls,
+        })
+    }
+}
+
+impl Default for WorkspaceInvitationConfig {
+    fn default() -> Self {
+        Self {
+            require_active_workspace: true,
+            require_matching_email: true,
+            sync_invited_email: true,
+            limit_role_to_inviter: true,
+            invitation_expiry_days: 7,
+            max_invitation_expiry_days: 30,
+            cleanup_interval_secs: 3600,
+            cleanup_retention_days: 30,
+            invites_per_hour: 100,
+            blocked_email_domains: Vec::new(),
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum ConfigError {
     #[error("environment variable `{0}` is not set")]
@@ -222,6 +585,40 @@ impl RemoteServerConfig {
 
         let github_app = GitHubAppConfig::from_env()?;
 
+        let workspace_invitations = WorkspaceInvitationConfig::from_env()?;
+
+        let webhooks = WebhookConfig::from_env()?;
+
+        let avatar_allowed_hosts = env::var("AVATAR_ALLOWED_HOSTS")
+            .map(|v| {
+                v.split(',')
+                    .map(|host| host.trim().to_ascii_lowercase())
+                    .filter(|host| !host.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mail = MailConfig::from_env()?;
+
+        let mail_default_locale = match env::var("MAIL_DEFAULT_LOCALE") {
+            Ok(v) => v
+                .parse()
+                .map_err(|_| ConfigError::InvalidVar("MAIL_DEFAULT_LOCALE"))?,
+            Err(_) => Locale::default(),
+        };
+
+        let admin_token = env::var("REMOTE_ADMIN_TOKEN")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(|s| SecretString::new(s.into()));
+
+        let files_config_max_age_secs = match env::var("FILES_CONFIG_MAX_AGE_SECS") {
+            Ok(v) => v
+                .parse()
+                .map_err(|_| ConfigError::InvalidVar("FILES_CONFIG_MAX_AGE_SECS"))?,
+            Err(_) => 60,
+        };
+
         Ok(Self {
             database_url,
             listen_addr,
@@ -234,6 +631,13 @@ impl RemoteServerConfig {
             files_r2,
             review_worker_base_url,
             github_app,
+            workspace_invitations,
+            webhooks,
+            avatar_allowed_hosts,
+            mail,
+            mail_default_locale,
+            admin_token,
+            files_config_max_age_secs,
         })
     }
 }
@@ -343,3 +747,40 @@ fn validate_jwt_secret(secret: &str) -> Result<(), ConfigError> {
 
     Ok(())
 }
+
+/// `prod`, `/prod` and `prod/` all become `prod/`; blank stays empty. `None`
+/// for prefixes that could escape into another key space.
+fn normalize_key_prefix(raw: &str) -> Option<String> {
+    let trimmed = raw.trim().trim_matches('/');
+    if trimmed.is_empty() {
+        return Some(String::new());
+    }
+    let valid = !trimmed.contains('\\')
+        && !trimmed.chars().any(char::is_control)
+        && trimmed
+            .split('/')
+            .all(|segment| !segment.is_empty() && segment != "." && segment != "..");
+    valid.then(|| format!("{trimmed}/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_key_prefix() {
+        for (raw, expected) in [
+            ("prod", Some("prod/")),
+            ("/prod/", Some("prod/")),
+            ("team/prod", Some("team/prod/")),
+            ("", Some("")),
+            ("  ", Some("")),
+            ("a/../b", None),
+            ("a\\b", None),
+            ("a//b", None),
+            ("a\nb", None),
+        ] {
+            assert_eq!(normalize_key_prefix(raw).as_deref(), expected, "{raw:?}");
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -12,6 +13,16 @@ pub enum IdentityError {
     CannotDeleteOrganization(String),
     #[error("organization conflict: {0}")]
     OrganizationConflict(String),
+    #[error("workspace is no longer available")]
+    WorkspaceUnavailable,
+    #[error("the last admin cannot leave the workspace")]
+    LastAdmin,
+    #[error("invalid ownership transfer: {0}")]
+    InvalidTransfer(String),
+    #[error("invitation is not yet active (active at {0})")]
+    InvitationNotYetActive(DateTime<Utc>),
+    #[error("record was modified since it was read")]
+    StaleVersion,
     #[error(transparent)]
     Database(#[from] sqlx::Error),
 }
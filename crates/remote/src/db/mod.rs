@@ -8,10 +8,12 @@ pub mod organization_members;
 pub mod organizations;
 pub mod projects;
 pub mod reviews;
+pub mod task_attachments;
 pub mod tasks;
 pub mod users;
 pub mod workspace_invitations;
 pub mod workspace_members;
+pub mod workspace_settings;
 
 use sqlx::{PgPool, Postgres, Transaction, migrate::MigrateError, postgres::PgPoolOptions};
 
@@ -21,6 +23,36 @@ pub(crate) async fn migrate(pool: &PgPool) -> Result<(), MigrateError> {
     sqlx::migrate!("./migrations").run(pool).await
 }
 
+/// Migrations for `#[sqlx::test]`, which migrates a fresh database per test on
+/// one cluster. Roles are cluster-wide, so only the first test database to be
+/// migrated can create the Electric role; the others reuse it.
+#[cfg(test)]
+pub(crate) static TEST_MIGRATOR: std::sync::LazyLock<sqlx::migrate::Migrator> =
+    std::sync::LazyLock::new(|| {
+        use sqlx::migrate::Migration;
+
+        let mut migrator = sqlx::migrate!("./migrations");
+        migrator.migrations = migrator
+            .migrations
+            .iter()
+            .map(|migration| {
+                let sql = migration.sql.replace(
+                    "CREATE ROLE electric_sync WITH LOGIN REPLICATION;",
+                    "DO $$ BEGIN CREATE ROLE electric_sync WITH LOGIN REPLICATION; \
+                     EXCEPTION WHEN duplicate_object OR unique_violation THEN NULL; END $$;",
+                );
+                Migration::new(
+                    migration.version,
+                    migration.description.clone(),
+                    migration.migration_type,
+                    sql.into(),
+                    migration.no_tx,
+                )
+            })
+            .collect();
+        migrator
+    });
+
 pub(crate) async fn create_pool(database_url: &str) -> Result<PgPool, sqlx::Error> {
     PgPoolOptions::new()
         .max_connections(10)
@@ -45,3 +77,111 @@ pub(crate) async fn ensure_electric_role_password(
 
     Ok(())
 }
+
+/// Labels of a Postgres enum type, in declaration order.
+pub(crate) async fn enum_labels(
+    pool: &PgPool,
+    type_name: &str,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        SELECT e.enumlabel
+        FROM pg_enum e
+        JOIN pg_type t ON t.oid = e.enumtypid
+        WHERE t.typname = $1
+        ORDER BY e.enumsortorder
+        "#,
+    )
+    .bind(type_name)
+    .fetch_all(pool)
+    .await
+}
+
+/// Describe how the labels of the Postgres enum `type_name` differ from the
+/// ones the matching Rust type encodes to, or `None` when they agree. Either
+/// direction breaks decoding at runtime, so both are reported.
+pub(crate) fn enum_label_mismatch(
+    type_name: &str,
+    expected: &[&str],
+    actual: &[String],
+) -> Option<String> {
+    let missing: Vec<&str> = expected
+        .iter()
+        .copied()
+        .filter(|label| !actual.iter().any(|actual| actual == label))
+        .collect();
+    let unknown: Vec<&str> = actual
+        .iter()
+        .map(String::as_str)
+        .filter(|label| !expected.contains(label))
+        .collect();
+
+    if missing.is_empty() && unknown.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "Postgres enum {type_name} is out of sync with the server: \
+         missing from database [{}], unknown to server [{}]",
+        missing.join(", "),
+        unknown.join(", ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use utils::api::workspaces::WorkspacePermission;
+
+    use super::*;
+
+    fn permission_keys() -> Vec<&'static str> {
+        WorkspacePermission::ALL
+            .into_iter()
+            .map(WorkspacePermission::as_str)
+            .collect()
+    }
+
+    fn labels(labels: &[&str]) -> Vec<String> {
+        labels.iter().map(|label| label.to_string()).collect()
+    }
+
+    #[test]
+    fn test_matching_enum_labels() {
+        // member.view was appended by a later migration, so order differs
+        let actual = labels(&[
+            "member.invite",
+            "member.remove",
+            "member.role.change",
+            "member.view",
+        ]);
+        assert_eq!(
+            enum_label_mismatch("workspace_permission", &permission_keys(), &actual),
+            None
+        );
+    }
+
+    #[test]
+    fn test_enum_label_mismatch_lists_both_directions() {
+        // Database missed the member.view migration and has a stray label
+        let actual = labels(&[
+            "member.invite",
+            "member.remove",
+            "member.role.change",
+            "member.ban",
+        ]);
+        let message =
+            enum_label_mismatch("workspace_permission", &permission_keys(), &actual).unwrap();
+        assert!(message.contains("workspace_permission"));
+        assert!(message.contains("missing from database [member.view]"));
+        assert!(message.contains("unknown to server [member.ban]"));
+    }
+
+    #[test]
+    fn test_permission_keys_match_serialization() {
+        for permission in WorkspacePermission::ALL {
+            assert_eq!(
+                serde_json::to_value(permission).unwrap(),
+                permission.as_str()
+            );
+        }
+    }
+}
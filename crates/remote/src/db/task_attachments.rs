@@ -0,0 +1,101 @@
+use sqlx::{Executor, PgPool, Postgres};
+use uuid::Uuid;
+
+/// Record that `user_id` was given an upload URL for `object_key` on a task
+pub async fn record_upload<'a, E>(
+    executor: E,
+    object_key: &str,
+    task_id: Uuid,
+    user_id: Uuid,
+) -> Result<(), sqlx::Error>
+where
+    E: Executor<'a, Database = Postgres>,
+{
+    sqlx::query(
+        r#"
+        INSERT INTO task_attachments (object_key, task_id, uploaded_by_user_id)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (object_key) DO NOTHING
+        "#,
+    )
+    .bind(object_key)
+    .bind(task_id)
+    .bind(user_id)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Users who have uploaded attachments to a task
+pub async fn uploaders(pool: &PgPool, task_id: Uuid) -> Result<Vec<Uuid>, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        SELECT DISTINCT uploaded_by_user_id
+        FROM task_attachments
+        WHERE task_id = $1
+        "#,
+    )
+    .bind(task_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Forget an attachment once its object is deleted
+pub async fn delete(pool: &PgPool, object_key: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM task_attachments WHERE object_key = $1")
+        .bind(object_key)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[sqlx::test(migrator = "crate::db::TEST_MIGRATOR")]
+    #[ignore = "requires DATABASE_URL pointing at a Postgres server"]
+    async fn test_uploaders_lists_each_user_once_until_deleted(pool: PgPool) {
+        let (user_id, org_id, project_id) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        sqlx::query("INSERT INTO users (id, email) VALUES ($1, 'dana@example.com')")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO organizations (id, name, slug) VALUES ($1, 'X-Files', 'x-files')")
+            .bind(org_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO projects (id, organization_id, name) VALUES ($1, $2, 'Cases')")
+            .bind(project_id)
+            .bind(org_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let task_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO shared_tasks (organization_id, project_id, title)
+             VALUES ($1, $2, 'Case one')
+             RETURNING id",
+        )
+        .bind(org_id)
+        .bind(project_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        let first = format!("attachments/{user_id}/{task_id}/{}.pdf", Uuid::new_v4());
+        let second = format!("attachments/{user_id}/{task_id}/{}.png", Uuid::new_v4());
+        for key in [&first, &second, &first] {
+            record_upload(&pool, key, task_id, user_id).await.unwrap();
+        }
+        assert_eq!(uploaders(&pool, task_id).await.unwrap(), vec![user_id]);
+
+        delete(&pool, &first).await.unwrap();
+        assert_eq!(uploaders(&pool, task_id).await.unwrap(), vec![user_id]);
+        delete(&pool, &second).await.unwrap();
+        assert!(uploaders(&pool, task_id).await.unwrap().is_empty());
+    }
+}
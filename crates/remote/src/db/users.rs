@@ -2,6 +2,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{PgPool, query_as};
 use ts_rs::TS;
+use utils::api::users::UserProfile;
 use uuid::Uuid;
 
 use super::{Tx, identity_errors::IdentityError};
@@ -18,33 +19,6 @@ pub struct User {
     pub updated_at: DateTime<Utc>,
 }
 
-/// User struct without the avatar_url field for backward compatibility with .sqlx cache
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
-struct UserWithoutAvatar {
-    id: Uuid,
-    email: String,
-    first_name: Option<String>,
-    last_name: Option<String>,
-    username: Option<String>,
-    created_at: DateTime<Utc>,
-    updated_at: DateTime<Utc>,
-}
-
-impl From<UserWithoutAvatar> for User {
-    fn from(u: UserWithoutAvatar) -> Self {
-        Self {
-            id: u.id,
-            email: u.email,
-            first_name: u.first_name,
-            last_name: u.last_name,
-            username: u.username,
-            avatar_url: None,
-            created_at: u.created_at,
-            updated_at: u.updated_at,
-        }
-    }
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, TS)]
 #[ts(export)]
 pub struct UserData {
@@ -55,27 +29,6 @@ pub struct UserData {
     pub avatar_url: Option<String>,
 }
 
-/// UserData struct without the avatar_url field for backward compatibility with .sqlx cache
-#[derive(Debug, Clone, sqlx::FromRow)]
-struct UserDataWithoutAvatar {
-    user_id: Uuid,
-    first_name: Option<String>,
-    last_name: Option<String>,
-    username: Option<String>,
-}
-
-impl From<UserDataWithoutAvatar> for UserData {
-    fn from(u: UserDataWithoutAvatar) -> Self {
-        Self {
-            user_id: u.user_id,
-            first_name: u.first_name,
-            last_name: u.last_name,
-            username: u.username,
-            avatar_url: None,
-        }
-    }
-}
-
 #[derive(Debug, Clone)]
 pub struct UpsertUser<'a> {
     pub id: Uuid,
@@ -85,6 +38,32 @@ pub struct UpsertUser<'a> {
     pub username: Option<&'a str>,
 }
 
+/// See `utils::api::users` for how remote fields map to the local model.
+impl From<User> for UserProfile {
+    fn from(user: User) -> Self {
+        Self {
+            id: user.id,
+            email: user.email,
+            first_name: user.first_name,
+            last_name: user.last_name,
+            username: user.username,
+            avatar_url: user.avatar_url,
+        }
+    }
+}
+
+impl<'a> From<&'a UserProfile> for UpsertUser<'a> {
+    fn from(profile: &'a UserProfile) -> Self {
+        Self {
+            id: profile.id,
+            email: &profile.email,
+            first_name: profile.first_name.as_deref(),
+            last_name: profile.last_name.as_deref(),
+            username: profile.username.as_deref(),
+        }
+    }
+}
+
 pub struct UserRepository<'a> {
     pool: &'a PgPool,
 }
@@ -101,10 +80,8 @@ impl<'a> UserRepository<'a> {
     }
 
     pub async fn fetch_user(&self, user_id: Uuid) -> Result<User, IdentityError> {
-        // Note: Use UserWithoutAvatar for compile-time SQLx cache compatibility,
-        // then convert to User. The avatar_url will be fetched at runtime after migration.
         query_as!(
-            UserWithoutAvatar,
+            User,
             r#"
             SELECT
                 id           AS "id!: Uuid",
@@ -112,6 +89,7 @@ impl<'a> UserRepository<'a> {
                 first_name   AS "first_name?",
                 last_name    AS "last_name?",
                 username     AS "username?",
+                avatar_url   AS "avatar_url?",
                 created_at   AS "created_at!",
                 updated_at   AS "updated_at!"
             FROM users
@@ -121,50 +99,35 @@ impl<'a> UserRepository<'a> {
         )
         .fetch_optional(self.pool)
         .await?
-        .map(User::from)
-        .ok_or(IdentityError::NotFound)
-    }
-
-    /// Fetch user with avatar_url (uses raw query, requires migration to be run)
-    pub async fn fetch_user_with_avatar(&self, user_id: Uuid) -> Result<User, IdentityError> {
-        sqlx::query_as::<_, User>(
-            r#"
-            SELECT
-                id,
-                email,
-                first_name,
-                last_name,
-                username,
-                avatar_url,
-                created_at,
-                updated_at
-            FROM users
-            WHERE id = $1
-            "#,
-        )
-        .bind(user_id)
-        .fetch_optional(self.pool)
-        .await?
         .ok_or(IdentityError::NotFound)
     }
 
     /// Fetch all assignees for a given project id.
     /// Returns Vec<UserData> containing all unique users assigned to tasks in the project.
+    /// Users without an uploaded avatar fall back to their most recently
+    /// updated OAuth provider avatar.
     pub async fn fetch_assignees_by_project(
         &self,
         project_id: Uuid,
     ) -> Result<Vec<UserData>, IdentityError> {
-        // Use UserDataWithoutAvatar for compile-time SQLx cache compatibility
-        let rows = sqlx::query_as!(
-            UserDataWithoutAvatar,
+        sqlx::query_as!(
+            UserData,
             r#"
             SELECT DISTINCT
                 u.id         as "user_id",
                 u.first_name as "first_name",
                 u.last_name  as "last_name",
-                u.username   as "username"
+                u.username   as "username",
+                COALESCE(u.avatar_url, oa.avatar_url) as "avatar_url?"
             FROM shared_tasks st
             INNER JOIN users u ON u.id = st.assignee_user_id
+            LEFT JOIN LATERAL (
+                SELECT avatar_url
+                FROM oauth_accounts
+                WHERE user_id = u.id AND avatar_url IS NOT NULL
+                ORDER BY updated_at DESC
+                LIMIT 1
+            ) oa ON TRUE
             WHERE st.project_id = $1
             AND st.assignee_user_id IS NOT NULL
             "#,
@@ -172,22 +135,24 @@ impl<'a> UserRepository<'a> {
         )
         .fetch_all(self.pool)
         .await
-        .map_err(IdentityError::from)?;
-
-        Ok(rows.into_iter().map(UserData::from).collect())
+        .map_err(IdentityError::from)
     }
 
-    /// Update the avatar_url for a user (uses raw query, requires migration to be run)
+    /// Update the avatar_url for a user. With `expected_updated_at` the update
+    /// only applies if the row still has that `updated_at`, failing with
+    /// `StaleVersion` when another write got there first.
     pub async fn update_avatar_url(
         &self,
         user_id: Uuid,
         avatar_url: Option<&str>,
+        expected_updated_at: Option<DateTime<Utc>>,
     ) -> Result<User, IdentityError> {
-        sqlx::query_as::<_, User>(
+        let user = sqlx::query_as::<_, User>(
             r#"
             UPDATE users
             SET avatar_url = $2
             WHERE id = $1
+              AND ($3::timestamptz IS NULL OR updated_at = $3)
             RETURNING
                 id,
                 email,
@@ -201,16 +166,25 @@ impl<'a> UserRepository<'a> {
         )
         .bind(user_id)
         .bind(avatar_url)
+        .bind(expected_updated_at)
         .fetch_optional(self.pool)
-        .await?
-        .ok_or(IdentityError::NotFound)
+        .await?;
+
+        match user {
+            Some(user) => Ok(user),
+            None if expected_updated_at.is_some() => {
+                // Tell a missing user apart from a lost race
+                self.fetch_user(user_id).await?;
+                Err(IdentityError::StaleVersion)
+            }
+            None => Err(IdentityError::NotFound),
+        }
     }
 }
 
 async fn upsert_user(pool: &PgPool, user: &UpsertUser<'_>) -> Result<User, sqlx::Error> {
-    // Use UserWithoutAvatar for compile-time SQLx cache compatibility
     query_as!(
-        UserWithoutAvatar,
+        User,
         r#"
         INSERT INTO users (id, email, first_name, last_name, username)
         VALUES ($1, $2, $3, $4, $5)
@@ -225,6 +199,7 @@ async fn upsert_user(pool: &PgPool, user: &UpsertUser<'_>) -> Result<User, sqlx:
             first_name   AS "first_name?",
             last_name    AS "last_name?",
             username     AS "username?",
+            avatar_url   AS "avatar_url?",
             created_at   AS "created_at!",
             updated_at   AS "updated_at!"
         "#,
@@ -236,18 +211,18 @@ async fn upsert_user(pool: &PgPool, user: &UpsertUser<'_>) -> Result<User, sqlx:
     )
     .fetch_one(pool)
     .await
-    .map(User::from)
 }
 
 pub async fn fetch_user(tx: &mut Tx<'_>, user_id: Uuid) -> Result<Option<UserData>, IdentityError> {
-    // Use raw query without avatar_url for SQLx cache compatibility
-    sqlx::query!(
+    sqlx::query_as!(
+        UserData,
         r#"
         SELECT
-            id         AS "id!: Uuid",
+            id         AS "user_id!: Uuid",
             first_name AS "first_name?",
             last_name  AS "last_name?",
-            username   AS "username?"
+            username   AS "username?",
+            avatar_url AS "avatar_url?"
         FROM users
         WHERE id = $1
         "#,
@@ -256,13 +231,158 @@ pub async fn fetch_user(tx: &mut Tx<'_>, user_id: Uuid) -> Result<Option<UserDat
     .fetch_optional(&mut **tx)
     .await
     .map_err(IdentityError::from)
-    .map(|row_opt| {
-        row_opt.map(|row| UserData {
-            user_id: row.id,
-            first_name: row.first_name,
-            last_name: row.last_name,
-            username: row.username,
-            avatar_url: None, // Will be populated after migration runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[sqlx::test(migrator = "crate::db::TEST_MIGRATOR")]
+    #[ignore = "requires DATABASE_URL pointing at a Postgres server"]
+    async fn test_fetch_user_returns_avatar_url(pool: PgPool) {
+        let repo = UserRepository::new(&pool);
+        let id = Uuid::new_v4();
+        repo.upsert_user(UpsertUser {
+            id,
+            email: "dana@example.com",
+            first_name: Some("Dana"),
+            last_name: Some("Scully"),
+            username: Some("dscully"),
         })
-    })
+        .await
+        .unwrap();
+        repo.update_avatar_url(id, Some("https://cdn.example.com/dana.png"), None)
+            .await
+            .unwrap();
+
+        let user = repo.fetch_user(id).await.unwrap();
+        assert_eq!(
+            user.avatar_url.as_deref(),
+            Some("https://cdn.example.com/dana.png")
+        );
+
+        let mut tx = pool.begin().await.unwrap();
+        let data = fetch_user(&mut tx, id).await.unwrap().unwrap();
+        assert_eq!(data.avatar_url, user.avatar_url);
+    }
+
+    #[sqlx::test(migrator = "crate::db::TEST_MIGRATOR")]
+    #[ignore = "requires DATABASE_URL pointing at a Postgres server"]
+    async fn test_fetch_assignees_by_project_returns_each_user_once(pool: PgPool) {
+        let repo = UserRepository::new(&pool);
+        let user_id = Uuid::new_v4();
+        repo.upsert_user(UpsertUser {
+            id: user_id,
+            email: "fox@example.com",
+            first_name: Some("Fox"),
+            last_name: Some("Mulder"),
+            username: Some("fmulder"),
+        })
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO oauth_accounts (user_id, provider, provider_user_id, avatar_url)
+             VALUES ($1, 'github', 'fmulder', 'https://avatars.example.com/fmulder.png')",
+        )
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let (org_id, project_id) = (Uuid::new_v4(), Uuid::new_v4());
+        sqlx::query("INSERT INTO organizations (id, name, slug) VALUES ($1, 'X-Files', 'x-files')")
+            .bind(org_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO projects (id, organization_id, name) VALUES ($1, $2, 'Cases')")
+            .bind(project_id)
+            .bind(org_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        for title in ["Case one", "Case two"] {
+            sqlx::query(
+                "INSERT INTO shared_tasks (organization_id, project_id, assignee_user_id, title)
+                 VALUES ($1, $2, $3, $4)",
+            )
+            .bind(org_id)
+            .bind(project_id)
+            .bind(user_id)
+            .bind(title)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let assignees = repo.fetch_assignees_by_project(project_id).await.unwrap();
+        assert_eq!(assignees.len(), 1);
+        assert_eq!(assignees[0].user_id, user_id);
+        assert_eq!(
+            assignees[0].avatar_url.as_deref(),
+            Some("https://avatars.example.com/fmulder.png")
+        );
+
+        // An uploaded avatar wins over the provider one
+        repo.update_avatar_url(user_id, Some("https://cdn.example.com/fox.png"), None)
+            .await
+            .unwrap();
+        let assignees = repo.fetch_assignees_by_project(project_id).await.unwrap();
+        assert_eq!(
+            assignees[0].avatar_url.as_deref(),
+            Some("https://cdn.example.com/fox.png")
+        );
+    }
+
+    #[sqlx::test(migrator = "crate::db::TEST_MIGRATOR")]
+    #[ignore = "requires DATABASE_URL pointing at a Postgres server"]
+    async fn test_conditional_avatar_update_rejects_stale_version(pool: PgPool) {
+        let repo = UserRepository::new(&pool);
+        let id = Uuid::new_v4();
+        let user = repo
+            .upsert_user(UpsertUser {
+                id,
+                email: "walter@example.com",
+                first_name: Some("Walter"),
+                last_name: Some("Skinner"),
+                username: Some("wskinner"),
+            })
+            .await
+            .unwrap();
+
+        // The version read before the first write is accepted once
+        let updated = repo
+            .update_avatar_url(
+                id,
+                Some("https://cdn.example.com/first.png"),
+                Some(user.updated_at),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            updated.avatar_url.as_deref(),
+            Some("https://cdn.example.com/first.png")
+        );
+
+        // A second tab still holding that version loses the race
+        let err = repo
+            .update_avatar_url(
+                id,
+                Some("https://cdn.example.com/second.png"),
+                Some(user.updated_at),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, IdentityError::StaleVersion));
+        assert_eq!(
+            repo.fetch_user(id).await.unwrap().avatar_url.as_deref(),
+            Some("https://cdn.example.com/first.png")
+        );
+
+        let err = repo
+            .update_avatar_url(Uuid::new_v4(), None, Some(user.updated_at))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, IdentityError::NotFound));
+    }
 }
@@ -1,13 +1,18 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, PgPool};
+use sqlx::{Executor, FromRow, PgPool, Postgres};
 pub use utils::api::organizations::InvitationStatus;
 use uuid::Uuid;
 
 use super::{
     identity_errors::IdentityError,
-    workspace_members::{MemberRole, add_member, assert_admin, is_member},
+    workspace_members::{
+        MemberRole, WorkspacePermission, add_member, assert_admin, assert_permission,
+        check_user_role, is_member, role_rank, workspace_has_admin,
+    },
+    workspace_settings::{WorkspaceSettings, get_settings},
 };
+use crate::config::WorkspaceInvitationConfig;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct WorkspaceInvitation {
@@ -21,6 +26,42 @@ pub struct WorkspaceInvitation {
     pub expires_at: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub revoked_by_user_id: Option<Uuid>,
+}
+
+/// A pending invitation as shown to its recipient
+#[derive(Debug, Clone, FromRow)]
+pub struct PendingInvitation {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub role: MemberRole,
+    pub token: String,
+    pub invited_by_username: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// An invitation looked up by its token, with the inviter's display name for
+/// the accept page
+#[derive(Debug, Clone, FromRow)]
+pub struct InvitationLookup {
+    #[sqlx(flatten)]
+    pub invitation: WorkspaceInvitation,
+    pub invited_by_name: Option<String>,
+}
+
+/// A page of invitations and the number matching across all pages
+#[derive(Debug, Clone)]
+pub struct InvitationPage {
+    pub invitations: Vec<WorkspaceInvitation>,
+    pub total: i64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct InvitationCleanup {
+    pub expired: u64,
+    pub deleted: u64,
 }
 
 pub struct WorkspaceInvitationRepository<'a> {
@@ -32,6 +73,9 @@ impl<'a> WorkspaceInvitationRepository<'a> {
         Self { pool }
     }
 
+    /// Invite `email` to the workspace. If it already has a pending
+    /// invitation, that one takes the new role, inviter, token and expiry
+    /// instead, so the previously sent link stops working.
     pub async fn create_invitation(
         &self,
         workspace_id: Uuid,
@@ -41,15 +85,95 @@ impl<'a> WorkspaceInvitationRepository<'a> {
         expires_at: DateTime<Utc>,
         token: &str,
     ) -> Result<WorkspaceInvitation, IdentityError> {
-        assert_admin(self.pool, workspace_id, invited_by_user_id).await?;
+        assert_permission(
+            self.pool,
+            workspace_id,
+            invited_by_user_id,
+            WorkspacePermission::MemberInvite,
+        )
+        .await?;
 
-        let invitation: WorkspaceInvitation = sqlx::query_as(
+        insert_invitation(
+            self.pool,
+            workspace_id,
+            invited_by_user_id,
+            email,
+            role,
+            expires_at,
+            token,
+        )
+        .await
+    }
+
+    /// Invite each `(email, token)` pair in one transaction. As with
+    /// [`Self::create_invitation`], an address with a pending invitation has
+    /// that one updated instead.
+    pub async fn create_invitations(
+        &self,
+        workspace_id: Uuid,
+        invited_by_user_id: Uuid,
+        invitees: &[(String, String)],
+        role: MemberRole,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Vec<WorkspaceInvitation>, IdentityError> {
+        assert_permission(
+            self.pool,
+            workspace_id,
+            invited_by_user_id,
+            WorkspacePermission::MemberInvite,
+        )
+        .await?;
+
+        let mut tx = self.pool.begin().await?;
+        let mut invitations = Vec::with_capacity(invitees.len());
+
+        for (email, token) in invitees {
+            invitations.push(
+                insert_invitation(
+                    &mut *tx,
+                    workspace_id,
+                    invited_by_user_id,
+                    email,
+                    role,
+                    expires_at,
+                    token,
+                )
+                .await?,
+            );
+        }
+
+        tx.commit().await?;
+
+        Ok(invitations)
+    }
+
+    /// One page of a workspace's invitations with `status`, newest first,
+    /// along with how many there are in total.
+    pub async fn list_invitations(
+        &self,
+        workspace_id: Uuid,
+        requesting_user_id: Uuid,
+        status: InvitationStatus,
+        limit: i64,
+        offset: i64,
+    ) -> Result<InvitationPage, IdentityError> {
+        assert_admin(self.pool, workspace_id, requesting_user_id).await?;
+
+        let total: i64 = sqlx::query_scalar(
             r#"
-            INSERT INTO workspace_invitations (
-                workspace_id, invited_by_user_id, email, role, token, expires_at
-            )
-            VALUES ($1, $2, $3, $4, $5, $6)
-            RETURNING
+            SELECT COUNT(*)
+            FROM workspace_invitations
+            WHERE workspace_id = $1 AND status = $2
+            "#,
+        )
+        .bind(workspace_id)
+        .bind(status)
+        .fetch_one(self.pool)
+        .await?;
+
+        let invitations: Vec<WorkspaceInvitation> = sqlx::query_as(
+            r#"
+            SELECT
                 id,
                 workspace_id,
                 invited_by_user_id,
@@ -59,41 +183,106 @@ impl<'a> WorkspaceInvitationRepository<'a> {
                 token,
                 expires_at,
                 created_at,
-                updated_at
+                updated_at,
+                revoked_at,
+                revoked_by_user_id
+            FROM workspace_invitations
+            WHERE workspace_id = $1 AND status = $2
+            ORDER BY created_at DESC, id
+            LIMIT $3 OFFSET $4
             "#,
         )
         .bind(workspace_id)
-        .bind(invited_by_user_id)
-        .bind(email)
-        .bind(role)
+        .bind(status)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(InvitationPage { invitations, total })
+    }
+
+    pub async fn get_invitation_by_token(
+        &self,
+        token: &str,
+    ) -> Result<InvitationLookup, IdentityError> {
+        let invitation: Option<InvitationLookup> = sqlx::query_as(
+            r#"
+            SELECT
+                wi.id,
+                wi.workspace_id,
+                wi.invited_by_user_id,
+                wi.email,
+                wi.role,
+                wi.status,
+                wi.token,
+                wi.expires_at,
+                wi.created_at,
+                wi.updated_at,
+                wi.revoked_at,
+                wi.revoked_by_user_id,
+                COALESCE(NULLIF(concat_ws(' ', u.first_name, u.last_name), ''), u.username)
+                    AS invited_by_name
+            FROM workspace_invitations wi
+            LEFT JOIN users u ON u.id = wi.invited_by_user_id
+            WHERE wi.token = $1
+            "#,
+        )
         .bind(token)
-        .bind(expires_at)
-        .fetch_one(self.pool)
-        .await
-        .map_err(|e| {
-            if let Some(db_err) = e.as_database_error()
-                && db_err.is_unique_violation()
-            {
-                return IdentityError::InvitationError(
-                    "A pending invitation already exists for this email".to_string(),
-                );
-            }
-            IdentityError::from(e)
-        })?;
+        .fetch_optional(self.pool)
+        .await?;
 
-        Ok(invitation)
+        invitation.ok_or(IdentityError::NotFound)
     }
 
-    pub async fn list_invitations(
+    /// Pending, unexpired invitations addressed to `email`, newest first
+    pub async fn list_for_email(
+        &self,
+        email: &str,
+    ) -> Result<Vec<PendingInvitation>, IdentityError> {
+        let invitations: Vec<PendingInvitation> = sqlx::query_as(
+            r#"
+            SELECT
+                wi.id,
+                wi.workspace_id,
+                wi.role,
+                wi.token,
+                u.username AS invited_by_username,
+                wi.created_at,
+                wi.expires_at
+            FROM workspace_invitations wi
+            LEFT JOIN users u ON u.id = wi.invited_by_user_id
+            WHERE lower(wi.email) = lower($1)
+              AND wi.status = 'pending'
+              AND wi.expires_at > NOW()
+            ORDER BY wi.created_at DESC
+            "#,
+        )
+        .bind(email)
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(invitations)
+    }
+
+    /// Revoke a pending invitation so its token stops working. The row is
+    /// kept with who revoked it and when, for auditing.
+    pub async fn revoke_invitation(
         &self,
         workspace_id: Uuid,
+        invitation_id: Uuid,
         requesting_user_id: Uuid,
-    ) -> Result<Vec<WorkspaceInvitation>, IdentityError> {
+    ) -> Result<WorkspaceInvitation, IdentityError> {
         assert_admin(self.pool, workspace_id, requesting_user_id).await?;
 
-        let invitations: Vec<WorkspaceInvitation> = sqlx::query_as(
+        let invitation: Option<WorkspaceInvitation> = sqlx::query_as(
             r#"
-            SELECT
+            UPDATE workspace_invitations
+            SET status = 'revoked',
+                revoked_at = NOW(),
+                revoked_by_user_id = $3
+            WHERE id = $1 AND workspace_id = $2 AND status = 'pending'
+            RETURNING
                 id,
                 workspace_id,
                 invited_by_user_id,
@@ -103,26 +292,65 @@ impl<'a> WorkspaceInvitationRepository<'a> {
                 token,
                 expires_at,
                 created_at,
-                updated_at
-            FROM workspace_invitations
+                updated_at,
+                revoked_at,
+                revoked_by_user_id
+            "#,
+        )
+        .bind(invitation_id)
+        .bind(workspace_id)
+        .bind(requesting_user_id)
+        .fetch_optional(self.pool)
+        .await?;
+
+        invitation.ok_or(IdentityError::NotFound)
+    }
+
+    /// Revoke every pending invitation `inviter_user_id` sent to the
+    /// workspace, e.g. once they have left it, attributing the revocation to
+    /// `requesting_user_id`. Returns how many were revoked.
+    pub async fn revoke_pending_by_inviter(
+        &self,
+        workspace_id: Uuid,
+        inviter_user_id: Uuid,
+        requesting_user_id: Uuid,
+    ) -> Result<u64, IdentityError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE workspace_invitations
+            SET status = 'revoked',
+                revoked_at = NOW(),
+                revoked_by_user_id = $3
             WHERE workspace_id = $1
-            ORDER BY created_at DESC
+              AND invited_by_user_id = $2
+              AND status = 'pending'
             "#,
         )
         .bind(workspace_id)
-        .fetch_all(self.pool)
+        .bind(inviter_user_id)
+        .bind(requesting_user_id)
+        .execute(self.pool)
         .await?;
 
-        Ok(invitations)
+        Ok(result.rows_affected())
     }
 
-    pub async fn get_invitation_by_token(
+    /// Mark a pending invitation as expired without deleting it, so it stays
+    /// visible in the invitation list while its token stops working.
+    pub async fn expire_invitation(
         &self,
-        token: &str,
+        workspace_id: Uuid,
+        invitation_id: Uuid,
+        requesting_user_id: Uuid,
     ) -> Result<WorkspaceInvitation, IdentityError> {
+        assert_admin(self.pool, workspace_id, requesting_user_id).await?;
+
         let invitation: Option<WorkspaceInvitation> = sqlx::query_as(
             r#"
-            SELECT
+            UPDATE workspace_invitations
+            SET status = 'expired'
+            WHERE id = $1 AND workspace_id = $2 AND status = 'pending'
+            RETURNING
                 id,
                 workspace_id,
                 invited_by_user_id,
@@ -132,48 +360,197 @@ impl<'a> WorkspaceInvitationRepository<'a> {
                 token,
                 expires_at,
                 created_at,
-                updated_at
-            FROM workspace_invitations
-            WHERE token = $1
+                updated_at,
+                revoked_at,
+                revoked_by_user_id
             "#,
         )
-        .bind(token)
+        .bind(invitation_id)
+        .bind(workspace_id)
         .fetch_optional(self.pool)
         .await?;
 
         invitation.ok_or(IdentityError::NotFound)
     }
 
-    pub async fn revoke_invitation(
+    /// Push back the expiry of a pending invitation by `additional_days`,
+    /// keeping its token so links already sent keep working. Invitations
+    /// that were accepted, declined or have expired can't be extended.
+    pub async fn extend_expiry(
         &self,
         workspace_id: Uuid,
         invitation_id: Uuid,
-        requesting_user_id: Uuid,
-    ) -> Result<(), IdentityError> {
-        assert_admin(self.pool, workspace_id, requesting_user_id).await?;
+        additional_days: i64,
+    ) -> Result<WorkspaceInvitation, IdentityError> {
+        let mut tx = self.pool.begin().await?;
 
-        let result = sqlx::query(
+        let invitation: Option<WorkspaceInvitation> = sqlx::query_as(
             r#"
-            DELETE FROM workspace_invitations
+            SELECT
+                id,
+                workspace_id,
+                invited_by_user_id,
+                email,
+                role,
+                status,
+                token,
+                expires_at,
+                created_at,
+                updated_at,
+                revoked_at,
+                revoked_by_user_id
+            FROM workspace_invitations
             WHERE id = $1 AND workspace_id = $2
+            FOR UPDATE
             "#,
         )
         .bind(invitation_id)
         .bind(workspace_id)
-        .execute(self.pool)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let invitation = invitation.ok_or(IdentityError::NotFound)?;
+        ensure_extendable(&invitation, Utc::now())?;
+
+        let invitation: WorkspaceInvitation = sqlx::query_as(
+            r#"
+            UPDATE workspace_invitations
+            SET expires_at = $2
+            WHERE id = $1
+            RETURNING
+                id,
+                workspace_id,
+                invited_by_user_id,
+                email,
+                role,
+                status,
+                token,
+                expires_at,
+                created_at,
+                updated_at,
+                revoked_at,
+                revoked_by_user_id
+            "#,
+        )
+        .bind(invitation.id)
+        .bind(invitation.expires_at + Duration::days(additional_days))
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(invitation)
+    }
+
+    /// Decline a pending invitation without joining the workspace. Declining
+    /// an already-declined invitation succeeds without changes.
+    pub async fn decline_invitation(
+        &self,
+        token: &str,
+        user_email: &str,
+        config: &WorkspaceInvitationConfig,
+    ) -> Result<(), IdentityError> {
+        let mut tx = self.pool.begin().await?;
+
+        let invitation: Option<WorkspaceInvitation> = sqlx::query_as(
+            r#"
+            SELECT
+                id,
+                workspace_id,
+                invited_by_user_id,
+                email,
+                role,
+                status,
+                token,
+                expires_at,
+                created_at,
+                updated_at,
+                revoked_at,
+                revoked_by_user_id
+            FROM workspace_invitations
+            WHERE token = $1
+            FOR UPDATE
+            "#,
+        )
+        .bind(token)
+        .fetch_optional(&mut *tx)
         .await?;
 
-        if result.rows_affected() == 0 {
-            return Err(IdentityError::NotFound);
+        let invitation = invitation.ok_or(IdentityError::NotFound)?;
+        ensure_email_matches(config, &invitation.email, user_email)?;
+
+        if !needs_decline(&invitation)? {
+            tx.rollback().await?;
+            return Ok(());
         }
 
+        sqlx::query(
+            r#"
+            UPDATE workspace_invitations
+            SET status = 'declined'
+            WHERE id = $1
+            "#,
+        )
+        .bind(invitation.id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
         Ok(())
     }
 
+    /// Mark pending invitations whose `expires_at` has passed as expired,
+    /// returning how many were updated.
+    pub async fn expire_stale(&self) -> Result<u64, IdentityError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE workspace_invitations
+            SET status = 'expired'
+            WHERE status = 'pending'
+              AND expires_at < NOW()
+            "#,
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Delete expired and revoked invitations last touched before `cutoff`.
+    /// Revoked rows keep their attribution until then.
+    pub async fn delete_finished_before(
+        &self,
+        cutoff: DateTime<Utc>,
+    ) -> Result<u64, IdentityError> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM workspace_invitations
+            WHERE status IN ('expired', 'revoked')
+              AND updated_at < $1
+            "#,
+        )
+        .bind(cutoff)
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Expire past-due invitations, then delete finished ones older than
+    /// `retention`.
+    pub async fn cleanup(&self, retention: Duration) -> Result<InvitationCleanup, IdentityError> {
+        let deleted = self.delete_finished_before(Utc::now() - retention).await?;
+        let expired = self.expire_stale().await?;
+        Ok(InvitationCleanup { expired, deleted })
+    }
+
     pub async fn accept_invitation(
         &self,
         token: &str,
         user_id: Uuid,
+        user_email: &str,
+        config: &WorkspaceInvitationConfig,
     ) -> Result<(Uuid, MemberRole), IdentityError> {
         let mut tx = self.pool.begin().await?;
 
@@ -189,9 +566,11 @@ impl<'a> WorkspaceInvitationRepository<'a> {
                 token,
                 expires_at,
                 created_at,
-                updated_at
+                updated_at,
+                revoked_at,
+                revoked_by_user_id
             FROM workspace_invitations
-            WHERE token = $1 AND status = 'pending'
+            WHERE token = $1
             FOR UPDATE
             "#,
         )
@@ -199,9 +578,7 @@ impl<'a> WorkspaceInvitationRepository<'a> {
         .fetch_optional(&mut *tx)
         .await?;
 
-        let invitation = invitation.ok_or_else(|| {
-            IdentityError::InvitationError("Invitation not found or already used".to_string())
-        })?;
+        let invitation = ensure_pending(invitation)?;
 
         if invitation.expires_at < Utc::now() {
             sqlx::query(
@@ -221,17 +598,46 @@ impl<'a> WorkspaceInvitationRepository<'a> {
             ));
         }
 
-        if is_member(&mut *tx, invitation.workspace_id, user_id).await? {
+        // Leave the invitation pending so the intended recipient can still use it
+        if let Err(e) = ensure_email_matches(config, &invitation.email, user_email) {
             tx.rollback().await?;
-            return Err(IdentityError::InvitationError(
-                "You are already a member of this workspace".to_string(),
-            ));
+            return Err(e);
         }
 
-        add_member(
-            &mut *tx,
-            invitation.workspace_id,
-            user_id,
+        // Too-early acceptance leaves the invitation pending so it can be retried
+        let settings = get_settings(&mut *tx, invitation.workspace_id).await?;
+        if let Err(e) = ensure_invitation_active(&settings, invitation.created_at, Utc::now()) {
+            tx.rollback().await?;
+            return Err(e);
+        }
+
+        // Leave the invitation pending so it can still be used if the workspace
+        // gets an admin again
+        if config.require_active_workspace
+            && !workspace_has_admin(&mut *tx, invitation.workspace_id).await?
+        {
+            tx.rollback().await?;
+            return Err(IdentityError::WorkspaceUnavailable);
+        }
+
+        if is_member(&mut *tx, invitation.workspace_id, user_id).await? {
+            tx.rollback().await?;
+            return Err(IdentityError::InvitationError(
+                "You are already a member of this workspace".to_string(),
+            ));
+        }
+
+        // Before add_member, which would stop the account counting as new.
+        // Shared links skip it: whoever holds the link must not take over the
+        // invited address.
+        if config.sync_invited_email && config.require_matching_email {
+            sync_invited_email(&mut *tx, user_id, &invitation.email).await?;
+        }
+
+        add_member(
+            &mut *tx,
+            invitation.workspace_id,
+            user_id,
             invitation.role,
         )
         .await?;
@@ -252,3 +658,1546 @@ impl<'a> WorkspaceInvitationRepository<'a> {
         Ok((invitation.workspace_id, invitation.role))
     }
 }
+
+/// Insert an invitation, or refresh the pending one for the same address
+async fn insert_invitation<'e, E>(
+    executor: E,
+    workspace_id: Uuid,
+    invited_by_user_id: Uuid,
+    email: &str,
+    role: MemberRole,
+    expires_at: DateTime<Utc>,
+    token: &str,
+) -> Result<WorkspaceInvitation, IdentityError>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    let invitation: WorkspaceInvitation = sqlx::query_as(
+        r#"
+        INSERT INTO workspace_invitations (
+            workspace_id, invited_by_user_id, email, role, token, expires_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (workspace_id, email) WHERE status = 'pending'
+        DO UPDATE SET
+            invited_by_user_id = EXCLUDED.invited_by_user_id,
+            role = EXCLUDED.role,
+            token = EXCLUDED.token,
+            expires_at = EXCLUDED.expires_at,
+            created_at = NOW()
+        RETURNING
+            id,
+            workspace_id,
+            invited_by_user_id,
+            email,
+            role,
+            status,
+            token,
+            expires_at,
+            created_at,
+            updated_at,
+            revoked_at,
+            revoked_by_user_id
+        "#,
+    )
+    .bind(workspace_id)
+    .bind(invited_by_user_id)
+    .bind(email)
+    .bind(role)
+    .bind(token)
+    .bind(expires_at)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(invitation)
+}
+
+/// Give a brand-new account the address it was invited at, so its profile
+/// agrees with the email invitations are matched against. Only called once
+/// the addresses are known to match, so this only changes their case. An
+/// account is new until it joins its first workspace. An email some linked
+/// OAuth provider reported is treated as verified and kept, as is the
+/// account's email when another account already holds the invited address.
+async fn sync_invited_email<'e, E>(
+    executor: E,
+    user_id: Uuid,
+    invited_email: &str,
+) -> Result<bool, IdentityError>
+where
+    E: Executor<'e, Database = Postgres>,
+{
+    let result = sqlx::query(
+        r#"
+        UPDATE users u
+        SET email = $2
+        WHERE u.id = $1
+          AND u.email <> $2
+          AND NOT EXISTS (
+              SELECT 1
+              FROM workspace_member_metadata wmm
+              WHERE wmm.user_id = u.id
+          )
+          AND NOT EXISTS (
+              SELECT 1
+              FROM oauth_accounts oa
+              WHERE oa.user_id = u.id AND LOWER(oa.email) = LOWER(u.email)
+          )
+          AND NOT EXISTS (
+              SELECT 1
+              FROM users other
+              WHERE other.id <> u.id AND LOWER(other.email) = LOWER($2)
+          )
+        "#,
+    )
+    .bind(user_id)
+    .bind(invited_email.trim().to_lowercase())
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Work out when a new invitation expires, using the inviter's requested
+/// lifetime if it is within the configured limit.
+pub fn invitation_expires_at(
+    config: &WorkspaceInvitationConfig,
+    requested_days: Option<i64>,
+    now: DateTime<Utc>,
+) -> Result<DateTime<Utc>, IdentityError> {
+    let days = requested_days.unwrap_or(config.invitation_expiry_days);
+    if !(1..=config.max_invitation_expiry_days).contains(&days) {
+        return Err(IdentityError::InvitationError(format!(
+            "Invitation expiry must be between 1 and {} days",
+            config.max_invitation_expiry_days
+        )));
+    }
+    Ok(now + Duration::days(days))
+}
+
+/// Trim and de-duplicate (case-insensitively) a pasted list of addresses,
+/// keeping the first spelling of each. Returns the usable addresses and the
+/// ones that don't look like an email at all.
+pub fn partition_bulk_emails(emails: &[String]) -> (Vec<String>, Vec<String>) {
+    let mut seen = std::collections::HashSet::new();
+    let mut valid = Vec::new();
+    let mut invalid = Vec::new();
+
+    for email in emails {
+        let email = email.trim();
+        if email.is_empty() || !seen.insert(email.to_lowercase()) {
+            continue;
+        }
+        if is_plausible_email(email) {
+            valid.push(email.to_string());
+        } else {
+            invalid.push(email.to_string());
+        }
+    }
+
+    (valid, invalid)
+}
+
+/// Reject addresses an invitation can't usefully be sent to: malformed ones,
+/// and ones on a domain from `blocked_email_domains`.
+pub fn validate_invite_email(
+    config: &WorkspaceInvitationConfig,
+    email: &str,
+) -> Result<(), IdentityError> {
+    if !is_plausible_email(email) {
+        return Err(IdentityError::InvitationError(
+            "Invalid email address".to_string(),
+        ));
+    }
+    if let Some(domain) = blocked_domain(config, email) {
+        return Err(IdentityError::InvitationError(format!(
+            "Invitations to {domain} addresses are not allowed"
+        )));
+    }
+    Ok(())
+}
+
+/// With `limit_role_to_inviter`, an invitation may not grant a role that
+/// outranks the inviter's own.
+pub fn validate_invite_role(
+    config: &WorkspaceInvitationConfig,
+    inviter_role: MemberRole,
+    role: MemberRole,
+) -> Result<(), IdentityError> {
+    if config.limit_role_to_inviter && role_rank(role) > role_rank(inviter_role) {
+        return Err(IdentityError::PermissionDenied);
+    }
+    Ok(())
+}
+
+/// Check `validate_invite_role` against the inviter's current role in the
+/// workspace. Non-members get `PermissionDenied` as well.
+pub async fn assert_can_invite_with_role(
+    pool: &PgPool,
+    config: &WorkspaceInvitationConfig,
+    workspace_id: Uuid,
+    inviter_user_id: Uuid,
+    role: MemberRole,
+) -> Result<(), IdentityError> {
+    let inviter_role = check_user_role(pool, workspace_id, inviter_user_id)
+        .await?
+        .ok_or(IdentityError::PermissionDenied)?;
+    validate_invite_role(config, inviter_role, role)
+}
+
+/// The blocklist entry matching the address's domain or a parent of it.
+fn blocked_domain<'a>(config: &'a WorkspaceInvitationConfig, email: &str) -> Option<&'a str> {
+    let domain = email.rsplit_once('@')?.1.to_lowercase();
+    config
+        .blocked_email_domains
+        .iter()
+        .find(|blocked| {
+            domain == **blocked
+                || domain
+                    .strip_suffix(blocked.as_str())
+                    .is_some_and(|prefix| prefix.ends_with('.'))
+        })
+        .map(String::as_str)
+}
+
+fn is_plausible_email(email: &str) -> bool {
+    match email.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.is_empty()
+                && !domain.contains('@')
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && !email.chars().any(char::is_whitespace)
+        }
+        None => false,
+    }
+}
+
+/// Only pending invitations can be accepted; expired, declined, revoked and
+/// accepted rows are kept for auditing but their tokens no longer work.
+fn ensure_pending(
+    invitation: Option<WorkspaceInvitation>,
+) -> Result<WorkspaceInvitation, IdentityError> {
+    match invitation {
+        Some(invitation) if invitation.status == InvitationStatus::Pending => Ok(invitation),
+        Some(invitation) if invitation.status == InvitationStatus::Revoked => Err(
+            IdentityError::InvitationError("Invitation has been revoked".to_string()),
+        ),
+        _ => Err(IdentityError::InvitationError(
+            "Invitation not found or already used".to_string(),
+        )),
+    }
+}
+
+/// Whether declining `invitation` changes anything: pending invitations are
+/// declined, already-declined ones are left alone, anything else is an error.
+fn needs_decline(invitation: &WorkspaceInvitation) -> Result<bool, IdentityError> {
+    match invitation.status {
+        InvitationStatus::Pending => Ok(true),
+        InvitationStatus::Declined => Ok(false),
+        InvitationStatus::Accepted | InvitationStatus::Expired => Err(
+            IdentityError::InvitationError("Invitation has already been used".to_string()),
+        ),
+        InvitationStatus::Revoked => Err(IdentityError::InvitationError(
+            "Invitation has been revoked".to_string(),
+        )),
+    }
+}
+
+/// Only invitations that are still pending and not yet past their expiry
+/// can be extended; `expire_stale` may not have marked them expired yet.
+fn ensure_extendable(
+    invitation: &WorkspaceInvitation,
+    now: DateTime<Utc>,
+) -> Result<(), IdentityError> {
+    if invitation.status != InvitationStatus::Pending || invitation.expires_at <= now {
+        return Err(IdentityError::InvitationError(
+            "Only pending invitations can be extended".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// In strict mode only the invited address may accept or decline, compared
+/// case-insensitively.
+fn ensure_email_matches(
+    config: &WorkspaceInvitationConfig,
+    invited_email: &str,
+    user_email: &str,
+) -> Result<(), IdentityError> {
+    if config.require_matching_email
+        && !invited_email.trim().eq_ignore_ascii_case(user_email.trim())
+    {
+        return Err(IdentityError::InvitationError(
+            "This invitation was sent to a different email".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Reject acceptance until the workspace's activation delay has elapsed
+/// since the invitation was created.
+fn ensure_invitation_active(
+    settings: &WorkspaceSettings,
+    created_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Result<(), IdentityError> {
+    let active_at = settings.invitation_active_at(created_at);
+    if now < active_at {
+        return Err(IdentityError::InvitationNotYetActive(active_at));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{
+        oauth_accounts::{OAuthAccountInsert, OAuthAccountRepository},
+        users::{UpsertUser, UserRepository},
+        workspace_settings::update_settings,
+    };
+
+    fn invitation_with_status(status: InvitationStatus) -> WorkspaceInvitation {
+        let now = Utc::now();
+        WorkspaceInvitation {
+            id: Uuid::new_v4(),
+            workspace_id: Uuid::new_v4(),
+            invited_by_user_id: Some(Uuid::new_v4()),
+            email: "invitee@example.com".to_string(),
+            role: MemberRole::Member,
+            status,
+            token: Uuid::new_v4().to_string(),
+            expires_at: now + Duration::days(7),
+            created_at: now,
+            updated_at: now,
+            revoked_at: None,
+            revoked_by_user_id: None,
+        }
+    }
+
+    #[test]
+    fn test_expired_invitation_cannot_be_accepted() {
+        let invitation = invitation_with_status(InvitationStatus::Expired);
+        assert!(matches!(
+            ensure_pending(Some(invitation)),
+            Err(IdentityError::InvitationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_revoked_invitation_cannot_be_accepted() {
+        let invitation = invitation_with_status(InvitationStatus::Revoked);
+        assert!(matches!(
+            ensure_pending(Some(invitation)),
+            Err(IdentityError::InvitationError(msg)) if msg == "Invitation has been revoked"
+        ));
+    }
+
+    #[test]
+    fn test_pending_invitation_can_be_accepted() {
+        let invitation = invitation_with_status(InvitationStatus::Pending);
+        let id = invitation.id;
+        assert_eq!(ensure_pending(Some(invitation)).unwrap().id, id);
+    }
+
+    #[test]
+    fn test_acceptance_rejected_before_activation_delay() {
+        let settings = WorkspaceSettings {
+            workspace_id: Uuid::new_v4(),
+            invitation_activation_delay_secs: 60,
+        };
+        let created_at = Utc::now();
+
+        let result =
+            ensure_invitation_active(&settings, created_at, created_at + Duration::seconds(59));
+        assert!(matches!(
+            result,
+            Err(IdentityError::InvitationNotYetActive(active_at))
+                if active_at == created_at + Duration::seconds(60)
+        ));
+    }
+
+    #[test]
+    fn test_acceptance_allowed_after_activation_delay() {
+        let settings = WorkspaceSettings {
+            workspace_id: Uuid::new_v4(),
+            invitation_activation_delay_secs: 60,
+        };
+        let created_at = Utc::now();
+
+        assert!(
+            ensure_invitation_active(&settings, created_at, created_at + Duration::seconds(60))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_default_settings_allow_immediate_acceptance() {
+        let settings = WorkspaceSettings::defaults(Uuid::new_v4());
+        let created_at = Utc::now();
+
+        assert!(ensure_invitation_active(&settings, created_at, created_at).is_ok());
+    }
+
+    fn emails(list: &[&str]) -> Vec<String> {
+        list.iter().map(|email| email.to_string()).collect()
+    }
+
+    #[test]
+    fn test_partition_bulk_emails_dedupes_case_insensitively() {
+        let (valid, invalid) = partition_bulk_emails(&emails(&[
+            "alice@example.com",
+            " Alice@Example.com ",
+            "bob@example.com",
+            "alice@example.com",
+        ]));
+        assert_eq!(valid, emails(&["alice@example.com", "bob@example.com"]));
+        assert!(invalid.is_empty());
+    }
+
+    #[test]
+    fn test_partition_bulk_emails_reports_invalid_addresses() {
+        let (valid, invalid) = partition_bulk_emails(&emails(&[
+            "carol@example.com",
+            "not-an-email",
+            "@example.com",
+            "dave@localhost",
+            "eve@@example.com",
+            "frank smith@example.com",
+            "",
+        ]));
+        assert_eq!(valid, emails(&["carol@example.com"]));
+        assert_eq!(
+            invalid,
+            emails(&[
+                "not-an-email",
+                "@example.com",
+                "dave@localhost",
+                "eve@@example.com",
+                "frank smith@example.com",
+            ])
+        );
+    }
+
+    #[test]
+    fn test_invite_email_rejects_malformed_address() {
+        let config = WorkspaceInvitationConfig::default();
+        for email in ["alice", "alice@", "alice@localhost", "al ice@example.com"] {
+            assert!(matches!(
+                validate_invite_email(&config, email),
+                Err(IdentityError::InvitationError(msg)) if msg == "Invalid email address"
+            ));
+        }
+    }
+
+    #[test]
+    fn test_invite_email_rejects_blocked_domain() {
+        let config = WorkspaceInvitationConfig {
+            blocked_email_domains: vec!["mailinator.com".to_string()],
+            ..WorkspaceInvitationConfig::default()
+        };
+        for email in [
+            "bob@mailinator.com",
+            "bob@MAILINATOR.com",
+            "bob@eu.mailinator.com",
+        ] {
+            assert!(matches!(
+                validate_invite_email(&config, email),
+                Err(IdentityError::InvitationError(msg)) if msg.contains("mailinator.com")
+            ));
+        }
+        // Only whole domain labels match
+        assert!(validate_invite_email(&config, "bob@notmailinator.com").is_ok());
+    }
+
+    #[test]
+    fn test_invite_email_accepts_valid_address() {
+        let config = WorkspaceInvitationConfig::default();
+        assert!(validate_invite_email(&config, "carol@example.com").is_ok());
+        // The blocklist is empty unless configured
+        assert!(validate_invite_email(&config, "carol@mailinator.com").is_ok());
+    }
+
+    #[test]
+    fn test_member_inviter_cannot_grant_admin() {
+        let config = WorkspaceInvitationConfig::default();
+        assert!(matches!(
+            validate_invite_role(&config, MemberRole::Member, MemberRole::Admin),
+            Err(IdentityError::PermissionDenied)
+        ));
+        assert!(validate_invite_role(&config, MemberRole::Member, MemberRole::Member).is_ok());
+    }
+
+    #[test]
+    fn test_admin_inviter_can_grant_any_role() {
+        let config = WorkspaceInvitationConfig::default();
+        for role in [MemberRole::Admin, MemberRole::Member] {
+            assert!(validate_invite_role(&config, MemberRole::Admin, role).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_role_limit_can_be_disabled() {
+        let config = WorkspaceInvitationConfig {
+            limit_role_to_inviter: false,
+            ..WorkspaceInvitationConfig::default()
+        };
+        assert!(validate_invite_role(&config, MemberRole::Member, MemberRole::Admin).is_ok());
+    }
+
+    #[test]
+    fn test_invitation_expiry_defaults_to_config() {
+        let config = WorkspaceInvitationConfig::default();
+        let now = Utc::now();
+        assert_eq!(
+            invitation_expires_at(&config, None, now).unwrap(),
+            now + Duration::days(config.invitation_expiry_days)
+        );
+    }
+
+    #[test]
+    fn test_invitation_expiry_override_within_limit() {
+        let config = WorkspaceInvitationConfig::default();
+        let now = Utc::now();
+        assert_eq!(
+            invitation_expires_at(&config, Some(2), now).unwrap(),
+            now + Duration::days(2)
+        );
+        assert_eq!(
+            invitation_expires_at(&config, Some(config.max_invitation_expiry_days), now).unwrap(),
+            now + Duration::days(config.max_invitation_expiry_days)
+        );
+    }
+
+    #[test]
+    fn test_invitation_expiry_override_beyond_limit_rejected() {
+        let config = WorkspaceInvitationConfig::default();
+        let now = Utc::now();
+        for days in [0, -1, config.max_invitation_expiry_days + 1] {
+            assert!(matches!(
+                invitation_expires_at(&config, Some(days), now),
+                Err(IdentityError::InvitationError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_acceptance_allowed_for_invited_email() {
+        let config = WorkspaceInvitationConfig::default();
+        assert!(
+            ensure_email_matches(&config, "invitee@example.com", "invitee@example.com").is_ok()
+        );
+        assert!(
+            ensure_email_matches(&config, "Invitee@Example.com", "invitee@example.COM").is_ok()
+        );
+    }
+
+    #[test]
+    fn test_acceptance_rejected_for_different_email() {
+        let config = WorkspaceInvitationConfig::default();
+        assert!(matches!(
+            ensure_email_matches(&config, "invitee@example.com", "someone@example.com"),
+            Err(IdentityError::InvitationError(msg))
+                if msg == "This invitation was sent to a different email"
+        ));
+    }
+
+    #[test]
+    fn test_open_mode_allows_any_email() {
+        let config = WorkspaceInvitationConfig {
+            require_matching_email: false,
+            ..WorkspaceInvitationConfig::default()
+        };
+        assert!(
+            ensure_email_matches(&config, "invitee@example.com", "someone@example.com").is_ok()
+        );
+    }
+
+    #[test]
+    fn test_pending_invitation_can_be_declined() {
+        let invitation = invitation_with_status(InvitationStatus::Pending);
+        assert!(needs_decline(&invitation).unwrap());
+    }
+
+    #[test]
+    fn test_declining_twice_is_a_no_op() {
+        let invitation = invitation_with_status(InvitationStatus::Declined);
+        assert!(!needs_decline(&invitation).unwrap());
+    }
+
+    #[test]
+    fn test_used_invitation_cannot_be_declined() {
+        for status in [
+            InvitationStatus::Accepted,
+            InvitationStatus::Expired,
+            InvitationStatus::Revoked,
+        ] {
+            let invitation = invitation_with_status(status);
+            assert!(matches!(
+                needs_decline(&invitation),
+                Err(IdentityError::InvitationError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_only_pending_unexpired_invitations_can_be_extended() {
+        let now = Utc::now();
+        assert!(ensure_extendable(&invitation_with_status(InvitationStatus::Pending), now).is_ok());
+
+        for status in [
+            InvitationStatus::Accepted,
+            InvitationStatus::Declined,
+            InvitationStatus::Expired,
+        ] {
+            assert!(matches!(
+                ensure_extendable(&invitation_with_status(status), now),
+                Err(IdentityError::InvitationError(_))
+            ));
+        }
+
+        // Past due but not yet swept by expire_stale
+        let mut past_due = invitation_with_status(InvitationStatus::Pending);
+        past_due.expires_at = now - Duration::hours(1);
+        assert!(ensure_extendable(&past_due, now).is_err());
+    }
+
+    #[sqlx::test(migrator = "crate::db::TEST_MIGRATOR")]
+    #[ignore = "requires DATABASE_URL pointing at a Postgres server"]
+    async fn test_list_invitations_filters_by_status_and_pages(pool: PgPool) {
+        let workspace_id = Uuid::new_v4();
+        let admin_id = Uuid::new_v4();
+        UserRepository::new(&pool)
+            .upsert_user(UpsertUser {
+                id: admin_id,
+                email: "admin@example.com",
+                first_name: None,
+                last_name: None,
+                username: None,
+            })
+            .await
+            .unwrap();
+        add_member(&pool, workspace_id, admin_id, MemberRole::Admin)
+            .await
+            .unwrap();
+
+        let repo = WorkspaceInvitationRepository::new(&pool);
+        let mut created = Vec::new();
+        for email in ["a@example.com", "b@example.com", "c@example.com"] {
+            let invitation = repo
+                .create_invitation(
+                    workspace_id,
+                    admin_id,
+                    email,
+                    MemberRole::Member,
+                    Utc::now() + Duration::days(7),
+                    &Uuid::new_v4().to_string(),
+                )
+                .await
+                .unwrap();
+            created.push(invitation.id);
+        }
+        repo.expire_invitation(workspace_id, created[0], admin_id)
+            .await
+            .unwrap();
+
+        let list = |status, limit, offset| {
+            repo.list_invitations(workspace_id, admin_id, status, limit, offset)
+        };
+        let ids = |page: &InvitationPage| -> Vec<Uuid> {
+            page.invitations
+                .iter()
+                .map(|invitation| invitation.id)
+                .collect()
+        };
+
+        let pending = list(InvitationStatus::Pending, 10, 0).await.unwrap();
+        assert_eq!(pending.total, 2);
+        assert_eq!(ids(&pending), vec![created[2], created[1]]);
+
+        let expired = list(InvitationStatus::Expired, 10, 0).await.unwrap();
+        assert_eq!(expired.total, 1);
+        assert_eq!(ids(&expired), vec![created[0]]);
+
+        // Pages split the newest-first order, while total counts all pages
+        let first = list(InvitationStatus::Pending, 1, 0).await.unwrap();
+        let second = list(InvitationStatus::Pending, 1, 1).await.unwrap();
+        let past_end = list(InvitationStatus::Pending, 1, 2).await.unwrap();
+        assert_eq!(ids(&first), vec![created[2]]);
+        assert_eq!(ids(&second), vec![created[1]]);
+        assert!(past_end.invitations.is_empty());
+        assert_eq!(past_end.total, 2);
+    }
+
+    #[sqlx::test(migrator = "crate::db::TEST_MIGRATOR")]
+    #[ignore = "requires DATABASE_URL pointing at a Postgres server"]
+    async fn test_force_expired_invitation_stays_listed_as_expired(pool: PgPool) {
+        let workspace_id = Uuid::new_v4();
+        let admin_id = Uuid::new_v4();
+        let invitee_id = Uuid::new_v4();
+        let users = UserRepository::new(&pool);
+        for (id, email) in [
+            (admin_id, "admin@example.com"),
+            (invitee_id, "invitee@example.com"),
+        ] {
+            users
+                .upsert_user(UpsertUser {
+                    id,
+                    email,
+                    first_name: None,
+                    last_name: None,
+                    username: None,
+                })
+                .await
+                .unwrap();
+        }
+        add_member(&pool, workspace_id, admin_id, MemberRole::Admin)
+            .await
+            .unwrap();
+
+        let repo = WorkspaceInvitationRepository::new(&pool);
+        let token = Uuid::new_v4().to_string();
+        let invitation = repo
+            .create_invitation(
+                workspace_id,
+                admin_id,
+                "invitee@example.com",
+                MemberRole::Member,
+                Utc::now() + Duration::days(7),
+                &token,
+            )
+            .await
+            .unwrap();
+
+        let expired = repo
+            .expire_invitation(workspace_id, invitation.id, admin_id)
+            .await
+            .unwrap();
+        assert_eq!(expired.status, InvitationStatus::Expired);
+
+        let listed = repo
+            .list_invitations(workspace_id, admin_id, InvitationStatus::Expired, 10, 0)
+            .await
+            .unwrap();
+        assert_eq!(listed.total, 1);
+        assert_eq!(listed.invitations[0].id, invitation.id);
+        assert_eq!(listed.invitations[0].status, InvitationStatus::Expired);
+
+        let lookup = repo.get_invitation_by_token(&token).await.unwrap();
+        assert_eq!(lookup.invitation.status, InvitationStatus::Expired);
+        assert!(
+            repo.accept_invitation(
+                &token,
+                invitee_id,
+                "invitee@example.com",
+                &WorkspaceInvitationConfig::default(),
+            )
+            .await
+            .is_err()
+        );
+        assert!(!is_member(&pool, workspace_id, invitee_id).await.unwrap());
+
+        // Expiring again finds no pending invitation
+        assert!(matches!(
+            repo.expire_invitation(workspace_id, invitation.id, admin_id)
+                .await,
+            Err(IdentityError::NotFound)
+        ));
+    }
+
+    /// An admin-run workspace, returning its id and the admin's
+    async fn seed_workspace(pool: &PgPool) -> (Uuid, Uuid) {
+        let workspace_id = Uuid::new_v4();
+        let admin_id = Uuid::new_v4();
+        UserRepository::new(pool)
+            .upsert_user(UpsertUser {
+                id: admin_id,
+                email: "admin@example.com",
+                first_name: None,
+                last_name: None,
+                username: None,
+            })
+            .await
+            .unwrap();
+        add_member(pool, workspace_id, admin_id, MemberRole::Admin)
+            .await
+            .unwrap();
+        (workspace_id, admin_id)
+    }
+
+    #[sqlx::test(migrator = "crate::db::TEST_MIGRATOR")]
+    #[ignore = "requires DATABASE_URL pointing at a Postgres server"]
+    async fn test_expire_stale_expires_past_due_reinvitations(pool: PgPool) {
+        let (workspace_id, admin_id) = seed_workspace(&pool).await;
+        let repo = WorkspaceInvitationRepository::new(&pool);
+        let invite = |expires_at, token: &'static str| {
+            repo.create_invitation(
+                workspace_id,
+                admin_id,
+                "invitee@example.com",
+                MemberRole::Member,
+                expires_at,
+                token,
+            )
+        };
+
+        // An older invitation for the same address is already expired
+        let older = invite(Utc::now() + Duration::days(7), "older-token")
+            .await
+            .unwrap();
+        repo.expire_invitation(workspace_id, older.id, admin_id)
+            .await
+            .unwrap();
+        let past_due = invite(Utc::now() - Duration::hours(1), "past-due-token")
+            .await
+            .unwrap();
+        repo.create_invitation(
+            workspace_id,
+            admin_id,
+            "other@example.com",
+            MemberRole::Member,
+            Utc::now() + Duration::days(7),
+            "current-token",
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(repo.expire_stale().await.unwrap(), 1);
+
+        assert_ne!(past_due.id, older.id);
+        for (token, status) in [
+            ("past-due-token", InvitationStatus::Expired),
+            ("current-token", InvitationStatus::Pending),
+        ] {
+            let lookup = repo.get_invitation_by_token(token).await.unwrap();
+            assert_eq!(lookup.invitation.status, status);
+        }
+        assert_eq!(repo.expire_stale().await.unwrap(), 0);
+    }
+
+    #[sqlx::test(migrator = "crate::db::TEST_MIGRATOR")]
+    #[ignore = "requires DATABASE_URL pointing at a Postgres server"]
+    async fn test_delete_finished_before_purges_expired_and_revoked(pool: PgPool) {
+        let (workspace_id, admin_id) = seed_workspace(&pool).await;
+        let repo = WorkspaceInvitationRepository::new(&pool);
+        let mut ids = Vec::new();
+        for (email, token) in [
+            ("expired@example.com", "expired-token"),
+            ("revoked@example.com", "revoked-token"),
+            ("declined@example.com", "declined-token"),
+            ("pending@example.com", "pending-token"),
+        ] {
+            let invitation = repo
+                .create_invitation(
+                    workspace_id,
+                    admin_id,
+                    email,
+                    MemberRole::Member,
+                    Utc::now() + Duration::days(7),
+                    token,
+                )
+                .await
+                .unwrap();
+            ids.push(invitation.id);
+        }
+        repo.expire_invitation(workspace_id, ids[0], admin_id)
+            .await
+            .unwrap();
+        repo.revoke_invitation(workspace_id, ids[1], admin_id)
+            .await
+            .unwrap();
+        repo.decline_invitation(
+            "declined-token",
+            "declined@example.com",
+            &WorkspaceInvitationConfig::default(),
+        )
+        .await
+        .unwrap();
+
+        // Nothing finished before the retention cutoff yet
+        let cutoff = Utc::now() - Duration::days(30);
+        assert_eq!(repo.delete_finished_before(cutoff).await.unwrap(), 0);
+
+        let cutoff = Utc::now() + Duration::minutes(1);
+        assert_eq!(repo.delete_finished_before(cutoff).await.unwrap(), 2);
+
+        let remaining: Vec<Uuid> =
+            sqlx::query_scalar("SELECT id FROM workspace_invitations WHERE workspace_id = $1")
+                .bind(workspace_id)
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(remaining.len(), 2);
+        assert!(remaining.contains(&ids[2]));
+        assert!(remaining.contains(&ids[3]));
+    }
+
+    #[sqlx::test(migrator = "crate::db::TEST_MIGRATOR")]
+    #[ignore = "requires DATABASE_URL pointing at a Postgres server"]
+    async fn test_reinviting_pending_email_updates_existing_invitation(pool: PgPool) {
+        let workspace_id = Uuid::new_v4();
+        let admin_id = Uuid::new_v4();
+        UserRepository::new(&pool)
+            .upsert_user(UpsertUser {
+                id: admin_id,
+                email: "admin@example.com",
+                first_name: None,
+                last_name: None,
+                username: None,
+            })
+            .await
+            .unwrap();
+        add_member(&pool, workspace_id, admin_id, MemberRole::Admin)
+            .await
+            .unwrap();
+
+        let repo = WorkspaceInvitationRepository::new(&pool);
+        let first = repo
+            .create_invitation(
+                workspace_id,
+                admin_id,
+                "invitee@example.com",
+                MemberRole::Member,
+                Utc::now() + Duration::days(1),
+                "first-token",
+            )
+            .await
+            .unwrap();
+        let second = repo
+            .create_invitation(
+                workspace_id,
+                admin_id,
+                "invitee@example.com",
+                MemberRole::Admin,
+                Utc::now() + Duration::days(7),
+                "second-token",
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(second.id, first.id);
+        assert_eq!(second.status, InvitationStatus::Pending);
+        assert_eq!(second.role, MemberRole::Admin);
+        assert_eq!(second.token, "second-token");
+        assert!(second.expires_at > first.expires_at);
+
+        let page = repo
+            .list_invitations(workspace_id, admin_id, InvitationStatus::Pending, 10, 0)
+            .await
+            .unwrap();
+        assert_eq!(page.total, 1);
+    }
+
+    #[sqlx::test(migrator = "crate::db::TEST_MIGRATOR")]
+    #[ignore = "requires DATABASE_URL pointing at a Postgres server"]
+    async fn test_bulk_reinvite_updates_existing_pending_invitation(pool: PgPool) {
+        let (workspace_id, admin_id) = seed_workspace(&pool).await;
+        let repo = WorkspaceInvitationRepository::new(&pool);
+        let first = repo
+            .create_invitation(
+                workspace_id,
+                admin_id,
+                "invitee@example.com",
+                MemberRole::Member,
+                Utc::now() + Duration::days(1),
+                "first-token",
+            )
+            .await
+            .unwrap();
+
+        let invitations = repo
+            .create_invitations(
+                workspace_id,
+                admin_id,
+                &[
+                    (
+                        "invitee@example.com".to_string(),
+                        "second-token".to_string(),
+                    ),
+                    ("other@example.com".to_string(), "other-token".to_string()),
+                ],
+                MemberRole::Admin,
+                Utc::now() + Duration::days(7),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(invitations.len(), 2);
+        assert_eq!(invitations[0].id, first.id);
+        assert_eq!(invitations[0].role, MemberRole::Admin);
+        assert_eq!(invitations[0].token, "second-token");
+        assert_ne!(invitations[1].id, first.id);
+
+        let page = repo
+            .list_invitations(workspace_id, admin_id, InvitationStatus::Pending, 10, 0)
+            .await
+            .unwrap();
+        assert_eq!(page.total, 2);
+    }
+
+    #[sqlx::test(migrator = "crate::db::TEST_MIGRATOR")]
+    #[ignore = "requires DATABASE_URL pointing at a Postgres server"]
+    async fn test_reinvite_restarts_activation_delay(pool: PgPool) {
+        let (workspace_id, admin_id) = seed_workspace(&pool).await;
+        let invitee_id = Uuid::new_v4();
+        UserRepository::new(&pool)
+            .upsert_user(UpsertUser {
+                id: invitee_id,
+                email: "invitee@example.com",
+                first_name: None,
+                last_name: None,
+                username: None,
+            })
+            .await
+            .unwrap();
+        update_settings(&pool, workspace_id, admin_id, 3600)
+            .await
+            .unwrap();
+        let repo = WorkspaceInvitationRepository::new(&pool);
+        let invite = |token: &'static str| {
+            repo.create_invitation(
+                workspace_id,
+                admin_id,
+                "invitee@example.com",
+                MemberRole::Member,
+                Utc::now() + Duration::days(7),
+                token,
+            )
+        };
+        let first = invite("first-token").await.unwrap();
+        // The first invitation has long been active
+        sqlx::query(
+            "UPDATE workspace_invitations SET created_at = NOW() - INTERVAL '2 hours' WHERE id = $1",
+        )
+        .bind(first.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let refreshed = invite("second-token").await.unwrap();
+
+        assert_eq!(refreshed.id, first.id);
+        assert!(refreshed.created_at > Utc::now() - Duration::minutes(1));
+        assert!(matches!(
+            repo.accept_invitation(
+                "second-token",
+                invitee_id,
+                "invitee@example.com",
+                &WorkspaceInvitationConfig::default(),
+            )
+            .await,
+            Err(IdentityError::InvitationNotYetActive(_))
+        ));
+        assert!(!is_member(&pool, workspace_id, invitee_id).await.unwrap());
+    }
+
+    #[sqlx::test(migrator = "crate::db::TEST_MIGRATOR")]
+    #[ignore = "requires DATABASE_URL pointing at a Postgres server"]
+    async fn test_declining_again_keeps_earlier_declined_invitation(pool: PgPool) {
+        let (workspace_id, admin_id) = seed_workspace(&pool).await;
+        let repo = WorkspaceInvitationRepository::new(&pool);
+        for token in ["first-token", "second-token"] {
+            repo.create_invitation(
+                workspace_id,
+                admin_id,
+                "invitee@example.com",
+                MemberRole::Member,
+                Utc::now() + Duration::days(7),
+                token,
+            )
+            .await
+            .unwrap();
+            repo.decline_invitation(
+                token,
+                "invitee@example.com",
+                &WorkspaceInvitationConfig::default(),
+            )
+            .await
+            .unwrap();
+        }
+
+        let page = repo
+            .list_invitations(workspace_id, admin_id, InvitationStatus::Declined, 10, 0)
+            .await
+            .unwrap();
+        assert_eq!(page.total, 2);
+    }
+
+    #[sqlx::test(migrator = "crate::db::TEST_MIGRATOR")]
+    #[ignore = "requires DATABASE_URL pointing at a Postgres server"]
+    async fn test_inviting_after_non_pending_invitation_creates_fresh_one(pool: PgPool) {
+        let workspace_id = Uuid::new_v4();
+        let admin_id = Uuid::new_v4();
+        UserRepository::new(&pool)
+            .upsert_user(UpsertUser {
+                id: admin_id,
+                email: "admin@example.com",
+                first_name: None,
+                last_name: None,
+                username: None,
+            })
+            .await
+            .unwrap();
+        add_member(&pool, workspace_id, admin_id, MemberRole::Admin)
+            .await
+            .unwrap();
+
+        let repo = WorkspaceInvitationRepository::new(&pool);
+        let invite = |token: &'static str| {
+            repo.create_invitation(
+                workspace_id,
+                admin_id,
+                "invitee@example.com",
+                MemberRole::Member,
+                Utc::now() + Duration::days(7),
+                token,
+            )
+        };
+
+        // Expire twice over, so two non-pending rows share the address
+        let first = invite("first-token").await.unwrap();
+        repo.expire_invitation(workspace_id, first.id, admin_id)
+            .await
+            .unwrap();
+        let second = invite("second-token").await.unwrap();
+        assert_ne!(second.id, first.id);
+        repo.expire_invitation(workspace_id, second.id, admin_id)
+            .await
+            .unwrap();
+
+        let third = invite("third-token").await.unwrap();
+        assert_ne!(third.id, first.id);
+        assert_ne!(third.id, second.id);
+        assert_eq!(third.status, InvitationStatus::Pending);
+
+        let expired = repo
+            .list_invitations(workspace_id, admin_id, InvitationStatus::Expired, 10, 0)
+            .await
+            .unwrap();
+        assert_eq!(expired.total, 2);
+    }
+
+    #[sqlx::test(migrator = "crate::db::TEST_MIGRATOR")]
+    #[ignore = "requires DATABASE_URL pointing at a Postgres server"]
+    async fn test_extend_expiry_moves_pending_invitation_forward(pool: PgPool) {
+        let workspace_id = Uuid::new_v4();
+        let admin_id = Uuid::new_v4();
+        UserRepository::new(&pool)
+            .upsert_user(UpsertUser {
+                id: admin_id,
+                email: "admin@example.com",
+                first_name: None,
+                last_name: None,
+                username: None,
+            })
+            .await
+            .unwrap();
+        add_member(&pool, workspace_id, admin_id, MemberRole::Admin)
+            .await
+            .unwrap();
+
+        let repo = WorkspaceInvitationRepository::new(&pool);
+        let invite = |email: &'static str, expires_at| {
+            let token = Uuid::new_v4().to_string();
+            let repo = &repo;
+            async move {
+                repo.create_invitation(
+                    workspace_id,
+                    admin_id,
+                    email,
+                    MemberRole::Member,
+                    expires_at,
+                    &token,
+                )
+                .await
+                .unwrap()
+            }
+        };
+
+        let pending = invite("pending@example.com", Utc::now() + Duration::days(1)).await;
+        let extended = repo
+            .extend_expiry(workspace_id, pending.id, 3)
+            .await
+            .unwrap();
+        assert_eq!(extended.expires_at, pending.expires_at + Duration::days(3));
+        assert_eq!(extended.token, pending.token);
+
+        let expired = invite("expired@example.com", Utc::now() - Duration::days(1)).await;
+        assert!(matches!(
+            repo.extend_expiry(workspace_id, expired.id, 3).await,
+            Err(IdentityError::InvitationError(_))
+        ));
+
+        assert!(matches!(
+            repo.extend_expiry(workspace_id, Uuid::new_v4(), 3).await,
+            Err(IdentityError::NotFound)
+        ));
+    }
+
+    #[sqlx::test(migrator = "crate::db::TEST_MIGRATOR")]
+    #[ignore = "requires DATABASE_URL pointing at a Postgres server"]
+    async fn test_invited_role_is_capped_at_inviters_role(pool: PgPool) {
+        let workspace_id = Uuid::new_v4();
+        let admin_id = Uuid::new_v4();
+        let manager_id = Uuid::new_v4();
+        let users = UserRepository::new(&pool);
+        for (id, email, role) in [
+            (admin_id, "admin@example.com", MemberRole::Admin),
+            (manager_id, "manager@example.com", MemberRole::Member),
+        ] {
+            users
+                .upsert_user(UpsertUser {
+                    id,
+                    email,
+                    first_name: None,
+                    last_name: None,
+                    username: None,
+                })
+                .await
+                .unwrap();
+            add_member(&pool, workspace_id, id, role).await.unwrap();
+        }
+        sqlx::query(
+            r#"
+            UPDATE workspace_member_metadata
+            SET permissions = ARRAY['member.invite']::workspace_permission[]
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(manager_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let config = WorkspaceInvitationConfig::default();
+        let check = |inviter_id, role| {
+            assert_can_invite_with_role(&pool, &config, workspace_id, inviter_id, role)
+        };
+        assert!(matches!(
+            check(manager_id, MemberRole::Admin).await,
+            Err(IdentityError::PermissionDenied)
+        ));
+        assert!(check(manager_id, MemberRole::Member).await.is_ok());
+        assert!(check(admin_id, MemberRole::Admin).await.is_ok());
+        assert!(matches!(
+            check(Uuid::new_v4(), MemberRole::Member).await,
+            Err(IdentityError::PermissionDenied)
+        ));
+
+        // A member granted member.invite can send invitations of their own rank
+        let invitation = WorkspaceInvitationRepository::new(&pool)
+            .create_invitation(
+                workspace_id,
+                manager_id,
+                "invitee@example.com",
+                MemberRole::Member,
+                Utc::now() + Duration::days(7),
+                &Uuid::new_v4().to_string(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(invitation.invited_by_user_id, Some(manager_id));
+    }
+
+    #[sqlx::test(migrator = "crate::db::TEST_MIGRATOR")]
+    #[ignore = "requires DATABASE_URL pointing at a Postgres server"]
+    async fn test_revoke_by_inviter_only_touches_that_inviters_pending(pool: PgPool) {
+        let workspace_id = Uuid::new_v4();
+        let departed_id = Uuid::new_v4();
+        let remaining_id = Uuid::new_v4();
+        let users = UserRepository::new(&pool);
+        for (id, email) in [
+            (departed_id, "departed@example.com"),
+            (remaining_id, "remaining@example.com"),
+        ] {
+            users
+                .upsert_user(UpsertUser {
+                    id,
+                    email,
+                    first_name: None,
+                    last_name: None,
+                    username: None,
+                })
+                .await
+                .unwrap();
+            add_member(&pool, workspace_id, id, MemberRole::Admin)
+                .await
+                .unwrap();
+        }
+
+        let repo = WorkspaceInvitationRepository::new(&pool);
+        let invite = |inviter_id, email: &'static str| {
+            let token = Uuid::new_v4().to_string();
+            let repo = &repo;
+            async move {
+                repo.create_invitation(
+                    workspace_id,
+                    inviter_id,
+                    email,
+                    MemberRole::Member,
+                    Utc::now() + Duration::days(7),
+                    &token,
+                )
+                .await
+                .unwrap()
+            }
+        };
+
+        invite(departed_id, "a@example.com").await;
+        invite(departed_id, "b@example.com").await;
+        let already_expired = invite(departed_id, "c@example.com").await;
+        repo.expire_invitation(workspace_id, already_expired.id, departed_id)
+            .await
+            .unwrap();
+        let kept = invite(remaining_id, "d@example.com").await;
+
+        let revoked = repo
+            .revoke_pending_by_inviter(workspace_id, departed_id, remaining_id)
+            .await
+            .unwrap();
+        assert_eq!(revoked, 2);
+
+        let list = |status| repo.list_invitations(workspace_id, remaining_id, status, 10, 0);
+        let pending = list(InvitationStatus::Pending).await.unwrap();
+        assert_eq!(
+            pending
+                .invitations
+                .iter()
+                .map(|invitation| invitation.id)
+                .collect::<Vec<_>>(),
+            vec![kept.id]
+        );
+        assert_eq!(list(InvitationStatus::Revoked).await.unwrap().total, 2);
+        assert_eq!(list(InvitationStatus::Expired).await.unwrap().total, 1);
+    }
+
+    #[sqlx::test(migrator = "crate::db::TEST_MIGRATOR")]
+    #[ignore = "requires DATABASE_URL pointing at a Postgres server"]
+    async fn test_revoked_invitation_is_kept_with_attribution(pool: PgPool) {
+        let workspace_id = Uuid::new_v4();
+        let admin_id = Uuid::new_v4();
+        let invitee_id = Uuid::new_v4();
+        let users = UserRepository::new(&pool);
+        for (id, email) in [
+            (admin_id, "admin@example.com"),
+            (invitee_id, "invitee@example.com"),
+        ] {
+            users
+                .upsert_user(UpsertUser {
+                    id,
+                    email,
+                    first_name: None,
+                    last_name: None,
+                    username: None,
+                })
+                .await
+                .unwrap();
+        }
+        add_member(&pool, workspace_id, admin_id, MemberRole::Admin)
+            .await
+            .unwrap();
+
+        let repo = WorkspaceInvitationRepository::new(&pool);
+        let token = Uuid::new_v4().to_string();
+        let invitation = repo
+            .create_invitation(
+                workspace_id,
+                admin_id,
+                "invitee@example.com",
+                MemberRole::Member,
+                Utc::now() + Duration::days(7),
+                &token,
+            )
+            .await
+            .unwrap();
+        assert!(invitation.revoked_at.is_none());
+
+        let revoked = repo
+            .revoke_invitation(workspace_id, invitation.id, admin_id)
+            .await
+            .unwrap();
+        assert_eq!(revoked.status, InvitationStatus::Revoked);
+        assert_eq!(revoked.revoked_by_user_id, Some(admin_id));
+        assert!(revoked.revoked_at.is_some());
+
+        let lookup = repo.get_invitation_by_token(&token).await.unwrap();
+        assert_eq!(lookup.invitation.revoked_at, revoked.revoked_at);
+        assert!(matches!(
+            repo.accept_invitation(
+                &token,
+                invitee_id,
+                "invitee@example.com",
+                &WorkspaceInvitationConfig::default(),
+            )
+            .await,
+            Err(IdentityError::InvitationError(msg)) if msg == "Invitation has been revoked"
+        ));
+        assert!(!is_member(&pool, workspace_id, invitee_id).await.unwrap());
+
+        // Revoking again finds no pending invitation
+        assert!(matches!(
+            repo.revoke_invitation(workspace_id, invitation.id, admin_id)
+                .await,
+            Err(IdentityError::NotFound)
+        ));
+    }
+
+    #[sqlx::test(migrator = "crate::db::TEST_MIGRATOR")]
+    #[ignore = "requires DATABASE_URL pointing at a Postgres server"]
+    async fn test_acceptance_rejected_once_workspace_has_no_admin(pool: PgPool) {
+        let (token, invitee_id) = seed_fresh_invitee(&pool, "invitee@example.com").await;
+        let repo = WorkspaceInvitationRepository::new(&pool);
+        let workspace_id = repo
+            .get_invitation_by_token(&token)
+            .await
+            .unwrap()
+            .invitation
+            .workspace_id;
+        sqlx::query("DELETE FROM workspace_member_metadata WHERE workspace_id = $1")
+            .bind(workspace_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            repo.accept_invitation(
+                &token,
+                invitee_id,
+                "invitee@example.com",
+                &WorkspaceInvitationConfig::default(),
+            )
+            .await,
+            Err(IdentityError::WorkspaceUnavailable)
+        ));
+        assert!(!is_member(&pool, workspace_id, invitee_id).await.unwrap());
+
+        // Still pending, so it works again if the workspace gets an admin back
+        let lookup = repo.get_invitation_by_token(&token).await.unwrap();
+        assert_eq!(lookup.invitation.status, InvitationStatus::Pending);
+    }
+
+    /// An admin-run workspace with a pending invitation for
+    /// `invitee@example.com` and an account that signed up under `signup_email`.
+    async fn seed_fresh_invitee(pool: &PgPool, signup_email: &str) -> (String, Uuid) {
+        let workspace_id = Uuid::new_v4();
+        let admin_id = Uuid::new_v4();
+        let invitee_id = Uuid::new_v4();
+        let users = UserRepository::new(pool);
+        for (id, email) in [(admin_id, "admin@example.com"), (invitee_id, signup_email)] {
+            users
+                .upsert_user(UpsertUser {
+                    id,
+                    email,
+                    first_name: None,
+                    last_name: None,
+                    username: None,
+                })
+                .await
+                .unwrap();
+        }
+        add_member(pool, workspace_id, admin_id, MemberRole::Admin)
+            .await
+            .unwrap();
+
+        let token = Uuid::new_v4().to_string();
+        WorkspaceInvitationRepository::new(pool)
+            .create_invitation(
+                workspace_id,
+                admin_id,
+                "invitee@example.com",
+                MemberRole::Member,
+                Utc::now() + Duration::days(7),
+                &token,
+            )
+            .await
+            .unwrap();
+        (token, invitee_id)
+    }
+
+    fn shared_link_config() -> WorkspaceInvitationConfig {
+        WorkspaceInvitationConfig {
+            require_matching_email: false,
+            ..WorkspaceInvitationConfig::default()
+        }
+    }
+
+    #[sqlx::test(migrator = "crate::db::TEST_MIGRATOR")]
+    #[ignore = "requires DATABASE_URL pointing at a Postgres server"]
+    async fn test_fresh_account_takes_invited_email_on_accept(pool: PgPool) {
+        let (token, invitee_id) = seed_fresh_invitee(&pool, "Invitee@Example.com").await;
+
+        WorkspaceInvitationRepository::new(&pool)
+            .accept_invitation(
+                &token,
+                invitee_id,
+                "Invitee@Example.com",
+                &WorkspaceInvitationConfig::default(),
+            )
+            .await
+            .unwrap();
+
+        let user = UserRepository::new(&pool)
+            .fetch_user(invitee_id)
+            .await
+            .unwrap();
+        assert_eq!(user.email, "invitee@example.com");
+    }
+
+    #[sqlx::test(migrator = "crate::db::TEST_MIGRATOR")]
+    #[ignore = "requires DATABASE_URL pointing at a Postgres server"]
+    async fn test_verified_email_is_kept_on_accept(pool: PgPool) {
+        let (token, invitee_id) = seed_fresh_invitee(&pool, "Invitee@Example.com").await;
+        OAuthAccountRepository::new(&pool)
+            .upsert(OAuthAccountInsert {
+                user_id: invitee_id,
+                provider: "github",
+                provider_user_id: "42",
+                email: Some("Invitee@Example.com"),
+                username: None,
+                display_name: None,
+                avatar_url: None,
+            })
+            .await
+            .unwrap();
+
+        WorkspaceInvitationRepository::new(&pool)
+            .accept_invitation(
+                &token,
+                invitee_id,
+                "Invitee@Example.com",
+                &WorkspaceInvitationConfig::default(),
+            )
+            .await
+            .unwrap();
+
+        let user = UserRepository::new(&pool)
+            .fetch_user(invitee_id)
+            .await
+            .unwrap();
+        assert_eq!(user.email, "Invitee@Example.com");
+    }
+
+    #[sqlx::test(migrator = "crate::db::TEST_MIGRATOR")]
+    #[ignore = "requires DATABASE_URL pointing at a Postgres server"]
+    async fn test_shared_link_keeps_accepting_account_email(pool: PgPool) {
+        let (token, invitee_id) = seed_fresh_invitee(&pool, "someone@example.com").await;
+
+        WorkspaceInvitationRepository::new(&pool)
+            .accept_invitation(
+                &token,
+                invitee_id,
+                "someone@example.com",
+                &shared_link_config(),
+            )
+            .await
+            .unwrap();
+
+        let user = UserRepository::new(&pool)
+            .fetch_user(invitee_id)
+            .await
+            .unwrap();
+        assert_eq!(user.email, "someone@example.com");
+    }
+}
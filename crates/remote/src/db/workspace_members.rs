@@ -1,3 +1,4 @@
+use serde::Deserialize;
 use sqlx::{Executor, PgPool, Postgres};
 pub use utils::api::organizations::MemberRole;
 pub use utils::api::workspaces::WorkspacePermission;
@@ -76,6 +77,33 @@ where
     Ok(exists)
 }
 
+/// Whether the workspace still has at least one admin. Workspaces have no
+/// table of their own on this side, so there is no archived or deleted flag
+/// to read; a workspace whose admins are all gone can no longer be managed
+/// and is treated as unavailable for invitation acceptance.
+pub async fn workspace_has_admin<'a, E>(
+    executor: E,
+    workspace_id: Uuid,
+) -> Result<bool, IdentityError>
+where
+    E: Executor<'a, Database = Postgres>,
+{
+    let exists: bool = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS(
+            SELECT 1
+            FROM workspace_member_metadata
+            WHERE workspace_id = $1 AND role = 'admin'
+        )
+        "#,
+    )
+    .bind(workspace_id)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(exists)
+}
+
 pub async fn assert_membership(
     pool: &PgPool,
     workspace_id: Uuid,
@@ -102,25 +130,42 @@ pub async fn assert_admin(
     }
 }
 
+/// Where `role` sits in the role hierarchy; higher outranks lower.
+pub fn role_rank(role: MemberRole) -> u8 {
+    match role {
+        MemberRole::Admin => 1,
+        MemberRole::Member => 0,
+    }
+}
+
+/// Permissions every member with `role` holds without an explicit grant.
+/// The per-member `permissions` array only ever adds to these.
+pub fn default_permissions(role: MemberRole) -> &'static [WorkspacePermission] {
+    match role {
+        MemberRole::Admin => &[
+            WorkspacePermission::MemberView,
+            WorkspacePermission::MemberInvite,
+            WorkspacePermission::MemberRemove,
+            WorkspacePermission::MemberRoleChange,
+        ],
+        MemberRole::Member => &[WorkspacePermission::MemberView],
+    }
+}
+
 pub async fn has_permission(
     pool: &PgPool,
     workspace_id: Uuid,
     user_id: Uuid,
     permission: WorkspacePermission,
 ) -> Result<bool, IdentityError> {
-    // Admins have all permissions
-    let role = check_user_role(pool, workspace_id, user_id).await?;
-    if let Some(MemberRole::Admin) = role {
+    let Some(role) = check_user_role(pool, workspace_id, user_id).await? else {
+        return Ok(false);
+    };
+    if default_permissions(role).contains(&permission) {
         return Ok(true);
     }
 
     // Check explicit permissions
-    let permission_str = match permission {
-        WorkspacePermission::MemberInvite => "member.invite",
-        WorkspacePermission::MemberRemove => "member.remove",
-        WorkspacePermission::MemberRoleChange => "member.role.change",
-    };
-
     let result: bool = sqlx::query_scalar(
         r#"
         SELECT EXISTS(
@@ -134,7 +179,7 @@ pub async fn has_permission(
     )
     .bind(workspace_id)
     .bind(user_id)
-    .bind(permission_str)
+    .bind(permission)
     .fetch_one(pool)
     .await?;
 
@@ -153,3 +198,325 @@ pub async fn assert_permission(
         Err(IdentityError::PermissionDenied)
     }
 }
+
+/// Make another existing member an admin, optionally demoting the caller to
+/// a regular member. Workspaces on this side have no separate owner, so
+/// admin is the role being handed over.
+pub async fn transfer_ownership(
+    pool: &PgPool,
+    workspace_id: Uuid,
+    from_user_id: Uuid,
+    to_user_id: Uuid,
+    demote_self: bool,
+) -> Result<(), IdentityError> {
+    assert_admin(pool, workspace_id, from_user_id).await?;
+
+    let mut tx = pool.begin().await?;
+
+    let target_role: Option<MemberRole> = sqlx::query_scalar(
+        r#"
+        SELECT role
+        FROM workspace_member_metadata
+        WHERE workspace_id = $1 AND user_id = $2
+        FOR UPDATE
+        "#,
+    )
+    .bind(workspace_id)
+    .bind(to_user_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    validate_transfer(from_user_id, to_user_id, target_role)?;
+
+    sqlx::query(
+        r#"
+        UPDATE workspace_member_metadata
+        SET role = 'admin'
+        WHERE workspace_id = $1 AND user_id = $2
+        "#,
+    )
+    .bind(workspace_id)
+    .bind(to_user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    if demote_self {
+        sqlx::query(
+            r#"
+            UPDATE workspace_member_metadata
+            SET role = 'member'
+            WHERE workspace_id = $1 AND user_id = $2
+            "#,
+        )
+        .bind(workspace_id)
+        .bind(from_user_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Ownership can only go to someone else who is already a member.
+fn validate_transfer(
+    from_user_id: Uuid,
+    to_user_id: Uuid,
+    target_role: Option<MemberRole>,
+) -> Result<(), IdentityError> {
+    if from_user_id == to_user_id {
+        return Err(IdentityError::InvalidTransfer(
+            "Cannot transfer a workspace to yourself".to_string(),
+        ));
+    }
+    if target_role.is_none() {
+        return Err(IdentityError::InvalidTransfer(
+            "New owner must already be a member of the workspace".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Remove the caller's own membership. The sole admin has to transfer
+/// ownership first so the workspace is never left without one.
+pub async fn leave_workspace(
+    pool: &PgPool,
+    workspace_id: Uuid,
+    user_id: Uuid,
+) -> Result<(), IdentityError> {
+    let mut tx = pool.begin().await?;
+
+    let role: Option<MemberRole> = sqlx::query_scalar(
+        r#"
+        SELECT role
+        FROM workspace_member_metadata
+        WHERE workspace_id = $1 AND user_id = $2
+        FOR UPDATE
+        "#,
+    )
+    .bind(workspace_id)
+    .bind(user_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let role = role.ok_or(IdentityError::NotFound)?;
+
+    let admin_ids: Vec<Uuid> = if role == MemberRole::Admin {
+        sqlx::query_scalar(
+            r#"
+            SELECT user_id
+            FROM workspace_member_metadata
+            WHERE workspace_id = $1 AND role = 'admin'
+            FOR UPDATE
+            "#,
+        )
+        .bind(workspace_id)
+        .fetch_all(&mut *tx)
+        .await?
+    } else {
+        Vec::new()
+    };
+
+    ensure_can_leave(user_id, role, &admin_ids)?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM workspace_member_metadata
+        WHERE workspace_id = $1 AND user_id = $2
+        "#,
+    )
+    .bind(workspace_id)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+fn ensure_can_leave(
+    user_id: Uuid,
+    role: MemberRole,
+    admin_ids: &[Uuid],
+) -> Result<(), IdentityError> {
+    if role == MemberRole::Admin && admin_ids == [user_id] {
+        return Err(IdentityError::LastAdmin);
+    }
+    Ok(())
+}
+
+/// Orderings offered when listing workspace members
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemberSort {
+    #[default]
+    JoinedAt,
+    Role,
+    Name,
+}
+
+impl MemberSort {
+    /// ORDER BY clause for a query over `workspace_member_metadata wmm`
+    /// joined with `users u`. `joined_at` breaks ties so pages are stable.
+    pub fn order_by(self) -> &'static str {
+        match self {
+            MemberSort::JoinedAt => "wmm.joined_at ASC, wmm.user_id ASC",
+            MemberSort::Role => "wmm.role ASC, wmm.joined_at ASC, wmm.user_id ASC",
+            MemberSort::Name => {
+                "lower(COALESCE(NULLIF(concat_ws(' ', u.first_name, u.last_name), ''), \
+                 u.username, u.email)) ASC NULLS LAST, wmm.joined_at ASC, wmm.user_id ASC"
+            }
+        }
+    }
+}
+
+/// Turn a member search term into an ILIKE pattern matching it anywhere,
+/// with LIKE wildcards in the term matched literally.
+pub fn member_search_pattern(search: Option<&str>) -> Option<String> {
+    let search = search.map(str::trim).filter(|s| !s.is_empty())?;
+    let escaped = search
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    Some(format!("%{escaped}%"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::users::{UpsertUser, UserRepository};
+
+    #[test]
+    fn test_transfer_to_non_member_rejected() {
+        assert!(matches!(
+            validate_transfer(Uuid::new_v4(), Uuid::new_v4(), None),
+            Err(IdentityError::InvalidTransfer(_))
+        ));
+    }
+
+    #[test]
+    fn test_transfer_to_self_rejected() {
+        let user_id = Uuid::new_v4();
+        assert!(matches!(
+            validate_transfer(user_id, user_id, Some(MemberRole::Admin)),
+            Err(IdentityError::InvalidTransfer(_))
+        ));
+    }
+
+    #[test]
+    fn test_transfer_to_member_allowed() {
+        assert!(
+            validate_transfer(Uuid::new_v4(), Uuid::new_v4(), Some(MemberRole::Member)).is_ok()
+        );
+        assert!(validate_transfer(Uuid::new_v4(), Uuid::new_v4(), Some(MemberRole::Admin)).is_ok());
+    }
+
+    #[test]
+    fn test_last_admin_cannot_leave() {
+        let user_id = Uuid::new_v4();
+        assert!(matches!(
+            ensure_can_leave(user_id, MemberRole::Admin, &[user_id]),
+            Err(IdentityError::LastAdmin)
+        ));
+    }
+
+    #[test]
+    fn test_admin_can_leave_when_another_admin_remains() {
+        let user_id = Uuid::new_v4();
+        assert!(ensure_can_leave(user_id, MemberRole::Admin, &[user_id, Uuid::new_v4()]).is_ok());
+    }
+
+    #[test]
+    fn test_member_can_leave() {
+        assert!(ensure_can_leave(Uuid::new_v4(), MemberRole::Member, &[]).is_ok());
+    }
+
+    #[test]
+    fn test_member_has_baseline_but_not_admin_permissions() {
+        let member = default_permissions(MemberRole::Member);
+        assert!(member.contains(&WorkspacePermission::MemberView));
+        assert!(!member.contains(&WorkspacePermission::MemberRemove));
+        assert!(!member.contains(&WorkspacePermission::MemberRoleChange));
+    }
+
+    #[test]
+    fn test_admin_defaults_cover_every_permission() {
+        let admin = default_permissions(MemberRole::Admin);
+        for permission in [
+            WorkspacePermission::MemberView,
+            WorkspacePermission::MemberInvite,
+            WorkspacePermission::MemberRemove,
+            WorkspacePermission::MemberRoleChange,
+        ] {
+            assert!(admin.contains(&permission), "{permission:?}");
+        }
+    }
+
+    #[sqlx::test(migrator = "crate::db::TEST_MIGRATOR")]
+    #[ignore = "requires DATABASE_URL pointing at a Postgres server"]
+    async fn test_member_permission_defaults_and_grants(pool: PgPool) {
+        let workspace_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+        UserRepository::new(&pool)
+            .upsert_user(UpsertUser {
+                id: user_id,
+                email: "member@example.com",
+                first_name: None,
+                last_name: None,
+                username: None,
+            })
+            .await
+            .unwrap();
+        add_member(&pool, workspace_id, user_id, MemberRole::Member)
+            .await
+            .unwrap();
+
+        let check = |permission| has_permission(&pool, workspace_id, user_id, permission);
+        assert!(check(WorkspacePermission::MemberView).await.unwrap());
+        assert!(!check(WorkspacePermission::MemberRemove).await.unwrap());
+
+        // Explicit grants extend the defaults
+        sqlx::query(
+            r#"
+            UPDATE workspace_member_metadata
+            SET permissions = ARRAY['member.remove']::workspace_permission[]
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        assert!(check(WorkspacePermission::MemberRemove).await.unwrap());
+        assert!(!check(WorkspacePermission::MemberRoleChange).await.unwrap());
+    }
+
+    #[test]
+    fn test_member_search_matches_partial_email() {
+        assert_eq!(
+            member_search_pattern(Some("alice@exa")).as_deref(),
+            Some("%alice@exa%")
+        );
+        assert_eq!(member_search_pattern(Some("   ")), None);
+        assert_eq!(member_search_pattern(None), None);
+    }
+
+    #[test]
+    fn test_member_search_escapes_wildcards() {
+        assert_eq!(
+            member_search_pattern(Some("100%_a\\b")).as_deref(),
+            Some("%100\\%\\_a\\\\b%")
+        );
+    }
+
+    #[test]
+    fn test_member_sort_parses_and_defaults_to_join_order() {
+        assert_eq!(
+            serde_json::from_str::<MemberSort>("\"role\"").unwrap(),
+            MemberSort::Role
+        );
+        assert_eq!(MemberSort::default(), MemberSort::JoinedAt);
+    }
+}
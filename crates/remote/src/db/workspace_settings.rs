@@ -0,0 +1,83 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, FromRow, PgPool, Postgres};
+use uuid::Uuid;
+
+use super::{identity_errors::IdentityError, workspace_members::assert_admin};
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WorkspaceSettings {
+    pub workspace_id: Uuid,
+    pub invitation_activation_delay_secs: i64,
+}
+
+impl WorkspaceSettings {
+    /// Settings used for workspaces that have never been configured.
+    pub fn defaults(workspace_id: Uuid) -> Self {
+        Self {
+            workspace_id,
+            invitation_activation_delay_secs: 0,
+        }
+    }
+
+    pub fn invitation_activation_delay(&self) -> Duration {
+        Duration::seconds(self.invitation_activation_delay_secs.max(0))
+    }
+
+    /// The earliest time an invitation created at `created_at` may be accepted.
+    pub fn invitation_active_at(&self, created_at: DateTime<Utc>) -> DateTime<Utc> {
+        created_at + self.invitation_activation_delay()
+    }
+}
+
+pub async fn get_settings<'a, E>(
+    executor: E,
+    workspace_id: Uuid,
+) -> Result<WorkspaceSettings, IdentityError>
+where
+    E: Executor<'a, Database = Postgres>,
+{
+    let settings: Option<WorkspaceSettings> = sqlx::query_as(
+        r#"
+        SELECT workspace_id, invitation_activation_delay_secs
+        FROM workspace_settings
+        WHERE workspace_id = $1
+        "#,
+    )
+    .bind(workspace_id)
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(settings.unwrap_or_else(|| WorkspaceSettings::defaults(workspace_id)))
+}
+
+pub async fn update_settings(
+    pool: &PgPool,
+    workspace_id: Uuid,
+    requesting_user_id: Uuid,
+    invitation_activation_delay_secs: i64,
+) -> Result<WorkspaceSettings, IdentityError> {
+    assert_admin(pool, workspace_id, requesting_user_id).await?;
+
+    if invitation_activation_delay_secs < 0 {
+        return Err(IdentityError::InvitationError(
+            "Invitation activation delay cannot be negative".to_string(),
+        ));
+    }
+
+    let settings: WorkspaceSettings = sqlx::query_as(
+        r#"
+        INSERT INTO workspace_settings (workspace_id, invitation_activation_delay_secs)
+        VALUES ($1, $2)
+        ON CONFLICT (workspace_id) DO UPDATE
+        SET invitation_activation_delay_secs = EXCLUDED.invitation_activation_delay_secs
+        RETURNING workspace_id, invitation_activation_delay_secs
+        "#,
+    )
+    .bind(workspace_id)
+    .bind(invitation_activation_delay_secs)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(settings)
+}
@@ -1,11 +1,19 @@
-use std::time::Duration;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use aws_credential_types::Credentials;
 use aws_sdk_s3::{
     Client,
     config::{Builder as S3ConfigBuilder, IdentityCache},
+    error::SdkError,
     presigning::PresigningConfig,
+    primitives::ByteStream,
+    types::{Delete, ObjectIdentifier},
 };
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64_STANDARD};
 use chrono::{DateTime, Utc};
 use secrecy::ExposeSecret;
 use uuid::Uuid;
@@ -15,24 +23,97 @@ use crate::config::FilesR2Config;
 /// Allowed MIME types for avatar uploads
 pub const ALLOWED_AVATAR_TYPES: &[&str] = &["image/jpeg", "image/png", "image/gif", "image/webp"];
 
+/// Maximum number of keys accepted by a single S3 `DeleteObjects` request
+const DELETE_OBJECTS_BATCH_SIZE: usize = 1000;
+
+/// Number of leading bytes fetched when confirming an upload's file type
+pub const MAGIC_BYTES_PROBE_LEN: u64 = 4096;
+
+/// Number of leading bytes fetched when the image dimensions aren't within
+/// [`MAGIC_BYTES_PROBE_LEN`]; JPEG metadata segments can push the frame
+/// header well past it
+pub const IMAGE_HEADER_PROBE_LEN: u64 = 256 * 1024;
+
 /// Maximum file size for avatars (5MB default, configurable via env)
 pub const DEFAULT_MAX_AVATAR_SIZE: u64 = 5 * 1024 * 1024;
 
+/// Maximum avatar width and height in pixels (configurable via env)
+pub const DEFAULT_MAX_AVATAR_DIMENSION: u32 = 4096;
+
+/// How long a repeated `Idempotency-Key` returns the original upload, unless
+/// the upload URL itself expires sooner
+const IDEMPOTENCY_KEY_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Upper bound on idempotency keys remembered across all users
+const IDEMPOTENCY_CACHE_CAPACITY: usize = 10_000;
+
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+/// MIME types accepted for task attachments unless `R2_FILES_ATTACHMENT_TYPES`
+/// overrides them
+pub const DEFAULT_ATTACHMENT_TYPES: &[&str] = &[
+    "image/jpeg",
+    "image/png",
+    "image/gif",
+    "image/webp",
+    "text/plain",
+    "application/pdf",
+    "application/zip",
+];
+
 #[derive(Clone)]
 pub struct FilesService {
     client: Client,
     bucket: String,
     public_url: String,
+    /// Environment prefix on every key in the bucket; see [`Self::storage_key`]
+    key_prefix: String,
     presign_expiry: Duration,
+    presign_min_expiry: Duration,
+    presign_max_expiry: Duration,
     max_file_size: u64,
+    attachment_allowed_types: Vec<String>,
+    max_image_dimension: u32,
+    user_quota: u64,
+    recent_uploads: Arc<Mutex<HashMap<(Uuid, String), CachedUpload>>>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PresignedUpload {
     pub upload_url: String,
     pub object_key: String,
     pub public_url: String,
     pub expires_at: DateTime<Utc>,
+    pub content_md5: Option<String>,
+}
+
+/// The request parameters an idempotency key was first used with
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct UploadParams {
+    content_type: String,
+    content_length: Option<u64>,
+    expires_in_secs: Option<u64>,
+    content_md5: Option<String>,
+}
+
+#[derive(Debug)]
+struct CachedUpload {
+    params: UploadParams,
+    upload: PresignedUpload,
+    expires_at: DateTime<Utc>,
+}
+
+/// An object the server stored on the client's behalf
+#[derive(Debug, Clone)]
+pub struct UploadedFile {
+    pub object_key: String,
+    pub public_url: String,
+}
+
+#[derive(Debug)]
+pub struct PresignedDownload {
+    pub download_url: String,
+    pub expires_at: DateTime<Utc>,
 }
 
 #[derive(Debug)]
@@ -57,6 +138,27 @@ pub enum FilesError {
     Delete(String),
     #[error("list error: {0}")]
     List(String),
+    #[error("storage quota exceeded: {0} of {1} bytes used")]
+    QuotaExceeded(u64, u64),
+    #[error("content length is required to check the storage quota")]
+    MissingContentLength,
+    #[error("invalid checksum: {0}")]
+    InvalidChecksum(String),
+    #[error("invalid expiry: {0}")]
+    InvalidExpiry(String),
+    #[error("invalid object key: {0}")]
+    InvalidKey(String),
+    #[error("fetch error: {0}")]
+    Fetch(String),
+    #[error("upload error: {0}")]
+    Upload(String),
+    #[error("invalid idempotency key: {0}")]
+    InvalidIdempotencyKey(String),
+    #[error("idempotency key was already used for a different request")]
+    IdempotencyKeyReused,
+    /// R2 couldn't be reached or didn't answer in time; worth retrying
+    #[error("storage unavailable: {0}")]
+    Unavailable(String),
 }
 
 impl FilesService {
@@ -85,12 +187,26 @@ impl FilesService {
         Self {
             client,
             bucket: config.bucket.clone(),
+            attachment_allowed_types: config.attachment_allowed_types.clone(),
             public_url: config.public_url.trim_end_matches('/').to_string(),
+            key_prefix: config.key_prefix.clone(),
             presign_expiry: Duration::from_secs(config.presign_expiry_secs),
+            presign_min_expiry: Duration::from_secs(config.presign_min_expiry_secs),
+            presign_max_expiry: Duration::from_secs(config.presign_max_expiry_secs),
             max_file_size: config.max_file_size_bytes,
+            max_image_dimension: config.max_image_dimension_px,
+            user_quota: config.user_quota_bytes,
+            recent_uploads: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
+    /// Where `object_key` lives in the bucket. Keys exchanged with clients
+    /// never carry the environment prefix, so ownership checks on
+    /// `avatars/{user_id}/` hold in every environment.
+    fn storage_key(&self, object_key: &str) -> String {
+        format!("{}{object_key}", self.key_prefix)
+    }
+
     /// Validate file type for avatar uploads
     pub fn validate_avatar_type(content_type: &str) -> Result<(), FilesError> {
         if !ALLOWED_AVATAR_TYPES.contains(&content_type) {
@@ -103,6 +219,80 @@ impl FilesService {
         Ok(())
     }
 
+    /// Check that the leading bytes of an uploaded file match its claimed
+    /// image type, so a client can't label arbitrary content as an image.
+    pub fn validate_magic_bytes(bytes: &[u8], claimed_type: &str) -> Result<(), FilesError> {
+        let matches = match claimed_type {
+            "image/jpeg" => bytes.starts_with(&[0xFF, 0xD8, 0xFF]),
+            "image/png" => bytes.starts_with(&[0x89, 0x50, 0x4E, 0x47]),
+            "image/gif" => bytes.starts_with(b"GIF"),
+            "image/webp" => {
+                bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP"
+            }
+            _ => false,
+        };
+
+        if !matches {
+            return Err(FilesError::InvalidFileType(format!(
+                "file contents do not match {claimed_type}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Read `(width, height)` from an image header. Returns `None` when the
+    /// header is malformed or lies beyond the bytes given.
+    pub fn image_dimensions(bytes: &[u8], content_type: &str) -> Option<(u32, u32)> {
+        match content_type {
+            "image/jpeg" => jpeg_dimensions(bytes),
+            "image/png" => png_dimensions(bytes),
+            "image/gif" => gif_dimensions(bytes),
+            "image/webp" => webp_dimensions(bytes),
+            _ => None,
+        }
+    }
+
+    /// Reject images whose width or height exceeds the configured maximum,
+    /// since a small file can still decode to a huge bitmap.
+    pub fn validate_image_dimensions(
+        &self,
+        bytes: &[u8],
+        content_type: &str,
+    ) -> Result<(), FilesError> {
+        let (width, height) = Self::image_dimensions(bytes, content_type).ok_or_else(|| {
+            FilesError::InvalidFileType(format!("could not read {content_type} dimensions"))
+        })?;
+
+        let max = self.max_image_dimension;
+        if width > max || height > max {
+            return Err(FilesError::InvalidFileType(format!(
+                "image is {width}x{height} pixels (max: {max}x{max})"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Validate file type for task attachments against the configured list
+    pub fn validate_attachment_type(&self, content_type: &str) -> Result<(), FilesError> {
+        if !self
+            .attachment_allowed_types
+            .iter()
+            .any(|allowed| allowed == content_type)
+        {
+            return Err(FilesError::InvalidFileType(format!(
+                "{} (allowed: {})",
+                content_type,
+                self.attachment_allowed_types.join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    /// MIME types accepted for task attachments
+    pub fn attachment_allowed_types(&self) -> &[String] {
+        &self.attachment_allowed_types
+    }
+
     /// Validate file size
     pub fn validate_file_size(&self, size: u64) -> Result<(), FilesError> {
         if size > self.max_file_size {
@@ -111,40 +301,203 @@ impl FilesService {
         Ok(())
     }
 
+    /// Check that storing `incoming` more bytes on top of `used` stays within
+    /// the per-user quota
+    pub fn validate_quota(&self, used: u64, incoming: u64) -> Result<(), FilesError> {
+        if used.saturating_add(incoming) > self.user_quota {
+            return Err(FilesError::QuotaExceeded(used, self.user_quota));
+        }
+        Ok(())
+    }
+
+    /// Validate an object key supplied by a client before using it to fetch
+    /// or delete. The key must be `{expected_prefix}{uuid}.{ext}`, where the
+    /// prefix ends with `/`, and must not contain traversal segments, leading
+    /// slashes, backslashes or control characters.
+    pub fn validate_object_key(key: &str, expected_prefix: &str) -> Result<(), FilesError> {
+        Self::validate_key_syntax(key)?;
+
+        let file_name = key.strip_prefix(expected_prefix).ok_or_else(|| {
+            FilesError::InvalidKey(format!("key must start with {expected_prefix}"))
+        })?;
+
+        let (stem, extension) = file_name
+            .split_once('.')
+            .ok_or_else(|| FilesError::InvalidKey("missing file extension".to_string()))?;
+
+        if Uuid::parse_str(stem).is_err() {
+            return Err(FilesError::InvalidKey("unexpected file name".to_string()));
+        }
+
+        if extension.is_empty()
+            || extension.len() > 8
+            || !extension.chars().all(|c| c.is_ascii_alphanumeric())
+        {
+            return Err(FilesError::InvalidKey(
+                "unexpected file extension".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Reject keys that could be interpreted as paths outside their prefix.
+    fn validate_key_syntax(key: &str) -> Result<(), FilesError> {
+        if key.is_empty() {
+            return Err(FilesError::InvalidKey("key is empty".to_string()));
+        }
+        if key.starts_with('/') {
+            return Err(FilesError::InvalidKey(
+                "key must not start with '/'".to_string(),
+            ));
+        }
+        if key.contains('\\') {
+            return Err(FilesError::InvalidKey(
+                "key must not contain '\\'".to_string(),
+            ));
+        }
+        if key.chars().any(char::is_control) {
+            return Err(FilesError::InvalidKey(
+                "key must not contain control characters".to_string(),
+            ));
+        }
+        if key
+            .split('/')
+            .any(|segment| segment.is_empty() || segment == "." || segment == "..")
+        {
+            return Err(FilesError::InvalidKey(
+                "key must not contain empty, '.' or '..' segments".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
     /// Get the maximum allowed file size
     pub fn max_file_size(&self) -> u64 {
         self.max_file_size
     }
 
+    /// Validate a client-supplied Content-MD5 value (base64 of a 16-byte digest)
+    pub fn validate_content_md5(content_md5: &str) -> Result<(), FilesError> {
+        match BASE64_STANDARD.decode(content_md5) {
+            Ok(digest) if digest.len() == 16 => Ok(()),
+            Ok(digest) => Err(FilesError::InvalidChecksum(format!(
+                "content_md5 must encode 16 bytes, got {}",
+                digest.len()
+            ))),
+            Err(e) => Err(FilesError::InvalidChecksum(format!(
+                "content_md5 is not valid base64: {e}"
+            ))),
+        }
+    }
+
+    /// Resolve a client-requested upload URL lifetime, falling back to the
+    /// service default when none is given
+    pub fn resolve_upload_expiry(
+        &self,
+        expires_in_secs: Option<u64>,
+    ) -> Result<Duration, FilesError> {
+        let Some(secs) = expires_in_secs else {
+            return Ok(self.presign_expiry);
+        };
+
+        let expiry = Duration::from_secs(secs);
+        if expiry < self.presign_min_expiry || expiry > self.presign_max_expiry {
+            return Err(FilesError::InvalidExpiry(format!(
+                "expires_in_secs must be between {} and {}",
+                self.presign_min_expiry.as_secs(),
+                self.presign_max_expiry.as_secs()
+            )));
+        }
+        Ok(expiry)
+    }
+
+    /// Get the per-user storage quota
+    pub fn user_quota(&self) -> u64 {
+        self.user_quota
+    }
+
     /// Create a presigned URL for avatar upload
     pub async fn create_avatar_upload_url(
         &self,
         user_id: Uuid,
         content_type: &str,
         content_length: Option<u64>,
+        expires_in_secs: Option<u64>,
+        content_md5: Option<&str>,
     ) -> Result<PresignedUpload, FilesError> {
         // Validate content type
         Self::validate_avatar_type(content_type)?;
 
-        // Validate content length if provided
-        if let Some(size) = content_length {
-            self.validate_file_size(size)?;
+        if let Some(content_md5) = content_md5 {
+            Self::validate_content_md5(content_md5)?;
         }
 
-        // Generate unique filename with extension based on content type
-        let extension = match content_type {
-            "image/jpeg" => "jpg",
-            "image/png" => "png",
-            "image/gif" => "gif",
-            "image/webp" => "webp",
-            _ => "bin",
-        };
+        let expiry = self.resolve_upload_expiry(expires_in_secs)?;
+        let content_length = self.check_declared_size(user_id, content_length).await?;
+
+        let object_key = avatar_object_key(user_id, content_type);
+        self.presign_upload(
+            object_key,
+            content_type,
+            content_length,
+            expiry,
+            content_md5,
+        )
+        .await
+    }
+
+    /// Create a presigned URL for uploading an attachment to a task. Access
+    /// to the task is the caller's to check.
+    pub async fn create_task_attachment_upload_url(
+        &self,
+        task_id: Uuid,
+        user_id: Uuid,
+        content_type: &str,
+        content_length: Option<u64>,
+    ) -> Result<PresignedUpload, FilesError> {
+        self.validate_attachment_type(content_type)?;
+        let content_length = self.check_declared_size(user_id, content_length).await?;
+
+        let object_key = attachment_object_key(user_id, task_id, content_type);
+        self.presign_upload(
+            object_key,
+            content_type,
+            content_length,
+            self.presign_expiry,
+            None,
+        )
+        .await
+    }
 
-        let file_id = Uuid::new_v4();
-        let object_key = format!("avatars/{user_id}/{file_id}.{extension}");
+    /// Presigned PUTs bypass the server, so the quota can only be checked
+    /// against the size the client declares up front. The size is required
+    /// and gets signed into the URL, so R2 rejects a body of any other length.
+    async fn check_declared_size(
+        &self,
+        user_id: Uuid,
+        content_length: Option<u64>,
+    ) -> Result<u64, FilesError> {
+        let size = content_length.ok_or(FilesError::MissingContentLength)?;
+        self.validate_file_size(size)?;
+
+        let used = self.user_storage_bytes(user_id).await?;
+        self.validate_quota(used, size)?;
+        Ok(size)
+    }
 
+    /// Sign a PUT of `object_key` with its exact length, optionally pinning
+    /// its Content-MD5
+    async fn presign_upload(
+        &self,
+        object_key: String,
+        content_type: &str,
+        content_length: u64,
+        expiry: Duration,
+        content_md5: Option<&str>,
+    ) -> Result<PresignedUpload, FilesError> {
         let presigning_config = PresigningConfig::builder()
-            .expires_in(self.presign_expiry)
+            .expires_in(expiry)
             .build()
             .map_err(|e| FilesError::PresignConfig(e.to_string()))?;
 
@@ -152,44 +505,266 @@ impl FilesService {
             .client
             .put_object()
             .bucket(&self.bucket)
-            .key(&object_key)
-            .content_type(content_type);
+            .key(self.storage_key(&object_key))
+            .content_type(content_type)
+            .content_length(i64::try_from(content_length).unwrap_or(i64::MAX))
+            // Signing the header makes R2 reject a body that doesn't match
+            .set_content_md5(content_md5.map(str::to_string));
 
         let presigned = request
             .presigned(presigning_config)
             .await
             .map_err(|e| FilesError::Presign(e.to_string()))?;
 
-        let expires_at = Utc::now()
-            + chrono::Duration::from_std(self.presign_expiry)
-                .unwrap_or(chrono::Duration::minutes(5));
+        let expires_at =
+            Utc::now() + chrono::Duration::from_std(expiry).unwrap_or(chrono::Duration::minutes(5));
 
-        let public_url = format!("{}/{}", self.public_url, object_key);
+        let public_url = self.get_public_url(&object_key);
 
         Ok(PresignedUpload {
             upload_url: presigned.uri().to_string(),
             object_key,
             public_url,
             expires_at,
+            content_md5: content_md5.map(str::to_string),
+        })
+    }
+
+    /// Run every avatar check the confirm step runs against a stored object,
+    /// on bytes held in memory
+    pub fn validate_avatar_bytes(
+        &self,
+        bytes: &[u8],
+        content_type: &str,
+    ) -> Result<(), FilesError> {
+        Self::validate_avatar_type(content_type)?;
+        self.validate_file_size(bytes.len() as u64)?;
+        Self::validate_magic_bytes(bytes, content_type)?;
+        self.validate_image_dimensions(bytes, content_type)
+    }
+
+    /// Validate and store an avatar whose bytes the client sent to the
+    /// server, for clients that can't reach R2 to use a presigned URL
+    pub async fn upload_avatar(
+        &self,
+        user_id: Uuid,
+        content_type: &str,
+        bytes: Vec<u8>,
+    ) -> Result<UploadedFile, FilesError> {
+        self.validate_avatar_bytes(&bytes, content_type)?;
+
+        let used = self.user_storage_bytes(user_id).await?;
+        self.validate_quota(used, bytes.len() as u64)?;
+
+        let object_key = avatar_object_key(user_id, content_type);
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(self.storage_key(&object_key))
+            .content_type(content_type)
+            .body(ByteStream::from(bytes))
+            .send()
+            .await
+            .map_err(|e| s3_error(e, FilesError::Upload))?;
+
+        Ok(UploadedFile {
+            public_url: self.get_public_url(&object_key),
+            object_key,
+        })
+    }
+
+    /// Like `create_avatar_upload_url`, but a retry carrying the same
+    /// idempotency key from the same user gets the original upload back
+    /// instead of a fresh object key. Reusing a key with different
+    /// parameters is rejected. Requests that race on a key before either
+    /// finishes may still each create an upload.
+    pub async fn create_avatar_upload_url_idempotent(
+        &self,
+        user_id: Uuid,
+        idempotency_key: &str,
+        content_type: &str,
+        content_length: Option<u64>,
+        expires_in_secs: Option<u64>,
+        content_md5: Option<&str>,
+    ) -> Result<PresignedUpload, FilesError> {
+        if idempotency_key.is_empty() || idempotency_key.len() > MAX_IDEMPOTENCY_KEY_LEN {
+            return Err(FilesError::InvalidIdempotencyKey(format!(
+                "must be between 1 and {MAX_IDEMPOTENCY_KEY_LEN} characters"
+            )));
+        }
+
+        let cache_key = (user_id, idempotency_key.to_string());
+        let params = UploadParams {
+            content_type: content_type.to_string(),
+            content_length,
+            expires_in_secs,
+            content_md5: content_md5.map(str::to_string),
+        };
+
+        if let Some(upload) = self.recent_upload(&cache_key, &params)? {
+            return Ok(upload);
+        }
+
+        let upload = self
+            .create_avatar_upload_url(
+                user_id,
+                content_type,
+                content_length,
+                expires_in_secs,
+                content_md5,
+            )
+            .await?;
+        self.remember_upload(cache_key, params, &upload);
+
+        Ok(upload)
+    }
+
+    fn recent_upload(
+        &self,
+        cache_key: &(Uuid, String),
+        params: &UploadParams,
+    ) -> Result<Option<PresignedUpload>, FilesError> {
+        let mut uploads = self
+            .recent_uploads
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+
+        match uploads.get(cache_key) {
+            Some(cached) if cached.expires_at <= Utc::now() => {
+                uploads.remove(cache_key);
+                Ok(None)
+            }
+            Some(cached) if &cached.params != params => Err(FilesError::IdempotencyKeyReused),
+            Some(cached) => Ok(Some(cached.upload.clone())),
+            None => Ok(None),
+        }
+    }
+
+    fn remember_upload(
+        &self,
+        cache_key: (Uuid, String),
+        params: UploadParams,
+        upload: &PresignedUpload,
+    ) {
+        let now = Utc::now();
+        let ttl = chrono::Duration::from_std(IDEMPOTENCY_KEY_TTL)
+            .unwrap_or(chrono::Duration::minutes(10));
+        let expires_at = upload.expires_at.min(now + ttl);
+
+        let mut uploads = self
+            .recent_uploads
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+
+        if uploads.len() >= IDEMPOTENCY_CACHE_CAPACITY {
+            uploads.retain(|_, cached| cached.expires_at > now);
+        }
+        if uploads.len() >= IDEMPOTENCY_CACHE_CAPACITY
+            && let Some(oldest) = uploads
+                .iter()
+                .min_by_key(|(_, cached)| cached.expires_at)
+                .map(|(key, _)| key.clone())
+        {
+            uploads.remove(&oldest);
+        }
+
+        uploads.insert(
+            cache_key,
+            CachedUpload {
+                params,
+                upload: upload.clone(),
+                expires_at,
+            },
+        );
+    }
+
+    /// Create a presigned URL for downloading a private object. Uses the
+    /// service's presign expiry unless `expiry` overrides it.
+    pub async fn create_download_url(
+        &self,
+        object_key: &str,
+        expiry: Option<Duration>,
+    ) -> Result<PresignedDownload, FilesError> {
+        let expiry = expiry.unwrap_or(self.presign_expiry);
+
+        let presigning_config = PresigningConfig::builder()
+            .expires_in(expiry)
+            .build()
+            .map_err(|e| FilesError::PresignConfig(e.to_string()))?;
+
+        let presigned = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(self.storage_key(object_key))
+            .presigned(presigning_config)
+            .await
+            .map_err(|e| FilesError::Presign(e.to_string()))?;
+
+        let expires_at =
+            Utc::now() + chrono::Duration::from_std(expiry).unwrap_or(chrono::Duration::minutes(5));
+
+        Ok(PresignedDownload {
+            download_url: presigned.uri().to_string(),
+            expires_at,
         })
     }
 
+    /// Fetch the first `len` bytes of an object along with its stored
+    /// content type.
+    pub async fn read_object_head(
+        &self,
+        object_key: &str,
+        len: u64,
+    ) -> Result<(Vec<u8>, Option<String>), FilesError> {
+        let response = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(self.storage_key(object_key))
+            .range(format!("bytes=0-{}", len.saturating_sub(1)))
+            .send()
+            .await
+            .map_err(|e| s3_error(e, FilesError::Fetch))?;
+
+        let content_type = response.content_type.clone();
+        let bytes = response
+            .body
+            .collect()
+            .await
+            .map_err(|e| FilesError::Fetch(e.to_string()))?
+            .into_bytes();
+
+        Ok((bytes.to_vec(), content_type))
+    }
+
+    /// Confirm the bucket exists and these credentials can reach it
+    pub async fn check_bucket(&self) -> Result<(), FilesError> {
+        self.client
+            .head_bucket()
+            .bucket(&self.bucket)
+            .send()
+            .await
+            .map_err(|e| s3_error(e, FilesError::Fetch))?;
+        Ok(())
+    }
+
     /// Delete a file from R2
     pub async fn delete_file(&self, object_key: &str) -> Result<(), FilesError> {
         self.client
             .delete_object()
             .bucket(&self.bucket)
-            .key(object_key)
+            .key(self.storage_key(object_key))
             .send()
             .await
-            .map_err(|e| FilesError::Delete(e.to_string()))?;
+            .map_err(|e| s3_error(e, FilesError::Delete))?;
 
         Ok(())
     }
 
     /// Delete all files in a user's avatar folder
     pub async fn delete_user_avatars(&self, user_id: Uuid) -> Result<u32, FilesError> {
-        let prefix = format!("avatars/{user_id}/");
+        let prefix = self.storage_key(&format!("avatars/{user_id}/"));
         let mut deleted_count = 0u32;
 
         let mut continuation_token: Option<String> = None;
@@ -208,15 +783,17 @@ impl FilesService {
             let response = request
                 .send()
                 .await
-                .map_err(|e| FilesError::List(e.to_string()))?;
+                .map_err(|e| s3_error(e, FilesError::List))?;
 
-            if let Some(contents) = response.contents {
-                for object in contents {
-                    if let Some(key) = object.key {
-                        self.delete_file(&key).await?;
-                        deleted_count += 1;
-                    }
-                }
+            let keys: Vec<String> = response
+                .contents
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|object| object.key)
+                .collect();
+
+            for batch in keys.chunks(DELETE_OBJECTS_BATCH_SIZE) {
+                deleted_count += self.delete_objects(batch).await?;
             }
 
             if response.is_truncated == Some(true) {
@@ -229,10 +806,62 @@ impl FilesService {
         Ok(deleted_count)
     }
 
-    /// List files for a user
-    pub async fn list_user_avatars(&self, user_id: Uuid) -> Result<Vec<FileInfo>, FilesError> {
-        let prefix = format!("avatars/{user_id}/");
-        let mut files = Vec::new();
+    /// Delete up to `DELETE_OBJECTS_BATCH_SIZE` keys in a single request,
+    /// returning how many were deleted
+    async fn delete_objects(&self, keys: &[String]) -> Result<u32, FilesError> {
+        let objects = keys
+            .iter()
+            .map(|key| ObjectIdentifier::builder().key(key).build())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| FilesError::Delete(e.to_string()))?;
+
+        let delete = Delete::builder()
+            .set_objects(Some(objects))
+            .quiet(false)
+            .build()
+            .map_err(|e| FilesError::Delete(e.to_string()))?;
+
+        let response = self
+            .client
+            .delete_objects()
+            .bucket(&self.bucket)
+            .delete(delete)
+            .send()
+            .await
+            .map_err(|e| s3_error(e, FilesError::Delete))?;
+
+        if !response.errors().is_empty() {
+            let failures = response
+                .errors()
+                .iter()
+                .map(|error| {
+                    format!(
+                        "{}: {}",
+                        error.key().unwrap_or("<unknown>"),
+                        error.message().or(error.code()).unwrap_or("unknown error")
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            return Err(FilesError::Delete(failures));
+        }
+
+        Ok(response.deleted().len() as u32)
+    }
+
+    /// Total bytes stored by a user across their avatars and attachments
+    pub async fn user_storage_bytes(&self, user_id: Uuid) -> Result<u64, FilesError> {
+        let avatars = self
+            .prefix_size(&self.storage_key(&format!("avatars/{user_id}/")))
+            .await?;
+        let attachments = self
+            .prefix_size(&self.storage_key(&format!("attachments/{user_id}/")))
+            .await?;
+        Ok(avatars + attachments)
+    }
+
+    async fn prefix_size(&self, prefix: &str) -> Result<u64, FilesError> {
+        let mut total = 0u64;
 
         let mut continuation_token: Option<String> = None;
 
@@ -241,7 +870,7 @@ impl FilesService {
                 .client
                 .list_objects_v2()
                 .bucket(&self.bucket)
-                .prefix(&prefix);
+                .prefix(prefix);
 
             if let Some(token) = continuation_token {
                 request = request.continuation_token(token);
@@ -250,25 +879,15 @@ impl FilesService {
             let response = request
                 .send()
                 .await
-                .map_err(|e| FilesError::List(e.to_string()))?;
-
-            if let Some(contents) = response.contents {
-                for object in contents {
-                    if let Some(key) = object.key {
-                        let public_url = format!("{}/{}", self.public_url, key);
-                        let last_modified = object
-                            .last_modified
-                            .and_then(|dt| DateTime::from_timestamp(dt.secs(), dt.subsec_nanos()));
+                .map_err(|e| s3_error(e, FilesError::List))?;
 
-                        files.push(FileInfo {
-                            key,
-                            public_url,
-                            size: object.size,
-                            last_modified,
-                        });
-                    }
-                }
-            }
+            total += response
+                .contents
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|object| object.size)
+                .map(|size| size.max(0) as u64)
+                .sum::<u64>();
 
             if response.is_truncated == Some(true) {
                 continuation_token = response.next_continuation_token;
@@ -277,26 +896,846 @@ impl FilesService {
             }
         }
 
-        Ok(files)
+        Ok(total)
     }
 
-    /// Get the public URL for an object key
-    pub fn get_public_url(&self, object_key: &str) -> String {
-        format!("{}/{}", self.public_url, object_key)
+    /// List files for a user
+    pub async fn list_user_avatars(&self, user_id: Uuid) -> Result<Vec<FileInfo>, FilesError> {
+        self.list_files(&format!("avatars/{user_id}/")).await
     }
 
-    /// Extract the object key from a public URL
-    pub fn extract_object_key(&self, public_url: &str) -> Option<String> {
-        public_url
-            .strip_prefix(&self.public_url)
-            .map(|s| s.trim_start_matches('/').to_string())
+    /// List the attachments `uploaders` have added to a task
+    pub async fn list_task_attachments(
+        &self,
+        task_id: Uuid,
+        uploaders: &[Uuid],
+    ) -> Result<Vec<FileInfo>, FilesError> {
+        let mut files = Vec::new();
+        for user_id in uploaders {
+            files.extend(
+                self.list_files(&attachment_prefix(*user_id, task_id))
+                    .await?,
+            );
+        }
+        Ok(files)
     }
-}
 
-#[cfg(test)]
+    /// The user who uploaded a task attachment. The key must be
+    /// `attachments/{uploader}/{task_id}/{file}`, so a caller with access to
+    /// one task can't reach another task's files.
+    pub fn attachment_uploader(task_id: Uuid, object_key: &str) -> Result<Uuid, FilesError> {
+        let uploader = object_key
+            .strip_prefix("attachments/")
+            .and_then(|rest| rest.split_once('/'))
+            .and_then(|(uploader, _)| Uuid::parse_str(uploader).ok())
+            .ok_or_else(|| FilesError::InvalidKey("not an attachment of this task".to_string()))?;
+        Self::validate_object_key(object_key, &attachment_prefix(uploader, task_id))?;
+        Ok(uploader)
+    }
+
+    /// Delete one of a task's attachments
+    pub async fn delete_task_attachment(
+        &self,
+        task_id: Uuid,
+        object_key: &str,
+    ) -> Result<(), FilesError> {
+        Self::attachment_uploader(task_id, object_key)?;
+        self.delete_file(object_key).await
+    }
+
+    /// List the files under `prefix`, returning keys without the
+    /// environment prefix
+    async fn list_files(&self, prefix: &str) -> Result<Vec<FileInfo>, FilesError> {
+        let prefix = self.storage_key(prefix);
+        let mut files = Vec::new();
+
+        let mut continuation_token: Option<String> = None;
+
+        loop {
+            let mut request = self
+                .client
+                .list_objects_v2()
+                .bucket(&self.bucket)
+                .prefix(&prefix);
+
+            if let Some(token) = continuation_token {
+                request = request.continuation_token(token);
+            }
+
+            let response = request
+                .send()
+                .await
+                .map_err(|e| s3_error(e, FilesError::List))?;
+
+            if let Some(contents) = response.contents {
+                for object in contents {
+                    if let Some(key) = object.key {
+                        let key = key
+                            .strip_prefix(&self.key_prefix)
+                            .map(str::to_string)
+                            .unwrap_or(key);
+                        let public_url = self.get_public_url(&key);
+                        let last_modified = object
+                            .last_modified
+                            .and_then(|dt| DateTime::from_timestamp(dt.secs(), dt.subsec_nanos()));
+
+                        files.push(FileInfo {
+                            key,
+                            public_url,
+                            size: object.size,
+                            last_modified,
+                        });
+                    }
+                }
+            }
+
+            if response.is_truncated == Some(true) {
+                continuation_token = response.next_continuation_token;
+            } else {
+                break;
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// Get the public URL for an object key
+    pub fn get_public_url(&self, object_key: &str) -> String {
+        format!("{}/{}", self.public_url, self.storage_key(object_key))
+    }
+
+    /// Extract the object key from a public URL, the inverse of
+    /// [`Self::get_public_url`]. The URL must continue with a path after the
+    /// public base, so lookalike hosts such as
+    /// `https://files.example.com.evil.test/...` are not mistaken for ours,
+    /// and with this environment's key prefix, so another environment's
+    /// objects in a shared bucket are not either.
+    pub fn extract_object_key(&self, public_url: &str) -> Option<String> {
+        public_url
+            .strip_prefix(self.public_url.trim_end_matches('/'))
+            .and_then(|rest| rest.strip_prefix('/'))
+            .map(|s| s.trim_start_matches('/'))
+            .and_then(|path| path.strip_prefix(&self.key_prefix))
+            .map(str::to_string)
+            .filter(|key| Self::validate_key_syntax(key).is_ok())
+    }
+}
+
+/// Failures to reach R2 at all (refused connections, timeouts) become
+/// [`FilesError::Unavailable`]; anything R2 answered, or that went wrong
+/// before a request was sent, is wrapped with `other`.
+fn s3_error<E, R>(error: SdkError<E, R>, other: fn(String) -> FilesError) -> FilesError {
+    let unavailable = match &error {
+        SdkError::TimeoutError(_) => true,
+        SdkError::DispatchFailure(failure) => failure.is_io() || failure.is_timeout(),
+        _ => false,
+    };
+    if unavailable {
+        FilesError::Unavailable(error.to_string())
+    } else {
+        other(error.to_string())
+    }
+}
+
+/// A fresh key under the user's avatar prefix, with an extension matching
+/// the content type
+fn avatar_object_key(user_id: Uuid, content_type: &str) -> String {
+    let extension = match content_type {
+        "image/jpeg" => "jpg",
+        "image/png" => "png",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        _ => "bin",
+    };
+    format!("avatars/{user_id}/{}.{extension}", Uuid::new_v4())
+}
+
+fn be_u16(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn le_u16(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn le_u24(bytes: &[u8], at: usize) -> Option<u32> {
+    let b = bytes.get(at..at + 3)?;
+    Some(u32::from_le_bytes([b[0], b[1], b[2], 0]))
+}
+
+/// Width and height from the `IHDR` chunk, which must come first
+fn png_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    if bytes.get(12..16)? != b"IHDR" {
+        return None;
+    }
+    let width = u32::from_be_bytes(bytes.get(16..20)?.try_into().ok()?);
+    let height = u32::from_be_bytes(bytes.get(20..24)?.try_into().ok()?);
+    Some((width, height))
+}
+
+/// Logical screen size from the header; every frame must fit inside it
+fn gif_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    Some((le_u16(bytes, 6)?.into(), le_u16(bytes, 8)?.into()))
+}
+
+/// Canvas size from the first chunk, which depends on the WebP variant
+fn webp_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    match bytes.get(12..16)? {
+        // Lossy: 14-bit sizes after the key frame start code
+        b"VP8 " => {
+            if bytes.get(23..26)? != [0x9D, 0x01, 0x2A] {
+                return None;
+            }
+            let width = le_u16(bytes, 26)? & 0x3FFF;
+            let height = le_u16(bytes, 28)? & 0x3FFF;
+            Some((width.into(), height.into()))
+        }
+        // Lossless: signature byte, then two 14-bit sizes minus one
+        b"VP8L" => {
+            if *bytes.get(20)? != 0x2F {
+                return None;
+            }
+            let bits = u32::from_le_bytes(bytes.get(21..25)?.try_into().ok()?);
+            Some(((bits & 0x3FFF) + 1, ((bits >> 14) & 0x3FFF) + 1))
+        }
+        // Extended: 24-bit canvas sizes minus one
+        b"VP8X" => Some((le_u24(bytes, 24)? + 1, le_u24(bytes, 27)? + 1)),
+        _ => None,
+    }
+}
+
+/// Size from the first start-of-frame segment, skipping the segments
+/// (EXIF, ICC profiles, ...) before it
+fn jpeg_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    // Skip the SOI marker
+    let mut pos = 2;
+    loop {
+        if *bytes.get(pos)? != 0xFF {
+            return None;
+        }
+        let marker = *bytes.get(pos + 1)?;
+        pos += 2;
+        match marker {
+            // Fill byte before the real marker
+            0xFF => pos -= 1,
+            // Standalone markers carry no length
+            0x01 | 0xD0..=0xD7 => {}
+            // Image data or end of image before any frame header
+            0xD9 | 0xDA => return None,
+            // SOF0-SOF15, except DHT, JPG and DAC which share the range
+            0xC0..=0xCF if !matches!(marker, 0xC4 | 0xC8 | 0xCC) => {
+                let height = be_u16(bytes, pos + 3)?;
+                let width = be_u16(bytes, pos + 5)?;
+                return Some((width.into(), height.into()));
+            }
+            _ => {
+                let len = usize::from(be_u16(bytes, pos)?);
+                if len < 2 {
+                    return None;
+                }
+                pos += len;
+            }
+        }
+    }
+}
+
+/// Where a user's attachments to a task live. Keying by uploader first lets
+/// storage usage be summed per user.
+fn attachment_prefix(user_id: Uuid, task_id: Uuid) -> String {
+    format!("attachments/{user_id}/{task_id}/")
+}
+
+/// A fresh key under the uploader's prefix for the task, with an extension
+/// matching the content type
+fn attachment_object_key(user_id: Uuid, task_id: Uuid, content_type: &str) -> String {
+    let extension = match content_type {
+        "image/jpeg" => "jpg",
+        "image/png" => "png",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "text/plain" => "txt",
+        "application/pdf" => "pdf",
+        "application/zip" => "zip",
+        _ => "bin",
+    };
+    format!(
+        "{}{}.{extension}",
+        attachment_prefix(user_id, task_id),
+        Uuid::new_v4()
+    )
+}
+
+/// A service pointed at an unreachable bucket, for tests that never reach it
+#[cfg(test)]
+pub(crate) fn test_service() -> FilesService {
+    FilesService::new(&FilesR2Config {
+        access_key_id: "test-access-key".to_string(),
+        secret_access_key: secrecy::SecretString::from("test-secret-key".to_string()),
+        endpoint: "https://example.r2.cloudflarestorage.com".to_string(),
+        bucket: "files".to_string(),
+        public_url: "https://files.example.com".to_string(),
+        presign_expiry_secs: 300,
+        presign_min_expiry_secs: 60,
+        presign_max_expiry_secs: 3600,
+        max_file_size_bytes: DEFAULT_MAX_AVATAR_SIZE,
+        max_image_dimension_px: DEFAULT_MAX_AVATAR_DIMENSION,
+        user_quota_bytes: 10 * DEFAULT_MAX_AVATAR_SIZE,
+        key_prefix: String::new(),
+        attachment_allowed_types: DEFAULT_ATTACHMENT_TYPES
+            .iter()
+            .map(|content_type| content_type.to_string())
+            .collect(),
+    })
+}
+
+#[cfg(test)]
 mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use aws_sdk_s3::config::{StalledStreamProtectionConfig, retry::RetryConfig};
+    use aws_smithy_runtime_api::{
+        client::{
+            http::{
+                HttpClient, HttpConnector, HttpConnectorFuture, HttpConnectorSettings,
+                SharedHttpConnector,
+            },
+            orchestrator::{HttpRequest, HttpResponse},
+            result::ConnectorError,
+            runtime_components::RuntimeComponents,
+        },
+        http::StatusCode,
+    };
+    use aws_smithy_types::body::SdkBody;
+
     use super::*;
 
+    /// In-process stand-in for the bucket: lists a fixed set of keys and
+    /// answers `DeleteObjects`, recording the method and URI of each request.
+    #[derive(Debug, Clone, Default)]
+    struct FakeBucket {
+        keys: Vec<String>,
+        object_size: i64,
+        failing_key: Option<String>,
+        requests: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl FakeBucket {
+        fn with_keys(keys: Vec<String>) -> Self {
+            Self {
+                keys,
+                ..Default::default()
+            }
+        }
+
+        fn requests(&self) -> Vec<String> {
+            self.requests.lock().unwrap().clone()
+        }
+
+        fn list_response(&self, uri: &str) -> String {
+            let prefix = uri
+                .split(['?', '&'])
+                .find_map(|param| param.strip_prefix("prefix="))
+                .map(|prefix| urlencoding::decode(prefix).unwrap().into_owned())
+                .unwrap_or_default();
+            let keys: Vec<&String> = self
+                .keys
+                .iter()
+                .filter(|key| key.starts_with(&prefix))
+                .collect();
+            let contents: String = keys
+                .iter()
+                .map(|key| {
+                    format!(
+                        "<Contents><Key>{key}</Key><Size>{}</Size></Contents>",
+                        self.object_size
+                    )
+                })
+                .collect();
+            format!(
+                "<ListBucketResult><Name>files</Name><KeyCount>{}</KeyCount>\
+                 <IsTruncated>false</IsTruncated>{contents}</ListBucketResult>",
+                keys.len()
+            )
+        }
+
+        fn delete_response(&self, body: &str) -> String {
+            let results: String = body
+                .split("<Key>")
+                .skip(1)
+                .filter_map(|rest| rest.split("</Key>").next())
+                .map(|key| {
+                    if self.failing_key.as_deref() == Some(key) {
+                        format!(
+                            "<Error><Key>{key}</Key><Code>AccessDenied</Code>\
+                             <Message>Access Denied</Message></Error>"
+                        )
+                    } else {
+                        format!("<Deleted><Key>{key}</Key></Deleted>")
+                    }
+                })
+                .collect();
+            format!("<DeleteResult>{results}</DeleteResult>")
+        }
+    }
+
+    impl HttpConnector for FakeBucket {
+        fn call(&self, request: HttpRequest) -> HttpConnectorFuture {
+            let method = request.method().to_string();
+            let uri = request.uri().to_string();
+            self.requests
+                .lock()
+                .unwrap()
+                .push(format!("{method} {uri}"));
+
+            let body = match method.as_str() {
+                "GET" => self.list_response(&uri),
+                "POST" => {
+                    let body = request
+                        .body()
+                        .bytes()
+                        .map(|bytes| String::from_utf8_lossy(bytes).into_owned())
+                        .unwrap_or_default();
+                    self.delete_response(&body)
+                }
+                _ => String::new(),
+            };
+
+            HttpConnectorFuture::ready(Ok(HttpResponse::new(
+                StatusCode::try_from(200).unwrap(),
+                SdkBody::from(body),
+            )))
+        }
+    }
+
+    impl HttpClient for FakeBucket {
+        fn http_connector(
+            &self,
+            _settings: &HttpConnectorSettings,
+            _components: &RuntimeComponents,
+        ) -> SharedHttpConnector {
+            SharedHttpConnector::new(self.clone())
+        }
+    }
+
+    /// R2 endpoint that refuses every connection
+    #[derive(Debug, Clone)]
+    struct UnreachableBucket;
+
+    impl HttpConnector for UnreachableBucket {
+        fn call(&self, _request: HttpRequest) -> HttpConnectorFuture {
+            HttpConnectorFuture::ready(Err(ConnectorError::io("connection refused".into())))
+        }
+    }
+
+    impl HttpClient for UnreachableBucket {
+        fn http_connector(
+            &self,
+            _settings: &HttpConnectorSettings,
+            _components: &RuntimeComponents,
+        ) -> SharedHttpConnector {
+            SharedHttpConnector::new(self.clone())
+        }
+    }
+
+    fn service_with_bucket(bucket: impl HttpClient + 'static) -> FilesService {
+        let s3_config = S3ConfigBuilder::new()
+            .region(aws_sdk_s3::config::Region::new("auto"))
+            .endpoint_url("https://example.r2.cloudflarestorage.com")
+            .credentials_provider(Credentials::new(
+                "test-access-key",
+                "test-secret-key",
+                None,
+                None,
+                "test",
+            ))
+            .force_path_style(true)
+            .stalled_stream_protection(StalledStreamProtectionConfig::disabled())
+            .identity_cache(IdentityCache::no_cache())
+            .retry_config(RetryConfig::disabled())
+            .http_client(bucket)
+            .build();
+
+        FilesService {
+            client: Client::from_conf(s3_config),
+            ..test_service()
+        }
+    }
+
+    fn avatar_keys(user_id: Uuid, count: usize) -> Vec<String> {
+        (0..count)
+            .map(|_| format!("avatars/{user_id}/{}.png", Uuid::new_v4()))
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_attachment_type() {
+        let service = test_service();
+        assert!(service.validate_attachment_type("application/pdf").is_ok());
+        assert!(service.validate_attachment_type("text/plain").is_ok());
+        assert!(service.validate_attachment_type("image/png").is_ok());
+        assert!(matches!(
+            service.validate_attachment_type("application/x-msdownload"),
+            Err(FilesError::InvalidFileType(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_create_task_attachment_upload_url_keys_by_uploader_and_task() {
+        let service = service_with_bucket(FakeBucket::default());
+        let task_id = Uuid::new_v4();
+        let user_id = Uuid::new_v4();
+
+        let upload = service
+            .create_task_attachment_upload_url(task_id, user_id, "application/pdf", Some(1))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            FilesService::attachment_uploader(task_id, &upload.object_key).unwrap(),
+            user_id
+        );
+        assert!(upload.object_key.ends_with(".pdf"));
+        assert!(upload.upload_url.contains(&upload.object_key));
+    }
+
+    #[tokio::test]
+    async fn test_delete_task_attachment_rejects_other_tasks_keys() {
+        let service = test_service();
+        let other_key = attachment_object_key(Uuid::new_v4(), Uuid::new_v4(), "application/pdf");
+
+        let result = service
+            .delete_task_attachment(Uuid::new_v4(), &other_key)
+            .await;
+
+        assert!(matches!(result, Err(FilesError::InvalidKey(_))));
+    }
+
+    #[test]
+    fn test_attachment_uploader_rejects_malformed_keys() {
+        let task_id = Uuid::new_v4();
+        for key in [
+            format!("attachments/{task_id}/{}.pdf", Uuid::new_v4()),
+            format!("attachments/not-a-user/{task_id}/{}.pdf", Uuid::new_v4()),
+            format!(
+                "avatars/{}/{task_id}/{}.png",
+                Uuid::new_v4(),
+                Uuid::new_v4()
+            ),
+        ] {
+            assert!(matches!(
+                FilesService::attachment_uploader(task_id, &key),
+                Err(FilesError::InvalidKey(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_validate_magic_bytes_jpeg() {
+        let bytes = [0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x10];
+        assert!(FilesService::validate_magic_bytes(&bytes, "image/jpeg").is_ok());
+    }
+
+    #[test]
+    fn test_validate_magic_bytes_png() {
+        let bytes = [0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+        assert!(FilesService::validate_magic_bytes(&bytes, "image/png").is_ok());
+    }
+
+    #[test]
+    fn test_validate_magic_bytes_gif() {
+        assert!(FilesService::validate_magic_bytes(b"GIF89a", "image/gif").is_ok());
+        assert!(FilesService::validate_magic_bytes(b"GIF87a", "image/gif").is_ok());
+    }
+
+    #[test]
+    fn test_validate_magic_bytes_webp() {
+        let bytes = b"RIFF\x24\x00\x00\x00WEBPVP8 ";
+        assert!(FilesService::validate_magic_bytes(bytes, "image/webp").is_ok());
+    }
+
+    #[test]
+    fn test_validate_magic_bytes_rejects_mismatch() {
+        // ELF executable labelled as a PNG
+        let elf = [0x7F, 0x45, 0x4C, 0x46, 0x02, 0x01, 0x01, 0x00];
+        assert!(matches!(
+            FilesService::validate_magic_bytes(&elf, "image/png"),
+            Err(FilesError::InvalidFileType(_))
+        ));
+
+        // Real PNG header claimed as JPEG
+        let png = [0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+        assert!(FilesService::validate_magic_bytes(&png, "image/jpeg").is_err());
+
+        // RIFF container that isn't WEBP (e.g. WAV)
+        assert!(
+            FilesService::validate_magic_bytes(b"RIFF\x24\x00\x00\x00WAVEfmt ", "image/webp")
+                .is_err()
+        );
+
+        // Truncated input
+        assert!(FilesService::validate_magic_bytes(&[0xFF, 0xD8], "image/jpeg").is_err());
+    }
+
+    fn png_header(width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = vec![0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+        bytes.extend_from_slice(&13u32.to_be_bytes());
+        bytes.extend_from_slice(b"IHDR");
+        bytes.extend_from_slice(&width.to_be_bytes());
+        bytes.extend_from_slice(&height.to_be_bytes());
+        bytes
+    }
+
+    #[test]
+    fn test_image_dimensions_png() {
+        assert_eq!(
+            FilesService::image_dimensions(&png_header(640, 480), "image/png"),
+            Some((640, 480))
+        );
+        // The first chunk must be IHDR
+        let mut bytes = png_header(640, 480);
+        bytes[12..16].copy_from_slice(b"tEXt");
+        assert_eq!(FilesService::image_dimensions(&bytes, "image/png"), None);
+    }
+
+    #[test]
+    fn test_image_dimensions_gif() {
+        let bytes = b"GIF89a\x40\x01\xF0\x00";
+        assert_eq!(
+            FilesService::image_dimensions(bytes, "image/gif"),
+            Some((320, 240))
+        );
+    }
+
+    #[test]
+    fn test_image_dimensions_webp() {
+        let lossy =
+            b"RIFF\x00\x00\x00\x00WEBPVP8 \x00\x00\x00\x00\x00\x00\x00\x9D\x01\x2A\x80\x02\xE0\x01";
+        assert_eq!(
+            FilesService::image_dimensions(lossy, "image/webp"),
+            Some((640, 480))
+        );
+
+        // 640x480 stored as (width - 1) | (height - 1) << 14
+        let bits: u32 = 639 | (479 << 14);
+        let mut lossless = b"RIFF\x00\x00\x00\x00WEBPVP8L\x00\x00\x00\x00\x2F".to_vec();
+        lossless.extend_from_slice(&bits.to_le_bytes());
+        assert_eq!(
+            FilesService::image_dimensions(&lossless, "image/webp"),
+            Some((640, 480))
+        );
+
+        let extended =
+            b"RIFF\x00\x00\x00\x00WEBPVP8X\x0A\x00\x00\x00\x00\x00\x00\x00\x7F\x02\x00\xDF\x01\x00";
+        assert_eq!(
+            FilesService::image_dimensions(extended, "image/webp"),
+            Some((640, 480))
+        );
+    }
+
+    #[test]
+    fn test_image_dimensions_jpeg_skips_metadata_segments() {
+        let mut bytes = vec![0xFF, 0xD8];
+        // APP1 (EXIF) segment with 4 bytes of payload
+        bytes.extend_from_slice(&[0xFF, 0xE1, 0x00, 0x06, 0, 0, 0, 0]);
+        // DHT uses a SOF-range marker but isn't a frame header
+        bytes.extend_from_slice(&[0xFF, 0xC4, 0x00, 0x03, 0]);
+        // SOF2: length, precision, height, width
+        bytes.extend_from_slice(&[0xFF, 0xC2, 0x00, 0x11, 0x08, 0x01, 0xE0, 0x02, 0x80]);
+        assert_eq!(
+            FilesService::image_dimensions(&bytes, "image/jpeg"),
+            Some((640, 480))
+        );
+
+        // Frame header not yet reached
+        assert_eq!(
+            FilesService::image_dimensions(&bytes[..12], "image/jpeg"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_validate_image_dimensions_rejects_oversized() {
+        let service = test_service();
+        let max = DEFAULT_MAX_AVATAR_DIMENSION;
+
+        assert!(
+            service
+                .validate_image_dimensions(&png_header(max, max), "image/png")
+                .is_ok()
+        );
+        assert!(matches!(
+            service.validate_image_dimensions(&png_header(50_000, 50_000), "image/png"),
+            Err(FilesError::InvalidFileType(_))
+        ));
+        assert!(matches!(
+            service.validate_image_dimensions(&png_header(100, max + 1), "image/png"),
+            Err(FilesError::InvalidFileType(_))
+        ));
+        // Unreadable headers are rejected rather than let through
+        assert!(matches!(
+            service.validate_image_dimensions(b"GIF89a", "image/gif"),
+            Err(FilesError::InvalidFileType(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_upload_avatar_stores_valid_png() {
+        let bucket = FakeBucket::default();
+        let service = service_with_bucket(bucket.clone());
+        let user_id = Uuid::new_v4();
+
+        let uploaded = service
+            .upload_avatar(user_id, "image/png", png_header(64, 64))
+            .await
+            .unwrap();
+
+        assert!(
+            uploaded
+                .object_key
+                .starts_with(&format!("avatars/{user_id}/"))
+        );
+        assert!(uploaded.object_key.ends_with(".png"));
+        assert_eq!(
+            uploaded.public_url,
+            format!("https://files.example.com/{}", uploaded.object_key)
+        );
+        let puts: Vec<String> = bucket
+            .requests()
+            .into_iter()
+            .filter(|request| request.starts_with("PUT "))
+            .collect();
+        assert_eq!(puts.len(), 1);
+        assert!(puts[0].contains(&uploaded.object_key));
+    }
+
+    #[tokio::test]
+    async fn test_key_prefix_is_applied_to_stored_keys() {
+        let bucket = FakeBucket::default();
+        let service = FilesService {
+            key_prefix: "staging/".to_string(),
+            ..service_with_bucket(bucket.clone())
+        };
+        let user_id = Uuid::new_v4();
+
+        let uploaded = service
+            .upload_avatar(user_id, "image/png", png_header(64, 64))
+            .await
+            .unwrap();
+        // Clients keep seeing unprefixed keys, so ownership checks are unchanged
+        assert!(
+            uploaded
+                .object_key
+                .starts_with(&format!("avatars/{user_id}/"))
+        );
+        assert_eq!(
+            uploaded.public_url,
+            format!("https://files.example.com/staging/{}", uploaded.object_key)
+        );
+        let puts: Vec<String> = bucket
+            .requests()
+            .into_iter()
+            .filter(|request| request.starts_with("PUT "))
+            .collect();
+        assert_eq!(puts.len(), 1);
+        assert!(puts[0].contains(&format!("/files/staging/{}", uploaded.object_key)));
+
+        let presigned = service
+            .create_avatar_upload_url(user_id, "image/png", Some(1), None, None)
+            .await
+            .unwrap();
+        assert!(
+            presigned
+                .upload_url
+                .contains(&format!("/files/staging/{}", presigned.object_key))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_upload_avatar_rejects_spoofed_content_type() {
+        let bucket = FakeBucket::default();
+        let service = service_with_bucket(bucket.clone());
+
+        let result = service
+            .upload_avatar(Uuid::new_v4(), "image/jpeg", png_header(64, 64))
+            .await;
+
+        assert!(matches!(result, Err(FilesError::InvalidFileType(_))));
+        assert!(bucket.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_create_download_url_is_signed() {
+        let service = test_service();
+        let key = format!("avatars/{}/{}.png", Uuid::new_v4(), Uuid::new_v4());
+
+        let download = service
+            .create_download_url(&key, Some(Duration::from_secs(60)))
+            .await
+            .unwrap();
+
+        assert!(download.download_url.contains(&key));
+        for param in [
+            "X-Amz-Algorithm=AWS4-HMAC-SHA256",
+            "X-Amz-Credential=",
+            "X-Amz-Date=",
+            "X-Amz-Expires=60",
+            "X-Amz-SignedHeaders=",
+            "X-Amz-Signature=",
+        ] {
+            assert!(
+                download.download_url.contains(param),
+                "missing {param} in {}",
+                download.download_url
+            );
+        }
+        assert!(download.expires_at > Utc::now());
+    }
+
+    #[test]
+    fn test_extract_object_key() {
+        let service = test_service();
+        assert_eq!(
+            service.extract_object_key("https://files.example.com/avatars/u/a.png"),
+            Some("avatars/u/a.png".to_string())
+        );
+        assert_eq!(
+            service.extract_object_key("https://files.example.com.evil.test/avatars/u/a.png"),
+            None
+        );
+        assert_eq!(
+            service.extract_object_key("https://cdn.example.com/avatars/u/a.png"),
+            None
+        );
+        assert_eq!(
+            service.extract_object_key("https://files.example.com/"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_extract_object_key_round_trips_with_prefix() {
+        let service = FilesService {
+            key_prefix: "staging/".to_string(),
+            ..test_service()
+        };
+        let url = service.get_public_url("avatars/u/a.png");
+        assert_eq!(url, "https://files.example.com/staging/avatars/u/a.png");
+        assert_eq!(
+            service.extract_object_key(&url),
+            Some("avatars/u/a.png".to_string())
+        );
+
+        // Objects from other environments sharing the bucket aren't ours
+        assert_eq!(
+            service.extract_object_key("https://files.example.com/avatars/u/a.png"),
+            None
+        );
+        assert_eq!(
+            service.extract_object_key("https://files.example.com/prod/avatars/u/a.png"),
+            None
+        );
+    }
+
     #[test]
     fn test_validate_avatar_type() {
         assert!(FilesService::validate_avatar_type("image/jpeg").is_ok());
@@ -306,4 +1745,394 @@ mod tests {
         assert!(FilesService::validate_avatar_type("application/pdf").is_err());
         assert!(FilesService::validate_avatar_type("text/plain").is_err());
     }
+
+    #[test]
+    fn test_validate_object_key_accepts_valid_keys() {
+        let user_id = Uuid::new_v4();
+        let prefix = format!("avatars/{user_id}/");
+        let key = format!("{prefix}{}.png", Uuid::new_v4());
+        assert!(FilesService::validate_object_key(&key, &prefix).is_ok());
+    }
+
+    #[test]
+    fn test_validate_object_key_rejects_traversal() {
+        let user_id = Uuid::new_v4();
+        let prefix = format!("avatars/{user_id}/");
+        let file = format!("{}.png", Uuid::new_v4());
+
+        for key in [
+            format!("{prefix}../other/{file}"),
+            format!("{prefix}./{file}"),
+            format!("{prefix}/{file}"),
+            format!("/{prefix}{file}"),
+            format!("{prefix}..\\{file}"),
+            format!("{prefix}{file}/../secret"),
+            format!("avatars/{}/{file}", Uuid::new_v4()),
+            format!("{prefix}secret.png"),
+            format!("{prefix}{}", Uuid::new_v4()),
+            format!("{prefix}{}.p-g", Uuid::new_v4()),
+            String::new(),
+        ] {
+            assert!(
+                matches!(
+                    FilesService::validate_object_key(&key, &prefix),
+                    Err(FilesError::InvalidKey(_))
+                ),
+                "expected {key:?} to be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_object_key_rejects_control_characters() {
+        let user_id = Uuid::new_v4();
+        let prefix = format!("avatars/{user_id}/");
+        let id = Uuid::new_v4();
+
+        for key in [
+            format!("{prefix}{id}.png\n"),
+            format!("{prefix}{id}\0.png"),
+            format!("{prefix}\t{id}.png"),
+            format!("{prefix}{id}.p\u{7f}g"),
+        ] {
+            assert!(
+                matches!(
+                    FilesService::validate_object_key(&key, &prefix),
+                    Err(FilesError::InvalidKey(_))
+                ),
+                "expected {key:?} to be rejected"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delete_user_avatars_uses_batch_delete() {
+        let user_id = Uuid::new_v4();
+        let bucket = FakeBucket::with_keys(avatar_keys(user_id, 3));
+        let service = service_with_bucket(bucket.clone());
+
+        let deleted = service.delete_user_avatars(user_id).await.unwrap();
+        assert_eq!(deleted, 3);
+
+        let requests = bucket.requests();
+        assert_eq!(requests.len(), 2, "unexpected requests: {requests:?}");
+        assert!(requests[0].starts_with("GET "));
+        assert!(requests[1].starts_with("POST ") && requests[1].contains("delete"));
+    }
+
+    #[tokio::test]
+    async fn test_delete_user_avatars_splits_large_pages() {
+        let user_id = Uuid::new_v4();
+        let bucket = FakeBucket::with_keys(avatar_keys(user_id, DELETE_OBJECTS_BATCH_SIZE + 1));
+        let service = service_with_bucket(bucket.clone());
+
+        let deleted = service.delete_user_avatars(user_id).await.unwrap();
+        assert_eq!(deleted as usize, DELETE_OBJECTS_BATCH_SIZE + 1);
+
+        let posts = bucket
+            .requests()
+            .iter()
+            .filter(|request| request.starts_with("POST "))
+            .count();
+        assert_eq!(posts, 2);
+    }
+
+    #[tokio::test]
+    async fn test_delete_user_avatars_surfaces_per_key_errors() {
+        let user_id = Uuid::new_v4();
+        let keys = avatar_keys(user_id, 2);
+        let bucket = FakeBucket {
+            failing_key: Some(keys[1].clone()),
+            ..FakeBucket::with_keys(keys.clone())
+        };
+        let service = service_with_bucket(bucket);
+
+        match service.delete_user_avatars(user_id).await {
+            Err(FilesError::Delete(message)) => {
+                assert!(message.contains(&keys[1]));
+                assert!(!message.contains(&keys[0]));
+            }
+            other => panic!("expected delete error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_validate_quota_under_limit() {
+        let service = test_service();
+        assert!(service.validate_quota(0, DEFAULT_MAX_AVATAR_SIZE).is_ok());
+        assert!(service.validate_quota(service.user_quota() - 2, 1).is_ok());
+    }
+
+    #[test]
+    fn test_validate_quota_exactly_at_limit() {
+        let service = test_service();
+        let limit = service.user_quota();
+        assert!(service.validate_quota(limit - 100, 100).is_ok());
+        assert!(service.validate_quota(limit, 0).is_ok());
+    }
+
+    #[test]
+    fn test_validate_quota_over_limit() {
+        let service = test_service();
+        let limit = service.user_quota();
+        assert!(matches!(
+            service.validate_quota(limit - 100, 101),
+            Err(FilesError::QuotaExceeded(used, max)) if used == limit - 100 && max == limit
+        ));
+        assert!(matches!(
+            service.validate_quota(u64::MAX, u64::MAX),
+            Err(FilesError::QuotaExceeded(_, _))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_user_storage_bytes_sums_avatars_and_attachments() {
+        let user_id = Uuid::new_v4();
+        let mut keys = avatar_keys(user_id, 2);
+        keys.push(attachment_object_key(
+            user_id,
+            Uuid::new_v4(),
+            "application/pdf",
+        ));
+        keys.extend(avatar_keys(Uuid::new_v4(), 4));
+        let bucket = FakeBucket {
+            object_size: 1000,
+            ..FakeBucket::with_keys(keys)
+        };
+        let service = service_with_bucket(bucket);
+
+        assert_eq!(service.user_storage_bytes(user_id).await.unwrap(), 3000);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_bucket_is_reported_as_unavailable() {
+        let service = service_with_bucket(UnreachableBucket);
+        let user_id = Uuid::new_v4();
+
+        let result = service
+            .create_avatar_upload_url(user_id, "image/png", Some(1), None, None)
+            .await;
+        assert!(matches!(result, Err(FilesError::Unavailable(_))));
+
+        let result = service
+            .delete_file(&format!("avatars/{user_id}/{}.png", Uuid::new_v4()))
+            .await;
+        assert!(matches!(result, Err(FilesError::Unavailable(_))));
+    }
+
+    #[tokio::test]
+    async fn test_create_avatar_upload_url_rejects_over_quota() {
+        let user_id = Uuid::new_v4();
+        let bucket = FakeBucket {
+            object_size: DEFAULT_MAX_AVATAR_SIZE as i64,
+            ..FakeBucket::with_keys(avatar_keys(user_id, 10))
+        };
+        let service = service_with_bucket(bucket);
+
+        let result = service
+            .create_avatar_upload_url(user_id, "image/png", Some(1), None, None)
+            .await;
+        assert!(matches!(result, Err(FilesError::QuotaExceeded(_, _))));
+    }
+
+    #[tokio::test]
+    async fn test_upload_urls_require_content_length() {
+        let service = service_with_bucket(FakeBucket::default());
+
+        let avatar = service
+            .create_avatar_upload_url(Uuid::new_v4(), "image/png", None, None, None)
+            .await;
+        let attachment = service
+            .create_task_attachment_upload_url(
+                Uuid::new_v4(),
+                Uuid::new_v4(),
+                "application/pdf",
+                None,
+            )
+            .await;
+
+        assert!(matches!(avatar, Err(FilesError::MissingContentLength)));
+        assert!(matches!(attachment, Err(FilesError::MissingContentLength)));
+    }
+
+    #[tokio::test]
+    async fn test_upload_url_signs_content_length() {
+        let service = service_with_bucket(FakeBucket::default());
+
+        let upload = service
+            .create_avatar_upload_url(Uuid::new_v4(), "image/png", Some(1024), None, None)
+            .await
+            .unwrap();
+
+        let url = url::Url::parse(&upload.upload_url).unwrap();
+        let signed_headers = url
+            .query_pairs()
+            .find(|(name, _)| name == "X-Amz-SignedHeaders")
+            .map(|(_, value)| value.into_owned())
+            .unwrap();
+        assert!(signed_headers.split(';').any(|h| h == "content-length"));
+    }
+
+    #[test]
+    fn test_resolve_upload_expiry_defaults_to_service_value() {
+        let service = test_service();
+        assert_eq!(
+            service.resolve_upload_expiry(None).unwrap(),
+            Duration::from_secs(300)
+        );
+    }
+
+    #[test]
+    fn test_resolve_upload_expiry_accepts_bounds() {
+        let service = test_service();
+        assert_eq!(
+            service.resolve_upload_expiry(Some(60)).unwrap(),
+            Duration::from_secs(60)
+        );
+        assert_eq!(
+            service.resolve_upload_expiry(Some(3600)).unwrap(),
+            Duration::from_secs(3600)
+        );
+    }
+
+    #[test]
+    fn test_resolve_upload_expiry_rejects_out_of_range() {
+        let service = test_service();
+        for secs in [0, 59, 3601] {
+            assert!(matches!(
+                service.resolve_upload_expiry(Some(secs)),
+                Err(FilesError::InvalidExpiry(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_avatar_upload_url_uses_requested_expiry() {
+        let user_id = Uuid::new_v4();
+        let service = service_with_bucket(FakeBucket::default());
+
+        let before = Utc::now();
+        let upload = service
+            .create_avatar_upload_url(user_id, "image/png", Some(1), Some(1800), None)
+            .await
+            .unwrap();
+
+        assert!(upload.upload_url.contains("X-Amz-Expires=1800"));
+        let lifetime = upload.expires_at - before;
+        assert!(lifetime >= chrono::Duration::seconds(1799));
+        assert!(lifetime <= chrono::Duration::seconds(1801));
+    }
+
+    #[test]
+    fn test_validate_content_md5() {
+        // MD5 of the empty string
+        assert!(FilesService::validate_content_md5("1B2M2Y8AsgTpgAmY7PhCfg==").is_ok());
+        assert!(matches!(
+            FilesService::validate_content_md5("not base64!"),
+            Err(FilesError::InvalidChecksum(_))
+        ));
+        assert!(matches!(
+            FilesService::validate_content_md5("AAAA"),
+            Err(FilesError::InvalidChecksum(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_create_avatar_upload_url_signs_content_md5() {
+        let user_id = Uuid::new_v4();
+        let service = service_with_bucket(FakeBucket::default());
+        let checksum = "1B2M2Y8AsgTpgAmY7PhCfg==";
+
+        let upload = service
+            .create_avatar_upload_url(user_id, "image/png", Some(1), None, Some(checksum))
+            .await
+            .unwrap();
+
+        let url = url::Url::parse(&upload.upload_url).unwrap();
+        let signed_headers = url
+            .query_pairs()
+            .find(|(name, _)| name == "X-Amz-SignedHeaders")
+            .map(|(_, value)| value.into_owned())
+            .unwrap();
+        assert!(signed_headers.split(';').any(|h| h == "content-md5"));
+        assert_eq!(upload.content_md5.as_deref(), Some(checksum));
+    }
+
+    #[tokio::test]
+    async fn test_create_avatar_upload_url_without_content_md5() {
+        let user_id = Uuid::new_v4();
+        let service = service_with_bucket(FakeBucket::default());
+
+        let upload = service
+            .create_avatar_upload_url(user_id, "image/png", Some(1), None, None)
+            .await
+            .unwrap();
+
+        assert!(!upload.upload_url.contains("content-md5"));
+        assert!(upload.content_md5.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key_returns_original_upload() {
+        let user_id = Uuid::new_v4();
+        let service = service_with_bucket(FakeBucket::default());
+
+        let upload = |key: &'static str| {
+            service.create_avatar_upload_url_idempotent(
+                user_id,
+                key,
+                "image/png",
+                Some(1024),
+                None,
+                None,
+            )
+        };
+
+        let first = upload("retry-1").await.unwrap();
+        let retry = upload("retry-1").await.unwrap();
+        let other = upload("retry-2").await.unwrap();
+
+        assert_eq!(retry.object_key, first.object_key);
+        assert_eq!(retry.upload_url, first.upload_url);
+        assert_ne!(other.object_key, first.object_key);
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_keys_are_scoped_per_user() {
+        let service = service_with_bucket(FakeBucket::default());
+
+        let mut keys = Vec::new();
+        for user_id in [Uuid::new_v4(), Uuid::new_v4()] {
+            let upload = service
+                .create_avatar_upload_url_idempotent(
+                    user_id,
+                    "shared-key",
+                    "image/png",
+                    Some(1),
+                    None,
+                    None,
+                )
+                .await
+                .unwrap();
+            keys.push(upload.object_key);
+        }
+
+        assert_ne!(keys[0], keys[1]);
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key_rejects_different_request() {
+        let user_id = Uuid::new_v4();
+        let service = service_with_bucket(FakeBucket::default());
+
+        service
+            .create_avatar_upload_url_idempotent(user_id, "key", "image/png", Some(1), None, None)
+            .await
+            .unwrap();
+        let result = service
+            .create_avatar_upload_url_idempotent(user_id, "key", "image/jpeg", Some(1), None, None)
+            .await;
+
+        assert!(matches!(result, Err(FilesError::IdempotencyKeyReused)));
+    }
 }
@@ -0,0 +1,45 @@
+//! Periodic expiry and pruning of workspace invitations.
+//!
+//! Pending invitations are otherwise only marked expired when someone tries
+//! to accept them, so this keeps invitation lists accurate and the table
+//! bounded.
+
+use std::time::Duration;
+
+use sqlx::PgPool;
+use tokio::{task::JoinHandle, time::MissedTickBehavior};
+
+use crate::{
+    config::WorkspaceInvitationConfig, db::workspace_invitations::WorkspaceInvitationRepository,
+};
+
+pub struct InvitationCleanupService;
+
+impl InvitationCleanupService {
+    pub fn spawn(pool: PgPool, config: WorkspaceInvitationConfig) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(config.cleanup_interval_secs));
+            interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+            loop {
+                interval.tick().await;
+
+                let repo = WorkspaceInvitationRepository::new(&pool);
+                match repo.cleanup(config.cleanup_retention()).await {
+                    Ok(result) if result.expired > 0 || result.deleted > 0 => {
+                        tracing::info!(
+                            expired = result.expired,
+                            deleted = result.deleted,
+                            "Cleaned up workspace invitations"
+                        );
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        tracing::warn!(?e, "Workspace invitation cleanup failed");
+                    }
+                }
+            }
+        })
+    }
+}
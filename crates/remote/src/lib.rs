@@ -4,11 +4,14 @@ pub mod config;
 pub mod db;
 pub mod files;
 pub mod github_app;
+mod invitation_cleanup;
 pub mod mail;
 pub mod r2;
+mod rate_limit;
 pub mod routes;
 mod state;
 pub mod validated_where;
+pub mod webhooks;
 
 use std::{env, sync::OnceLock};
 
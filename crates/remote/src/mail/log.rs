@@ -0,0 +1,101 @@
+use std::{collections::VecDeque, sync::Mutex};
+
+use async_trait::async_trait;
+
+use super::{EmailMessage, Mailer};
+
+/// Messages kept for inspection; older ones are discarded.
+const RECENT_MESSAGES: usize = 50;
+
+/// Logs every message instead of delivering it, and keeps the most recent
+/// ones so local tooling and tests can see what would have been sent.
+#[derive(Debug, Default)]
+pub struct LogMailer {
+    recent: Mutex<VecDeque<EmailMessage>>,
+}
+
+impl LogMailer {
+    /// Messages sent so far, oldest first.
+    pub fn sent(&self) -> Vec<EmailMessage> {
+        self.recent
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .cloned()
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, message: EmailMessage) {
+        tracing::info!(
+            "Email to {}\nSubject: {}\n\n{}",
+            message.to,
+            message.subject,
+            message.text_body
+        );
+
+        let mut recent = self.recent.lock().unwrap_or_else(|e| e.into_inner());
+        if recent.len() == RECENT_MESSAGES {
+            recent.pop_front();
+        }
+        recent.push_back(message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{db::organization_members::MemberRole, mail::Locale};
+
+    #[tokio::test]
+    async fn test_records_rendered_workspace_invitation() {
+        let mailer = LogMailer::default();
+        let workspace_id = Uuid::new_v4();
+
+        mailer
+            .send_workspace_invitation(
+                workspace_id,
+                "invitee@example.com",
+                "https://vibe.example.com/invitations/abc/accept",
+                MemberRole::Admin,
+                Some("alice"),
+                Locale::En,
+            )
+            .await;
+
+        let sent = mailer.sent();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].to, "invitee@example.com");
+        assert!(sent[0].subject.contains("invited"));
+        assert!(sent[0].text_body.contains("alice invited you"));
+        assert!(sent[0].text_body.contains(&workspace_id.to_string()));
+        assert!(sent[0].text_body.contains("as Administrator"));
+        assert!(
+            sent[0]
+                .text_body
+                .contains("https://vibe.example.com/invitations/abc/accept")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_keeps_only_recent_messages() {
+        let mailer = LogMailer::default();
+        for i in 0..(RECENT_MESSAGES + 1) {
+            mailer
+                .send(EmailMessage {
+                    to: format!("user{i}@example.com"),
+                    subject: "Hello".to_string(),
+                    text_body: String::new(),
+                })
+                .await;
+        }
+
+        let sent = mailer.sent();
+        assert_eq!(sent.len(), RECENT_MESSAGES);
+        assert_eq!(sent[0].to, "user1@example.com");
+    }
+}
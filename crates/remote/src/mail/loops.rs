@@ -1,9 +1,15 @@
 use std::time::Duration;
 
 use async_trait::async_trait;
-use serde_json::json;
+use reqwest::StatusCode;
+use serde_json::{Value, json};
 use uuid::Uuid;
 
+use super::{
+    EmailMessage, Locale, Mailer,
+    retry::{RetryPolicy, SendError, send_with_retry},
+    templates::role_name,
+};
 use crate::db::organization_members::MemberRole;
 
 const LOOPS_INVITE_TEMPLATE_ID: &str = "cmhvy2wgs3s13z70i1pxakij9";
@@ -11,31 +17,6 @@ const LOOPS_WORKSPACE_INVITE_TEMPLATE_ID: &str = "cmhvy2wgs3s13z70i1pxakij9"; //
 const LOOPS_REVIEW_READY_TEMPLATE_ID: &str = "cmj47k5ge16990iylued9by17";
 const LOOPS_REVIEW_FAILED_TEMPLATE_ID: &str = "cmj49ougk1c8s0iznavijdqpo";
 
-#[async_trait]
-pub trait Mailer: Send + Sync {
-    async fn send_org_invitation(
-        &self,
-        org_name: &str,
-        email: &str,
-        accept_url: &str,
-        role: MemberRole,
-        invited_by: Option<&str>,
-    );
-
-    async fn send_workspace_invitation(
-        &self,
-        workspace_id: Uuid,
-        email: &str,
-        accept_url: &str,
-        role: MemberRole,
-        invited_by: Option<&str>,
-    );
-
-    async fn send_review_ready(&self, email: &str, review_url: &str, pr_name: &str);
-
-    async fn send_review_failed(&self, email: &str, pr_name: &str, review_id: &str);
-}
-
 pub struct LoopsMailer {
     client: reqwest::Client,
     api_key: String,
@@ -50,10 +31,46 @@ impl LoopsMailer {
 
         Self { client, api_key }
     }
+
+    /// One attempt at sending a transactional template. Rate limiting and
+    /// server errors are worth retrying; any other rejection, such as an
+    /// invalid address, is not.
+    async fn send_transactional(&self, payload: &Value) -> Result<(), SendError> {
+        let resp = self
+            .client
+            .post("https://app.loops.so/api/v1/transactional")
+            .bearer_auth(&self.api_key)
+            .json(payload)
+            .send()
+            .await
+            .map_err(|err| SendError::Transient(err.to_string()))?;
+
+        let status = resp.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        let body = resp.text().await.unwrap_or_default();
+        let reason = format!("Loops returned {status}: {body}");
+        if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+            Err(SendError::Transient(reason))
+        } else {
+            Err(SendError::Permanent(reason))
+        }
+    }
 }
 
 #[async_trait]
 impl Mailer for LoopsMailer {
+    /// Loops only sends its own stored templates, so there is no way to
+    /// deliver an arbitrary message through it.
+    async fn send(&self, message: EmailMessage) {
+        tracing::warn!(
+            to = %message.to,
+            subject = %message.subject,
+            "Loops cannot send untemplated emails; dropping message"
+        );
+    }
+
     async fn send_org_invitation(
         &self,
         org_name: &str,
@@ -61,11 +78,9 @@ impl Mailer for LoopsMailer {
         accept_url: &str,
         role: MemberRole,
         invited_by: Option<&str>,
+        locale: Locale,
     ) {
-        let role_str = match role {
-            MemberRole::Admin => "admin",
-            MemberRole::Member => "member",
-        };
+        let role_str = role_name(locale, role);
         let inviter = invited_by.unwrap_or("someone");
 
         if cfg!(debug_assertions) {
@@ -85,30 +100,15 @@ impl Mailer for LoopsMailer {
                 "org_name": org_name,
                 "accept_url": accept_url,
                 "invited_by": inviter,
+                "role": role_str,
+                "locale": locale.code(),
             }
         });
 
-        let res = self
-            .client
-            .post("https://app.loops.so/api/v1/transactional")
-            .bearer_auth(&self.api_key)
-            .json(&payload)
-            .send()
-            .await;
-
-        match res {
-            Ok(resp) if resp.status().is_success() => {
-                tracing::debug!("Invitation email sent via Loops to {email}");
-            }
-            Ok(resp) => {
-                let status = resp.status();
-                let body = resp.text().await.unwrap_or_default();
-                tracing::warn!(status = %status, body = %body, "Loops send failed");
-            }
-            Err(err) => {
-                tracing::error!(error = ?err, "Loops request error");
-            }
-        }
+        send_with_retry(RetryPolicy::default(), email, || {
+            self.send_transactional(&payload)
+        })
+        .await;
     }
 
     async fn send_workspace_invitation(
@@ -118,11 +118,9 @@ impl Mailer for LoopsMailer {
         accept_url: &str,
         role: MemberRole,
         invited_by: Option<&str>,
+        locale: Locale,
     ) {
-        let role_str = match role {
-            MemberRole::Admin => "admin",
-            MemberRole::Member => "member",
-        };
+        let role_str = role_name(locale, role);
         let inviter = invited_by.unwrap_or("someone");
 
         if cfg!(debug_assertions) {
@@ -142,30 +140,15 @@ impl Mailer for LoopsMailer {
                 "workspace_id": workspace_id.to_string(),
                 "accept_url": accept_url,
                 "invited_by": inviter,
+                "role": role_str,
+                "locale": locale.code(),
             }
         });
 
-        let res = self
-            .client
-            .post("https://app.loops.so/api/v1/transactional")
-            .bearer_auth(&self.api_key)
-            .json(&payload)
-            .send()
-            .await;
-
-        match res {
-            Ok(resp) if resp.status().is_success() => {
-                tracing::debug!("Workspace invitation email sent via Loops to {email}");
-            }
-            Ok(resp) => {
-                let status = resp.status();
-                let body = resp.text().await.unwrap_or_default();
-                tracing::warn!(status = %status, body = %body, "Loops send failed for workspace invitation");
-            }
-            Err(err) => {
-                tracing::error!(error = ?err, "Loops request error for workspace invitation");
-            }
-        }
+        send_with_retry(RetryPolicy::default(), email, || {
+            self.send_transactional(&payload)
+        })
+        .await;
     }
 
     async fn send_review_ready(&self, email: &str, review_url: &str, pr_name: &str) {
@@ -186,27 +169,10 @@ impl Mailer for LoopsMailer {
             }
         });
 
-        let res = self
-            .client
-            .post("https://app.loops.so/api/v1/transactional")
-            .bearer_auth(&self.api_key)
-            .json(&payload)
-            .send()
-            .await;
-
-        match res {
-            Ok(resp) if resp.status().is_success() => {
-                tracing::debug!("Review ready email sent via Loops to {email}");
-            }
-            Ok(resp) => {
-                let status = resp.status();
-                let body = resp.text().await.unwrap_or_default();
-                tracing::warn!(status = %status, body = %body, "Loops send failed for review ready");
-            }
-            Err(err) => {
-                tracing::error!(error = ?err, "Loops request error for review ready");
-            }
-        }
+        send_with_retry(RetryPolicy::default(), email, || {
+            self.send_transactional(&payload)
+        })
+        .await;
     }
 
     async fn send_review_failed(&self, email: &str, pr_name: &str, review_id: &str) {
@@ -227,26 +193,9 @@ impl Mailer for LoopsMailer {
             }
         });
 
-        let res = self
-            .client
-            .post("https://app.loops.so/api/v1/transactional")
-            .bearer_auth(&self.api_key)
-            .json(&payload)
-            .send()
-            .await;
-
-        match res {
-            Ok(resp) if resp.status().is_success() => {
-                tracing::debug!("Review failed email sent via Loops to {email}");
-            }
-            Ok(resp) => {
-                let status = resp.status();
-                let body = resp.text().await.unwrap_or_default();
-                tracing::warn!(status = %status, body = %body, "Loops send failed for review failed");
-            }
-            Err(err) => {
-                tracing::error!(error = ?err, "Loops request error for review failed");
-            }
-        }
+        send_with_retry(RetryPolicy::default(), email, || {
+            self.send_transactional(&payload)
+        })
+        .await;
     }
 }
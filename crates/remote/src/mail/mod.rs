@@ -0,0 +1,135 @@
+//! Outgoing email.
+//!
+//! `MAIL_PROVIDER` picks the transport: Loops templates (the default), any
+//! SMTP server, or a log-only provider for local development. Providers that
+//! send raw messages only implement `send`; the typed methods render a plain
+//! text message for them. Transient send failures are retried with backoff;
+//! a final failure is logged and never surfaces to the request that
+//! triggered the email.
+
+mod log;
+mod loops;
+mod retry;
+mod smtp;
+pub mod templates;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use axum::http::{HeaderMap, header};
+use secrecy::ExposeSecret;
+use uuid::Uuid;
+
+pub use self::{
+    log::LogMailer,
+    loops::LoopsMailer,
+    retry::{RetryPolicy, SendError},
+    smtp::SmtpMailer,
+    templates::{Locale, render_invitation},
+};
+use crate::{config::MailConfig, db::organization_members::MemberRole};
+
+/// A rendered email ready for a transport.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailMessage {
+    pub to: String,
+    pub subject: String,
+    pub text_body: String,
+}
+
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, message: EmailMessage);
+
+    async fn send_org_invitation(
+        &self,
+        org_name: &str,
+        email: &str,
+        accept_url: &str,
+        role: MemberRole,
+        invited_by: Option<&str>,
+        locale: Locale,
+    ) {
+        let rendered = render_invitation(locale, org_name, accept_url, role, invited_by);
+        self.send(EmailMessage {
+            to: email.to_string(),
+            subject: rendered.subject,
+            text_body: rendered.text_body,
+        })
+        .await;
+    }
+
+    /// Workspace names live on the local server, so the workspace is
+    /// identified by ID in the rendered copy.
+    async fn send_workspace_invitation(
+        &self,
+        workspace_id: Uuid,
+        email: &str,
+        accept_url: &str,
+        role: MemberRole,
+        invited_by: Option<&str>,
+        locale: Locale,
+    ) {
+        let rendered = render_invitation(
+            locale,
+            &workspace_id.to_string(),
+            accept_url,
+            role,
+            invited_by,
+        );
+        self.send(EmailMessage {
+            to: email.to_string(),
+            subject: rendered.subject,
+            text_body: rendered.text_body,
+        })
+        .await;
+    }
+
+    async fn send_review_ready(&self, email: &str, review_url: &str, pr_name: &str) {
+        self.send(EmailMessage {
+            to: email.to_string(),
+            subject: format!("Your review of {pr_name} is ready"),
+            text_body: format!("The review of {pr_name} is ready.\n\nView it: {review_url}\n"),
+        })
+        .await;
+    }
+
+    async fn send_review_failed(&self, email: &str, pr_name: &str, review_id: &str) {
+        self.send(EmailMessage {
+            to: email.to_string(),
+            subject: format!("Your review of {pr_name} failed"),
+            text_body: format!(
+                "The review of {pr_name} could not be completed.\n\nReview ID: {review_id}\n"
+            ),
+        })
+        .await;
+    }
+}
+
+/// Locale for an email triggered by this request: the best supported match
+/// from `Accept-Language`, else `fallback`.
+pub fn request_locale(headers: &HeaderMap, fallback: Locale) -> Locale {
+    headers
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(Locale::from_accept_language)
+        .unwrap_or(fallback)
+}
+
+/// Build the mailer selected by the configuration.
+pub fn build_mailer(config: &MailConfig) -> anyhow::Result<Arc<dyn Mailer>> {
+    let mailer: Arc<dyn Mailer> = match config {
+        MailConfig::Loops { api_key } => {
+            Arc::new(LoopsMailer::new(api_key.expose_secret().to_string()))
+        }
+        MailConfig::Smtp(smtp) => {
+            tracing::info!(host = %smtp.host, "Sending email through SMTP");
+            Arc::new(SmtpMailer::new(smtp)?)
+        }
+        MailConfig::Log => {
+            tracing::warn!("MAIL_PROVIDER=log: emails are logged, not delivered");
+            Arc::new(LogMailer::default())
+        }
+    };
+    Ok(mailer)
+}
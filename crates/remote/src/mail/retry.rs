@@ -0,0 +1,187 @@
+use std::{future::Future, time::Duration};
+
+/// Why a provider could not deliver a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SendError {
+    /// Worth another attempt: timeouts, connection failures, 5xx and 429
+    /// responses, 4xx SMTP replies.
+    Transient(String),
+    /// Retrying cannot help: a malformed address or a rejected request.
+    Permanent(String),
+}
+
+/// How often and how patiently a failed send is retried.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    /// Delay before the second attempt; doubles for each one after.
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Un-jittered delay after the given failed attempt (1-based).
+    fn base_delay(&self, attempt: u32) -> Duration {
+        self.initial_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay)
+    }
+}
+
+/// Spread a delay over `[base / 2, base]` so retries from concurrent sends
+/// don't line up.
+fn jittered(base: Duration, factor: f64) -> Duration {
+    base.mul_f64(0.5 + 0.5 * factor.clamp(0.0, 1.0))
+}
+
+/// Run `attempt` until it succeeds, fails permanently, or the policy runs
+/// out of attempts. The final failure is logged, never returned, so a lost
+/// email cannot fail the request that triggered it.
+pub async fn send_with_retry<F, Fut>(policy: RetryPolicy, to: &str, mut attempt: F)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<(), SendError>>,
+{
+    let max_attempts = policy.max_attempts.max(1);
+    for n in 1..=max_attempts {
+        match attempt().await {
+            Ok(()) => return,
+            Err(SendError::Permanent(reason)) => {
+                tracing::error!(to = %to, reason = %reason, "Email rejected; not retrying");
+                return;
+            }
+            Err(SendError::Transient(reason)) if n == max_attempts => {
+                tracing::error!(
+                    to = %to,
+                    reason = %reason,
+                    attempts = n,
+                    "Email could not be sent; giving up"
+                );
+            }
+            Err(SendError::Transient(reason)) => {
+                let delay = jittered(policy.base_delay(n), rand::random::<f64>());
+                tracing::warn!(
+                    to = %to,
+                    reason = %reason,
+                    attempt = n,
+                    "Email send failed; retrying in {:?}",
+                    delay
+                );
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Mutex,
+        atomic::{AtomicU32, Ordering},
+    };
+
+    use async_trait::async_trait;
+
+    use super::*;
+    use crate::mail::{EmailMessage, Mailer};
+
+    const FAST: RetryPolicy = RetryPolicy {
+        max_attempts: 3,
+        initial_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(5),
+    };
+
+    /// Fails the first `failures` attempts with the given error.
+    struct FlakyMailer {
+        failures: u32,
+        error: SendError,
+        attempts: AtomicU32,
+        sent: Mutex<Vec<EmailMessage>>,
+    }
+
+    impl FlakyMailer {
+        fn new(failures: u32, error: SendError) -> Self {
+            Self {
+                failures,
+                error,
+                attempts: AtomicU32::new(0),
+                sent: Mutex::new(Vec::new()),
+            }
+        }
+
+        async fn try_send(&self, message: &EmailMessage) -> Result<(), SendError> {
+            if self.attempts.fetch_add(1, Ordering::SeqCst) < self.failures {
+                return Err(self.error.clone());
+            }
+            self.sent.lock().unwrap().push(message.clone());
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl Mailer for FlakyMailer {
+        async fn send(&self, message: EmailMessage) {
+            send_with_retry(FAST, &message.to, || self.try_send(&message)).await;
+        }
+    }
+
+    fn message() -> EmailMessage {
+        EmailMessage {
+            to: "invitee@example.com".to_string(),
+            subject: "Hello".to_string(),
+            text_body: String::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transient_failures_are_retried_until_sent() {
+        let mailer = FlakyMailer::new(2, SendError::Transient("timed out".to_string()));
+
+        mailer.send(message()).await;
+
+        assert_eq!(mailer.attempts.load(Ordering::SeqCst), 3);
+        assert_eq!(*mailer.sent.lock().unwrap(), vec![message()]);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_attempts() {
+        let mailer = FlakyMailer::new(5, SendError::Transient("timed out".to_string()));
+
+        mailer.send(message()).await;
+
+        assert_eq!(mailer.attempts.load(Ordering::SeqCst), 3);
+        assert!(mailer.sent.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_permanent_failure_is_not_retried() {
+        let mailer = FlakyMailer::new(2, SendError::Permanent("bad address".to_string()));
+
+        mailer.send(message()).await;
+
+        assert_eq!(mailer.attempts.load(Ordering::SeqCst), 1);
+        assert!(mailer.sent.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.base_delay(1), Duration::from_millis(500));
+        assert_eq!(policy.base_delay(2), Duration::from_secs(1));
+        assert_eq!(policy.base_delay(10), Duration::from_secs(5));
+
+        let base = Duration::from_secs(2);
+        assert_eq!(jittered(base, 0.0), Duration::from_secs(1));
+        assert_eq!(jittered(base, 1.0), base);
+    }
+}
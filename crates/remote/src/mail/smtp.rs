@@ -0,0 +1,142 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+    message::{Mailbox, header::ContentType},
+    transport::smtp::authentication::Credentials,
+};
+use secrecy::ExposeSecret;
+
+use super::{
+    EmailMessage, Mailer,
+    retry::{RetryPolicy, SendError, send_with_retry},
+};
+use crate::config::{SmtpConfig, SmtpTls};
+
+const SMTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Delivers rendered messages through a self-hosted or third-party SMTP
+/// server.
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn new(config: &SmtpConfig) -> anyhow::Result<Self> {
+        let mut builder = match config.tls {
+            SmtpTls::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)
+                .context("Failed to configure SMTP STARTTLS")?,
+            SmtpTls::Implicit => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host)
+                .context("Failed to configure SMTP TLS")?,
+            SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host),
+        };
+        if let Some(port) = config.port {
+            builder = builder.port(port);
+        }
+        if let (Some(username), Some(password)) = (&config.username, &config.password) {
+            builder = builder.credentials(Credentials::new(
+                username.clone(),
+                password.expose_secret().to_string(),
+            ));
+        }
+
+        let from = config
+            .from
+            .parse::<Mailbox>()
+            .context("SMTP_FROM is not a valid address")?;
+
+        Ok(Self {
+            transport: builder.timeout(Some(SMTP_TIMEOUT)).build(),
+            from,
+        })
+    }
+
+    fn build_message(&self, message: &EmailMessage) -> anyhow::Result<Message> {
+        let to = message
+            .to
+            .parse::<Mailbox>()
+            .with_context(|| format!("invalid recipient address {:?}", message.to))?;
+
+        Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(&message.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(message.text_body.clone())
+            .context("failed to build email")
+    }
+
+    /// One delivery attempt. A message that can't be built and a 5xx reply
+    /// are permanent; connection problems, timeouts and 4xx replies are not.
+    async fn try_send(&self, message: &EmailMessage) -> Result<(), SendError> {
+        let email = self
+            .build_message(message)
+            .map_err(|err| SendError::Permanent(format!("{err:#}")))?;
+
+        match self.transport.send(email).await {
+            Ok(_) => {
+                tracing::debug!("Email sent via SMTP to {}", message.to);
+                Ok(())
+            }
+            Err(err) if err.is_permanent() => Err(SendError::Permanent(err.to_string())),
+            Err(err) => Err(SendError::Transient(err.to_string())),
+        }
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, message: EmailMessage) {
+        send_with_retry(RetryPolicy::default(), &message.to, || {
+            self.try_send(&message)
+        })
+        .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mailer() -> SmtpMailer {
+        SmtpMailer::new(&SmtpConfig {
+            host: "localhost".to_string(),
+            port: Some(2525),
+            username: None,
+            password: None,
+            from: "Vibe Kanban <noreply@example.com>".to_string(),
+            tls: SmtpTls::None,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_build_message_sets_headers_and_body() {
+        let email = mailer()
+            .build_message(&EmailMessage {
+                to: "invitee@example.com".to_string(),
+                subject: "Hello".to_string(),
+                text_body: "Welcome aboard".to_string(),
+            })
+            .unwrap();
+
+        let formatted = String::from_utf8(email.formatted()).unwrap();
+        assert!(formatted.contains("From: \"Vibe Kanban\" <noreply@example.com>"));
+        assert!(formatted.contains("To: invitee@example.com"));
+        assert!(formatted.contains("Subject: Hello"));
+        assert!(formatted.contains("Welcome aboard"));
+    }
+
+    #[test]
+    fn test_build_message_rejects_invalid_recipient() {
+        let result = mailer().build_message(&EmailMessage {
+            to: "not an address".to_string(),
+            subject: "Hello".to_string(),
+            text_body: String::new(),
+        });
+        assert!(result.is_err());
+    }
+}
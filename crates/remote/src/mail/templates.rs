@@ -0,0 +1,231 @@
+//! Localized copy for the emails rendered by this server.
+//!
+//! Templates are plain strings with `{name}` placeholders, one set per
+//! locale. Unknown locales fall back to English.
+
+use std::str::FromStr;
+
+use crate::db::organization_members::MemberRole;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    En,
+    Es,
+}
+
+impl Locale {
+    pub fn code(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Es => "es",
+        }
+    }
+
+    /// Pick the preferred supported locale from an `Accept-Language` header,
+    /// honouring quality values. Returns `None` when nothing matches.
+    pub fn from_accept_language(header: &str) -> Option<Self> {
+        let mut ranges: Vec<(&str, f32)> = header
+            .split(',')
+            .filter_map(|range| {
+                let mut parts = range.split(';');
+                let tag = parts.next()?.trim();
+                let quality = parts
+                    .find_map(|p| p.trim().strip_prefix("q="))
+                    .and_then(|q| q.parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                (!tag.is_empty() && quality > 0.0).then_some((tag, quality))
+            })
+            .collect();
+        // Stable sort keeps header order among equal weights
+        ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+        ranges.into_iter().find_map(|(tag, _)| tag.parse().ok())
+    }
+
+    fn templates(self) -> &'static InvitationTemplates {
+        match self {
+            Locale::En => &EN,
+            Locale::Es => &ES,
+        }
+    }
+}
+
+impl FromStr for Locale {
+    type Err = ();
+
+    /// Accepts a language tag such as `es`, `es-MX` or `en_US`.
+    fn from_str(tag: &str) -> Result<Self, Self::Err> {
+        let primary = tag.split(['-', '_']).next().unwrap_or_default();
+        match primary.to_ascii_lowercase().as_str() {
+            "en" => Ok(Locale::En),
+            "es" => Ok(Locale::Es),
+            _ => Err(()),
+        }
+    }
+}
+
+struct InvitationTemplates {
+    subject: &'static str,
+    body: &'static str,
+    unknown_inviter: &'static str,
+    admin: &'static str,
+    member: &'static str,
+}
+
+const EN: InvitationTemplates = InvitationTemplates {
+    subject: "{inviter} invited you to join {workspace_name}",
+    body: "Hi,\n\n\
+           {inviter} invited you to join {workspace_name} on Vibe Kanban as {role}.\n\n\
+           Accept the invitation: {accept_url}\n\n\
+           If you weren't expecting this invitation, you can ignore this email.\n",
+    unknown_inviter: "Someone",
+    admin: "Administrator",
+    member: "Member",
+};
+
+const ES: InvitationTemplates = InvitationTemplates {
+    subject: "{inviter} te ha invitado a unirte a {workspace_name}",
+    body: "Hola:\n\n\
+           {inviter} te ha invitado a unirte a {workspace_name} en Vibe Kanban con el rol {role}.\n\n\
+           Acepta la invitación: {accept_url}\n\n\
+           Si no esperabas esta invitación, puedes ignorar este correo.\n",
+    unknown_inviter: "Alguien",
+    admin: "Administrador",
+    member: "Miembro",
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedEmail {
+    pub subject: String,
+    pub text_body: String,
+}
+
+/// Human-readable role name in the given locale.
+pub fn role_name(locale: Locale, role: MemberRole) -> &'static str {
+    let templates = locale.templates();
+    match role {
+        MemberRole::Admin => templates.admin,
+        MemberRole::Member => templates.member,
+    }
+}
+
+pub fn render_invitation(
+    locale: Locale,
+    workspace_name: &str,
+    accept_url: &str,
+    role: MemberRole,
+    inviter: Option<&str>,
+) -> RenderedEmail {
+    let templates = locale.templates();
+    let vars = [
+        ("inviter", inviter.unwrap_or(templates.unknown_inviter)),
+        ("workspace_name", workspace_name),
+        ("role", role_name(locale, role)),
+        ("accept_url", accept_url),
+    ];
+    RenderedEmail {
+        subject: fill(templates.subject, &vars),
+        text_body: fill(templates.body, &vars),
+    }
+}
+
+/// Replace each `{name}` in `template`. Values are inserted verbatim and
+/// never re-scanned, so user-provided text can't inject placeholders.
+fn fill(template: &str, vars: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let value = after.find('}').and_then(|end| {
+            let name = &after[..end];
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| (*value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                out.push_str(value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACCEPT_URL: &str = "https://vibe.example.com/workspace-invitations/abc/accept";
+
+    #[test]
+    fn test_render_invitation_includes_url_and_workspace_for_each_locale() {
+        for locale in [Locale::En, Locale::Es] {
+            let email = render_invitation(
+                locale,
+                "Acme Roadmap",
+                ACCEPT_URL,
+                MemberRole::Admin,
+                Some("alice"),
+            );
+            assert!(email.subject.contains("Acme Roadmap"), "{locale:?}");
+            assert!(email.text_body.contains("Acme Roadmap"), "{locale:?}");
+            assert!(email.text_body.contains(ACCEPT_URL), "{locale:?}");
+            assert!(email.text_body.contains("alice"), "{locale:?}");
+            assert!(!email.text_body.contains('{'), "{locale:?}");
+        }
+    }
+
+    #[test]
+    fn test_role_renders_human_readable() {
+        let en = render_invitation(Locale::En, "W", ACCEPT_URL, MemberRole::Admin, None);
+        assert!(en.text_body.contains("as Administrator"));
+        assert!(en.text_body.starts_with("Hi,\n\nSomeone invited you"));
+
+        let es = render_invitation(Locale::Es, "W", ACCEPT_URL, MemberRole::Member, None);
+        assert!(es.text_body.contains("con el rol Miembro"));
+    }
+
+    #[test]
+    fn test_values_are_not_expanded_as_placeholders() {
+        let email = render_invitation(
+            Locale::En,
+            "{accept_url}",
+            ACCEPT_URL,
+            MemberRole::Member,
+            Some("{role}"),
+        );
+        assert!(
+            email
+                .subject
+                .starts_with("{role} invited you to join {accept_url}")
+        );
+    }
+
+    #[test]
+    fn test_locale_parsing_falls_back_to_none_for_unknown() {
+        assert_eq!("es-MX".parse(), Ok(Locale::Es));
+        assert_eq!("EN_us".parse(), Ok(Locale::En));
+        assert_eq!("fr".parse::<Locale>(), Err(()));
+    }
+
+    #[test]
+    fn test_from_accept_language_honours_quality() {
+        assert_eq!(
+            Locale::from_accept_language("fr-CH, fr;q=0.9, es;q=0.8, en;q=0.7"),
+            Some(Locale::Es)
+        );
+        assert_eq!(
+            Locale::from_accept_language("en;q=0.5, es-ES"),
+            Some(Locale::Es)
+        );
+        assert_eq!(Locale::from_accept_language("es;q=0, de"), None);
+        assert_eq!(Locale::from_accept_language(""), None);
+    }
+}
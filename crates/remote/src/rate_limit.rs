@@ -0,0 +1,181 @@
+//! Per-workspace limit on how many invitations can be sent.
+//!
+//! Each workspace gets a token bucket holding up to `capacity` invites that
+//! refills evenly over `window`. State is in memory, so the limit applies per
+//! server process and resets on restart.
+
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use uuid::Uuid;
+
+/// Above this many tracked workspaces, full buckets are dropped; a missing
+/// bucket behaves exactly like a full one.
+const PRUNE_THRESHOLD: usize = 1024;
+
+#[derive(Debug)]
+pub struct InviteRateLimiter {
+    capacity: u32,
+    window: Duration,
+    buckets: Mutex<HashMap<Uuid, Bucket>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl Bucket {
+    fn tokens_at(&self, now: Instant, per_sec: f64, capacity: f64) -> f64 {
+        let elapsed = now.saturating_duration_since(self.refilled_at);
+        (self.tokens + elapsed.as_secs_f64() * per_sec).min(capacity)
+    }
+}
+
+impl InviteRateLimiter {
+    /// A `capacity` of 0 disables the limit.
+    pub fn new(capacity: u32, window: Duration) -> Self {
+        Self {
+            capacity,
+            window,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn per_hour(capacity: u32) -> Self {
+        Self::new(capacity, Duration::from_secs(3600))
+    }
+
+    /// The most invites a workspace can send in one window, or `None` when
+    /// the limit is disabled. A batch larger than this can never be accepted.
+    pub fn capacity(&self) -> Option<u32> {
+        (self.capacity > 0).then_some(self.capacity)
+    }
+
+    /// Reserve `count` invites for a workspace. When not enough are left,
+    /// nothing is taken and the wait until they would be is returned.
+    pub fn try_acquire(&self, workspace_id: Uuid, count: u32) -> Result<(), Duration> {
+        self.try_acquire_at(workspace_id, count, Instant::now())
+    }
+
+    /// Give back invites reserved by [`Self::try_acquire`] that weren't sent.
+    pub fn release(&self, workspace_id: Uuid, count: u32) {
+        if self.capacity == 0 || count == 0 {
+            return;
+        }
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(bucket) = buckets.get_mut(&workspace_id) {
+            bucket.tokens = (bucket.tokens + f64::from(count)).min(f64::from(self.capacity));
+        }
+    }
+
+    fn try_acquire_at(&self, workspace_id: Uuid, count: u32, now: Instant) -> Result<(), Duration> {
+        if self.capacity == 0 || count == 0 {
+            return Ok(());
+        }
+        let capacity = f64::from(self.capacity);
+        let per_sec = capacity / self.window.as_secs_f64();
+
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if buckets.len() > PRUNE_THRESHOLD {
+            buckets.retain(|_, bucket| bucket.tokens_at(now, per_sec, capacity) < capacity);
+        }
+
+        let bucket = buckets.entry(workspace_id).or_insert(Bucket {
+            tokens: capacity,
+            refilled_at: now,
+        });
+        bucket.tokens = bucket.tokens_at(now, per_sec, capacity);
+        bucket.refilled_at = now;
+
+        let wanted = f64::from(count);
+        if bucket.tokens >= wanted {
+            bucket.tokens -= wanted;
+            Ok(())
+        } else {
+            // A request larger than the bucket waits for a full refill
+            let missing = wanted.min(capacity) - bucket.tokens;
+            Err(Duration::from_secs_f64(missing / per_sec))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    #[test]
+    fn test_invite_over_limit_is_rejected() {
+        let limiter = InviteRateLimiter::new(3, HOUR);
+        let workspace_id = Uuid::new_v4();
+        let now = Instant::now();
+
+        for _ in 0..3 {
+            assert!(limiter.try_acquire_at(workspace_id, 1, now).is_ok());
+        }
+        let retry_after = limiter.try_acquire_at(workspace_id, 1, now).unwrap_err();
+        // One token refills every 20 minutes
+        assert_eq!(retry_after.as_secs_f64().round(), 1200.0);
+
+        // Other workspaces have their own bucket
+        assert!(limiter.try_acquire_at(Uuid::new_v4(), 1, now).is_ok());
+    }
+
+    #[test]
+    fn test_bucket_refills_over_window() {
+        let limiter = InviteRateLimiter::new(2, HOUR);
+        let workspace_id = Uuid::new_v4();
+        let now = Instant::now();
+
+        assert!(limiter.try_acquire_at(workspace_id, 2, now).is_ok());
+        assert!(limiter.try_acquire_at(workspace_id, 1, now).is_err());
+        assert!(
+            limiter
+                .try_acquire_at(workspace_id, 1, now + HOUR / 2)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_released_invites_can_be_reused() {
+        let limiter = InviteRateLimiter::new(1, HOUR);
+        let workspace_id = Uuid::new_v4();
+        let now = Instant::now();
+
+        assert!(limiter.try_acquire_at(workspace_id, 1, now).is_ok());
+        limiter.release(workspace_id, 1);
+        assert!(limiter.try_acquire_at(workspace_id, 1, now).is_ok());
+    }
+
+    #[test]
+    fn test_rejected_batch_takes_nothing() {
+        let limiter = InviteRateLimiter::new(5, HOUR);
+        let workspace_id = Uuid::new_v4();
+        let now = Instant::now();
+
+        assert!(limiter.try_acquire_at(workspace_id, 3, now).is_ok());
+        assert!(limiter.try_acquire_at(workspace_id, 3, now).is_err());
+        assert!(limiter.try_acquire_at(workspace_id, 2, now).is_ok());
+    }
+
+    #[test]
+    fn test_capacity_is_none_when_disabled() {
+        assert_eq!(InviteRateLimiter::new(5, HOUR).capacity(), Some(5));
+        assert_eq!(InviteRateLimiter::new(0, HOUR).capacity(), None);
+    }
+
+    #[test]
+    fn test_zero_capacity_disables_limit() {
+        let limiter = InviteRateLimiter::new(0, HOUR);
+        let workspace_id = Uuid::new_v4();
+        for _ in 0..1000 {
+            assert!(limiter.try_acquire(workspace_id, 1).is_ok());
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use axum::{
+    Json, Router,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::post,
+};
+use axum_extra::headers::{Authorization, HeaderMapExt, authorization::Bearer};
+use secrecy::{ExposeSecret, SecretString};
+use subtle::ConstantTimeEq;
+
+use super::error::ErrorResponse;
+use crate::{
+    AppState,
+    db::workspace_invitations::{InvitationCleanup, WorkspaceInvitationRepository},
+};
+
+/// Operator-only endpoints, authenticated with `REMOTE_ADMIN_TOKEN` rather
+/// than a user session.
+pub fn router() -> Router<AppState> {
+    Router::new().route("/admin/invitations/cleanup", post(cleanup_invitations))
+}
+
+/// Run the workspace invitation cleanup immediately
+pub async fn cleanup_invitations(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<InvitationCleanup>, ErrorResponse> {
+    ensure_admin(state.config.admin_token.as_ref(), &headers)?;
+
+    let config = &state.config.workspace_invitations;
+    let result = WorkspaceInvitationRepository::new(&state.pool)
+        .cleanup(config.cleanup_retention())
+        .await
+        .map_err(|e| {
+            tracing::error!(?e, "Workspace invitation cleanup failed");
+            ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error")
+        })?;
+
+    tracing::info!(
+        expired = result.expired,
+        deleted = result.deleted,
+        "Cleaned up workspace invitations on request"
+    );
+
+    Ok(Json(result))
+}
+
+fn ensure_admin(
+    admin_token: Option<&SecretString>,
+    headers: &HeaderMap,
+) -> Result<(), ErrorResponse> {
+    // Hide the endpoints entirely when no token is configured
+    let Some(admin_token) = admin_token else {
+        return Err(ErrorResponse::new(StatusCode::NOT_FOUND, "Not found"));
+    };
+
+    let Some(bearer) = headers.typed_get::<Authorization<Bearer>>() else {
+        return Err(ErrorResponse::new(StatusCode::UNAUTHORIZED, "Unauthorized"));
+    };
+
+    let expected = admin_token.expose_secret().as_bytes();
+    if !bool::from(bearer.token().as_bytes().ct_eq(expected)) {
+        return Err(ErrorResponse::new(StatusCode::UNAUTHORIZED, "Unauthorized"));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        http::{HeaderValue, header::AUTHORIZATION},
+        response::IntoResponse,
+    };
+
+    use super::*;
+
+    fn headers_with(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    fn status(result: Result<(), ErrorResponse>) -> Option<StatusCode> {
+        result.err().map(|e| e.into_response().status())
+    }
+
+    #[test]
+    fn test_admin_endpoints_hidden_without_token() {
+        assert_eq!(
+            status(ensure_admin(None, &headers_with("Bearer anything"))),
+            Some(StatusCode::NOT_FOUND)
+        );
+    }
+
+    #[test]
+    fn test_admin_token_must_match() {
+        let token = SecretString::from("s3cret".to_string());
+        assert_eq!(
+            status(ensure_admin(Some(&token), &headers_with("Bearer s3cret"))),
+            None
+        );
+        assert_eq!(
+            status(ensure_admin(Some(&token), &headers_with("Bearer wrong"))),
+            Some(StatusCode::UNAUTHORIZED)
+        );
+        assert_eq!(
+            status(ensure_admin(Some(&token), &HeaderMap::new())),
+            Some(StatusCode::UNAUTHORIZED)
+        );
+    }
+}
@@ -1,12 +1,120 @@
+use std::time::Duration;
+
 use axum::{
     Json,
-    http::StatusCode,
+    http::{HeaderValue, StatusCode, header},
     response::{IntoResponse, Response},
 };
-use serde_json::json;
+use serde_json::{Value, json};
 
 use crate::db::{identity_errors::IdentityError, projects::ProjectError, tasks::SharedTaskError};
 
+/// Error returned by route handlers. Every variant renders the same body:
+///
+/// ```json
+/// { "error": { "code": "not_found", "message": "Workspace not found" } }
+/// ```
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    #[error("{0}")]
+    BadRequest(String),
+    #[error("{0}")]
+    Unauthorized(String),
+    #[error("{0}")]
+    Forbidden(String),
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    Conflict(String),
+    #[error("{0}")]
+    PayloadTooLarge(String),
+    #[error("{0}")]
+    UnprocessableEntity(String),
+    #[error("{message}")]
+    TooManyRequests {
+        message: String,
+        /// Sent as `Retry-After`, rounded up to whole seconds
+        retry_after: Duration,
+    },
+    #[error("{0}")]
+    ServiceUnavailable(String),
+    /// Like `ServiceUnavailable`, for outages expected to clear on their own
+    #[error("{message}")]
+    TemporarilyUnavailable {
+        message: String,
+        /// Sent as `Retry-After`, rounded up to whole seconds
+        retry_after: Duration,
+    },
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl ApiError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            ApiError::UnprocessableEntity(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::ServiceUnavailable(_) | ApiError::TemporarilyUnavailable { .. } => {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let mut response = error_body(status, &self.to_string(), None);
+        if let ApiError::TooManyRequests { retry_after, .. }
+        | ApiError::TemporarilyUnavailable { retry_after, .. } = &self
+        {
+            let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(secs.max(1)));
+        }
+        response
+    }
+}
+
+/// Machine-readable `code` for an error status.
+fn error_code(status: StatusCode) -> &'static str {
+    match status {
+        StatusCode::BAD_REQUEST => "bad_request",
+        StatusCode::UNAUTHORIZED => "unauthorized",
+        StatusCode::FORBIDDEN => "forbidden",
+        StatusCode::NOT_FOUND => "not_found",
+        StatusCode::CONFLICT => "conflict",
+        StatusCode::PAYLOAD_TOO_LARGE => "payload_too_large",
+        StatusCode::UNPROCESSABLE_ENTITY => "unprocessable_entity",
+        StatusCode::TOO_MANY_REQUESTS => "too_many_requests",
+        StatusCode::NOT_IMPLEMENTED => "not_implemented",
+        StatusCode::BAD_GATEWAY => "bad_gateway",
+        StatusCode::SERVICE_UNAVAILABLE => "service_unavailable",
+        status if status.is_client_error() => "bad_request",
+        _ => "internal_error",
+    }
+}
+
+/// Render the standard error body. `details` are merged into the `error`
+/// object alongside `code` and `message`.
+fn error_body(status: StatusCode, message: &str, details: Option<Value>) -> Response {
+    let mut error = json!({ "code": error_code(status), "message": message });
+    if let (Some(Value::Object(details)), Value::Object(error)) = (details, &mut error) {
+        error.extend(details);
+    }
+    (status, Json(json!({ "error": error }))).into_response()
+}
+
+/// Status and message pair for handlers that pick the status at runtime.
+/// Renders the same body as [`ApiError`].
 #[derive(Debug)]
 pub struct ErrorResponse {
     status: StatusCode,
@@ -24,97 +132,214 @@ impl ErrorResponse {
 
 impl IntoResponse for ErrorResponse {
     fn into_response(self) -> Response {
-        (self.status, Json(json!({ "error": self.message }))).into_response()
+        error_body(self.status, &self.message, None)
+    }
+}
+
+impl From<ApiError> for ErrorResponse {
+    fn from(error: ApiError) -> Self {
+        Self::new(error.status(), error.to_string())
+    }
+}
+
+/// Lets handlers returning [`ApiError`] propagate checks that pick their
+/// status at runtime. Statuses without a variant of their own are internal.
+impl From<ErrorResponse> for ApiError {
+    fn from(error: ErrorResponse) -> Self {
+        let message = error.message;
+        match error.status {
+            StatusCode::BAD_REQUEST => ApiError::BadRequest(message),
+            StatusCode::UNAUTHORIZED => ApiError::Unauthorized(message),
+            StatusCode::FORBIDDEN => ApiError::Forbidden(message),
+            StatusCode::NOT_FOUND => ApiError::NotFound(message),
+            StatusCode::CONFLICT => ApiError::Conflict(message),
+            StatusCode::PAYLOAD_TOO_LARGE => ApiError::PayloadTooLarge(message),
+            StatusCode::UNPROCESSABLE_ENTITY => ApiError::UnprocessableEntity(message),
+            StatusCode::SERVICE_UNAVAILABLE => ApiError::ServiceUnavailable(message),
+            _ => ApiError::Internal(message),
+        }
     }
 }
 
 pub(crate) fn task_error_response(error: SharedTaskError, context: &str) -> Response {
-    let response = match error {
-        SharedTaskError::NotFound => (
-            StatusCode::NOT_FOUND,
-            Json(json!({ "error": "task not found" })),
-        ),
-        SharedTaskError::Forbidden => (
-            StatusCode::FORBIDDEN,
-            Json(json!({ "error": "only the assignee can modify this task" })),
-        ),
-        SharedTaskError::Conflict(message) => {
-            (StatusCode::CONFLICT, Json(json!({ "error": message })))
+    let error = match error {
+        SharedTaskError::NotFound => ApiError::NotFound("task not found".to_string()),
+        SharedTaskError::Forbidden => {
+            ApiError::Forbidden("only the assignee can modify this task".to_string())
         }
-        SharedTaskError::PayloadTooLarge => (
-            StatusCode::BAD_REQUEST,
-            Json(json!({
-                "error": "title and description cannot exceed 50 KiB combined"
-            })),
-        ),
-        SharedTaskError::Project(ProjectError::Conflict(message)) => {
-            (StatusCode::CONFLICT, Json(json!({ "error": message })))
+        SharedTaskError::Conflict(message) => ApiError::Conflict(message),
+        SharedTaskError::PayloadTooLarge => {
+            ApiError::BadRequest("title and description cannot exceed 50 KiB combined".to_string())
         }
+        SharedTaskError::Project(ProjectError::Conflict(message)) => ApiError::Conflict(message),
         SharedTaskError::Project(err) => {
             tracing::error!(?err, "{context}", context = context);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": "internal server error" })),
-            )
+            ApiError::Internal("internal server error".to_string())
         }
         SharedTaskError::Identity(err) => return identity_error_response(err, context),
         SharedTaskError::Serialization(err) => {
             tracing::error!(?err, "{context}", context = context);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": "failed to serialize shared task" })),
-            )
+            ApiError::Internal("failed to serialize shared task".to_string())
         }
         SharedTaskError::Database(err) => {
             tracing::error!(?err, "{context}", context = context);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": "internal server error" })),
-            )
+            ApiError::Internal("internal server error".to_string())
         }
     };
 
-    response.into_response()
+    error.into_response()
 }
 
 pub(crate) fn identity_error_response(error: IdentityError, message: &str) -> Response {
-    match error {
-        IdentityError::NotFound => (StatusCode::BAD_REQUEST, Json(json!({ "error": message }))),
-        IdentityError::PermissionDenied => (
-            StatusCode::FORBIDDEN,
-            Json(json!({ "error": "permission denied" })),
-        ),
-        IdentityError::InvitationError(msg) => {
-            (StatusCode::BAD_REQUEST, Json(json!({ "error": msg })))
+    let error = match error {
+        IdentityError::NotFound => ApiError::BadRequest(message.to_string()),
+        IdentityError::PermissionDenied => ApiError::Forbidden("permission denied".to_string()),
+        IdentityError::InvitationError(msg) => ApiError::BadRequest(msg),
+        IdentityError::CannotDeleteOrganization(msg) => ApiError::Conflict(msg),
+        IdentityError::OrganizationConflict(msg) => ApiError::Conflict(msg),
+        IdentityError::WorkspaceUnavailable => {
+            ApiError::Conflict("workspace is no longer available".to_string())
         }
-        IdentityError::CannotDeleteOrganization(msg) => {
-            (StatusCode::CONFLICT, Json(json!({ "error": msg })))
+        IdentityError::LastAdmin => {
+            ApiError::Conflict("the last admin cannot leave the workspace".to_string())
         }
-        IdentityError::OrganizationConflict(msg) => {
-            (StatusCode::CONFLICT, Json(json!({ "error": msg })))
+        IdentityError::InvalidTransfer(msg) => ApiError::BadRequest(msg),
+        IdentityError::StaleVersion => {
+            ApiError::Conflict("the record was modified by another request".to_string())
+        }
+        IdentityError::InvitationNotYetActive(active_at) => {
+            return error_body(
+                StatusCode::CONFLICT,
+                "invitation is not yet active",
+                Some(json!({ "active_at": active_at })),
+            );
         }
         IdentityError::Database(err) => {
             tracing::error!(?err, "identity sync failed");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": "internal server error" })),
-            )
+            ApiError::Internal("internal server error".to_string())
         }
-    }
-    .into_response()
+    };
+
+    error.into_response()
 }
 
-pub(crate) fn membership_error(error: IdentityError, forbidden_message: &str) -> ErrorResponse {
+pub(crate) fn membership_error(error: IdentityError, forbidden_message: &str) -> ApiError {
     match error {
         IdentityError::NotFound | IdentityError::PermissionDenied => {
-            ErrorResponse::new(StatusCode::FORBIDDEN, forbidden_message)
-        }
-        IdentityError::Database(_) => {
-            ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error")
+            ApiError::Forbidden(forbidden_message.to_string())
         }
+        IdentityError::Database(_) => ApiError::Internal("Database error".to_string()),
         other => {
             tracing::warn!(?other, "unexpected membership error");
-            ErrorResponse::new(StatusCode::FORBIDDEN, forbidden_message)
+            ApiError::Forbidden(forbidden_message.to_string())
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::body::to_bytes;
+
+    use super::*;
+
+    async fn body_json(response: Response) -> (StatusCode, Value) {
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_not_found_body() {
+        let (status, body) =
+            body_json(ApiError::NotFound("Workspace not found".to_string()).into_response()).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(
+            body,
+            json!({ "error": { "code": "not_found", "message": "Workspace not found" } })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_forbidden_body_keeps_membership_message() {
+        let error = membership_error(
+            IdentityError::PermissionDenied,
+            "Permission denied: member.invite required",
+        );
+        let (status, body) = body_json(error.into_response()).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(
+            body,
+            json!({
+                "error": {
+                    "code": "forbidden",
+                    "message": "Permission denied: member.invite required"
+                }
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bad_request_body() {
+        let (status, body) =
+            body_json(ApiError::BadRequest("Invalid email".to_string()).into_response()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body,
+            json!({ "error": { "code": "bad_request", "message": "Invalid email" } })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_too_many_requests_sets_retry_after() {
+        let response = ApiError::TooManyRequests {
+            message: "Invitation limit reached".to_string(),
+            retry_after: Duration::from_millis(90_500),
+        }
+        .into_response();
+        assert_eq!(response.headers()[header::RETRY_AFTER], "91");
+        let (status, body) = body_json(response).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(body["error"]["code"], "too_many_requests");
+    }
+
+    #[tokio::test]
+    async fn test_error_response_matches_api_error() {
+        let (status, body) = body_json(
+            ErrorResponse::new(StatusCode::BAD_GATEWAY, "Upstream failed").into_response(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_GATEWAY);
+        assert_eq!(
+            body,
+            json!({ "error": { "code": "bad_gateway", "message": "Upstream failed" } })
+        );
+    }
+
+    #[test]
+    fn test_error_response_converts_to_matching_api_error() {
+        for status in [
+            StatusCode::BAD_REQUEST,
+            StatusCode::UNAUTHORIZED,
+            StatusCode::FORBIDDEN,
+            StatusCode::NOT_FOUND,
+            StatusCode::INTERNAL_SERVER_ERROR,
+        ] {
+            let error = ApiError::from(ErrorResponse::new(status, "task not accessible"));
+            assert_eq!(error.status(), status);
+            assert_eq!(error.to_string(), "task not accessible");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_details_are_nested_in_error() {
+        let active_at = chrono::Utc::now();
+        let (status, body) = body_json(identity_error_response(
+            IdentityError::InvitationNotYetActive(active_at),
+            "unused",
+        ))
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["error"]["code"], "conflict");
+        assert_eq!(body["error"]["active_at"], json!(active_at));
+    }
+}
@@ -1,22 +1,69 @@
+use std::time::Duration;
+
 use axum::{
     Extension, Json, Router,
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{DefaultBodyLimit, Multipart, Path, State, multipart::MultipartError},
+    http::{
+        HeaderMap, HeaderValue, StatusCode,
+        header::{CACHE_CONTROL, CONTENT_TYPE, ETAG, IF_NONE_MATCH},
+    },
     response::{IntoResponse, Response},
     routing::{delete, get, post},
 };
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use tracing::instrument;
+use uuid::Uuid;
+
+use super::{error::ApiError, organization_members::ensure_task_access};
+use crate::{
+    AppState,
+    auth::RequestContext,
+    db::{
+        organization_members::MemberRole, organizations::OrganizationRepository, task_attachments,
+    },
+    files::{FilesError, FilesService, IMAGE_HEADER_PROBE_LEN, MAGIC_BYTES_PROBE_LEN},
+};
+
+/// `Retry-After` sent while R2 can't be reached
+const STORAGE_RETRY_AFTER: Duration = Duration::from_secs(30);
 
-use crate::{AppState, auth::RequestContext, files::FilesError};
+/// Retries carrying the same value get the original upload URL back
+const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Multipart field carrying the image for `/files/avatars/direct`
+const DIRECT_UPLOAD_FIELD: &str = "file";
+
+/// Ceiling on a `/files/avatars/direct` request body. The configured avatar
+/// size limit is enforced separately while the file part is read, so only
+/// that much of an image is ever buffered.
+const DIRECT_UPLOAD_BODY_LIMIT: usize = 16 * 1024 * 1024;
 
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/files/avatars/upload", post(create_avatar_upload_url))
+        .route("/files/avatars/confirm", post(confirm_avatar_upload))
+        .route(
+            "/files/avatars/direct",
+            post(upload_avatar_direct).layer(DefaultBodyLimit::max(DIRECT_UPLOAD_BODY_LIMIT)),
+        )
         .route("/files/avatars", get(list_avatars))
         .route("/files/avatars", delete(delete_all_avatars))
         .route("/files/avatars/{key:.*}", delete(delete_avatar))
+        .route("/files/avatars/{key:.*}/download", get(download_avatar))
+        .route(
+            "/files/tasks/{task_id}/attachments/upload",
+            post(create_task_attachment_upload_url),
+        )
+        .route(
+            "/files/tasks/{task_id}/attachments",
+            get(list_task_attachments),
+        )
+        .route(
+            "/files/tasks/{task_id}/attachments/{key:.*}",
+            delete(delete_task_attachment),
+        )
         .route("/files/config", get(get_files_config))
 }
 
@@ -25,6 +72,11 @@ pub struct CreateAvatarUploadRequest {
     pub content_type: String,
     #[serde(default)]
     pub content_length: Option<u64>,
+    #[serde(default)]
+    pub expires_in_secs: Option<u64>,
+    /// Base64-encoded MD5 of the body; R2 rejects uploads that don't match
+    #[serde(default)]
+    pub content_md5: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -33,6 +85,39 @@ pub struct CreateAvatarUploadResponse {
     pub object_key: String,
     pub public_url: String,
     pub expires_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_md5: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConfirmAvatarUploadRequest {
+    pub object_key: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfirmAvatarUploadResponse {
+    pub object_key: String,
+    pub public_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateAttachmentUploadRequest {
+    pub content_type: String,
+    #[serde(default)]
+    pub content_length: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateAttachmentUploadResponse {
+    pub upload_url: String,
+    pub object_key: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DownloadUrlResponse {
+    pub download_url: String,
+    pub expires_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Serialize)]
@@ -48,6 +133,11 @@ pub struct ListAvatarsResponse {
     pub avatars: Vec<FileInfoResponse>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct ListAttachmentsResponse {
+    pub attachments: Vec<FileInfoResponse>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct DeleteAvatarsResponse {
     pub deleted_count: u32,
@@ -58,45 +148,106 @@ pub struct FilesConfigResponse {
     pub enabled: bool,
     pub max_file_size_bytes: Option<u64>,
     pub allowed_types: Vec<String>,
+    pub storage_used_bytes: Option<u64>,
+    pub storage_limit_bytes: Option<u64>,
 }
 
-#[derive(Debug, thiserror::Error)]
-pub enum FilesRouteError {
-    #[error("files storage not configured")]
-    NotConfigured,
-    #[error("files error: {0}")]
-    Files(#[from] FilesError),
-}
-
-impl IntoResponse for FilesRouteError {
-    fn into_response(self) -> Response {
-        let (status, message) = match &self {
-            FilesRouteError::NotConfigured => (
-                StatusCode::SERVICE_UNAVAILABLE,
-                "File storage service not available".to_string(),
-            ),
-            FilesRouteError::Files(FilesError::InvalidFileType(msg)) => {
-                (StatusCode::BAD_REQUEST, msg.clone())
+impl From<FilesError> for ApiError {
+    fn from(error: FilesError) -> Self {
+        match error {
+            FilesError::InvalidFileType(msg) => ApiError::BadRequest(msg),
+            FilesError::MissingContentLength => {
+                ApiError::BadRequest("content_length is required".to_string())
+            }
+            FilesError::InvalidChecksum(msg) => {
+                ApiError::BadRequest(format!("Invalid checksum: {msg}"))
             }
-            FilesRouteError::Files(FilesError::FileTooLarge(size, max)) => (
-                StatusCode::PAYLOAD_TOO_LARGE,
-                format!("File size {} bytes exceeds maximum {} bytes", size, max),
+            FilesError::InvalidExpiry(msg) => {
+                ApiError::BadRequest(format!("Invalid expiry: {msg}"))
+            }
+            FilesError::InvalidKey(msg) => ApiError::BadRequest(format!("Invalid file key: {msg}")),
+            FilesError::InvalidIdempotencyKey(msg) => {
+                ApiError::BadRequest(format!("Invalid Idempotency-Key: {msg}"))
+            }
+            FilesError::IdempotencyKeyReused => ApiError::UnprocessableEntity(
+                "Idempotency-Key was already used for a different request".to_string(),
             ),
-            FilesRouteError::Files(e) => {
+            FilesError::FileTooLarge(size, max) => ApiError::PayloadTooLarge(format!(
+                "File size {size} bytes exceeds maximum {max} bytes"
+            )),
+            FilesError::QuotaExceeded(used, limit) => ApiError::PayloadTooLarge(format!(
+                "Storage quota exceeded: {used} of {limit} bytes used"
+            )),
+            FilesError::Unavailable(msg) => {
+                tracing::warn!(error = %msg, "File storage unreachable");
+                ApiError::TemporarilyUnavailable {
+                    message: "File storage is temporarily unavailable".to_string(),
+                    retry_after: STORAGE_RETRY_AFTER,
+                }
+            }
+            e => {
                 tracing::error!(error = %e, "Files service error");
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "Internal server error".to_string(),
-                )
+                ApiError::Internal("Internal server error".to_string())
             }
-        };
+        }
+    }
+}
 
-        let body = serde_json::json!({
-            "error": message
-        });
+fn files_not_configured() -> ApiError {
+    ApiError::ServiceUnavailable("File storage service not available".to_string())
+}
 
-        (status, Json(body)).into_response()
+/// Serialize `body` as JSON with a weak ETag derived from its bytes. Returns
+/// 304 with an empty body when the request's `If-None-Match` already names
+/// that tag, so polling clients only download responses that changed.
+fn json_with_etag<T: Serialize>(headers: &HeaderMap, body: &T) -> Response {
+    let bytes = match serde_json::to_vec(body) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to serialize response");
+            return ApiError::Internal("Internal server error".to_string()).into_response();
+        }
+    };
+    let digest = Sha256::digest(&bytes);
+    let etag = format!("W/\"{}\"", hex::encode(&digest[..16]));
+    let etag_value = HeaderValue::from_str(&etag).expect("hex ETag is a valid header value");
+
+    if if_none_match_matches(headers, &etag) {
+        return (StatusCode::NOT_MODIFIED, [(ETAG, etag_value)]).into_response();
+    }
+
+    (
+        [
+            (ETAG, etag_value),
+            (CONTENT_TYPE, HeaderValue::from_static("application/json")),
+        ],
+        bytes,
+    )
+        .into_response()
+}
+
+/// Let the browser reuse `response` for `max_age_secs`. The cache is
+/// `private` because the bodies carry per-user data such as storage usage.
+fn with_max_age(mut response: Response, max_age_secs: u64) -> Response {
+    if response.status().is_success() || response.status() == StatusCode::NOT_MODIFIED {
+        let value = HeaderValue::from_str(&format!("private, max-age={max_age_secs}"))
+            .expect("numeric Cache-Control is a valid header value");
+        response.headers_mut().insert(CACHE_CONTROL, value);
     }
+    response
+}
+
+/// Weak comparison against every tag in `If-None-Match`, as GET requires.
+fn if_none_match_matches(headers: &HeaderMap, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let etag = opaque(etag);
+
+    headers
+        .get_all(IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|tag| tag.trim() == "*" || opaque(tag) == etag)
 }
 
 /// Create a presigned URL for avatar upload
@@ -104,29 +255,179 @@ impl IntoResponse for FilesRouteError {
 pub async fn create_avatar_upload_url(
     State(state): State<AppState>,
     Extension(ctx): Extension<RequestContext>,
+    headers: HeaderMap,
     Json(payload): Json<CreateAvatarUploadRequest>,
-) -> Result<Json<CreateAvatarUploadResponse>, FilesRouteError> {
-    let files = state.files().ok_or(FilesRouteError::NotConfigured)?;
+) -> Result<Json<CreateAvatarUploadResponse>, ApiError> {
+    let files = state.files().ok_or_else(files_not_configured)?;
 
-    let upload = files
-        .create_avatar_upload_url(ctx.user.id, &payload.content_type, payload.content_length)
-        .await?;
+    let idempotency_key = headers
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .map(|value| {
+            value
+                .to_str()
+                .map_err(|_| FilesError::InvalidIdempotencyKey("must be visible ASCII".to_string()))
+        })
+        .transpose()?;
+
+    let upload = match idempotency_key {
+        Some(key) => {
+            files
+                .create_avatar_upload_url_idempotent(
+                    ctx.user.id,
+                    key,
+                    &payload.content_type,
+                    payload.content_length,
+                    payload.expires_in_secs,
+                    payload.content_md5.as_deref(),
+                )
+                .await?
+        }
+        None => {
+            files
+                .create_avatar_upload_url(
+                    ctx.user.id,
+                    &payload.content_type,
+                    payload.content_length,
+                    payload.expires_in_secs,
+                    payload.content_md5.as_deref(),
+                )
+                .await?
+        }
+    };
 
     Ok(Json(CreateAvatarUploadResponse {
         upload_url: upload.upload_url,
         object_key: upload.object_key,
         public_url: upload.public_url,
         expires_at: upload.expires_at,
+        content_md5: upload.content_md5,
+    }))
+}
+
+/// Verify a just-uploaded avatar really is the image type it was uploaded
+/// as and within the size limits in pixels. Objects that fail the check are
+/// deleted.
+#[instrument(name = "files.confirm_avatar_upload", skip(state, ctx), fields(user_id = %ctx.user.id))]
+pub async fn confirm_avatar_upload(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<RequestContext>,
+    Json(payload): Json<ConfirmAvatarUploadRequest>,
+) -> Result<Json<ConfirmAvatarUploadResponse>, ApiError> {
+    let files = state.files().ok_or_else(files_not_configured)?;
+
+    let expected_prefix = format!("avatars/{}/", ctx.user.id);
+    FilesService::validate_object_key(&payload.object_key, &expected_prefix)?;
+
+    let (mut bytes, content_type) = files
+        .read_object_head(&payload.object_key, MAGIC_BYTES_PROBE_LEN)
+        .await?;
+
+    let mut check = match content_type.as_deref() {
+        Some(content_type) => FilesService::validate_avatar_type(content_type)
+            .and_then(|_| FilesService::validate_magic_bytes(&bytes, content_type)),
+        None => Err(FilesError::InvalidFileType(
+            "uploaded object has no content type".to_string(),
+        )),
+    };
+
+    if let (Ok(()), Some(content_type)) = (&check, content_type.as_deref()) {
+        // Only fetch more when the probe was cut short before the header ended
+        if FilesService::image_dimensions(&bytes, content_type).is_none()
+            && bytes.len() as u64 >= MAGIC_BYTES_PROBE_LEN
+        {
+            bytes = files
+                .read_object_head(&payload.object_key, IMAGE_HEADER_PROBE_LEN)
+                .await?
+                .0;
+        }
+        check = files.validate_image_dimensions(&bytes, content_type);
+    }
+
+    if let Err(e) = check {
+        tracing::warn!(object_key = %payload.object_key, error = %e, "Rejected avatar upload");
+        files.delete_file(&payload.object_key).await?;
+        return Err(e.into());
+    }
+
+    Ok(Json(ConfirmAvatarUploadResponse {
+        public_url: files.get_public_url(&payload.object_key),
+        object_key: payload.object_key,
+    }))
+}
+
+/// Upload an avatar through the server instead of straight to R2. Expects a
+/// multipart body with the image in a `file` part carrying its content type;
+/// the bytes get the same checks `confirm` applies before they are stored.
+#[instrument(name = "files.upload_avatar_direct", skip(state, ctx, multipart), fields(user_id = %ctx.user.id))]
+pub async fn upload_avatar_direct(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<RequestContext>,
+    mut multipart: Multipart,
+) -> Result<Json<ConfirmAvatarUploadResponse>, ApiError> {
+    let files = state.files().ok_or_else(files_not_configured)?;
+
+    let (content_type, bytes) = read_avatar_part(&mut multipart, files.max_file_size()).await?;
+    let uploaded = files
+        .upload_avatar(ctx.user.id, &content_type, bytes)
+        .await?;
+
+    Ok(Json(ConfirmAvatarUploadResponse {
+        object_key: uploaded.object_key,
+        public_url: uploaded.public_url,
     }))
 }
 
+/// Pull the image out of a direct upload, refusing to buffer more than
+/// `max_size` bytes of it
+async fn read_avatar_part(
+    multipart: &mut Multipart,
+    max_size: u64,
+) -> Result<(String, Vec<u8>), ApiError> {
+    while let Some(mut field) = multipart.next_field().await.map_err(multipart_error)? {
+        if field.name() != Some(DIRECT_UPLOAD_FIELD) {
+            continue;
+        }
+
+        let content_type = field
+            .content_type()
+            .ok_or_else(|| {
+                ApiError::BadRequest(format!("`{DIRECT_UPLOAD_FIELD}` part has no content type"))
+            })?
+            .to_string();
+        FilesService::validate_avatar_type(&content_type)?;
+
+        let mut bytes = Vec::new();
+        while let Some(chunk) = field.chunk().await.map_err(multipart_error)? {
+            let size = (bytes.len() + chunk.len()) as u64;
+            if size > max_size {
+                return Err(FilesError::FileTooLarge(size, max_size).into());
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        return Ok((content_type, bytes));
+    }
+
+    Err(ApiError::BadRequest(format!(
+        "missing `{DIRECT_UPLOAD_FIELD}` part"
+    )))
+}
+
+fn multipart_error(error: MultipartError) -> ApiError {
+    if error.status() == StatusCode::PAYLOAD_TOO_LARGE {
+        ApiError::PayloadTooLarge(error.body_text())
+    } else {
+        ApiError::BadRequest(error.body_text())
+    }
+}
+
 /// List all avatars for the current user
 #[instrument(name = "files.list_avatars", skip(state, ctx), fields(user_id = %ctx.user.id))]
 pub async fn list_avatars(
     State(state): State<AppState>,
     Extension(ctx): Extension<RequestContext>,
-) -> Result<Json<ListAvatarsResponse>, FilesRouteError> {
-    let files = state.files().ok_or(FilesRouteError::NotConfigured)?;
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let files = state.files().ok_or_else(files_not_configured)?;
 
     let avatars = files.list_user_avatars(ctx.user.id).await?;
 
@@ -140,7 +441,7 @@ pub async fn list_avatars(
         })
         .collect();
 
-    Ok(Json(ListAvatarsResponse { avatars }))
+    Ok(json_with_etag(&headers, &ListAvatarsResponse { avatars }))
 }
 
 /// Delete a specific avatar by key
@@ -149,46 +450,193 @@ pub async fn delete_avatar(
     State(state): State<AppState>,
     Extension(ctx): Extension<RequestContext>,
     Path(key): Path<String>,
-) -> Result<StatusCode, FilesRouteError> {
-    let files = state.files().ok_or(FilesRouteError::NotConfigured)?;
+) -> Result<StatusCode, ApiError> {
+    let files = state.files().ok_or_else(files_not_configured)?;
 
     // Ensure the user can only delete their own avatars
     let expected_prefix = format!("avatars/{}/", ctx.user.id);
-    if !key.starts_with(&expected_prefix) {
-        return Err(FilesRouteError::Files(FilesError::Delete(
-            "Cannot delete files belonging to other users".to_string(),
-        )));
-    }
+    FilesService::validate_object_key(&key, &expected_prefix)?;
 
     files.delete_file(&key).await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Create a presigned download URL for one of the current user's avatars.
+/// The key is a single path segment, so its slashes must be percent-encoded.
+#[instrument(name = "files.download_avatar", skip(state, ctx), fields(user_id = %ctx.user.id))]
+pub async fn download_avatar(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<RequestContext>,
+    Path(key): Path<String>,
+) -> Result<Json<DownloadUrlResponse>, ApiError> {
+    let files = state.files().ok_or_else(files_not_configured)?;
+
+    // Ensure the user can only download their own avatars
+    let expected_prefix = format!("avatars/{}/", ctx.user.id);
+    FilesService::validate_object_key(&key, &expected_prefix)?;
+
+    let download = files.create_download_url(&key, None).await?;
+
+    Ok(Json(DownloadUrlResponse {
+        download_url: download.download_url,
+        expires_at: download.expires_at,
+    }))
+}
+
 /// Delete all avatars for the current user
 #[instrument(name = "files.delete_all_avatars", skip(state, ctx), fields(user_id = %ctx.user.id))]
 pub async fn delete_all_avatars(
     State(state): State<AppState>,
     Extension(ctx): Extension<RequestContext>,
-) -> Result<Json<DeleteAvatarsResponse>, FilesRouteError> {
-    let files = state.files().ok_or(FilesRouteError::NotConfigured)?;
+) -> Result<Json<DeleteAvatarsResponse>, ApiError> {
+    let files = state.files().ok_or_else(files_not_configured)?;
 
     let deleted_count = files.delete_user_avatars(ctx.user.id).await?;
 
     Ok(Json(DeleteAvatarsResponse { deleted_count }))
 }
 
-/// Get files configuration
-#[instrument(name = "files.get_config", skip(state))]
+/// Create a presigned URL for uploading an attachment to a task the user
+/// can access
+#[instrument(
+    name = "files.create_task_attachment_upload",
+    skip(state, ctx, payload),
+    fields(user_id = %ctx.user.id, task_id = %task_id)
+)]
+pub async fn create_task_attachment_upload_url(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<RequestContext>,
+    Path(task_id): Path<Uuid>,
+    Json(payload): Json<CreateAttachmentUploadRequest>,
+) -> Result<Json<CreateAttachmentUploadResponse>, ApiError> {
+    let files = state.files().ok_or_else(files_not_configured)?;
+
+    ensure_task_access(state.pool(), ctx.user.id, task_id).await?;
+
+    let upload = files
+        .create_task_attachment_upload_url(
+            task_id,
+            ctx.user.id,
+            &payload.content_type,
+            payload.content_length,
+        )
+        .await?;
+
+    task_attachments::record_upload(state.pool(), &upload.object_key, task_id, ctx.user.id)
+        .await
+        .map_err(|error| {
+            tracing::error!(?error, %task_id, "failed to record attachment upload");
+            ApiError::Internal("Internal server error".to_string())
+        })?;
+
+    Ok(Json(CreateAttachmentUploadResponse {
+        upload_url: upload.upload_url,
+        object_key: upload.object_key,
+        expires_at: upload.expires_at,
+    }))
+}
+
+/// List the attachments of a task the user can access
+#[instrument(
+    name = "files.list_task_attachments",
+    skip(state, ctx),
+    fields(user_id = %ctx.user.id, task_id = %task_id)
+)]
+pub async fn list_task_attachments(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<RequestContext>,
+    Path(task_id): Path<Uuid>,
+) -> Result<Json<ListAttachmentsResponse>, ApiError> {
+    let files = state.files().ok_or_else(files_not_configured)?;
+
+    ensure_task_access(state.pool(), ctx.user.id, task_id).await?;
+
+    let uploaders = task_attachments::uploaders(state.pool(), task_id)
+        .await
+        .map_err(|error| {
+            tracing::error!(?error, %task_id, "failed to load attachment uploaders");
+            ApiError::Internal("Internal server error".to_string())
+        })?;
+
+    let attachments = files
+        .list_task_attachments(task_id, &uploaders)
+        .await?
+        .into_iter()
+        .map(|f| FileInfoResponse {
+            key: f.key,
+            public_url: f.public_url,
+            size: f.size,
+            last_modified: f.last_modified,
+        })
+        .collect();
+
+    Ok(Json(ListAttachmentsResponse { attachments }))
+}
+
+/// Delete an attachment from a task the user can access. Only the uploader
+/// or an admin of the task's organization may delete it.
+#[instrument(
+    name = "files.delete_task_attachment",
+    skip(state, ctx),
+    fields(user_id = %ctx.user.id, task_id = %task_id)
+)]
+pub async fn delete_task_attachment(
+    State(state): State<AppState>,
+    Extension(ctx): Extension<RequestContext>,
+    Path((task_id, key)): Path<(Uuid, String)>,
+) -> Result<StatusCode, ApiError> {
+    let files = state.files().ok_or_else(files_not_configured)?;
+
+    let organization_id = ensure_task_access(state.pool(), ctx.user.id, task_id).await?;
+
+    let uploader = FilesService::attachment_uploader(task_id, &key)?;
+    if uploader != ctx.user.id {
+        let role = OrganizationRepository::new(state.pool())
+            .check_user_role(organization_id, ctx.user.id)
+            .await
+            .map_err(|error| {
+                tracing::error!(?error, %organization_id, "failed to load member role");
+                ApiError::Internal("Internal server error".to_string())
+            })?;
+        if role != Some(MemberRole::Admin) {
+            return Err(ApiError::Forbidden(
+                "Only the uploader or an admin can delete this attachment".to_string(),
+            ));
+        }
+    }
+
+    files.delete_task_attachment(task_id, &key).await?;
+
+    task_attachments::delete(state.pool(), &key)
+        .await
+        .map_err(|error| {
+            tracing::error!(?error, %task_id, "failed to forget deleted attachment");
+            ApiError::Internal("Internal server error".to_string())
+        })?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Get files configuration and the current user's storage usage
+#[instrument(name = "files.get_config", skip(state, ctx), fields(user_id = %ctx.user.id))]
 pub async fn get_files_config(
     State(state): State<AppState>,
-) -> Json<FilesConfigResponse> {
-    let (enabled, max_file_size_bytes) = match state.files() {
-        Some(files) => (true, Some(files.max_file_size())),
-        None => (false, None),
-    };
+    Extension(ctx): Extension<RequestContext>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let (enabled, max_file_size_bytes, storage_used_bytes, storage_limit_bytes) =
+        match state.files() {
+            Some(files) => (
+                true,
+                Some(files.max_file_size()),
+                Some(files.user_storage_bytes(ctx.user.id).await?),
+                Some(files.user_quota()),
+            ),
+            None => (false, None, None, None),
+        };
 
-    Json(FilesConfigResponse {
+    let response = FilesConfigResponse {
         enabled,
         max_file_size_bytes,
         allowed_types: vec![
@@ -197,5 +645,167 @@ pub async fn get_files_config(
             "image/gif".to_string(),
             "image/webp".to_string(),
         ],
-    })
+        storage_used_bytes,
+        storage_limit_bytes,
+    };
+
+    Ok(with_max_age(
+        json_with_etag(&headers, &response),
+        state.config().files_config_max_age_secs,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::{Body, to_bytes},
+        extract::FromRequest,
+        http::Request,
+    };
+
+    use super::*;
+
+    async fn multipart(parts: &[(&str, &str, &[u8])]) -> Multipart {
+        let boundary = "avatar-boundary";
+        let mut body = Vec::new();
+        for (name, content_type, data) in parts {
+            body.extend_from_slice(
+                format!(
+                    "--{boundary}\r\n\
+                     Content-Disposition: form-data; name=\"{name}\"; filename=\"avatar\"\r\n\
+                     Content-Type: {content_type}\r\n\r\n"
+                )
+                .as_bytes(),
+            );
+            body.extend_from_slice(data);
+            body.extend_from_slice(b"\r\n");
+        }
+        body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+
+        let request = Request::builder()
+            .header(
+                CONTENT_TYPE,
+                format!("multipart/form-data; boundary={boundary}"),
+            )
+            .body(Body::from(body))
+            .unwrap();
+        Multipart::from_request(request, &()).await.unwrap()
+    }
+
+    const PNG: &[u8] = &[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+
+    #[tokio::test]
+    async fn test_direct_upload_reads_file_part_with_its_content_type() {
+        let mut parts =
+            multipart(&[("note", "text/plain", b"hi"), ("file", "image/png", PNG)]).await;
+
+        let (content_type, bytes) = read_avatar_part(&mut parts, 1024).await.unwrap();
+
+        assert_eq!(content_type, "image/png");
+        assert_eq!(bytes, PNG);
+    }
+
+    #[tokio::test]
+    async fn test_direct_upload_rejects_non_image_and_oversized_parts() {
+        let mut spoofed = multipart(&[("file", "application/x-sh", PNG)]).await;
+        assert!(matches!(
+            read_avatar_part(&mut spoofed, 1024).await,
+            Err(ApiError::BadRequest(_))
+        ));
+
+        let mut oversized = multipart(&[("file", "image/png", &[0u8; 2048])]).await;
+        assert!(matches!(
+            read_avatar_part(&mut oversized, 1024).await,
+            Err(ApiError::PayloadTooLarge(_))
+        ));
+
+        let mut missing = multipart(&[("avatar", "image/png", PNG)]).await;
+        assert!(matches!(
+            read_avatar_part(&mut missing, 1024).await,
+            Err(ApiError::BadRequest(_))
+        ));
+    }
+
+    fn avatars() -> ListAvatarsResponse {
+        ListAvatarsResponse {
+            avatars: vec![FileInfoResponse {
+                key: "avatars/user/a.png".to_string(),
+                public_url: "https://files.example.com/avatars/user/a.png".to_string(),
+                size: Some(1024),
+                last_modified: None,
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_matching_if_none_match_returns_not_modified() {
+        let first = json_with_etag(&HeaderMap::new(), &avatars());
+        assert_eq!(first.status(), StatusCode::OK);
+        assert_eq!(first.headers()[CONTENT_TYPE], "application/json");
+        let etag = first.headers()[ETAG].clone();
+        assert!(etag.to_str().unwrap().starts_with("W/\""));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(IF_NONE_MATCH, etag.clone());
+        let second = json_with_etag(&headers, &avatars());
+
+        assert_eq!(second.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(second.headers()[ETAG], etag);
+        let body = to_bytes(second.into_body(), usize::MAX).await.unwrap();
+        assert!(body.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_changed_response_is_sent_in_full() {
+        let first = json_with_etag(&HeaderMap::new(), &avatars());
+        let mut headers = HeaderMap::new();
+        headers.insert(IF_NONE_MATCH, first.headers()[ETAG].clone());
+
+        let changed = json_with_etag(&headers, &ListAvatarsResponse { avatars: vec![] });
+
+        assert_eq!(changed.status(), StatusCode::OK);
+        assert_ne!(changed.headers()[ETAG], first.headers()[ETAG]);
+        let body = to_bytes(changed.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], br#"{"avatars":[]}"#);
+    }
+
+    #[test]
+    fn test_max_age_is_set_on_fresh_and_not_modified_responses() {
+        let first = with_max_age(json_with_etag(&HeaderMap::new(), &avatars()), 120);
+        assert_eq!(first.headers()[CACHE_CONTROL], "private, max-age=120");
+
+        let mut headers = HeaderMap::new();
+        headers.insert(IF_NONE_MATCH, first.headers()[ETAG].clone());
+        let second = with_max_age(json_with_etag(&headers, &avatars()), 120);
+        assert_eq!(second.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(second.headers()[CACHE_CONTROL], "private, max-age=120");
+    }
+
+    #[test]
+    fn test_if_none_match_lists_and_wildcard() {
+        let etag = "W/\"abc\"";
+        let matches = |value: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(IF_NONE_MATCH, HeaderValue::from_static(value));
+            if_none_match_matches(&headers, etag)
+        };
+
+        assert!(matches("\"xyz\", W/\"abc\""));
+        assert!(matches("\"abc\""));
+        assert!(matches("*"));
+        assert!(!matches("W/\"xyz\""));
+    }
+
+    #[test]
+    fn test_unreachable_storage_maps_to_503_with_retry_after() {
+        let response =
+            ApiError::from(FilesError::Unavailable("dispatch failure".to_string())).into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[axum::http::header::RETRY_AFTER], "30");
+
+        // Errors R2 answered with are still ours to fix, not retry
+        let response =
+            ApiError::from(FilesError::List("access denied".to_string())).into_response();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
 }
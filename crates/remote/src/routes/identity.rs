@@ -1,15 +1,26 @@
 use axum::{
     Extension, Json, Router,
     extract::State,
-    http::StatusCode,
+    http::{
+        HeaderMap, HeaderValue, StatusCode,
+        header::{ETAG, IF_MATCH},
+    },
     response::IntoResponse,
     routing::{get, patch},
 };
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use tracing::instrument;
+use url::Url;
 use uuid::Uuid;
 
-use crate::{AppState, auth::RequestContext, db::users::UserRepository};
+use super::error::ApiError;
+use crate::{
+    AppState,
+    auth::RequestContext,
+    db::{identity_errors::IdentityError, users::UserRepository},
+    files::FilesService,
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct IdentityResponse {
@@ -30,58 +41,260 @@ pub struct UpdateAvatarResponse {
 }
 
 pub fn router() -> Router<AppState> {
-    Router::new()
-        .route("/identity", get(get_identity))
-        .route("/identity/avatar", patch(update_avatar))
+    Router::new().route("/identity", get(get_identity)).route(
+        "/identity/avatar",
+        patch(update_avatar).delete(delete_avatar),
+    )
+}
+
+/// The identity's version is its `updated_at`, sent as a strong ETag so
+/// clients can echo it back in `If-Match` on `PATCH /identity/avatar`.
+fn version_etag(updated_at: DateTime<Utc>) -> HeaderValue {
+    HeaderValue::from_str(&format!("\"{}\"", updated_at.timestamp_micros()))
+        .expect("numeric ETag is a valid header value")
 }
 
-#[instrument(name = "identity.get_identity", skip(state, ctx), fields(user_id = %ctx.user.id))]
-pub async fn get_identity(
+/// The `updated_at` named by the request's `If-Match`, or `None` when the
+/// header is absent or `*` and the update should apply unconditionally.
+fn expected_version(headers: &HeaderMap) -> Result<Option<DateTime<Utc>>, ApiError> {
+    let Some(value) = headers.get(IF_MATCH) else {
+        return Ok(None);
+    };
+    let invalid = || ApiError::BadRequest("If-Match must be an ETag from /identity".to_string());
+    let value = value.to_str().map_err(|_| invalid())?.trim();
+    if value == "*" {
+        return Ok(None);
+    }
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .and_then(|micros| micros.parse::<i64>().ok())
+        .and_then(DateTime::from_timestamp_micros)
+        .map(Some)
+        .ok_or_else(invalid)
+}
+
+#[instrument(name = "identity.get_identity", skip(ctx), fields(user_id = %ctx.user.id))]
+pub async fn get_identity(Extension(ctx): Extension<RequestContext>) -> impl IntoResponse {
+    (
+        [(ETAG, version_etag(ctx.user.updated_at))],
+        Json(IdentityResponse {
+            user_id: ctx.user.id,
+            username: ctx.user.username,
+            email: ctx.user.email,
+            avatar_url: ctx.user.avatar_url,
+        }),
+    )
+}
+
+/// Set the avatar. When `If-Match` carries the identity's ETag the update
+/// only applies if nothing else changed the user since, otherwise 409.
+#[instrument(name = "identity.update_avatar", skip(state, ctx, headers), fields(user_id = %ctx.user.id))]
+pub async fn update_avatar(
     State(state): State<AppState>,
     Extension(ctx): Extension<RequestContext>,
-) -> Json<IdentityResponse> {
-    // Fetch the full user with avatar_url using raw query
-    let repo = UserRepository::new(state.pool());
-    let avatar_url = repo
-        .fetch_user_with_avatar(ctx.user.id)
+    headers: HeaderMap,
+    Json(payload): Json<UpdateAvatarRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let expected_updated_at = expected_version(&headers)?;
+    if let Some(url) = payload.avatar_url.as_deref()
+        && !is_allowed_avatar_url(state.files(), &state.config.avatar_allowed_hosts, url)
+    {
+        return Err(ApiError::BadRequest(
+            "avatar_url must point at uploaded files or an allowed host".to_string(),
+        ));
+    }
+
+    let user = UserRepository::new(state.pool())
+        .update_avatar_url(
+            ctx.user.id,
+            payload.avatar_url.as_deref(),
+            expected_updated_at,
+        )
         .await
-        .ok()
-        .and_then(|u| u.avatar_url);
-
-    Json(IdentityResponse {
-        user_id: ctx.user.id,
-        username: ctx.user.username,
-        email: ctx.user.email,
-        avatar_url,
+        .map_err(|e| match e {
+            IdentityError::StaleVersion => ApiError::Conflict(
+                "Profile was changed elsewhere; reload and try again".to_string(),
+            ),
+            e => {
+                tracing::error!(error = %e, "Failed to update avatar");
+                ApiError::Internal("Failed to update avatar".to_string())
+            }
+        })?;
+
+    Ok((
+        [(ETAG, version_etag(user.updated_at))],
+        Json(UpdateAvatarResponse {
+            avatar_url: user.avatar_url,
+        }),
+    ))
+}
+
+/// Avatars must be hosted in our files bucket or, over HTTPS, on one of the
+/// configured `AVATAR_ALLOWED_HOSTS`.
+fn is_allowed_avatar_url(
+    files: Option<&FilesService>,
+    allowed_hosts: &[String],
+    url: &str,
+) -> bool {
+    if files.is_some_and(|files| files.extract_object_key(url).is_some()) {
+        return true;
+    }
+    Url::parse(url).is_ok_and(|url| {
+        url.scheme() == "https"
+            && url
+                .host_str()
+                .is_some_and(|host| allowed_hosts.iter().any(|allowed| allowed == host))
     })
 }
 
-#[instrument(name = "identity.update_avatar", skip(state, ctx), fields(user_id = %ctx.user.id))]
-pub async fn update_avatar(
+/// Object key of `avatar_url` if it is one of this user's uploads in our
+/// bucket. Anything else (external URLs, other users' objects) is left alone.
+fn owned_avatar_key(files: &FilesService, user_id: Uuid, avatar_url: &str) -> Option<String> {
+    files
+        .extract_object_key(avatar_url)
+        .filter(|key| key.starts_with(&format!("avatars/{user_id}/")))
+}
+
+/// Remove the avatar, deleting the stored object when it lives in our bucket
+#[instrument(name = "identity.delete_avatar", skip(state, ctx), fields(user_id = %ctx.user.id))]
+pub async fn delete_avatar(
     State(state): State<AppState>,
     Extension(ctx): Extension<RequestContext>,
-    Json(payload): Json<UpdateAvatarRequest>,
-) -> impl IntoResponse {
-    let repo = UserRepository::new(state.pool());
+) -> Result<StatusCode, ApiError> {
+    let object_key = match (state.files(), ctx.user.avatar_url.as_deref()) {
+        (Some(files), Some(url)) => owned_avatar_key(files, ctx.user.id, url),
+        _ => None,
+    };
 
-    match repo
-        .update_avatar_url(ctx.user.id, payload.avatar_url.as_deref())
+    if let (Some(files), Some(key)) = (state.files(), object_key) {
+        files.delete_file(&key).await.map_err(|e| {
+            tracing::error!(error = %e, object_key = %key, "Failed to delete avatar object");
+            ApiError::Internal("Failed to delete avatar".to_string())
+        })?;
+    }
+
+    UserRepository::new(state.pool())
+        .update_avatar_url(ctx.user.id, None, None)
         .await
-    {
-        Ok(user) => (
-            StatusCode::OK,
-            Json(UpdateAvatarResponse {
-                avatar_url: user.avatar_url,
-            }),
-        )
-            .into_response(),
-        Err(e) => {
-            tracing::error!(error = %e, "Failed to update avatar");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({ "error": "Failed to update avatar" })),
-            )
-                .into_response()
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to clear avatar");
+            ApiError::Internal("Failed to delete avatar".to_string())
+        })?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::test_service;
+
+    #[test]
+    fn test_bucket_avatar_url_is_allowed() {
+        let url = format!(
+            "https://files.example.com/avatars/{}/face.png",
+            Uuid::new_v4()
+        );
+        assert!(is_allowed_avatar_url(Some(&test_service()), &[], &url));
+        // Without files storage nothing is hosted by us
+        assert!(!is_allowed_avatar_url(None, &[], &url));
+    }
+
+    #[test]
+    fn test_disallowed_avatar_host_is_rejected() {
+        let allowed = ["gravatar.com".to_string()];
+        for url in [
+            "https://evil.example.com/face.png",
+            "https://files.example.com.evil.test/avatars/u/face.png",
+            "http://gravatar.com/avatar/abc",
+            "https://gravatar.com.evil.test/avatar/abc",
+            "not a url",
+        ] {
+            assert!(
+                !is_allowed_avatar_url(Some(&test_service()), &allowed, url),
+                "{url}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_allow_listed_avatar_host_is_accepted() {
+        let allowed = ["gravatar.com".to_string(), "www.gravatar.com".to_string()];
+        assert!(is_allowed_avatar_url(
+            None,
+            &allowed,
+            "https://www.gravatar.com/avatar/205e460b479e2e5b48aec07710c08d50"
+        ));
+        assert!(is_allowed_avatar_url(
+            None,
+            &allowed,
+            "https://Gravatar.com/avatar/205e460b479e2e5b48aec07710c08d50"
+        ));
+    }
+
+    #[test]
+    fn test_if_match_round_trips_the_version_etag() {
+        let updated_at = DateTime::from_timestamp_micros(1_767_225_600_123_456).unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(IF_MATCH, version_etag(updated_at));
+
+        assert_eq!(expected_version(&headers).unwrap(), Some(updated_at));
+    }
+
+    #[test]
+    fn test_missing_or_wildcard_if_match_is_unconditional() {
+        assert_eq!(expected_version(&HeaderMap::new()).unwrap(), None);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(IF_MATCH, HeaderValue::from_static("*"));
+        assert_eq!(expected_version(&headers).unwrap(), None);
+    }
+
+    #[test]
+    fn test_malformed_if_match_is_rejected() {
+        for value in ["1767225600123456", "W/\"1767225600123456\"", "\"abc\""] {
+            let mut headers = HeaderMap::new();
+            headers.insert(IF_MATCH, HeaderValue::from_static(value));
+            assert!(
+                matches!(expected_version(&headers), Err(ApiError::BadRequest(_))),
+                "{value}"
+            );
         }
     }
+
+    #[test]
+    fn test_bucket_avatar_is_deleted() {
+        let user_id = Uuid::new_v4();
+        let url = format!("https://files.example.com/avatars/{user_id}/face.png");
+        assert_eq!(
+            owned_avatar_key(&test_service(), user_id, &url),
+            Some(format!("avatars/{user_id}/face.png"))
+        );
+    }
+
+    #[test]
+    fn test_external_avatar_is_not_deleted() {
+        let user_id = Uuid::new_v4();
+        assert_eq!(
+            owned_avatar_key(
+                &test_service(),
+                user_id,
+                "https://www.gravatar.com/avatar/205e460b479e2e5b48aec07710c08d50"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_other_users_object_is_not_deleted() {
+        let url = format!(
+            "https://files.example.com/avatars/{}/face.png",
+            Uuid::new_v4()
+        );
+        assert_eq!(
+            owned_avatar_key(&test_service(), Uuid::new_v4(), &url),
+            None
+        );
+    }
 }
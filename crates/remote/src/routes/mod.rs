@@ -14,6 +14,9 @@ use tracing::{Level, field};
 
 use crate::{AppState, auth::require_session};
 
+const REQUEST_ID_HEADER: &str = "x-request-id";
+
+mod admin;
 mod electric_proxy;
 mod error;
 mod files;
@@ -29,26 +32,6 @@ mod tokens;
 pub(crate) mod workspace_members;
 
 pub fn router(state: AppState) -> Router {
-    let trace_layer = TraceLayer::new_for_http()
-        .make_span_with(|request: &Request<_>| {
-            let request_id = request
-                .extensions()
-                .get::<RequestId>()
-                .and_then(|id| id.header_value().to_str().ok());
-            let span = tracing::info_span!(
-                "http_request",
-                method = %request.method(),
-                uri = %request.uri(),
-                request_id = field::Empty
-            );
-            if let Some(request_id) = request_id {
-                span.record("request_id", field::display(request_id));
-            }
-            span
-        })
-        .on_response(DefaultOnResponse::new().level(Level::INFO))
-        .on_failure(DefaultOnFailure::new().level(Level::ERROR));
-
     let v1_public = Router::<AppState>::new()
         .route("/health", get(health))
         .merge(oauth::public_router())
@@ -56,7 +39,8 @@ pub fn router(state: AppState) -> Router {
         .merge(workspace_members::public_router())
         .merge(tokens::public_router())
         .merge(review::public_router())
-        .merge(github_app::public_router());
+        .merge(github_app::public_router())
+        .merge(admin::router());
 
     let v1_protected = Router::<AppState>::new()
         .merge(identity::router())
@@ -78,7 +62,7 @@ pub fn router(state: AppState) -> Router {
     let spa =
         ServeDir::new(static_dir).fallback(ServeFile::new(format!("{static_dir}/index.html")));
 
-    Router::<AppState>::new()
+    let router = Router::<AppState>::new()
         .nest("/v1", v1_public)
         .nest("/v1", v1_protected)
         .fallback_service(spa)
@@ -89,17 +73,101 @@ pub fn router(state: AppState) -> Router {
                 .allow_headers(AllowHeaders::mirror_request())
                 .allow_credentials(true),
         )
+        .with_state(state);
+
+    with_request_id(router)
+}
+
+/// Tag every request with an `X-Request-Id`, keeping the client's when it
+/// sends one, echo it on the response and record it on the request span so
+/// all log lines for the request share it.
+fn with_request_id(router: Router) -> Router {
+    let trace_layer = TraceLayer::new_for_http()
+        .make_span_with(|request: &Request<_>| {
+            let request_id = request
+                .extensions()
+                .get::<RequestId>()
+                .and_then(|id| id.header_value().to_str().ok());
+            let span = tracing::info_span!(
+                "http_request",
+                method = %request.method(),
+                uri = %request.uri(),
+                request_id = field::Empty
+            );
+            if let Some(request_id) = request_id {
+                span.record("request_id", field::display(request_id));
+            }
+            span
+        })
+        .on_response(DefaultOnResponse::new().level(Level::INFO))
+        .on_failure(DefaultOnFailure::new().level(Level::ERROR));
+
+    router
         .layer(trace_layer)
         .layer(PropagateRequestIdLayer::new(HeaderName::from_static(
-            "x-request-id",
+            REQUEST_ID_HEADER,
         )))
         .layer(SetRequestIdLayer::new(
-            HeaderName::from_static("x-request-id"),
-            MakeRequestUuid {},
+            HeaderName::from_static(REQUEST_ID_HEADER),
+            MakeRequestUuid,
         ))
-        .with_state(state)
 }
 
 async fn health() -> &'static str {
     "ok"
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::{Extension, body::Body};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    /// Echoes the request id seen by handlers.
+    async fn seen_request_id(Extension(request_id): Extension<RequestId>) -> String {
+        request_id.header_value().to_str().unwrap().to_string()
+    }
+
+    fn app() -> Router {
+        with_request_id(Router::new().route("/", get(seen_request_id)))
+    }
+
+    #[tokio::test]
+    async fn test_provided_request_id_is_echoed() {
+        let response = app()
+            .oneshot(
+                Request::builder()
+                    .uri("/")
+                    .header(REQUEST_ID_HEADER, "client-req-42")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "client-req-42");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"client-req-42");
+    }
+
+    #[tokio::test]
+    async fn test_missing_request_id_is_generated() {
+        let response = app()
+            .oneshot(Request::builder().uri("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        let request_id = response.headers()[REQUEST_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert!(uuid::Uuid::parse_str(&request_id).is_ok());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], request_id.as_bytes());
+    }
+}
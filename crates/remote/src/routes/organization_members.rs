@@ -1,17 +1,20 @@
 use axum::{
     Json, Router,
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
     routing::{delete, get, patch, post},
 };
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use tracing::warn;
-use utils::api::organizations::{
-    ListMembersResponse, OrganizationMemberWithProfile, RevokeInvitationRequest,
-    UpdateMemberRoleRequest, UpdateMemberRoleResponse,
+use utils::{
+    api::organizations::{
+        ListMembersResponse, OrganizationMemberWithProfile, RevokeInvitationRequest,
+        UpdateMemberRoleRequest, UpdateMemberRoleResponse,
+    },
+    pagination::{self, Cursor, PageQuery},
 };
 use uuid::Uuid;
 
@@ -27,6 +30,7 @@ use crate::{
         projects::ProjectRepository,
         tasks::SharedTaskRepository,
     },
+    mail,
 };
 
 pub fn public_router() -> Router<AppState> {
@@ -92,9 +96,11 @@ pub async fn create_invitation(
     State(state): State<AppState>,
     axum::extract::Extension(ctx): axum::extract::Extension<RequestContext>,
     Path(org_id): Path<Uuid>,
+    headers: HeaderMap,
     Json(payload): Json<CreateInvitationRequest>,
 ) -> Result<impl IntoResponse, ErrorResponse> {
     let user = ctx.user;
+    let locale = mail::request_locale(&headers, state.config.mail_default_locale);
     let org_repo = OrganizationRepository::new(&state.pool);
     let invitation_repo = InvitationRepository::new(&state.pool);
 
@@ -140,6 +146,7 @@ pub async fn create_invitation(
             &accept_url,
             payload.role,
             user.username.as_deref(),
+            locale,
         )
         .await;
 
@@ -257,26 +264,39 @@ pub async fn accept_invitation(
     }))
 }
 
+/// Members in join order, paged with `?limit=&cursor=`.
 pub async fn list_members(
     State(state): State<AppState>,
     axum::extract::Extension(ctx): axum::extract::Extension<RequestContext>,
     Path(org_id): Path<Uuid>,
+    Query(page): Query<PageQuery>,
 ) -> Result<impl IntoResponse, ErrorResponse> {
     let user = ctx.user;
     ensure_member_access(&state.pool, org_id, user.id).await?;
 
-    let members = sqlx::query_as!(
-        OrganizationMemberWithProfile,
+    let limit = page.limit();
+    let after = page
+        .cursor::<DateTime<Utc>>()
+        .map_err(|e| ErrorResponse::new(StatusCode::BAD_REQUEST, e.to_string()))?;
+    let after_filter = match after {
+        Some(_) => format!(
+            "AND {}",
+            pagination::after_clause("omm.joined_at", "omm.user_id", 3)
+        ),
+        None => String::new(),
+    };
+
+    let sql = format!(
         r#"
         SELECT
-            omm.user_id AS "user_id!: Uuid",
-            omm.role AS "role!: MemberRole",
-            omm.joined_at AS "joined_at!",
-            u.first_name AS "first_name?",
-            u.last_name AS "last_name?",
-            u.username AS "username?",
-            u.email AS "email?",
-            oa.avatar_url AS "avatar_url?"
+            omm.user_id,
+            omm.role,
+            omm.joined_at,
+            u.first_name,
+            u.last_name,
+            u.username,
+            u.email,
+            oa.avatar_url
         FROM organization_member_metadata omm
         INNER JOIN users u ON omm.user_id = u.id
         LEFT JOIN LATERAL (
@@ -287,15 +307,30 @@ pub async fn list_members(
             LIMIT 1
         ) oa ON true
         WHERE omm.organization_id = $1
-        ORDER BY omm.joined_at ASC
-        "#,
-        org_id
-    )
-    .fetch_all(&state.pool)
-    .await
-    .map_err(|_| ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error"))?;
+          {after_filter}
+        ORDER BY omm.joined_at ASC, omm.user_id ASC
+        LIMIT $2
+        "#
+    );
+    let mut query = sqlx::query_as::<_, OrganizationMemberWithProfile>(&sql)
+        .bind(org_id)
+        .bind(limit + 1);
+    if let Some(cursor) = after {
+        query = query.bind(cursor.sort_key).bind(cursor.id);
+    }
+    let rows = query
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|_| ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error"))?;
 
-    Ok(Json(ListMembersResponse { members }))
+    let (members, next_cursor) = pagination::split_page(rows, limit, |member| {
+        Cursor::new(member.joined_at, member.user_id)
+    });
+
+    Ok(Json(ListMembersResponse {
+        members,
+        next_cursor,
+    }))
 }
 
 pub async fn remove_member(
@@ -496,7 +531,7 @@ pub(crate) async fn ensure_member_access(
 ) -> Result<(), ErrorResponse> {
     organization_members::assert_membership(pool, organization_id, user_id)
         .await
-        .map_err(|err| membership_error(err, "Not a member of organization"))
+        .map_err(|err| membership_error(err, "Not a member of organization").into())
 }
 
 pub(crate) async fn ensure_admin_access(
@@ -507,7 +542,7 @@ pub(crate) async fn ensure_admin_access(
     OrganizationRepository::new(pool)
         .assert_admin(organization_id, user_id)
         .await
-        .map_err(|err| membership_error(err, "Admin access required"))
+        .map_err(|err| membership_error(err, "Admin access required").into())
 }
 
 pub(crate) async fn ensure_project_access(
@@ -1,34 +1,50 @@
+use std::{collections::HashMap, time::Duration};
+
 use axum::{
     Json, Router,
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
     routing::{delete, get, patch, post},
 };
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::json;
 use sqlx::{FromRow, PgPool};
 use utils::api::{
-    organizations::MemberRole,
+    organizations::{InvitationStatus, MemberRole},
     workspaces::{
-        AcceptWorkspaceInvitationResponse, GetWorkspaceInvitationResponse,
+        AcceptWorkspaceInvitationResponse, BulkInviteOutcome, BulkInviteResult,
+        BulkInviteWorkspaceMembersRequest, BulkInviteWorkspaceMembersResponse,
+        ExtendWorkspaceInvitationRequest, GetWorkspaceInvitationResponse,
         InviteWorkspaceMemberRequest, InviteWorkspaceMemberResponse,
-        ListWorkspaceInvitationsResponse, ListWorkspaceMembersResponse,
-        RevokeWorkspaceInvitationRequest, UpdateWorkspaceMemberRoleRequest,
-        UpdateWorkspaceMemberRoleResponse, WorkspaceInvitation as ApiWorkspaceInvitation,
-        WorkspaceMemberWithProfile, WorkspacePermission,
+        ListPendingWorkspaceInvitationsResponse, ListWorkspaceInvitationsResponse,
+        ListWorkspaceMembersResponse, PendingWorkspaceInvitation,
+        RevokeInvitationsByInviterRequest, RevokeInvitationsByInviterResponse,
+        RevokeWorkspaceInvitationRequest, TransferWorkspaceOwnershipRequest,
+        TransferWorkspaceOwnershipResponse, UpdateWorkspaceMemberRoleRequest,
+        UpdateWorkspaceMemberRoleResponse, UpdateWorkspaceSettingsRequest,
+        WorkspaceInvitation as ApiWorkspaceInvitation, WorkspaceMemberWithProfile,
+        WorkspacePermission, WorkspaceSettings as ApiWorkspaceSettings,
     },
 };
 use uuid::Uuid;
 
-use super::error::{ErrorResponse, membership_error};
+use super::error::{ApiError, membership_error};
 use crate::{
     AppState,
     auth::RequestContext,
     db::{
         identity_errors::IdentityError,
-        workspace_invitations::WorkspaceInvitationRepository,
-        workspace_members::{self, assert_permission},
+        workspace_invitations::{
+            InvitationLookup, WorkspaceInvitationRepository, assert_can_invite_with_role,
+            invitation_expires_at, partition_bulk_emails, validate_invite_email,
+        },
+        workspace_members::{self, MemberSort, assert_permission, member_search_pattern},
+        workspace_settings,
     },
+    mail,
+    webhooks::{WebhookEvent, WebhookEventKind},
 };
 
 pub fn public_router() -> Router<AppState> {
@@ -38,7 +54,12 @@ pub fn public_router() -> Router<AppState> {
 pub fn protected_router() -> Router<AppState> {
     Router::new()
         .route("/workspaces/{id}/members/invite", post(invite_member))
+        .route(
+            "/workspaces/{id}/members/invite-bulk",
+            post(bulk_invite_members),
+        )
         .route("/workspaces/{id}/members", get(list_members))
+        .route("/workspaces/{id}/members/leave", post(leave_workspace))
         .route(
             "/workspaces/{id}/members/{user_id}",
             delete(remove_member),
@@ -47,6 +68,7 @@ pub fn protected_router() -> Router<AppState> {
             "/workspaces/{id}/members/{user_id}/role",
             patch(update_member_role),
         )
+        .route("/workspaces/{id}/transfer", post(transfer_ownership))
         .route(
             "/workspaces/{id}/invitations",
             get(list_invitations),
@@ -55,10 +77,31 @@ pub fn protected_router() -> Router<AppState> {
             "/workspaces/{id}/invitations/revoke",
             post(revoke_invitation),
         )
+        .route(
+            "/workspaces/{id}/invitations/revoke-by-inviter",
+            post(revoke_invitations_by_inviter),
+        )
+        .route(
+            "/workspaces/{id}/invitations/{invitation_id}/expire",
+            post(expire_invitation),
+        )
+        .route(
+            "/workspaces/{id}/invitations/{invitation_id}/extend",
+            post(extend_invitation),
+        )
         .route(
             "/workspace-invitations/{token}/accept",
             post(accept_invitation),
         )
+        .route(
+            "/workspace-invitations/{token}/decline",
+            post(decline_invitation),
+        )
+        .route("/invitations/pending", get(list_pending_invitations))
+        .route(
+            "/workspaces/{id}/settings",
+            get(get_workspace_settings).put(update_workspace_settings),
+        )
 }
 
 fn to_api_invitation(inv: crate::db::workspace_invitations::WorkspaceInvitation) -> ApiWorkspaceInvitation {
@@ -72,6 +115,8 @@ fn to_api_invitation(inv: crate::db::workspace_invitations::WorkspaceInvitation)
         token: inv.token,
         created_at: inv.created_at,
         expires_at: inv.expires_at,
+        revoked_at: inv.revoked_at,
+        revoked_by_user_id: inv.revoked_by_user_id,
     }
 }
 
@@ -79,9 +124,11 @@ pub async fn invite_member(
     State(state): State<AppState>,
     axum::extract::Extension(ctx): axum::extract::Extension<RequestContext>,
     Path(workspace_id): Path<Uuid>,
+    headers: HeaderMap,
     Json(payload): Json<InviteWorkspaceMemberRequest>,
-) -> Result<impl IntoResponse, ErrorResponse> {
+) -> Result<impl IntoResponse, ApiError> {
     let user = ctx.user;
+    let locale = mail::request_locale(&headers, state.config.mail_default_locale);
     let invitation_repo = WorkspaceInvitationRepository::new(&state.pool);
 
     // Check permission: member.invite
@@ -93,43 +140,72 @@ pub async fn invite_member(
     )
     .await
     .map_err(|e| membership_error(e, "Permission denied: member.invite required"))?;
+    ensure_invite_role_allowed(&state, workspace_id, user.id, payload.role).await?;
+
+    let email = payload.email.trim();
+    validate_invite_email(&state.config.workspace_invitations, email).map_err(|e| match e {
+        IdentityError::InvitationError(msg) => ApiError::BadRequest(msg),
+        _ => ApiError::Internal("Internal server error".to_string()),
+    })?;
 
     let token = Uuid::new_v4().to_string();
-    let expires_at = Utc::now() + Duration::days(7);
+    let expires_at = invitation_expires_at(
+        &state.config.workspace_invitations,
+        payload.expires_in_days,
+        Utc::now(),
+    )
+    .map_err(|e| match e {
+        IdentityError::InvitationError(msg) => ApiError::BadRequest(msg),
+        _ => ApiError::Internal("Internal server error".to_string()),
+    })?;
+
+    state
+        .invite_limiter()
+        .try_acquire(workspace_id, 1)
+        .map_err(invite_rate_limited)?;
 
     let invitation = invitation_repo
         .create_invitation(
             workspace_id,
             user.id,
-            &payload.email,
+            email,
             payload.role,
             expires_at,
             &token,
         )
         .await
+        .inspect_err(|_| state.invite_limiter().release(workspace_id, 1))
         .map_err(|e| match e {
             IdentityError::PermissionDenied => {
-                ErrorResponse::new(StatusCode::FORBIDDEN, "Admin access required")
+                ApiError::Forbidden("Permission denied: member.invite required".to_string())
             }
-            IdentityError::InvitationError(msg) => ErrorResponse::new(StatusCode::BAD_REQUEST, msg),
-            _ => ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error"),
+            IdentityError::InvitationError(msg) => ApiError::BadRequest(msg),
+            _ => ApiError::Internal("Database error".to_string()),
         })?;
 
-    // Send invitation email
-    let accept_url = format!(
-        "{}/workspace-invitations/{}/accept",
-        state.server_public_base_url, token
+    spawn_invitation_email(
+        &state,
+        workspace_id,
+        email.to_string(),
+        &token,
+        payload.role,
+        user.username.clone(),
+        locale,
     );
-    state
-        .mailer
-        .send_workspace_invitation(
+
+    state.emit_webhook(
+        WebhookEvent::new(
+            WebhookEventKind::MemberInvited,
             workspace_id,
-            &payload.email,
-            &accept_url,
-            payload.role,
-            user.username.as_deref(),
+            user.id,
+            json!({
+                "invitation_id": invitation.id,
+                "email": invitation.email,
+                "role": invitation.role,
+            }),
         )
-        .await;
+        .with_request_id(ctx.request_id.clone()),
+    );
 
     Ok((
         StatusCode::CREATED,
@@ -139,11 +215,196 @@ pub async fn invite_member(
     ))
 }
 
+/// 403 unless the inviter's own role is at least `role`, per the
+/// `limit_role_to_inviter` policy.
+async fn ensure_invite_role_allowed(
+    state: &AppState,
+    workspace_id: Uuid,
+    inviter_user_id: Uuid,
+    role: MemberRole,
+) -> Result<(), ApiError> {
+    assert_can_invite_with_role(
+        &state.pool,
+        &state.config.workspace_invitations,
+        workspace_id,
+        inviter_user_id,
+        role,
+    )
+    .await
+    .map_err(|e| membership_error(e, "Cannot invite with a role above your own"))
+}
+
+/// Send the invitation email in the background. The mailer retries with
+/// backoff, which mustn't hold up the response.
+fn spawn_invitation_email(
+    state: &AppState,
+    workspace_id: Uuid,
+    email: String,
+    token: &str,
+    role: MemberRole,
+    invited_by: Option<String>,
+    locale: mail::Locale,
+) {
+    let mailer = state.mailer.clone();
+    let accept_url = format!(
+        "{}/workspace-invitations/{}/accept",
+        state.server_public_base_url, token
+    );
+    tokio::spawn(async move {
+        mailer
+            .send_workspace_invitation(
+                workspace_id,
+                &email,
+                &accept_url,
+                role,
+                invited_by.as_deref(),
+                locale,
+            )
+            .await;
+    });
+}
+
+fn invite_rate_limited(retry_after: Duration) -> ApiError {
+    ApiError::TooManyRequests {
+        message: "Too many invitations sent from this workspace, try again later".to_string(),
+        retry_after,
+    }
+}
+
+pub async fn bulk_invite_members(
+    State(state): State<AppState>,
+    axum::extract::Extension(ctx): axum::extract::Extension<RequestContext>,
+    Path(workspace_id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(payload): Json<BulkInviteWorkspaceMembersRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let user = ctx.user;
+    let locale = mail::request_locale(&headers, state.config.mail_default_locale);
+    let invitation_repo = WorkspaceInvitationRepository::new(&state.pool);
+
+    // Check permission: member.invite
+    assert_permission(
+        &state.pool,
+        workspace_id,
+        user.id,
+        WorkspacePermission::MemberInvite,
+    )
+    .await
+    .map_err(|e| membership_error(e, "Permission denied: member.invite required"))?;
+    ensure_invite_role_allowed(&state, workspace_id, user.id, payload.role).await?;
+
+    let (emails, invalid) = partition_bulk_emails(&payload.emails);
+    let (emails, blocked): (Vec<_>, Vec<_>) = emails
+        .into_iter()
+        .map(|email| {
+            let check = validate_invite_email(&state.config.workspace_invitations, &email);
+            (email, check)
+        })
+        .partition(|(_, check)| check.is_ok());
+    let invitees: Vec<(String, String)> = emails
+        .into_iter()
+        .map(|(email, _)| (email, Uuid::new_v4().to_string()))
+        .collect();
+    let expires_at = invitation_expires_at(
+        &state.config.workspace_invitations,
+        payload.expires_in_days,
+        Utc::now(),
+    )
+    .map_err(|e| match e {
+        IdentityError::InvitationError(msg) => ApiError::BadRequest(msg),
+        _ => ApiError::Internal("Internal server error".to_string()),
+    })?;
+
+    let reserved = u32::try_from(invitees.len()).unwrap_or(u32::MAX);
+    // Waiting would never free up enough invites for this batch
+    if let Some(capacity) = state.invite_limiter().capacity()
+        && reserved > capacity
+    {
+        return Err(ApiError::BadRequest(format!(
+            "A workspace can send at most {capacity} invitations per hour"
+        )));
+    }
+    state
+        .invite_limiter()
+        .try_acquire(workspace_id, reserved)
+        .map_err(invite_rate_limited)?;
+
+    let created = invitation_repo
+        .create_invitations(workspace_id, user.id, &invitees, payload.role, expires_at)
+        .await
+        .inspect_err(|_| state.invite_limiter().release(workspace_id, reserved))
+        .map_err(|e| match e {
+            IdentityError::PermissionDenied => {
+                ApiError::Forbidden("Permission denied: member.invite required".to_string())
+            }
+            _ => ApiError::Internal("Database error".to_string()),
+        })?;
+
+    let mut results = Vec::with_capacity(invitees.len() + invalid.len());
+    for ((email, token), invitation) in invitees.into_iter().zip(created) {
+        spawn_invitation_email(
+            &state,
+            workspace_id,
+            email.clone(),
+            &token,
+            payload.role,
+            user.username.clone(),
+            locale,
+        );
+
+        state.emit_webhook(
+            WebhookEvent::new(
+                WebhookEventKind::MemberInvited,
+                workspace_id,
+                user.id,
+                json!({
+                    "invitation_id": invitation.id,
+                    "email": invitation.email,
+                    "role": invitation.role,
+                }),
+            )
+            .with_request_id(ctx.request_id.clone()),
+        );
+
+        results.push(BulkInviteResult {
+            email,
+            outcome: BulkInviteOutcome::Created,
+            invitation: Some(to_api_invitation(invitation)),
+            error: None,
+        });
+    }
+    results.extend(invalid.into_iter().map(|email| BulkInviteResult {
+        email,
+        outcome: BulkInviteOutcome::Invalid,
+        invitation: None,
+        error: Some("Invalid email address".to_string()),
+    }));
+    results.extend(blocked.into_iter().filter_map(|(email, check)| {
+        let error = match check.err()? {
+            IdentityError::InvitationError(msg) => msg,
+            other => other.to_string(),
+        };
+        Some(BulkInviteResult {
+            email,
+            outcome: BulkInviteOutcome::Invalid,
+            invitation: None,
+            error: Some(error),
+        })
+    }));
+
+    Ok((
+        StatusCode::OK,
+        Json(BulkInviteWorkspaceMembersResponse { results }),
+    ))
+}
+
 #[derive(Debug, FromRow)]
 struct MemberRow {
     workspace_id: Uuid,
     user_id: Uuid,
     role: MemberRole,
+    /// Explicit grants on top of the role's defaults
+    permissions: Vec<WorkspacePermission>,
     joined_at: DateTime<Utc>,
     first_name: Option<String>,
     last_name: Option<String>,
@@ -152,74 +413,206 @@ struct MemberRow {
     avatar_url: Option<String>,
 }
 
-pub async fn list_members(
-    State(state): State<AppState>,
-    axum::extract::Extension(ctx): axum::extract::Extension<RequestContext>,
-    Path(workspace_id): Path<Uuid>,
-) -> Result<impl IntoResponse, ErrorResponse> {
-    let user = ctx.user;
-    ensure_member_access(&state.pool, workspace_id, user.id).await?;
+const MAX_MEMBER_PAGE_SIZE: i64 = 200;
+
+/// Matches `$2` (an ILIKE pattern, or NULL for no search) against the
+/// member's email, username and name.
+const MEMBER_SEARCH_FILTER: &str = r#"(
+            $2::text IS NULL
+            OR u.email ILIKE $2
+            OR u.username ILIKE $2
+            OR u.first_name ILIKE $2
+            OR u.last_name ILIKE $2
+            OR concat_ws(' ', u.first_name, u.last_name) ILIKE $2
+        )"#;
+
+const DEFAULT_INVITATION_PAGE_SIZE: i64 = 50;
+const MAX_INVITATION_PAGE_SIZE: i64 = 200;
+
+#[derive(Debug, Deserialize)]
+pub struct ListInvitationsQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    /// Defaults to pending invitations
+    pub status: Option<InvitationStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListMembersQuery {
+    /// Omitted returns every member, as before the listing was paginated
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    pub search: Option<String>,
+    pub sort: Option<MemberSort>,
+}
 
-    let rows: Vec<MemberRow> = sqlx::query_as(
+/// One page of members with their profile and granted permissions, which
+/// live on the membership row so no per-member lookup is needed. A `None`
+/// limit returns every member from `offset` on.
+async fn fetch_member_rows(
+    pool: &PgPool,
+    workspace_id: Uuid,
+    search: Option<&str>,
+    sort: MemberSort,
+    limit: Option<i64>,
+    offset: i64,
+) -> Result<Vec<MemberRow>, sqlx::Error> {
+    sqlx::query_as(&format!(
         r#"
         SELECT
             wmm.workspace_id,
             wmm.user_id,
             wmm.role,
+            wmm.permissions,
             wmm.joined_at,
             u.first_name,
             u.last_name,
             u.username,
             u.email,
-            oa.avatar_url
+            u.avatar_url
         FROM workspace_member_metadata wmm
         INNER JOIN users u ON wmm.user_id = u.id
-        LEFT JOIN LATERAL (
-            SELECT avatar_url
-            FROM oauth_accounts
-            WHERE user_id = wmm.user_id
-            ORDER BY created_at ASC
-            LIMIT 1
-        ) oa ON true
         WHERE wmm.workspace_id = $1
-        ORDER BY wmm.joined_at ASC
+          AND {MEMBER_SEARCH_FILTER}
+        ORDER BY {order_by}
+        LIMIT $3 OFFSET $4
+        "#,
+        order_by = sort.order_by(),
+    ))
+    .bind(workspace_id)
+    .bind(search)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await
+}
+
+/// Provider avatar for each user, taken from their oldest OAuth account that
+/// has one.
+async fn fetch_oauth_avatars(
+    pool: &PgPool,
+    user_ids: &[Uuid],
+) -> Result<HashMap<Uuid, String>, sqlx::Error> {
+    let rows: Vec<(Uuid, String)> = sqlx::query_as(
+        r#"
+        SELECT DISTINCT ON (user_id) user_id, avatar_url
+        FROM oauth_accounts
+        WHERE user_id = ANY($1)
+          AND avatar_url IS NOT NULL
+        ORDER BY user_id, created_at ASC
         "#,
     )
+    .bind(user_ids)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().collect())
+}
+
+/// An uploaded avatar wins; otherwise fall back to the OAuth provider's.
+fn member_with_profile(
+    row: MemberRow,
+    oauth_avatars: &HashMap<Uuid, String>,
+) -> WorkspaceMemberWithProfile {
+    let avatar_url = row
+        .avatar_url
+        .or_else(|| oauth_avatars.get(&row.user_id).cloned());
+    // Effective permissions: the role's defaults, then any explicit grants
+    let mut permissions = workspace_members::default_permissions(row.role).to_vec();
+    for permission in row.permissions {
+        if !permissions.contains(&permission) {
+            permissions.push(permission);
+        }
+    }
+    WorkspaceMemberWithProfile {
+        workspace_id: row.workspace_id,
+        user_id: row.user_id,
+        role: row.role,
+        permissions,
+        joined_at: row.joined_at,
+        first_name: row.first_name,
+        last_name: row.last_name,
+        username: row.username,
+        email: row.email,
+        avatar_url,
+    }
+}
+
+pub async fn list_members(
+    State(state): State<AppState>,
+    axum::extract::Extension(ctx): axum::extract::Extension<RequestContext>,
+    Path(workspace_id): Path<Uuid>,
+    Query(params): Query<ListMembersQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let user = ctx.user;
+    ensure_member_access(&state.pool, workspace_id, user.id).await?;
+
+    let limit = params
+        .limit
+        .map(|limit| limit.clamp(1, MAX_MEMBER_PAGE_SIZE));
+    let offset = params.offset.unwrap_or(0).max(0);
+    let search = member_search_pattern(params.search.as_deref());
+    let sort = params.sort.unwrap_or_default();
+
+    let total: i64 = sqlx::query_scalar(&format!(
+        r#"
+        SELECT COUNT(*)
+        FROM workspace_member_metadata wmm
+        INNER JOIN users u ON wmm.user_id = u.id
+        WHERE wmm.workspace_id = $1
+          AND {MEMBER_SEARCH_FILTER}
+        "#
+    ))
     .bind(workspace_id)
-    .fetch_all(&state.pool)
+    .bind(&search)
+    .fetch_one(&state.pool)
     .await
-    .map_err(|_| ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error"))?;
+    .map_err(|_| ApiError::Internal("Database error".to_string()))?;
+
+    let rows = fetch_member_rows(
+        &state.pool,
+        workspace_id,
+        search.as_deref(),
+        sort,
+        limit,
+        offset,
+    )
+    .await
+    .map_err(|_| ApiError::Internal("Database error".to_string()))?;
+
+    // Avatars are best effort: a failed lookup must not hide the members
+    let missing_avatars: Vec<Uuid> = rows
+        .iter()
+        .filter(|row| row.avatar_url.is_none())
+        .map(|row| row.user_id)
+        .collect();
+    let oauth_avatars = if missing_avatars.is_empty() {
+        HashMap::new()
+    } else {
+        fetch_oauth_avatars(&state.pool, &missing_avatars)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!(?e, %workspace_id, "Failed to resolve member avatars");
+                HashMap::new()
+            })
+    };
 
     let members: Vec<WorkspaceMemberWithProfile> = rows
         .into_iter()
-        .map(|row| WorkspaceMemberWithProfile {
-            workspace_id: row.workspace_id,
-            user_id: row.user_id,
-            role: row.role,
-            permissions: vec![], // Permissions loaded separately if needed
-            joined_at: row.joined_at,
-            first_name: row.first_name,
-            last_name: row.last_name,
-            username: row.username,
-            email: row.email,
-            avatar_url: row.avatar_url,
-        })
+        .map(|row| member_with_profile(row, &oauth_avatars))
         .collect();
 
-    Ok(Json(ListWorkspaceMembersResponse { members }))
+    Ok(Json(ListWorkspaceMembersResponse { members, total }))
 }
 
 pub async fn remove_member(
     State(state): State<AppState>,
     axum::extract::Extension(ctx): axum::extract::Extension<RequestContext>,
     Path((workspace_id, user_id)): Path<(Uuid, Uuid)>,
-) -> Result<impl IntoResponse, ErrorResponse> {
+) -> Result<impl IntoResponse, ApiError> {
     let user = ctx.user;
     if user.id == user_id {
-        return Err(ErrorResponse::new(
-            StatusCode::BAD_REQUEST,
-            "Cannot remove yourself",
-        ));
+        return Err(ApiError::BadRequest("Cannot remove yourself".to_string()));
     }
 
     // Check permission: member.remove
@@ -236,7 +629,7 @@ pub async fn remove_member(
         .pool
         .begin()
         .await
-        .map_err(|_| ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error"))?;
+        .map_err(|_| ApiError::Internal("Database error".to_string()))?;
 
     let target_role: Option<MemberRole> = sqlx::query_scalar(
         r#"
@@ -250,10 +643,10 @@ pub async fn remove_member(
     .bind(user_id)
     .fetch_optional(&mut *tx)
     .await
-    .map_err(|_| ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error"))?;
+    .map_err(|_| ApiError::Internal("Database error".to_string()))?;
 
-    let target_role = target_role
-        .ok_or_else(|| ErrorResponse::new(StatusCode::NOT_FOUND, "Member not found"))?;
+    let target_role =
+        target_role.ok_or_else(|| ApiError::NotFound("Member not found".to_string()))?;
 
     if target_role == MemberRole::Admin {
         let admin_ids: Vec<Uuid> = sqlx::query_scalar(
@@ -267,12 +660,11 @@ pub async fn remove_member(
         .bind(workspace_id)
         .fetch_all(&mut *tx)
         .await
-        .map_err(|_| ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error"))?;
+        .map_err(|_| ApiError::Internal("Database error".to_string()))?;
 
         if admin_ids.len() == 1 && admin_ids[0] == user_id {
-            return Err(ErrorResponse::new(
-                StatusCode::CONFLICT,
-                "Cannot remove the last admin",
+            return Err(ApiError::Conflict(
+                "Cannot remove the last admin".to_string(),
             ));
         }
     }
@@ -287,11 +679,53 @@ pub async fn remove_member(
     .bind(user_id)
     .execute(&mut *tx)
     .await
-    .map_err(|_| ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error"))?;
+    .map_err(|_| ApiError::Internal("Database error".to_string()))?;
 
     tx.commit()
         .await
-        .map_err(|_| ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error"))?;
+        .map_err(|_| ApiError::Internal("Database error".to_string()))?;
+
+    state.emit_webhook(
+        WebhookEvent::new(
+            WebhookEventKind::MemberRemoved,
+            workspace_id,
+            user.id,
+            json!({ "user_id": user_id, "role": target_role, "reason": "removed" }),
+        )
+        .with_request_id(ctx.request_id.clone()),
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn leave_workspace(
+    State(state): State<AppState>,
+    axum::extract::Extension(ctx): axum::extract::Extension<RequestContext>,
+    Path(workspace_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    let user = ctx.user;
+
+    workspace_members::leave_workspace(&state.pool, workspace_id, user.id)
+        .await
+        .map_err(|e| match e {
+            IdentityError::NotFound => {
+                ApiError::NotFound("Not a member of this workspace".to_string())
+            }
+            IdentityError::LastAdmin => ApiError::Conflict(
+                "You are the last admin; transfer ownership before leaving".to_string(),
+            ),
+            _ => ApiError::Internal("Database error".to_string()),
+        })?;
+
+    state.emit_webhook(
+        WebhookEvent::new(
+            WebhookEventKind::MemberRemoved,
+            workspace_id,
+            user.id,
+            json!({ "user_id": user.id, "reason": "left" }),
+        )
+        .with_request_id(ctx.request_id.clone()),
+    );
 
     Ok(StatusCode::NO_CONTENT)
 }
@@ -301,13 +735,10 @@ pub async fn update_member_role(
     axum::extract::Extension(ctx): axum::extract::Extension<RequestContext>,
     Path((workspace_id, user_id)): Path<(Uuid, Uuid)>,
     Json(payload): Json<UpdateWorkspaceMemberRoleRequest>,
-) -> Result<impl IntoResponse, ErrorResponse> {
+) -> Result<impl IntoResponse, ApiError> {
     let user = ctx.user;
     if user.id == user_id && payload.role == MemberRole::Member {
-        return Err(ErrorResponse::new(
-            StatusCode::BAD_REQUEST,
-            "Cannot demote yourself",
-        ));
+        return Err(ApiError::BadRequest("Cannot demote yourself".to_string()));
     }
 
     // Check permission: member.role.change
@@ -324,7 +755,7 @@ pub async fn update_member_role(
         .pool
         .begin()
         .await
-        .map_err(|_| ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error"))?;
+        .map_err(|_| ApiError::Internal("Database error".to_string()))?;
 
     let target_role: Option<MemberRole> = sqlx::query_scalar(
         r#"
@@ -338,10 +769,10 @@ pub async fn update_member_role(
     .bind(user_id)
     .fetch_optional(&mut *tx)
     .await
-    .map_err(|_| ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error"))?;
+    .map_err(|_| ApiError::Internal("Database error".to_string()))?;
 
-    let target_role = target_role
-        .ok_or_else(|| ErrorResponse::new(StatusCode::NOT_FOUND, "Member not found"))?;
+    let target_role =
+        target_role.ok_or_else(|| ApiError::NotFound("Member not found".to_string()))?;
 
     if target_role == payload.role {
         return Ok(Json(UpdateWorkspaceMemberRoleResponse {
@@ -362,12 +793,11 @@ pub async fn update_member_role(
         .bind(workspace_id)
         .fetch_all(&mut *tx)
         .await
-        .map_err(|_| ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error"))?;
+        .map_err(|_| ApiError::Internal("Database error".to_string()))?;
 
         if admin_ids.len() == 1 && admin_ids[0] == user_id {
-            return Err(ErrorResponse::new(
-                StatusCode::CONFLICT,
-                "Cannot demote the last admin",
+            return Err(ApiError::Conflict(
+                "Cannot demote the last admin".to_string(),
             ));
         }
     }
@@ -384,11 +814,25 @@ pub async fn update_member_role(
     .bind(payload.role)
     .execute(&mut *tx)
     .await
-    .map_err(|_| ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error"))?;
+    .map_err(|_| ApiError::Internal("Database error".to_string()))?;
 
     tx.commit()
         .await
-        .map_err(|_| ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error"))?;
+        .map_err(|_| ApiError::Internal("Database error".to_string()))?;
+
+    state.emit_webhook(
+        WebhookEvent::new(
+            WebhookEventKind::MemberRoleChanged,
+            workspace_id,
+            user.id,
+            json!({
+                "user_id": user_id,
+                "previous_role": target_role,
+                "role": payload.role,
+            }),
+        )
+        .with_request_id(ctx.request_id.clone()),
+    );
 
     Ok(Json(UpdateWorkspaceMemberRoleResponse {
         user_id,
@@ -396,11 +840,46 @@ pub async fn update_member_role(
     }))
 }
 
+pub async fn transfer_ownership(
+    State(state): State<AppState>,
+    axum::extract::Extension(ctx): axum::extract::Extension<RequestContext>,
+    Path(workspace_id): Path<Uuid>,
+    Json(payload): Json<TransferWorkspaceOwnershipRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let user = ctx.user;
+    let demote_self = payload.demote_self.unwrap_or(false);
+
+    workspace_members::transfer_ownership(
+        &state.pool,
+        workspace_id,
+        user.id,
+        payload.new_owner_user_id,
+        demote_self,
+    )
+    .await
+    .map_err(|e| match e {
+        IdentityError::PermissionDenied => ApiError::Forbidden("Admin access required".to_string()),
+        IdentityError::InvalidTransfer(msg) => ApiError::BadRequest(msg),
+        _ => ApiError::Internal("Database error".to_string()),
+    })?;
+
+    Ok(Json(TransferWorkspaceOwnershipResponse {
+        workspace_id,
+        new_owner_user_id: payload.new_owner_user_id,
+        previous_owner_role: if demote_self {
+            MemberRole::Member
+        } else {
+            MemberRole::Admin
+        },
+    }))
+}
+
 pub async fn list_invitations(
     State(state): State<AppState>,
     axum::extract::Extension(ctx): axum::extract::Extension<RequestContext>,
     Path(workspace_id): Path<Uuid>,
-) -> Result<impl IntoResponse, ErrorResponse> {
+    Query(params): Query<ListInvitationsQuery>,
+) -> Result<impl IntoResponse, ApiError> {
     let user = ctx.user;
     let invitation_repo = WorkspaceInvitationRepository::new(&state.pool);
 
@@ -408,39 +887,58 @@ pub async fn list_invitations(
         .await
         .map_err(|e| membership_error(e, "Admin access required"))?;
 
-    let invitations = invitation_repo
-        .list_invitations(workspace_id, user.id)
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_INVITATION_PAGE_SIZE)
+        .clamp(1, MAX_INVITATION_PAGE_SIZE);
+    let offset = params.offset.unwrap_or(0).max(0);
+    let status = params.status.unwrap_or(InvitationStatus::Pending);
+
+    let page = invitation_repo
+        .list_invitations(workspace_id, user.id, status, limit, offset)
         .await
         .map_err(|e| match e {
             IdentityError::PermissionDenied => {
-                ErrorResponse::new(StatusCode::FORBIDDEN, "Admin access required")
+                ApiError::Forbidden("Admin access required".to_string())
             }
-            IdentityError::InvitationError(msg) => ErrorResponse::new(StatusCode::BAD_REQUEST, msg),
-            _ => ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error"),
+            IdentityError::InvitationError(msg) => ApiError::BadRequest(msg),
+            _ => ApiError::Internal("Database error".to_string()),
         })?;
 
     Ok(Json(ListWorkspaceInvitationsResponse {
-        invitations: invitations.into_iter().map(to_api_invitation).collect(),
+        invitations: page
+            .invitations
+            .into_iter()
+            .map(to_api_invitation)
+            .collect(),
+        total: page.total,
     }))
 }
 
 pub async fn get_invitation(
     State(state): State<AppState>,
     Path(token): Path<String>,
-) -> Result<impl IntoResponse, ErrorResponse> {
+) -> Result<impl IntoResponse, ApiError> {
     let invitation_repo = WorkspaceInvitationRepository::new(&state.pool);
 
     let invitation = invitation_repo
         .get_invitation_by_token(&token)
         .await
-        .map_err(|_| ErrorResponse::new(StatusCode::NOT_FOUND, "Invitation not found"))?;
+        .map_err(|_| ApiError::NotFound("Invitation not found".to_string()))?;
 
-    Ok(Json(GetWorkspaceInvitationResponse {
-        id: invitation.id,
-        workspace_id: invitation.workspace_id,
-        role: invitation.role,
-        expires_at: invitation.expires_at,
-    }))
+    Ok(Json(public_invitation(invitation)))
+}
+
+/// What anyone holding the token may see: no invitee email and nothing about
+/// other members.
+fn public_invitation(lookup: InvitationLookup) -> GetWorkspaceInvitationResponse {
+    GetWorkspaceInvitationResponse {
+        id: lookup.invitation.id,
+        workspace_id: lookup.invitation.workspace_id,
+        role: lookup.invitation.role,
+        expires_at: lookup.invitation.expires_at,
+        invited_by_name: lookup.invited_by_name,
+    }
 }
 
 pub async fn revoke_invitation(
@@ -448,7 +946,7 @@ pub async fn revoke_invitation(
     axum::extract::Extension(ctx): axum::extract::Extension<RequestContext>,
     Path(workspace_id): Path<Uuid>,
     Json(payload): Json<RevokeWorkspaceInvitationRequest>,
-) -> Result<impl IntoResponse, ErrorResponse> {
+) -> Result<impl IntoResponse, ApiError> {
     let user = ctx.user;
     let invitation_repo = WorkspaceInvitationRepository::new(&state.pool);
 
@@ -461,45 +959,481 @@ pub async fn revoke_invitation(
         .await
         .map_err(|e| match e {
             IdentityError::PermissionDenied => {
-                ErrorResponse::new(StatusCode::FORBIDDEN, "Admin access required")
+                ApiError::Forbidden("Admin access required".to_string())
+            }
+            IdentityError::NotFound => ApiError::NotFound("Invitation not found".to_string()),
+            _ => ApiError::Internal("Database error".to_string()),
+        })?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Revoke the pending invitations a departing member sent, so their links
+/// stop working once the member is removed.
+pub async fn revoke_invitations_by_inviter(
+    State(state): State<AppState>,
+    axum::extract::Extension(ctx): axum::extract::Extension<RequestContext>,
+    Path(workspace_id): Path<Uuid>,
+    Json(payload): Json<RevokeInvitationsByInviterRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let user = ctx.user;
+    let invitation_repo = WorkspaceInvitationRepository::new(&state.pool);
+
+    assert_permission(
+        &state.pool,
+        workspace_id,
+        user.id,
+        WorkspacePermission::MemberInvite,
+    )
+    .await
+    .map_err(|e| membership_error(e, "Permission denied: member.invite required"))?;
+
+    let revoked = invitation_repo
+        .revoke_pending_by_inviter(workspace_id, payload.inviter_user_id, user.id)
+        .await
+        .map_err(|_| ApiError::Internal("Database error".to_string()))?;
+
+    Ok(Json(RevokeInvitationsByInviterResponse { revoked }))
+}
+
+pub async fn expire_invitation(
+    State(state): State<AppState>,
+    axum::extract::Extension(ctx): axum::extract::Extension<RequestContext>,
+    Path((workspace_id, invitation_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, ApiError> {
+    let user = ctx.user;
+    let invitation_repo = WorkspaceInvitationRepository::new(&state.pool);
+
+    // The repository checks that the caller is an admin
+    let invitation = invitation_repo
+        .expire_invitation(workspace_id, invitation_id, user.id)
+        .await
+        .map_err(|e| match e {
+            IdentityError::PermissionDenied => {
+                ApiError::Forbidden("Admin access required".to_string())
             }
             IdentityError::NotFound => {
-                ErrorResponse::new(StatusCode::NOT_FOUND, "Invitation not found")
+                ApiError::NotFound("Pending invitation not found".to_string())
             }
-            _ => ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error"),
+            IdentityError::InvitationError(msg) => ApiError::Conflict(msg),
+            _ => ApiError::Internal("Database error".to_string()),
         })?;
 
-    Ok(StatusCode::NO_CONTENT)
+    Ok(Json(to_api_invitation(invitation)))
+}
+
+/// Push back a pending invitation's expiry without changing its token, so
+/// links that were already sent keep working.
+pub async fn extend_invitation(
+    State(state): State<AppState>,
+    axum::extract::Extension(ctx): axum::extract::Extension<RequestContext>,
+    Path((workspace_id, invitation_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<ExtendWorkspaceInvitationRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let user = ctx.user;
+    let invitation_repo = WorkspaceInvitationRepository::new(&state.pool);
+
+    assert_permission(
+        &state.pool,
+        workspace_id,
+        user.id,
+        WorkspacePermission::MemberInvite,
+    )
+    .await
+    .map_err(|e| membership_error(e, "Permission denied: member.invite required"))?;
+
+    let max_days = state
+        .config
+        .workspace_invitations
+        .max_invitation_expiry_days;
+    if !(1..=max_days).contains(&payload.additional_days) {
+        return Err(ApiError::BadRequest(format!(
+            "additional_days must be between 1 and {max_days}"
+        )));
+    }
+
+    let invitation = invitation_repo
+        .extend_expiry(workspace_id, invitation_id, payload.additional_days)
+        .await
+        .map_err(|e| match e {
+            IdentityError::NotFound => ApiError::NotFound("Invitation not found".to_string()),
+            IdentityError::InvitationError(msg) => ApiError::Conflict(msg),
+            _ => ApiError::Internal("Database error".to_string()),
+        })?;
+
+    Ok(Json(to_api_invitation(invitation)))
 }
 
 pub async fn accept_invitation(
     State(state): State<AppState>,
     axum::extract::Extension(ctx): axum::extract::Extension<RequestContext>,
     Path(token): Path<String>,
-) -> Result<impl IntoResponse, ErrorResponse> {
+) -> Result<impl IntoResponse, ApiError> {
     let user = ctx.user;
     let invitation_repo = WorkspaceInvitationRepository::new(&state.pool);
 
     let (workspace_id, role) = invitation_repo
-        .accept_invitation(&token, user.id)
+        .accept_invitation(
+            &token,
+            user.id,
+            &user.email,
+            &state.config.workspace_invitations,
+        )
         .await
         .map_err(|e| match e {
-            IdentityError::InvitationError(msg) => ErrorResponse::new(StatusCode::BAD_REQUEST, msg),
-            IdentityError::NotFound => {
-                ErrorResponse::new(StatusCode::NOT_FOUND, "Invitation not found")
+            IdentityError::InvitationError(msg) => ApiError::BadRequest(msg),
+            IdentityError::WorkspaceUnavailable => {
+                ApiError::Conflict("This workspace is no longer available".to_string())
             }
-            _ => ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Database error"),
+            IdentityError::InvitationNotYetActive(active_at) => ApiError::Conflict(format!(
+                "Invitation is not yet active; try again after {active_at}"
+            )),
+            IdentityError::NotFound => ApiError::NotFound("Invitation not found".to_string()),
+            _ => ApiError::Internal("Database error".to_string()),
+        })?;
+
+    state.emit_webhook(
+        WebhookEvent::new(
+            WebhookEventKind::MemberJoined,
+            workspace_id,
+            user.id,
+            json!({ "user_id": user.id, "role": role }),
+        )
+        .with_request_id(ctx.request_id.clone()),
+    );
+
+    Ok(Json(AcceptWorkspaceInvitationResponse {
+        workspace_id,
+        role,
+    }))
+}
+
+pub async fn decline_invitation(
+    State(state): State<AppState>,
+    axum::extract::Extension(ctx): axum::extract::Extension<RequestContext>,
+    Path(token): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let user = ctx.user;
+    let invitation_repo = WorkspaceInvitationRepository::new(&state.pool);
+
+    invitation_repo
+        .decline_invitation(&token, &user.email, &state.config.workspace_invitations)
+        .await
+        .map_err(|e| match e {
+            IdentityError::NotFound => ApiError::NotFound("Invitation not found".to_string()),
+            IdentityError::InvitationError(msg) => ApiError::BadRequest(msg),
+            _ => ApiError::Internal("Database error".to_string()),
         })?;
 
-    Ok(Json(AcceptWorkspaceInvitationResponse { workspace_id, role }))
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn list_pending_invitations(
+    State(state): State<AppState>,
+    axum::extract::Extension(ctx): axum::extract::Extension<RequestContext>,
+) -> Result<impl IntoResponse, ApiError> {
+    let user = ctx.user;
+    let invitation_repo = WorkspaceInvitationRepository::new(&state.pool);
+
+    let invitations = invitation_repo
+        .list_for_email(&user.email)
+        .await
+        .map_err(|_| ApiError::Internal("Database error".to_string()))?;
+
+    let invitations = invitations
+        .into_iter()
+        .map(|inv| PendingWorkspaceInvitation {
+            id: inv.id,
+            workspace_id: inv.workspace_id,
+            role: inv.role,
+            token: inv.token,
+            invited_by_username: inv.invited_by_username,
+            created_at: inv.created_at,
+            expires_at: inv.expires_at,
+        })
+        .collect();
+
+    Ok(Json(ListPendingWorkspaceInvitationsResponse {
+        invitations,
+    }))
+}
+
+pub async fn get_workspace_settings(
+    State(state): State<AppState>,
+    axum::extract::Extension(ctx): axum::extract::Extension<RequestContext>,
+    Path(workspace_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ApiError> {
+    let user = ctx.user;
+
+    workspace_members::assert_admin(&state.pool, workspace_id, user.id)
+        .await
+        .map_err(|e| membership_error(e, "Admin access required"))?;
+
+    let settings = workspace_settings::get_settings(&state.pool, workspace_id)
+        .await
+        .map_err(|_| ApiError::Internal("Database error".to_string()))?;
+
+    Ok(Json(to_api_settings(settings)))
+}
+
+pub async fn update_workspace_settings(
+    State(state): State<AppState>,
+    axum::extract::Extension(ctx): axum::extract::Extension<RequestContext>,
+    Path(workspace_id): Path<Uuid>,
+    Json(payload): Json<UpdateWorkspaceSettingsRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let user = ctx.user;
+
+    let settings = workspace_settings::update_settings(
+        &state.pool,
+        workspace_id,
+        user.id,
+        payload.invitation_activation_delay_secs,
+    )
+    .await
+    .map_err(|e| match e {
+        IdentityError::PermissionDenied => ApiError::Forbidden("Admin access required".to_string()),
+        IdentityError::InvitationError(msg) => ApiError::BadRequest(msg),
+        _ => ApiError::Internal("Database error".to_string()),
+    })?;
+
+    Ok(Json(to_api_settings(settings)))
+}
+
+fn to_api_settings(
+    settings: crate::db::workspace_settings::WorkspaceSettings,
+) -> ApiWorkspaceSettings {
+    ApiWorkspaceSettings {
+        workspace_id: settings.workspace_id,
+        invitation_activation_delay_secs: settings.invitation_activation_delay_secs,
+    }
 }
 
 pub(crate) async fn ensure_member_access(
     pool: &PgPool,
     workspace_id: Uuid,
     user_id: Uuid,
-) -> Result<(), ErrorResponse> {
+) -> Result<(), ApiError> {
     workspace_members::assert_membership(pool, workspace_id, user_id)
         .await
         .map_err(|err| membership_error(err, "Not a member of workspace"))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{
+        users::{UpsertUser, UserRepository},
+        workspace_invitations::{InvitationStatus, WorkspaceInvitation},
+        workspace_members::add_member,
+    };
+
+    fn member_row(avatar_url: Option<&str>) -> MemberRow {
+        MemberRow {
+            workspace_id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            role: MemberRole::Member,
+            permissions: Vec::new(),
+            joined_at: Utc::now(),
+            first_name: Some("Dana".to_string()),
+            last_name: Some("Scully".to_string()),
+            username: Some("dscully".to_string()),
+            email: Some("dana@example.com".to_string()),
+            avatar_url: avatar_url.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_member_without_avatar_is_still_listed() {
+        // Same result whether the lookup found nothing or failed outright
+        let row = member_row(None);
+        let user_id = row.user_id;
+        let member = member_with_profile(row, &HashMap::new());
+        assert_eq!(member.user_id, user_id);
+        assert_eq!(member.username.as_deref(), Some("dscully"));
+        assert_eq!(member.avatar_url, None);
+    }
+
+    #[test]
+    fn test_member_avatar_falls_back_to_oauth() {
+        let row = member_row(None);
+        let avatars = HashMap::from([(
+            row.user_id,
+            "https://avatars.example.com/dscully.png".to_string(),
+        )]);
+        let member = member_with_profile(row, &avatars);
+        assert_eq!(
+            member.avatar_url.as_deref(),
+            Some("https://avatars.example.com/dscully.png")
+        );
+    }
+
+    #[test]
+    fn test_uploaded_member_avatar_wins() {
+        let row = member_row(Some("https://files.example.com/avatars/a.png"));
+        let avatars = HashMap::from([(
+            row.user_id,
+            "https://avatars.example.com/dscully.png".to_string(),
+        )]);
+        let member = member_with_profile(row, &avatars);
+        assert_eq!(
+            member.avatar_url.as_deref(),
+            Some("https://files.example.com/avatars/a.png")
+        );
+    }
+
+    #[sqlx::test(migrator = "crate::db::TEST_MIGRATOR")]
+    #[ignore = "requires DATABASE_URL pointing at a Postgres server"]
+    async fn test_member_rows_list_effective_permissions(pool: PgPool) {
+        let workspace_id = Uuid::new_v4();
+        let granted_id = Uuid::new_v4();
+        let plain_id = Uuid::new_v4();
+        let admin_id = Uuid::new_v4();
+        for (id, email, role) in [
+            (granted_id, "granted@example.com", MemberRole::Member),
+            (plain_id, "plain@example.com", MemberRole::Member),
+            (admin_id, "admin@example.com", MemberRole::Admin),
+        ] {
+            UserRepository::new(&pool)
+                .upsert_user(UpsertUser {
+                    id,
+                    email,
+                    first_name: None,
+                    last_name: None,
+                    username: None,
+                })
+                .await
+                .unwrap();
+            add_member(&pool, workspace_id, id, role).await.unwrap();
+        }
+        sqlx::query(
+            r#"
+            UPDATE workspace_member_metadata
+            SET permissions = ARRAY['member.invite', 'member.remove']::workspace_permission[]
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(granted_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let rows = fetch_member_rows(&pool, workspace_id, None, MemberSort::JoinedAt, None, 0)
+            .await
+            .unwrap();
+        let members: HashMap<Uuid, WorkspaceMemberWithProfile> = rows
+            .into_iter()
+            .map(|row| member_with_profile(row, &HashMap::new()))
+            .map(|member| (member.user_id, member))
+            .collect();
+
+        assert_eq!(
+            members[&granted_id].permissions,
+            vec![
+                WorkspacePermission::MemberView,
+                WorkspacePermission::MemberInvite,
+                WorkspacePermission::MemberRemove
+            ]
+        );
+        assert_eq!(
+            members[&plain_id].permissions,
+            vec![WorkspacePermission::MemberView]
+        );
+        assert_eq!(
+            members[&admin_id].permissions,
+            workspace_members::default_permissions(MemberRole::Admin)
+        );
+    }
+
+    #[sqlx::test(migrator = "crate::db::TEST_MIGRATOR")]
+    #[ignore = "requires DATABASE_URL pointing at a Postgres server"]
+    async fn test_member_rows_sort_and_page(pool: PgPool) {
+        let workspace_id = Uuid::new_v4();
+        let members = [
+            (Uuid::new_v4(), "zed@example.com", "Zed", MemberRole::Member),
+            (Uuid::new_v4(), "amy@example.com", "Amy", MemberRole::Admin),
+            (Uuid::new_v4(), "bob@example.com", "Bob", MemberRole::Member),
+        ];
+        for (days_ago, (id, email, first_name, role)) in (1..=3).rev().zip(members) {
+            UserRepository::new(&pool)
+                .upsert_user(UpsertUser {
+                    id,
+                    email,
+                    first_name: Some(first_name),
+                    last_name: None,
+                    username: None,
+                })
+                .await
+                .unwrap();
+            add_member(&pool, workspace_id, id, role).await.unwrap();
+            sqlx::query(
+                "UPDATE workspace_member_metadata SET joined_at = NOW() - make_interval(days => $2)
+                 WHERE user_id = $1",
+            )
+            .bind(id)
+            .bind(days_ago)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        let [zed, amy, bob] = members.map(|(id, ..)| id);
+
+        let pool = &pool;
+        let ids = |sort, limit, offset| async move {
+            fetch_member_rows(pool, workspace_id, None, sort, limit, offset)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|row| row.user_id)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            ids(MemberSort::JoinedAt, None, 0).await,
+            vec![zed, amy, bob]
+        );
+        // member_role is declared as ('admin', 'member'), so admins come first
+        assert_eq!(ids(MemberSort::Role, None, 0).await, vec![amy, zed, bob]);
+        assert_eq!(ids(MemberSort::Name, None, 0).await, vec![amy, bob, zed]);
+        assert_eq!(ids(MemberSort::JoinedAt, Some(1), 1).await, vec![amy]);
+        assert_eq!(ids(MemberSort::Role, Some(5), 2).await, vec![bob]);
+    }
+
+    fn lookup(invited_by_name: Option<&str>) -> InvitationLookup {
+        let now = Utc::now();
+        InvitationLookup {
+            invitation: WorkspaceInvitation {
+                id: Uuid::new_v4(),
+                workspace_id: Uuid::new_v4(),
+                invited_by_user_id: invited_by_name.map(|_| Uuid::new_v4()),
+                email: "invitee@example.com".to_string(),
+                role: MemberRole::Member,
+                status: InvitationStatus::Pending,
+                token: "token".to_string(),
+                expires_at: now,
+                created_at: now,
+                updated_at: now,
+                revoked_at: None,
+                revoked_by_user_id: None,
+            },
+            invited_by_name: invited_by_name.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_public_invitation_includes_inviter_name() {
+        let response = public_invitation(lookup(Some("Alice Smith")));
+        assert_eq!(response.invited_by_name.as_deref(), Some("Alice Smith"));
+    }
+
+    #[test]
+    fn test_public_invitation_without_inviter() {
+        let response = public_invitation(lookup(None));
+        assert!(response.invited_by_name.is_none());
+
+        let json = serde_json::to_value(&response).unwrap();
+        assert!(json["invited_by_name"].is_null());
+        assert!(json.get("email").is_none());
+        assert!(json.get("token").is_none());
+    }
+}
@@ -9,6 +9,8 @@ use crate::{
     github_app::GitHubAppService,
     mail::Mailer,
     r2::R2Service,
+    rate_limit::InviteRateLimiter,
+    webhooks::{WebhookEvent, WebhookService},
 };
 
 #[derive(Clone)]
@@ -24,6 +26,8 @@ pub struct AppState {
     r2: Option<R2Service>,
     files: Option<FilesService>,
     github_app: Option<Arc<GitHubAppService>>,
+    webhooks: Option<WebhookService>,
+    invite_limiter: Arc<InviteRateLimiter>,
 }
 
 impl AppState {
@@ -40,7 +44,11 @@ impl AppState {
         r2: Option<R2Service>,
         files: Option<FilesService>,
         github_app: Option<Arc<GitHubAppService>>,
+        webhooks: Option<WebhookService>,
     ) -> Self {
+        let invite_limiter = Arc::new(InviteRateLimiter::per_hour(
+            config.workspace_invitations.invites_per_hour,
+        ));
         Self {
             pool,
             config,
@@ -53,6 +61,8 @@ impl AppState {
             r2,
             files,
             github_app,
+            webhooks,
+            invite_limiter,
         }
     }
 
@@ -91,4 +101,15 @@ impl AppState {
     pub fn github_app(&self) -> Option<&GitHubAppService> {
         self.github_app.as_deref()
     }
+
+    pub(crate) fn invite_limiter(&self) -> &InviteRateLimiter {
+        &self.invite_limiter
+    }
+
+    /// Queue a webhook event if webhooks are configured.
+    pub fn emit_webhook(&self, event: WebhookEvent) {
+        if let Some(webhooks) = &self.webhooks {
+            webhooks.emit(event);
+        }
+    }
 }
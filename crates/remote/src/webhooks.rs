@@ -0,0 +1,238 @@
+//! Outbound webhooks for workspace membership events.
+//!
+//! Events are queued on a bounded channel and delivered by a background
+//! task, so request handlers never wait on a subscriber. Each body is signed
+//! with HMAC-SHA256 using the configured secret and sent in the
+//! `X-Vibe-Signature` header as `sha256=<hex>`, the same format GitHub uses.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use secrecy::ExposeSecret;
+use serde::Serialize;
+use sha2::Sha256;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::config::WebhookConfig;
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const SIGNATURE_HEADER: &str = "X-Vibe-Signature";
+
+/// Events waiting for delivery; new events are dropped while it is full.
+const QUEUE_CAPACITY: usize = 256;
+/// Delivery attempts per URL before an event is given up on.
+const MAX_ATTEMPTS: u32 = 3;
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum WebhookEventKind {
+    #[serde(rename = "member.invited")]
+    MemberInvited,
+    #[serde(rename = "member.joined")]
+    MemberJoined,
+    #[serde(rename = "member.removed")]
+    MemberRemoved,
+    #[serde(rename = "member.role_changed")]
+    MemberRoleChanged,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookEvent {
+    pub id: Uuid,
+    pub event: WebhookEventKind,
+    pub workspace_id: Uuid,
+    /// User whose request caused the event
+    pub actor_user_id: Uuid,
+    pub occurred_at: DateTime<Utc>,
+    /// `X-Request-Id` of the request that caused the event
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    pub data: serde_json::Value,
+}
+
+impl WebhookEvent {
+    pub fn new(
+        event: WebhookEventKind,
+        workspace_id: Uuid,
+        actor_user_id: Uuid,
+        data: serde_json::Value,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            event,
+            workspace_id,
+            actor_user_id,
+            occurred_at: Utc::now(),
+            request_id: None,
+            data,
+        }
+    }
+
+    pub fn with_request_id(mut self, request_id: Option<String>) -> Self {
+        self.request_id = request_id;
+        self
+    }
+}
+
+/// Handle for queueing webhook events.
+#[derive(Debug, Clone)]
+pub struct WebhookService {
+    tx: mpsc::Sender<WebhookEvent>,
+}
+
+impl WebhookService {
+    /// Spawn the delivery task and return a handle that feeds it.
+    pub fn spawn(config: WebhookConfig, http_client: reqwest::Client) -> Self {
+        let (tx, mut rx) = mpsc::channel::<WebhookEvent>(QUEUE_CAPACITY);
+
+        tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                let body = match serde_json::to_vec(&event) {
+                    Ok(body) => body,
+                    Err(e) => {
+                        tracing::error!(
+                            ?e,
+                            event_id = %event.id,
+                            request_id = event.request_id.as_deref(),
+                            "Failed to serialize webhook"
+                        );
+                        continue;
+                    }
+                };
+                let signature = sign(config.secret.expose_secret().as_bytes(), &body);
+
+                for url in &config.urls {
+                    deliver(&http_client, url, &body, &signature, &event).await;
+                }
+            }
+        });
+
+        Self { tx }
+    }
+
+    /// Queue an event for delivery. Drops the event if the queue is full.
+    pub fn emit(&self, event: WebhookEvent) {
+        if let Err(e) = self.tx.try_send(event) {
+            tracing::warn!(error = %e, "Webhook queue unavailable, dropping event");
+        }
+    }
+}
+
+/// `sha256=<hex>` HMAC of exactly the bytes sent as the request body.
+pub fn sign(secret: &[u8], body: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any size");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+async fn deliver(
+    client: &reqwest::Client,
+    url: &str,
+    body: &[u8],
+    signature: &str,
+    event: &WebhookEvent,
+) {
+    for attempt in 1..=MAX_ATTEMPTS {
+        let result = client
+            .post(url)
+            .timeout(DELIVERY_TIMEOUT)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, signature)
+            .body(body.to_vec())
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+
+        match result {
+            Ok(_) => return,
+            Err(e) if attempt < MAX_ATTEMPTS => {
+                tracing::debug!(
+                    ?e,
+                    %url,
+                    attempt,
+                    event_id = %event.id,
+                    request_id = event.request_id.as_deref(),
+                    "Webhook delivery failed, retrying"
+                );
+                tokio::time::sleep(RETRY_BASE_DELAY * 2u32.pow(attempt - 1)).await;
+            }
+            Err(e) => {
+                tracing::warn!(
+                    ?e,
+                    %url,
+                    event_id = %event.id,
+                    request_id = event.request_id.as_deref(),
+                    "Webhook delivery failed, giving up"
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::github_app::verify_webhook_signature;
+
+    #[test]
+    fn test_signature_covers_exact_serialized_body() {
+        let secret = b"webhook-secret";
+        let event = WebhookEvent::new(
+            WebhookEventKind::MemberJoined,
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            json!({ "user_id": Uuid::new_v4(), "role": "member" }),
+        );
+        let body = serde_json::to_vec(&event).unwrap();
+
+        let signature = sign(secret, &body);
+
+        let mut mac = HmacSha256::new_from_slice(secret).unwrap();
+        mac.update(&body);
+        let expected = format!("sha256={}", hex::encode(mac.finalize().into_bytes()));
+        assert_eq!(signature, expected);
+        assert!(verify_webhook_signature(secret, &signature, &body));
+
+        // Any change to the body, even whitespace, invalidates the signature
+        let reformatted = serde_json::to_vec_pretty(&event).unwrap();
+        assert!(!verify_webhook_signature(secret, &signature, &reformatted));
+    }
+
+    #[test]
+    fn test_event_names() {
+        let event = WebhookEvent::new(
+            WebhookEventKind::MemberRoleChanged,
+            Uuid::nil(),
+            Uuid::nil(),
+            json!({}),
+        );
+
+        let value = serde_json::to_value(&event).unwrap();
+        assert_eq!(value["event"], "member.role_changed");
+    }
+
+    #[test]
+    fn test_request_id_is_sent_only_when_known() {
+        let event = || {
+            WebhookEvent::new(
+                WebhookEventKind::MemberJoined,
+                Uuid::nil(),
+                Uuid::nil(),
+                json!({}),
+            )
+        };
+
+        let tagged =
+            serde_json::to_value(event().with_request_id(Some("req-1".to_string()))).unwrap();
+        assert_eq!(tagged["request_id"], "req-1");
+
+        let untagged = serde_json::to_value(event()).unwrap();
+        assert!(untagged.get("request_id").is_none());
+    }
+}
@@ -0,0 +1,1533 @@
+//! Local agent connection to the remote dashboard relay.
+//!
+//! Liveness is handled at the WebSocket protocol layer: the agent sends
+//! periodic ping frames and the watchdog keys off pong receipt. Pings from
+//! the server are answered automatically by tungstenite. A lightweight JSON
+//! `HEARTBEAT` is still sent because the relay reports `isLive` from it and
+//! cannot observe protocol-level frames.
+//!
+//! `EXECUTE` messages start a task attempt locally through an
+//! [`ExecutionRunner`]; its status frames are sent back over the same socket.
+//! Executions run concurrently up to a configurable limit and are tracked in
+//! [`Executions`]; requests beyond the limit are rejected.
+//! `CANCEL` stops whatever is running for the task and is always acknowledged,
+//! even when nothing was running.
+//! Every session opens with a `REGISTER` frame describing the agent and the
+//! executors it has configured. Frames are defined in [`protocol`].
+//! Inbound messages are capped at [`MAX_INBOUND_MESSAGE_SIZE`]; a larger one
+//! closes the session, which then reconnects.
+//! Whether the agent is connected is recorded in a status file that the
+//! `status` command reads.
+
+pub mod protocol;
+pub mod tls;
+pub mod token;
+
+use std::{collections::HashMap, time::Duration};
+
+use axum::http::HeaderValue;
+use chrono::Utc;
+use db::models::{
+    execution_process::{ExecutionProcess, ExecutionProcessStatus},
+    task::Task,
+};
+use deployment::Deployment;
+use executors::{executors::BaseCodingAgent, profile::ExecutorConfigs};
+use futures_util::{Sink, SinkExt, Stream, StreamExt, future::BoxFuture};
+use services::services::container::ContainerService;
+use socket2::{SockRef, TcpKeepalive};
+use sqlx::Error as SqlxError;
+use tokio::{
+    net::TcpStream,
+    sync::mpsc,
+    task::JoinHandle,
+    time::{Instant, MissedTickBehavior},
+};
+use tokio_tungstenite::{
+    Connector, MaybeTlsStream, WebSocketStream, client_async_tls_with_config,
+    tungstenite::{
+        Error as WsError,
+        client::IntoClientRequest,
+        protocol::{CloseFrame, Message, WebSocketConfig, frame::coding::CloseCode},
+    },
+};
+use tokio_util::sync::CancellationToken;
+use utils::port_file::{AgentStatus, remove_agent_status, write_agent_status};
+use uuid::Uuid;
+
+use self::{
+    protocol::{AgentMessage, ExecutePayload, frame_type},
+    tls::TlsVerification,
+};
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    routes::task_attempts::{WorkspaceRepoInput, create_workspace_for_task},
+    shutdown::shutdown_signal,
+};
+
+const PING_INTERVAL: Duration = Duration::from_secs(15);
+const PONG_TIMEOUT: Duration = Duration::from_secs(45);
+pub const APP_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+/// Bounds for a configured heartbeat: faster is needless chatter, slower
+/// lets a dead connection go unnoticed for minutes.
+pub const MIN_APP_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+pub const MAX_APP_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(120);
+pub const INBOUND_TIMEOUT_MULTIPLIER: u32 = 3;
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+/// A connection that stays up this long resets the reconnect backoff.
+const STABLE_CONNECTION_THRESHOLD: Duration = Duration::from_secs(60);
+const EXECUTION_POLL_INTERVAL: Duration = Duration::from_secs(2);
+pub const MAX_CONCURRENT_EXECUTIONS: usize = 4;
+/// Relay frames are small JSON documents, so anything larger than this is
+/// refused rather than buffered.
+pub const MAX_INBOUND_MESSAGE_SIZE: usize = 1024 * 1024;
+/// Status frames queued for the socket before executions wait for it to
+/// drain.
+const UPDATE_CHANNEL_CAPACITY: usize = 64;
+/// How long a clean shutdown waits for cancel replies and the close ack.
+const SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Copy)]
+pub struct LivenessConfig {
+    pub ping_interval: Duration,
+    pub pong_timeout: Duration,
+    pub app_heartbeat_interval: Duration,
+    /// Heartbeat intervals without any inbound frame before the connection
+    /// is considered dead
+    pub inbound_timeout_multiplier: u32,
+}
+
+impl Default for LivenessConfig {
+    fn default() -> Self {
+        Self {
+            ping_interval: PING_INTERVAL,
+            pong_timeout: PONG_TIMEOUT,
+            app_heartbeat_interval: APP_HEARTBEAT_INTERVAL,
+            inbound_timeout_multiplier: INBOUND_TIMEOUT_MULTIPLIER,
+        }
+    }
+}
+
+impl LivenessConfig {
+    /// Heartbeat interval from a configured number of seconds, clamped to
+    /// [`MIN_APP_HEARTBEAT_INTERVAL`]..=[`MAX_APP_HEARTBEAT_INTERVAL`]
+    pub fn heartbeat_interval_from_secs(secs: u64) -> Duration {
+        let interval =
+            Duration::from_secs(secs).clamp(MIN_APP_HEARTBEAT_INTERVAL, MAX_APP_HEARTBEAT_INTERVAL);
+        if interval.as_secs() != secs {
+            tracing::warn!(
+                "Heartbeat interval of {}s is out of range, using {}s",
+                secs,
+                interval.as_secs()
+            );
+        }
+        interval
+    }
+
+    pub fn inbound_timeout(&self) -> Duration {
+        self.app_heartbeat_interval
+            .saturating_mul(self.inbound_timeout_multiplier.max(1))
+    }
+
+    /// Whether nothing has been received from the relay for too long.
+    pub fn is_inbound_stale(&self, last_inbound: Instant, now: Instant) -> bool {
+        now.saturating_duration_since(last_inbound) > self.inbound_timeout()
+    }
+}
+
+pub const TCP_KEEPALIVE_IDLE: Duration = Duration::from_secs(30);
+pub const TCP_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(10);
+pub const TCP_KEEPALIVE_RETRIES: u32 = 3;
+
+/// Socket options applied to the agent's TCP connection before the
+/// WebSocket handshake, so half-open connections are detected by the OS
+/// well before the pong watchdog would notice.
+#[derive(Debug, Clone, Copy)]
+pub struct TcpConfig {
+    pub keepalive_idle: Duration,
+    pub keepalive_interval: Duration,
+    pub keepalive_retries: u32,
+    pub nodelay: bool,
+}
+
+impl Default for TcpConfig {
+    fn default() -> Self {
+        Self {
+            keepalive_idle: TCP_KEEPALIVE_IDLE,
+            keepalive_interval: TCP_KEEPALIVE_INTERVAL,
+            keepalive_retries: TCP_KEEPALIVE_RETRIES,
+            nodelay: true,
+        }
+    }
+}
+
+/// Apply keep-alive and nodelay settings to a connected socket.
+pub fn apply_tcp_config(stream: &TcpStream, config: &TcpConfig) -> std::io::Result<()> {
+    stream.set_nodelay(config.nodelay)?;
+
+    let keepalive = TcpKeepalive::new().with_time(config.keepalive_idle);
+    #[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+    let keepalive = keepalive.with_interval(config.keepalive_interval);
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    let keepalive = keepalive.with_retries(config.keepalive_retries);
+
+    SockRef::from(stream).set_tcp_keepalive(&keepalive)
+}
+
+/// Why a connected session ended. Every variant except `Shutdown` leads to a
+/// reconnect.
+#[derive(Debug)]
+pub enum SessionEnd {
+    PongTimeout,
+    InboundTimeout,
+    Closed,
+    Error(WsError),
+    Shutdown,
+}
+
+/// Starts executions requested over the relay. Status frames are sent on
+/// `updates` and forwarded over the socket while the session is open. The
+/// channel is bounded, so senders wait while the socket is backed up.
+pub trait ExecutionRunner: Send + Sync {
+    /// Run an execution to completion, reporting its progress on `updates`.
+    /// The future is spawned and tracked by [`Executions`].
+    fn execute(
+        &self,
+        task_id: Uuid,
+        payload: ExecutePayload,
+        updates: mpsc::Sender<AgentMessage>,
+    ) -> BoxFuture<'static, ()>;
+
+    fn cancel_execution(&self, task_id: Uuid, updates: mpsc::Sender<AgentMessage>);
+
+    /// Executors this agent can run, reported to the relay on connect.
+    fn available_executors(&self) -> Vec<BaseCodingAgent>;
+}
+
+impl ExecutionRunner for DeploymentImpl {
+    fn execute(
+        &self,
+        task_id: Uuid,
+        payload: ExecutePayload,
+        updates: mpsc::Sender<AgentMessage>,
+    ) -> BoxFuture<'static, ()> {
+        let deployment = self.clone();
+        Box::pin(async move {
+            match start_execution(&deployment, task_id, payload).await {
+                Ok((workspace_id, execution_process)) => {
+                    let _ = updates
+                        .send(AgentMessage::ExecutionStarted {
+                            task_id,
+                            workspace_id,
+                            execution_process_id: execution_process.id,
+                        })
+                        .await;
+                    watch_execution(&deployment, task_id, execution_process.id, updates).await;
+                }
+                Err(e) => {
+                    tracing::error!("Failed to start execution for task {}: {}", task_id, e);
+                    let _ = updates
+                        .send(AgentMessage::ExecutionFailed {
+                            task_id: Some(task_id),
+                            execution_process_id: None,
+                            error: e.to_string(),
+                        })
+                        .await;
+                }
+            }
+        })
+    }
+
+    fn cancel_execution(&self, task_id: Uuid, updates: mpsc::Sender<AgentMessage>) {
+        let deployment = self.clone();
+        tokio::spawn(async move {
+            let result = deployment.container().stop_task_executions(task_id).await;
+            if let Err(e) = &result {
+                tracing::error!("Failed to cancel execution for task {}: {}", task_id, e);
+            }
+            let _ = updates.send(cancel_reply(task_id, result)).await;
+        });
+    }
+
+    fn available_executors(&self) -> Vec<BaseCodingAgent> {
+        ExecutorConfigs::get_cached()
+            .executor_infos()
+            .into_iter()
+            .map(|info| info.executor)
+            .collect()
+    }
+}
+
+/// Build the `REGISTER` frame for this machine from the given executors and
+/// heartbeat interval. Executors are sorted so the frame is stable across
+/// connections.
+fn registration(
+    mut available_executors: Vec<BaseCodingAgent>,
+    heartbeat_interval: Duration,
+) -> AgentMessage {
+    available_executors.sort_by_key(|executor| executor.to_string());
+    available_executors.dedup();
+    let hostname = hostname::get()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| "unknown".to_string());
+
+    AgentMessage::Register {
+        agent_version: env!("CARGO_PKG_VERSION").to_string(),
+        hostname,
+        available_executors,
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        heartbeat_interval_secs: heartbeat_interval.as_secs(),
+    }
+}
+
+/// Executions started by this agent, keyed by task, so several can run at
+/// once without blocking the session loop.
+pub struct Executions {
+    max_concurrent: usize,
+    running: HashMap<Uuid, JoinHandle<()>>,
+}
+
+impl Executions {
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            max_concurrent: max_concurrent.max(1),
+            running: HashMap::new(),
+        }
+    }
+
+    /// Number of executions that have not finished yet.
+    pub fn running(&mut self) -> usize {
+        self.reap();
+        self.running.len()
+    }
+
+    /// Tasks whose executions have not finished yet.
+    fn running_tasks(&mut self) -> Vec<Uuid> {
+        self.reap();
+        self.running.keys().copied().collect()
+    }
+
+    /// Spawn an execution for the task, refusing it if the task is already
+    /// running or every slot is taken.
+    fn start(
+        &mut self,
+        task_id: Uuid,
+        execution: impl FnOnce() -> BoxFuture<'static, ()>,
+    ) -> Result<(), String> {
+        self.reap();
+        if self.running.contains_key(&task_id) {
+            return Err(format!("Task {task_id} is already running on this agent"));
+        }
+        if self.running.len() >= self.max_concurrent {
+            return Err(format!(
+                "Agent is at capacity ({} concurrent executions)",
+                self.max_concurrent
+            ));
+        }
+
+        self.running.insert(task_id, tokio::spawn(execution()));
+        Ok(())
+    }
+
+    fn reap(&mut self) {
+        self.running.retain(|_, handle| !handle.is_finished());
+    }
+}
+
+/// Build the reply to a `CANCEL` from the ids of the processes that were
+/// stopped. Nothing running is not an error and gets a `CANCEL_NOOP`.
+fn cancel_reply<E: std::fmt::Display>(task_id: Uuid, result: Result<Vec<Uuid>, E>) -> AgentMessage {
+    match result {
+        Ok(ids) if ids.is_empty() => AgentMessage::CancelNoop { task_id },
+        Ok(execution_process_ids) => AgentMessage::ExecutionCancelled {
+            task_id,
+            execution_process_ids,
+        },
+        Err(e) => AgentMessage::ExecutionFailed {
+            task_id: Some(task_id),
+            execution_process_id: None,
+            error: format!("Failed to cancel execution: {e}"),
+        },
+    }
+}
+
+/// Create a workspace for the task and start it, returning the workspace id
+/// and the initial execution process.
+async fn start_execution(
+    deployment: &DeploymentImpl,
+    task_id: Uuid,
+    payload: ExecutePayload,
+) -> Result<(Uuid, ExecutionProcess), ApiError> {
+    let task = Task::find_by_id(&deployment.db().pool, task_id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+    let executor_profile_id = match payload.executor_profile_id {
+        Some(id) => id,
+        None => deployment.config().read().await.executor_profile.clone(),
+    };
+    let repos: Vec<WorkspaceRepoInput> = payload
+        .repos
+        .into_iter()
+        .map(|r| WorkspaceRepoInput {
+            repo_id: r.repo_id,
+            target_branch: r.target_branch,
+        })
+        .collect();
+
+    let workspace = create_workspace_for_task(deployment, &task, &repos).await?;
+    let execution_process = deployment
+        .container()
+        .start_workspace(&workspace, executor_profile_id)
+        .await?;
+    Ok((workspace.id, execution_process))
+}
+
+/// Poll the execution until it finishes and report the outcome. Stops early
+/// if the session that requested it has gone away.
+async fn watch_execution(
+    deployment: &DeploymentImpl,
+    task_id: Uuid,
+    execution_process_id: Uuid,
+    updates: mpsc::Sender<AgentMessage>,
+) {
+    let pool = &deployment.db().pool;
+    let mut interval = tokio::time::interval(EXECUTION_POLL_INTERVAL);
+
+    while !updates.is_closed() {
+        interval.tick().await;
+
+        let frame = match ExecutionProcess::find_by_id(pool, execution_process_id).await {
+            Ok(Some(process)) => match process.status {
+                ExecutionProcessStatus::Running => continue,
+                ExecutionProcessStatus::Completed => AgentMessage::ExecutionCompleted {
+                    task_id,
+                    execution_process_id,
+                },
+                status => AgentMessage::ExecutionFailed {
+                    task_id: Some(task_id),
+                    execution_process_id: Some(execution_process_id),
+                    error: format!("Execution ended with status {:?}", status),
+                },
+            },
+            Ok(None) => AgentMessage::ExecutionFailed {
+                task_id: Some(task_id),
+                execution_process_id: Some(execution_process_id),
+                error: "Execution process not found".to_string(),
+            },
+            Err(e) => AgentMessage::ExecutionFailed {
+                task_id: Some(task_id),
+                execution_process_id: Some(execution_process_id),
+                error: e.to_string(),
+            },
+        };
+
+        let _ = updates.send(frame).await;
+        return;
+    }
+}
+
+/// Capped exponential backoff for reconnect attempts. The base delay
+/// doubles per attempt; the actual delay is jittered into the upper half of
+/// the base so many agents dropped at once don't reconnect in lockstep.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    initial: Duration,
+    max: Duration,
+    attempt: u32,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new(INITIAL_RECONNECT_DELAY, MAX_RECONNECT_DELAY)
+    }
+}
+
+impl Backoff {
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Self {
+            initial,
+            max,
+            attempt: 0,
+        }
+    }
+
+    /// Un-jittered delay for the current attempt.
+    pub fn base_delay(&self) -> Duration {
+        let factor = 1u32.checked_shl(self.attempt).unwrap_or(u32::MAX);
+        self.initial.saturating_mul(factor).min(self.max)
+    }
+
+    /// Delay before the next attempt, advancing the backoff.
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = jittered(self.base_delay(), rand::random::<f64>());
+        self.attempt = self.attempt.saturating_add(1);
+        delay
+    }
+
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+}
+
+/// Scale `base` into `[base / 2, base]` by `factor` in `[0, 1)`.
+fn jittered(base: Duration, factor: f64) -> Duration {
+    let half = base / 2;
+    half + half.mul_f64(factor.clamp(0.0, 1.0))
+}
+
+/// Connect to the relay and keep reconnecting with capped exponential backoff
+/// whenever the session ends. Returns once Ctrl+C or SIGTERM is received,
+/// after the open session (if any) has been closed cleanly.
+pub async fn run<R: ExecutionRunner>(
+    token: String,
+    url: String,
+    tcp_config: TcpConfig,
+    tls: TlsVerification,
+    config: LivenessConfig,
+    max_concurrent_executions: usize,
+    runner: R,
+) -> anyhow::Result<()> {
+    let connector = tls.connector()?;
+    let mut backoff = Backoff::default();
+    let mut executions = Executions::new(max_concurrent_executions);
+
+    let shutdown = CancellationToken::new();
+    let signal = shutdown.clone();
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        signal.cancel();
+    });
+
+    loop {
+        tracing::info!("Connecting to {}...", url);
+
+        let connected = tokio::select! {
+            connected = connect(&token, &url, &tcp_config, connector.clone()) => connected,
+            _ = shutdown.cancelled() => break,
+        };
+
+        let uptime = match connected {
+            Ok(ws_stream) => {
+                tracing::info!("Connected to remote dashboard");
+                record_status(&url, true).await;
+                let connected_at = Instant::now();
+
+                match run_session(ws_stream, config, &runner, &mut executions, &shutdown).await {
+                    SessionEnd::PongTimeout => {
+                        tracing::warn!("No pong received within {:?}", config.pong_timeout)
+                    }
+                    SessionEnd::InboundTimeout => tracing::warn!(
+                        "Nothing received from remote within {:?}",
+                        config.inbound_timeout()
+                    ),
+                    SessionEnd::Closed => tracing::warn!("Connection closed by remote"),
+                    SessionEnd::Error(e) => tracing::error!("WebSocket error: {}", e),
+                    SessionEnd::Shutdown => break,
+                }
+                record_status(&url, false).await;
+
+                Some(connected_at.elapsed())
+            }
+            Err(e) => {
+                tracing::error!("Failed to connect: {}", e);
+                None
+            }
+        };
+
+        if shutdown.is_cancelled() {
+            break;
+        }
+
+        if uptime.is_some_and(|uptime| uptime >= STABLE_CONNECTION_THRESHOLD) {
+            backoff.reset();
+        }
+
+        let delay = backoff.next_delay();
+        tracing::info!("Reconnecting in {:?}", delay);
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = shutdown.cancelled() => break,
+        }
+    }
+
+    tracing::info!("Shutting down local agent");
+    if let Err(e) = remove_agent_status("vibe-kanban").await {
+        tracing::debug!("Failed to remove agent status file: {}", e);
+    }
+    Ok(())
+}
+
+/// Note the connection state for `status`. Failures only cost the report.
+async fn record_status(url: &str, connected: bool) {
+    let status = AgentStatus {
+        pid: std::process::id(),
+        url: url.to_string(),
+        connected,
+        updated_at: Utc::now(),
+    };
+    if let Err(e) = write_agent_status("vibe-kanban", &status).await {
+        tracing::debug!("Failed to record agent status: {}", e);
+    }
+}
+
+/// Socket limits for the relay connection. An oversized frame fails the read
+/// instead of being buffered in full.
+pub fn websocket_config() -> WebSocketConfig {
+    WebSocketConfig {
+        max_message_size: Some(MAX_INBOUND_MESSAGE_SIZE),
+        max_frame_size: Some(MAX_INBOUND_MESSAGE_SIZE),
+        ..Default::default()
+    }
+}
+
+async fn connect(
+    token: &str,
+    url: &str,
+    tcp_config: &TcpConfig,
+    connector: Option<Connector>,
+) -> anyhow::Result<WebSocketStream<MaybeTlsStream<TcpStream>>> {
+    // Construct the request with Authorization header
+    let mut request = url.into_client_request()?;
+    request.headers_mut().insert(
+        "Authorization",
+        HeaderValue::from_str(&format!("Bearer {}", token))?,
+    );
+
+    // Open the socket ourselves so its options are set before the handshake
+    let uri = request.uri();
+    let host = uri
+        .host()
+        .ok_or_else(|| anyhow::anyhow!("URL has no host: {url}"))?
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_string();
+    let port = uri
+        .port_u16()
+        .unwrap_or(if uri.scheme_str() == Some("wss") {
+            443
+        } else {
+            80
+        });
+
+    let stream = TcpStream::connect((host.as_str(), port)).await?;
+    apply_tcp_config(&stream, tcp_config)?;
+
+    let (ws_stream, _) =
+        client_async_tls_with_config(request, stream, Some(websocket_config()), connector).await?;
+    Ok(ws_stream)
+}
+
+/// Drive a single connected session until it ends.
+pub async fn run_session<S, R>(
+    ws_stream: S,
+    config: LivenessConfig,
+    runner: &R,
+    executions: &mut Executions,
+    shutdown: &CancellationToken,
+) -> SessionEnd
+where
+    S: Stream<Item = Result<Message, WsError>> + Sink<Message, Error = WsError> + Unpin,
+    R: ExecutionRunner,
+{
+    let (mut write, mut read) = ws_stream.split();
+    let (updates_tx, mut updates_rx) = mpsc::channel(UPDATE_CHANNEL_CAPACITY);
+
+    let mut ping_interval = tokio::time::interval(config.ping_interval);
+    ping_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut heartbeat_interval = tokio::time::interval(config.app_heartbeat_interval);
+    heartbeat_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut last_pong = Instant::now();
+    let mut last_inbound = Instant::now();
+
+    let register = registration(runner.available_executors(), config.app_heartbeat_interval);
+    if let Err(e) = write.send(Message::Text(register.encode())).await {
+        return SessionEnd::Error(e);
+    }
+
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => {
+                if let Err(e) = close_session(
+                    &mut write,
+                    &mut read,
+                    runner,
+                    executions,
+                    &updates_tx,
+                    &mut updates_rx,
+                )
+                .await
+                {
+                    tracing::warn!("Failed to close the connection cleanly: {}", e);
+                }
+                return SessionEnd::Shutdown;
+            }
+            _ = ping_interval.tick() => {
+                if last_pong.elapsed() > config.pong_timeout {
+                    return SessionEnd::PongTimeout;
+                }
+                if let Err(e) = write.send(Message::Ping(Vec::new())).await {
+                    return SessionEnd::Error(e);
+                }
+            }
+            _ = heartbeat_interval.tick() => {
+                if config.is_inbound_stale(last_inbound, Instant::now()) {
+                    return SessionEnd::InboundTimeout;
+                }
+                if let Err(e) = write.send(Message::Text(AgentMessage::Heartbeat.encode())).await {
+                    return SessionEnd::Error(e);
+                }
+            }
+            Some(frame) = updates_rx.recv() => {
+                if let Err(e) = write.send(Message::Text(frame.encode())).await {
+                    return SessionEnd::Error(e);
+                }
+            }
+            message = read.next() => {
+                if matches!(message, Some(Ok(_))) {
+                    last_inbound = Instant::now();
+                }
+                match message {
+                    Some(Ok(Message::Pong(_))) => last_pong = Instant::now(),
+                    Some(Ok(Message::Text(text))) => {
+                        tracing::debug!("Received: {}", text);
+                        if let Some(reply) = handle_text(&text, runner, executions, &updates_tx) {
+                            if let Err(e) = write.send(Message::Text(reply.encode())).await {
+                                return SessionEnd::Error(e);
+                            }
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | None => return SessionEnd::Closed,
+                    Some(Ok(_)) => {}
+                    Some(Err(e @ WsError::Capacity(_))) => {
+                        let close = Message::Close(Some(CloseFrame {
+                            code: CloseCode::Size,
+                            reason: "frame exceeds size limit".into(),
+                        }));
+                        if let Err(close_err) = write.send(close).await {
+                            tracing::debug!("Failed to send close frame: {}", close_err);
+                        }
+                        return SessionEnd::Error(e);
+                    }
+                    Some(Err(e)) => return SessionEnd::Error(e),
+                }
+            }
+        }
+    }
+}
+
+/// Frames sent on a clean shutdown: `GOODBYE` so the relay marks the agent
+/// offline rather than timed out, then the websocket close.
+fn shutdown_frames() -> [Message; 2] {
+    [
+        Message::Text(AgentMessage::Goodbye.encode()),
+        Message::Close(Some(CloseFrame {
+            code: CloseCode::Normal,
+            reason: "agent shutting down".into(),
+        })),
+    ]
+}
+
+/// Cancel in-flight executions through the `CANCEL` path, then say goodbye
+/// and close the socket. Cancel replies and the relay's close ack are each
+/// awaited for at most [`SHUTDOWN_GRACE_PERIOD`] in total.
+async fn close_session<W, S, R>(
+    write: &mut W,
+    read: &mut S,
+    runner: &R,
+    executions: &mut Executions,
+    updates_tx: &mpsc::Sender<AgentMessage>,
+    updates_rx: &mut mpsc::Receiver<AgentMessage>,
+) -> Result<(), WsError>
+where
+    W: Sink<Message, Error = WsError> + Unpin,
+    S: Stream<Item = Result<Message, WsError>> + Unpin,
+    R: ExecutionRunner,
+{
+    let deadline = Instant::now() + SHUTDOWN_GRACE_PERIOD;
+
+    let running = executions.running_tasks();
+    let mut pending = running.len();
+    for task_id in running {
+        tracing::info!("Cancelling execution for task {} before shutdown", task_id);
+        runner.cancel_execution(task_id, updates_tx.clone());
+    }
+    while pending > 0 {
+        let Ok(Some(frame)) = tokio::time::timeout_at(deadline, updates_rx.recv()).await else {
+            break;
+        };
+        if matches!(
+            frame,
+            AgentMessage::ExecutionCancelled { .. }
+                | AgentMessage::CancelNoop { .. }
+                | AgentMessage::ExecutionFailed {
+                    execution_process_id: None,
+                    ..
+                }
+        ) {
+            pending -= 1;
+        }
+        write.send(Message::Text(frame.encode())).await?;
+    }
+
+    for frame in shutdown_frames() {
+        write.send(frame).await?;
+    }
+
+    let close_ack = async {
+        while let Some(Ok(message)) = read.next().await {
+            if message.is_close() {
+                break;
+            }
+        }
+    };
+    if tokio::time::timeout_at(deadline, close_ack).await.is_err() {
+        tracing::debug!("Relay did not acknowledge the close in time");
+    }
+    Ok(())
+}
+
+/// Handle a text frame from the relay, returning an immediate reply if any.
+fn handle_text<R: ExecutionRunner>(
+    text: &str,
+    runner: &R,
+    executions: &mut Executions,
+    updates: &mpsc::Sender<AgentMessage>,
+) -> Option<AgentMessage> {
+    match AgentMessage::decode(text) {
+        Ok(AgentMessage::Execute { task_id, payload }) => {
+            tracing::info!("Received execution task for task {}", task_id);
+            let started = executions.start(task_id, || {
+                runner.execute(task_id, payload, updates.clone())
+            });
+            match started {
+                Ok(()) => None,
+                Err(error) => {
+                    tracing::warn!("Rejecting execution for task {}: {}", task_id, error);
+                    Some(AgentMessage::ExecutionFailed {
+                        task_id: Some(task_id),
+                        execution_process_id: None,
+                        error,
+                    })
+                }
+            }
+        }
+        Ok(AgentMessage::Cancel { task_id }) => {
+            tracing::info!("Received cancel for task {}", task_id);
+            runner.cancel_execution(task_id, updates.clone());
+            None
+        }
+        Ok(_) => None,
+        Err(e) if frame_type(text).as_deref() == Some("EXECUTE") => {
+            tracing::warn!("Invalid EXECUTE frame: {}", e);
+            Some(AgentMessage::ExecutionFailed {
+                task_id: None,
+                execution_process_id: None,
+                error: format!("Invalid EXECUTE frame: {e}"),
+            })
+        }
+        Err(e) => {
+            tracing::debug!("Ignoring unrecognised frame: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tokio::sync::Notify;
+    use tokio_tungstenite::tungstenite::protocol::Role;
+
+    use super::*;
+
+    /// Records requested executions and immediately reports them started.
+    /// Tasks with a gate keep running until it is notified, then complete.
+    /// Cancelling a recorded task stops it; anything else is a no-op.
+    #[derive(Default)]
+    struct RecordingRunner {
+        payloads: Mutex<Vec<(Uuid, ExecutePayload)>>,
+        gates: Mutex<HashMap<Uuid, Arc<Notify>>>,
+    }
+
+    impl RecordingRunner {
+        fn gate(&self, task_id: Uuid) -> Arc<Notify> {
+            self.gates
+                .lock()
+                .unwrap()
+                .entry(task_id)
+                .or_default()
+                .clone()
+        }
+    }
+
+    impl ExecutionRunner for RecordingRunner {
+        fn execute(
+            &self,
+            task_id: Uuid,
+            payload: ExecutePayload,
+            updates: mpsc::Sender<AgentMessage>,
+        ) -> BoxFuture<'static, ()> {
+            self.payloads.lock().unwrap().push((task_id, payload));
+            let gate = self.gates.lock().unwrap().get(&task_id).cloned();
+            Box::pin(async move {
+                let _ = updates
+                    .send(AgentMessage::ExecutionStarted {
+                        task_id,
+                        workspace_id: Uuid::nil(),
+                        execution_process_id: Uuid::nil(),
+                    })
+                    .await;
+                if let Some(gate) = gate {
+                    gate.notified().await;
+                    let _ = updates
+                        .send(AgentMessage::ExecutionCompleted {
+                            task_id,
+                            execution_process_id: Uuid::nil(),
+                        })
+                        .await;
+                }
+            })
+        }
+
+        fn cancel_execution(&self, task_id: Uuid, updates: mpsc::Sender<AgentMessage>) {
+            let mut payloads = self.payloads.lock().unwrap();
+            let before = payloads.len();
+            payloads.retain(|(id, _)| *id != task_id);
+            let stopped = vec![Uuid::nil(); before - payloads.len()];
+            let _ = updates.try_send(cancel_reply(task_id, Ok::<_, String>(stopped)));
+        }
+
+        fn available_executors(&self) -> Vec<BaseCodingAgent> {
+            vec![BaseCodingAgent::Codex, BaseCodingAgent::ClaudeCode]
+        }
+    }
+
+    const TASK_ID: &str = "5b0cfb7e-3f7e-4d2b-9a34-0d8f2c1c9e11";
+    const REPO_ID: &str = "0f4a4d6c-2a57-4c59-8d1e-6f1a8b8f0a42";
+
+    fn execute_message() -> String {
+        execute_message_for(TASK_ID)
+    }
+
+    fn execute_message_for(task_id: &str) -> String {
+        serde_json::json!({
+            "type": "EXECUTE",
+            "taskId": task_id,
+            "payload": {
+                "executorProfileId": { "executor": "CURSOR_AGENT", "variant": "PLAN" },
+                "repos": [{ "repoId": REPO_ID, "targetBranch": "main" }],
+            }
+        })
+        .to_string()
+    }
+
+    fn fast_config() -> LivenessConfig {
+        LivenessConfig {
+            ping_interval: Duration::from_millis(20),
+            pong_timeout: Duration::from_millis(60),
+            app_heartbeat_interval: Duration::from_secs(60),
+            inbound_timeout_multiplier: INBOUND_TIMEOUT_MULTIPLIER,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_missing_pongs_end_session() {
+        let (client_io, _server_io) = tokio::io::duplex(64 * 1024);
+        let client = WebSocketStream::from_raw_socket(client_io, Role::Client, None).await;
+
+        // The server side is never read, so no pong is ever sent back.
+        let runner = RecordingRunner::default();
+        let end = tokio::time::timeout(
+            Duration::from_secs(5),
+            run_session(
+                client,
+                fast_config(),
+                &runner,
+                &mut executions(),
+                &CancellationToken::new(),
+            ),
+        )
+        .await
+        .expect("session should end once pongs stop arriving");
+
+        assert!(matches!(end, SessionEnd::PongTimeout));
+    }
+
+    #[tokio::test]
+    async fn test_pongs_keep_session_alive() {
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        let client = WebSocketStream::from_raw_socket(client_io, Role::Client, None).await;
+        let mut server = WebSocketStream::from_raw_socket(server_io, Role::Server, None).await;
+
+        // Reading on the server side answers pings automatically.
+        let server_task = tokio::spawn(async move { while server.next().await.is_some() {} });
+
+        let runner = RecordingRunner::default();
+        let result = tokio::time::timeout(
+            Duration::from_millis(300),
+            run_session(
+                client,
+                fast_config(),
+                &runner,
+                &mut executions(),
+                &CancellationToken::new(),
+            ),
+        )
+        .await;
+        server_task.abort();
+
+        assert!(result.is_err(), "session ended early: {:?}", result.ok());
+    }
+
+    #[tokio::test]
+    async fn test_oversized_frame_closes_session() {
+        // Large enough that the server's write completes without the client
+        // reading the payload it rejects
+        let (client_io, server_io) = tokio::io::duplex(4 * MAX_INBOUND_MESSAGE_SIZE);
+        let client =
+            WebSocketStream::from_raw_socket(client_io, Role::Client, Some(websocket_config()))
+                .await;
+        let mut server = WebSocketStream::from_raw_socket(server_io, Role::Server, None).await;
+
+        let server_task = tokio::spawn(async move {
+            let oversized = "x".repeat(MAX_INBOUND_MESSAGE_SIZE + 1);
+            server.send(Message::Text(oversized)).await.unwrap();
+            while let Some(Ok(message)) = server.next().await {
+                if let Message::Close(frame) = message {
+                    return frame;
+                }
+            }
+            None
+        });
+
+        let runner = RecordingRunner::default();
+        let end = tokio::time::timeout(
+            Duration::from_secs(5),
+            run_session(
+                client,
+                fast_config(),
+                &runner,
+                &mut executions(),
+                &CancellationToken::new(),
+            ),
+        )
+        .await
+        .expect("session should end on an oversized frame");
+        assert!(matches!(end, SessionEnd::Error(WsError::Capacity(_))));
+
+        let close = server_task.await.unwrap().expect("close frame");
+        assert_eq!(close.code, CloseCode::Size);
+    }
+
+    #[tokio::test]
+    async fn test_tcp_config_is_applied() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let stream = TcpStream::connect(addr).await.unwrap();
+
+        let config = TcpConfig {
+            keepalive_idle: Duration::from_secs(17),
+            keepalive_interval: Duration::from_secs(7),
+            keepalive_retries: 4,
+            nodelay: true,
+        };
+        apply_tcp_config(&stream, &config).unwrap();
+
+        let socket = SockRef::from(&stream);
+        assert!(stream.nodelay().unwrap());
+        assert!(socket.keepalive().unwrap());
+        #[cfg(target_os = "linux")]
+        {
+            assert_eq!(socket.keepalive_time().unwrap(), config.keepalive_idle);
+            assert_eq!(
+                socket.keepalive_interval().unwrap(),
+                config.keepalive_interval
+            );
+            assert_eq!(
+                socket.keepalive_retries().unwrap(),
+                config.keepalive_retries
+            );
+        }
+    }
+
+    fn executions() -> Executions {
+        Executions::new(MAX_CONCURRENT_EXECUTIONS)
+    }
+
+    #[tokio::test]
+    async fn test_execute_payload_is_parsed() {
+        let runner = RecordingRunner::default();
+        let (updates, _rx) = mpsc::channel(UPDATE_CHANNEL_CAPACITY);
+
+        handle_text(&execute_message(), &runner, &mut executions(), &updates);
+
+        let (task_id, payload) = runner.payloads.lock().unwrap().pop().unwrap();
+        assert_eq!(task_id, Uuid::parse_str(TASK_ID).unwrap());
+        let profile = payload.executor_profile_id.unwrap();
+        assert_eq!(profile.executor, BaseCodingAgent::CursorAgent);
+        assert_eq!(profile.variant.as_deref(), Some("PLAN"));
+        assert_eq!(payload.repos.len(), 1);
+        assert_eq!(payload.repos[0].repo_id, Uuid::parse_str(REPO_ID).unwrap());
+        assert_eq!(payload.repos[0].target_branch, "main");
+    }
+
+    #[tokio::test]
+    async fn test_execute_is_handed_to_runner() {
+        let runner = RecordingRunner::default();
+        let (updates, mut rx) = mpsc::channel(UPDATE_CHANNEL_CAPACITY);
+
+        assert!(handle_text(&execute_message(), &runner, &mut executions(), &updates).is_none());
+
+        assert_eq!(runner.payloads.lock().unwrap().len(), 1);
+        assert!(matches!(
+            rx.recv().await.unwrap(),
+            AgentMessage::ExecutionStarted { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_executions_complete_independently() {
+        let runner = RecordingRunner::default();
+        let mut executions = executions();
+        let (updates, mut rx) = mpsc::channel(UPDATE_CHANNEL_CAPACITY);
+        let first = Uuid::parse_str(TASK_ID).unwrap();
+        let second = Uuid::new_v4();
+        let first_gate = runner.gate(first);
+        let second_gate = runner.gate(second);
+
+        handle_text(&execute_message(), &runner, &mut executions, &updates);
+        handle_text(
+            &execute_message_for(&second.to_string()),
+            &runner,
+            &mut executions,
+            &updates,
+        );
+
+        // Both start before either finishes
+        for _ in 0..2 {
+            assert!(matches!(
+                rx.recv().await.unwrap(),
+                AgentMessage::ExecutionStarted { .. }
+            ));
+        }
+        assert_eq!(executions.running(), 2);
+
+        second_gate.notify_one();
+        assert_eq!(
+            rx.recv().await.unwrap(),
+            AgentMessage::ExecutionCompleted {
+                task_id: second,
+                execution_process_id: Uuid::nil(),
+            }
+        );
+
+        first_gate.notify_one();
+        assert_eq!(
+            rx.recv().await.unwrap(),
+            AgentMessage::ExecutionCompleted {
+                task_id: first,
+                execution_process_id: Uuid::nil(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execution_beyond_capacity_is_rejected() {
+        let runner = RecordingRunner::default();
+        let mut executions = Executions::new(1);
+        let (updates, _rx) = mpsc::channel(UPDATE_CHANNEL_CAPACITY);
+        let second = Uuid::new_v4();
+        runner.gate(Uuid::parse_str(TASK_ID).unwrap());
+
+        assert!(handle_text(&execute_message(), &runner, &mut executions, &updates).is_none());
+        let reply = handle_text(
+            &execute_message_for(&second.to_string()),
+            &runner,
+            &mut executions,
+            &updates,
+        );
+
+        assert!(matches!(
+            reply,
+            Some(AgentMessage::ExecutionFailed { task_id: Some(id), .. }) if id == second
+        ));
+        assert_eq!(runner.payloads.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_execution_is_rejected() {
+        let runner = RecordingRunner::default();
+        let mut executions = executions();
+        let (updates, _rx) = mpsc::channel(UPDATE_CHANNEL_CAPACITY);
+        runner.gate(Uuid::parse_str(TASK_ID).unwrap());
+
+        assert!(handle_text(&execute_message(), &runner, &mut executions, &updates).is_none());
+        let reply = handle_text(&execute_message(), &runner, &mut executions, &updates);
+
+        assert!(matches!(reply, Some(AgentMessage::ExecutionFailed { .. })));
+        assert_eq!(executions.running(), 1);
+    }
+
+    #[test]
+    fn test_invalid_execute_payload_replies_with_failure() {
+        let runner = RecordingRunner::default();
+        let (updates, _rx) = mpsc::channel(UPDATE_CHANNEL_CAPACITY);
+
+        let reply = handle_text(
+            r#"{"type":"EXECUTE","payload":{"taskId":"t1"}}"#,
+            &runner,
+            &mut executions(),
+            &updates,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            reply,
+            AgentMessage::ExecutionFailed { task_id: None, .. }
+        ));
+        assert!(runner.payloads.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_cancel_unknown_task_replies_with_noop() {
+        let runner = RecordingRunner::default();
+        let (updates, mut rx) = mpsc::channel(UPDATE_CHANNEL_CAPACITY);
+        let task_id = Uuid::new_v4();
+
+        let cancel = AgentMessage::Cancel { task_id }.encode();
+        assert!(handle_text(&cancel, &runner, &mut executions(), &updates).is_none());
+
+        assert_eq!(rx.try_recv().unwrap(), AgentMessage::CancelNoop { task_id });
+    }
+
+    #[tokio::test]
+    async fn test_cancel_running_task_replies_with_cancelled() {
+        let runner = RecordingRunner::default();
+        let mut executions = executions();
+        let (updates, mut rx) = mpsc::channel(UPDATE_CHANNEL_CAPACITY);
+        handle_text(&execute_message(), &runner, &mut executions, &updates);
+        rx.recv().await.unwrap();
+        let task_id = Uuid::parse_str(TASK_ID).unwrap();
+
+        let cancel = AgentMessage::Cancel { task_id }.encode();
+        handle_text(&cancel, &runner, &mut executions, &updates);
+
+        assert!(matches!(
+            rx.try_recv().unwrap(),
+            AgentMessage::ExecutionCancelled { task_id: id, .. } if id == task_id
+        ));
+        assert!(runner.payloads.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_cancel_failure_replies_with_execution_failed() {
+        let task_id = Uuid::new_v4();
+
+        let reply = cancel_reply(task_id, Err::<Vec<Uuid>, _>("boom"));
+
+        assert!(matches!(
+            reply,
+            AgentMessage::ExecutionFailed { task_id: Some(id), .. } if id == task_id
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_execution_updates_are_sent_over_the_socket() {
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        let client = WebSocketStream::from_raw_socket(client_io, Role::Client, None).await;
+        let mut server = WebSocketStream::from_raw_socket(server_io, Role::Server, None).await;
+        let runner = RecordingRunner::default();
+        let mut executions = executions();
+        let shutdown = CancellationToken::new();
+
+        let session = run_session(client, fast_config(), &runner, &mut executions, &shutdown);
+        let relay = async {
+            server.send(Message::Text(execute_message())).await.unwrap();
+            while let Some(Ok(message)) = server.next().await {
+                if let Message::Text(text) = message
+                    && let Ok(AgentMessage::ExecutionStarted { task_id, .. }) =
+                        AgentMessage::decode(&text)
+                {
+                    return task_id;
+                }
+            }
+            panic!("socket closed before an execution update arrived");
+        };
+
+        let task_id = tokio::select! {
+            task_id = relay => task_id,
+            end = session => panic!("session ended early: {:?}", end),
+            _ = tokio::time::sleep(Duration::from_secs(5)) => panic!("timed out"),
+        };
+        assert_eq!(task_id, Uuid::parse_str(TASK_ID).unwrap());
+    }
+
+    #[test]
+    fn test_registration_reports_capabilities() {
+        let message = registration(
+            vec![
+                BaseCodingAgent::Codex,
+                BaseCodingAgent::ClaudeCode,
+                BaseCodingAgent::Codex,
+            ],
+            Duration::from_secs(30),
+        );
+
+        let AgentMessage::Register {
+            agent_version,
+            hostname,
+            available_executors,
+            os,
+            arch,
+            heartbeat_interval_secs,
+        } = message
+        else {
+            panic!("expected REGISTER, got {:?}", message);
+        };
+        assert_eq!(agent_version, env!("CARGO_PKG_VERSION"));
+        assert!(!hostname.is_empty());
+        assert_eq!(
+            available_executors,
+            vec![BaseCodingAgent::ClaudeCode, BaseCodingAgent::Codex]
+        );
+        assert_eq!(os, std::env::consts::OS);
+        assert_eq!(arch, std::env::consts::ARCH);
+        assert_eq!(heartbeat_interval_secs, 30);
+    }
+
+    #[tokio::test]
+    async fn test_session_registers_before_heartbeat() {
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        let client = WebSocketStream::from_raw_socket(client_io, Role::Client, None).await;
+        let mut server = WebSocketStream::from_raw_socket(server_io, Role::Server, None).await;
+        let runner = RecordingRunner::default();
+        let mut executions = executions();
+        let shutdown = CancellationToken::new();
+
+        let session = run_session(client, fast_config(), &runner, &mut executions, &shutdown);
+        let relay = async {
+            while let Some(Ok(message)) = server.next().await {
+                if let Message::Text(text) = message {
+                    return AgentMessage::decode(&text).unwrap();
+                }
+            }
+            panic!("socket closed before any frame arrived");
+        };
+
+        let first = tokio::select! {
+            first = relay => first,
+            end = session => panic!("session ended early: {:?}", end),
+            _ = tokio::time::sleep(Duration::from_secs(5)) => panic!("timed out"),
+        };
+        assert!(matches!(first, AgentMessage::Register { .. }));
+    }
+
+    #[test]
+    fn test_shutdown_frames_say_goodbye_then_close() {
+        let [goodbye, close] = shutdown_frames();
+
+        let Message::Text(text) = goodbye else {
+            panic!("expected a text frame, got {:?}", goodbye);
+        };
+        assert_eq!(AgentMessage::decode(&text).unwrap(), AgentMessage::Goodbye);
+        let Message::Close(Some(frame)) = close else {
+            panic!("expected a close frame, got {:?}", close);
+        };
+        assert_eq!(frame.code, CloseCode::Normal);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_cancels_executions_and_closes_cleanly() {
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        let client = WebSocketStream::from_raw_socket(client_io, Role::Client, None).await;
+        let mut server = WebSocketStream::from_raw_socket(server_io, Role::Server, None).await;
+        let runner = RecordingRunner::default();
+        runner.gate(Uuid::parse_str(TASK_ID).unwrap());
+        let mut executions = executions();
+        let shutdown = CancellationToken::new();
+
+        let session = run_session(client, fast_config(), &runner, &mut executions, &shutdown);
+        let relay = async {
+            server.send(Message::Text(execute_message())).await.unwrap();
+            let mut frames = Vec::new();
+            // Keep reading past the close so the ack is flushed
+            while let Some(Ok(message)) = server.next().await {
+                if let Message::Text(text) = message {
+                    let frame = AgentMessage::decode(&text).unwrap();
+                    if matches!(frame, AgentMessage::ExecutionStarted { .. }) {
+                        shutdown.cancel();
+                    }
+                    frames.push(frame);
+                }
+            }
+            frames
+        };
+
+        let (end, frames) = tokio::time::timeout(Duration::from_secs(5), async {
+            tokio::join!(session, relay)
+        })
+        .await
+        .expect("session should close once shutdown is requested");
+
+        assert!(matches!(end, SessionEnd::Shutdown));
+        let cancelled = frames
+            .iter()
+            .position(|f| matches!(f, AgentMessage::ExecutionCancelled { .. }))
+            .expect("in-flight execution should be cancelled");
+        let goodbye = frames
+            .iter()
+            .position(|f| *f == AgentMessage::Goodbye)
+            .expect("a GOODBYE should be sent");
+        assert!(cancelled < goodbye);
+        assert_eq!(frames.last(), Some(&AgentMessage::Goodbye));
+    }
+
+    #[test]
+    fn test_inbound_staleness() {
+        let config = LivenessConfig {
+            app_heartbeat_interval: Duration::from_secs(15),
+            inbound_timeout_multiplier: 3,
+            ..LivenessConfig::default()
+        };
+        let last_inbound = Instant::now();
+
+        assert_eq!(config.inbound_timeout(), Duration::from_secs(45));
+        assert!(!config.is_inbound_stale(last_inbound, last_inbound));
+        assert!(!config.is_inbound_stale(last_inbound, last_inbound + Duration::from_secs(45)));
+        assert!(config.is_inbound_stale(last_inbound, last_inbound + Duration::from_secs(46)));
+        // A clock reading before the last frame is never stale
+        assert!(!config.is_inbound_stale(last_inbound + Duration::from_secs(1), last_inbound));
+    }
+
+    #[test]
+    fn test_heartbeat_interval_is_clamped() {
+        assert_eq!(
+            LivenessConfig::default().app_heartbeat_interval,
+            Duration::from_secs(15)
+        );
+        assert_eq!(
+            LivenessConfig::heartbeat_interval_from_secs(30),
+            Duration::from_secs(30)
+        );
+        assert_eq!(
+            LivenessConfig::heartbeat_interval_from_secs(0),
+            MIN_APP_HEARTBEAT_INTERVAL
+        );
+        assert_eq!(
+            LivenessConfig::heartbeat_interval_from_secs(5),
+            MIN_APP_HEARTBEAT_INTERVAL
+        );
+        assert_eq!(
+            LivenessConfig::heartbeat_interval_from_secs(120),
+            MAX_APP_HEARTBEAT_INTERVAL
+        );
+        assert_eq!(
+            LivenessConfig::heartbeat_interval_from_secs(u64::MAX),
+            MAX_APP_HEARTBEAT_INTERVAL
+        );
+    }
+
+    #[test]
+    fn test_inbound_timeout_multiplier_is_at_least_one() {
+        let config = LivenessConfig {
+            app_heartbeat_interval: Duration::from_secs(15),
+            inbound_timeout_multiplier: 0,
+            ..LivenessConfig::default()
+        };
+        assert_eq!(config.inbound_timeout(), Duration::from_secs(15));
+    }
+
+    #[tokio::test]
+    async fn test_silent_relay_ends_session() {
+        let (client_io, _server_io) = tokio::io::duplex(64 * 1024);
+        let client = WebSocketStream::from_raw_socket(client_io, Role::Client, None).await;
+        let runner = RecordingRunner::default();
+        // Pong watchdog effectively disabled so only inbound staleness can end it
+        let config = LivenessConfig {
+            ping_interval: Duration::from_secs(60),
+            pong_timeout: Duration::from_secs(600),
+            app_heartbeat_interval: Duration::from_millis(20),
+            inbound_timeout_multiplier: 2,
+        };
+
+        let end = tokio::time::timeout(
+            Duration::from_secs(5),
+            run_session(
+                client,
+                config,
+                &runner,
+                &mut executions(),
+                &CancellationToken::new(),
+            ),
+        )
+        .await
+        .expect("session should end once the relay goes silent");
+
+        assert!(matches!(end, SessionEnd::InboundTimeout));
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_cap() {
+        let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(60));
+
+        let bases: Vec<u64> = (0..9)
+            .map(|_| {
+                let base = backoff.base_delay();
+                backoff.next_delay();
+                base.as_secs()
+            })
+            .collect();
+
+        assert_eq!(bases, vec![1, 2, 4, 8, 16, 32, 60, 60, 60]);
+    }
+
+    #[test]
+    fn test_backoff_delay_is_jittered_within_base() {
+        let mut backoff = Backoff::new(Duration::from_secs(8), Duration::from_secs(60));
+
+        for _ in 0..20 {
+            backoff.reset();
+            let delay = backoff.next_delay();
+            assert!(delay >= Duration::from_secs(4) && delay <= Duration::from_secs(8));
+        }
+    }
+
+    #[test]
+    fn test_jitter_bounds() {
+        let base = Duration::from_secs(10);
+        assert_eq!(jittered(base, 0.0), Duration::from_secs(5));
+        assert_eq!(jittered(base, 0.5), Duration::from_millis(7500));
+        assert_eq!(jittered(base, 1.0), base);
+    }
+
+    #[test]
+    fn test_backoff_reset_and_overflow() {
+        let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(60));
+        for _ in 0..100 {
+            backoff.next_delay();
+        }
+        assert_eq!(backoff.base_delay(), Duration::from_secs(60));
+
+        backoff.reset();
+        assert_eq!(backoff.base_delay(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_heartbeat_ack_is_ignored() {
+        let runner = RecordingRunner::default();
+        let (updates, _rx) = mpsc::channel(UPDATE_CHANNEL_CAPACITY);
+        assert!(
+            handle_text(
+                r#"{"type":"HEARTBEAT_ACK"}"#,
+                &runner,
+                &mut executions(),
+                &updates
+            )
+            .is_none()
+        );
+    }
+}
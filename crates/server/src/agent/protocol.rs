@@ -0,0 +1,262 @@
+//! Frames exchanged between the local agent and the dashboard relay.
+//!
+//! Every frame is a JSON object tagged by `type`, with camelCase fields.
+
+use executors::{executors::BaseCodingAgent, profile::ExecutorProfileId};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+#[ts(tag = "type", rename_all = "SCREAMING_SNAKE_CASE", export)]
+pub enum AgentMessage {
+    /// First frame on every connection, so the relay only routes tasks this
+    /// agent can run
+    #[serde(rename_all = "camelCase")]
+    #[ts(rename_all = "camelCase")]
+    Register {
+        agent_version: String,
+        hostname: String,
+        available_executors: Vec<BaseCodingAgent>,
+        os: String,
+        arch: String,
+        /// Seconds between `Heartbeat` frames, so the relay can size its
+        /// liveness timeout
+        heartbeat_interval_secs: u64,
+    },
+    /// Agent liveness signal; the relay reports `isLive` from it
+    Heartbeat,
+    /// Relay reply to `Heartbeat`
+    HeartbeatAck,
+    /// Sent before the agent closes the socket on shutdown
+    Goodbye,
+    /// Relay request to start an attempt for a task
+    #[serde(rename_all = "camelCase")]
+    #[ts(rename_all = "camelCase")]
+    Execute {
+        task_id: Uuid,
+        payload: ExecutePayload,
+    },
+    #[serde(rename_all = "camelCase")]
+    #[ts(rename_all = "camelCase")]
+    ExecutionStarted {
+        task_id: Uuid,
+        workspace_id: Uuid,
+        execution_process_id: Uuid,
+    },
+    #[serde(rename_all = "camelCase")]
+    #[ts(rename_all = "camelCase")]
+    ExecutionCompleted {
+        task_id: Uuid,
+        execution_process_id: Uuid,
+    },
+    #[serde(rename_all = "camelCase")]
+    #[ts(rename_all = "camelCase")]
+    ExecutionFailed {
+        task_id: Option<Uuid>,
+        execution_process_id: Option<Uuid>,
+        error: String,
+    },
+    /// Relay request to stop a running task
+    #[serde(rename_all = "camelCase")]
+    #[ts(rename_all = "camelCase")]
+    Cancel { task_id: Uuid },
+    /// Reply to `Cancel` after the task's running executions were stopped
+    #[serde(rename_all = "camelCase")]
+    #[ts(rename_all = "camelCase")]
+    ExecutionCancelled {
+        task_id: Uuid,
+        execution_process_ids: Vec<Uuid>,
+    },
+    /// Reply to `Cancel` when nothing was running for the task
+    #[serde(rename_all = "camelCase")]
+    #[ts(rename_all = "camelCase")]
+    CancelNoop { task_id: Uuid },
+}
+
+/// Options for an `EXECUTE` request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ExecutePayload {
+    /// Falls back to the executor profile in the local config
+    #[serde(default)]
+    #[ts(optional)]
+    pub executor_profile_id: Option<ExecutorProfileId>,
+    pub repos: Vec<ExecuteRepo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ExecuteRepo {
+    pub repo_id: Uuid,
+    pub target_branch: String,
+}
+
+impl AgentMessage {
+    pub fn decode(text: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(text)
+    }
+
+    pub fn encode(&self) -> String {
+        serde_json::to_string(self).expect("agent messages always serialize")
+    }
+}
+
+/// The `type` tag of a frame, readable even when the rest of it is malformed.
+pub fn frame_type(text: &str) -> Option<String> {
+    #[derive(Deserialize)]
+    struct Tagged {
+        #[serde(rename = "type")]
+        kind: String,
+    }
+
+    serde_json::from_str::<Tagged>(text).ok().map(|t| t.kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(message: AgentMessage, expected_type: &str) -> serde_json::Value {
+        let encoded = message.encode();
+        assert_eq!(AgentMessage::decode(&encoded).unwrap(), message);
+
+        let value: serde_json::Value = serde_json::from_str(&encoded).unwrap();
+        assert_eq!(value["type"], expected_type);
+        value
+    }
+
+    #[test]
+    fn register_round_trips() {
+        let value = round_trip(
+            AgentMessage::Register {
+                agent_version: "0.0.149".to_string(),
+                hostname: "build-box".to_string(),
+                available_executors: vec![BaseCodingAgent::ClaudeCode, BaseCodingAgent::Codex],
+                os: "linux".to_string(),
+                arch: "x86_64".to_string(),
+                heartbeat_interval_secs: 15,
+            },
+            "REGISTER",
+        );
+        assert_eq!(value["agentVersion"], "0.0.149");
+        assert_eq!(value["heartbeatIntervalSecs"], 15);
+        assert_eq!(value["availableExecutors"][0], "CLAUDE_CODE");
+    }
+
+    #[test]
+    fn heartbeat_round_trips() {
+        round_trip(AgentMessage::Heartbeat, "HEARTBEAT");
+        round_trip(AgentMessage::HeartbeatAck, "HEARTBEAT_ACK");
+        round_trip(AgentMessage::Goodbye, "GOODBYE");
+    }
+
+    #[test]
+    fn execute_round_trips() {
+        let task_id = Uuid::new_v4();
+        let repo_id = Uuid::new_v4();
+        let value = round_trip(
+            AgentMessage::Execute {
+                task_id,
+                payload: ExecutePayload {
+                    executor_profile_id: Some(ExecutorProfileId::with_variant(
+                        BaseCodingAgent::CursorAgent,
+                        "PLAN".to_string(),
+                    )),
+                    repos: vec![ExecuteRepo {
+                        repo_id,
+                        target_branch: "main".to_string(),
+                    }],
+                },
+            },
+            "EXECUTE",
+        );
+
+        assert_eq!(value["taskId"], task_id.to_string());
+        assert_eq!(value["payload"]["executorProfileId"]["variant"], "PLAN");
+        assert_eq!(value["payload"]["repos"][0]["repoId"], repo_id.to_string());
+        assert_eq!(value["payload"]["repos"][0]["targetBranch"], "main");
+    }
+
+    #[test]
+    fn execution_status_round_trips() {
+        let task_id = Uuid::new_v4();
+        let execution_process_id = Uuid::new_v4();
+
+        let value = round_trip(
+            AgentMessage::ExecutionStarted {
+                task_id,
+                workspace_id: Uuid::new_v4(),
+                execution_process_id,
+            },
+            "EXECUTION_STARTED",
+        );
+        assert_eq!(
+            value["executionProcessId"],
+            execution_process_id.to_string()
+        );
+
+        round_trip(
+            AgentMessage::ExecutionCompleted {
+                task_id,
+                execution_process_id,
+            },
+            "EXECUTION_COMPLETED",
+        );
+
+        let value = round_trip(
+            AgentMessage::ExecutionFailed {
+                task_id: None,
+                execution_process_id: None,
+                error: "boom".to_string(),
+            },
+            "EXECUTION_FAILED",
+        );
+        assert_eq!(value["error"], "boom");
+    }
+
+    #[test]
+    fn cancel_round_trips() {
+        let task_id = Uuid::new_v4();
+        let value = round_trip(AgentMessage::Cancel { task_id }, "CANCEL");
+        assert_eq!(value["taskId"], task_id.to_string());
+
+        let execution_process_id = Uuid::new_v4();
+        let value = round_trip(
+            AgentMessage::ExecutionCancelled {
+                task_id,
+                execution_process_ids: vec![execution_process_id],
+            },
+            "EXECUTION_CANCELLED",
+        );
+        assert_eq!(
+            value["executionProcessIds"][0],
+            execution_process_id.to_string()
+        );
+
+        round_trip(AgentMessage::CancelNoop { task_id }, "CANCEL_NOOP");
+    }
+
+    #[test]
+    fn execute_without_profile_decodes() {
+        let task_id = Uuid::new_v4();
+        let text = format!(r#"{{"type":"EXECUTE","taskId":"{task_id}","payload":{{"repos":[]}}}}"#);
+
+        let AgentMessage::Execute { payload, .. } = AgentMessage::decode(&text).unwrap() else {
+            panic!("expected EXECUTE");
+        };
+        assert!(payload.executor_profile_id.is_none());
+    }
+
+    #[test]
+    fn frame_type_survives_malformed_frames() {
+        assert_eq!(
+            frame_type(r#"{"type":"EXECUTE","payload":{"taskId":"t1"}}"#).as_deref(),
+            Some("EXECUTE")
+        );
+        assert!(frame_type("not json").is_none());
+    }
+}
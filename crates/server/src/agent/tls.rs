@@ -0,0 +1,180 @@
+//! TLS settings for the relay connection.
+//!
+//! By default the socket verifies the relay against the platform's native
+//! roots. Self-hosted dashboards signed by a private CA can pass that CA
+//! instead, and verification can be switched off entirely for testing.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::Context;
+use rustls::{
+    ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme,
+    client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+    crypto::{CryptoProvider, verify_tls12_signature, verify_tls13_signature},
+    pki_types::{CertificateDer, ServerName, UnixTime, pem::PemObject},
+};
+use tokio_tungstenite::Connector;
+
+/// How the relay's certificate is verified.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum TlsVerification {
+    /// Public CAs from the platform's native root store
+    #[default]
+    Native,
+    /// Only certificates issued by the CAs in this PEM file
+    CustomCa(PathBuf),
+    /// Accept any certificate
+    Insecure,
+}
+
+impl TlsVerification {
+    /// Build the connector for this mode. `None` keeps tungstenite's default.
+    pub fn connector(&self) -> anyhow::Result<Option<Connector>> {
+        let config = match self {
+            TlsVerification::Native => return Ok(None),
+            TlsVerification::CustomCa(path) => ClientConfig::builder()
+                .with_root_certificates(load_root_store(path)?)
+                .with_no_client_auth(),
+            TlsVerification::Insecure => {
+                tracing::warn!(
+                    "!!! TLS certificate verification is DISABLED for the agent connection. \
+                     Anyone on the network path can impersonate the dashboard. \
+                     Use --ca-cert for self-hosted dashboards instead. !!!"
+                );
+                ClientConfig::builder()
+                    .dangerous()
+                    .with_custom_certificate_verifier(Arc::new(NoVerification::new()))
+                    .with_no_client_auth()
+            }
+        };
+        Ok(Some(Connector::Rustls(Arc::new(config))))
+    }
+}
+
+/// Load every certificate in a PEM file into a fresh root store.
+pub fn load_root_store(path: &Path) -> anyhow::Result<RootCertStore> {
+    let mut store = RootCertStore::empty();
+    for cert in CertificateDer::pem_file_iter(path)
+        .with_context(|| format!("Failed to read CA certificate {}", path.display()))?
+    {
+        let cert = cert.with_context(|| format!("Invalid CA certificate in {}", path.display()))?;
+        store
+            .add(cert)
+            .with_context(|| format!("Unusable CA certificate in {}", path.display()))?;
+    }
+
+    if store.is_empty() {
+        anyhow::bail!("No certificates found in {}", path.display());
+    }
+    Ok(store)
+}
+
+/// Accepts any server certificate while still checking handshake signatures.
+#[derive(Debug)]
+struct NoVerification(Arc<CryptoProvider>);
+
+impl NoVerification {
+    fn new() -> Self {
+        let provider = CryptoProvider::get_default()
+            .cloned()
+            .unwrap_or_else(|| Arc::new(rustls::crypto::aws_lc_rs::default_provider()));
+        Self(provider)
+    }
+}
+
+impl ServerCertVerifier for NoVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+
+    const TEST_CA: &str = "-----BEGIN CERTIFICATE-----
+MIIBkzCCATmgAwIBAgIUdIXZ5PnYidQ76lUa2yDqxMmum9YwCgYIKoZIzj0EAwIw
+HjEcMBoGA1UEAwwTVmliZSBLYW5iYW4gVGVzdCBDQTAgFw0yNjEwMTUxNjIzMTda
+GA8yMTI2MDkyMTE2MjMxN1owHjEcMBoGA1UEAwwTVmliZSBLYW5iYW4gVGVzdCBD
+QTBZMBMGByqGSM49AgEGCCqGSM49AwEHA0IABGlR8DgS7V3/RtmePAfVCGEUfvhk
++1518vhKb7liXP3ii42wopr4DxYHem7su5rQVE74akyFwldryq5gH9bp61ajUzBR
+MB0GA1UdDgQWBBRZucN+cyH1dOmvfjmKslSQfrX2UDAfBgNVHSMEGDAWgBRZucN+
+cyH1dOmvfjmKslSQfrX2UDAPBgNVHRMBAf8EBTADAQH/MAoGCCqGSM49BAMCA0gA
+MEUCIQCLaLsSB2NqKa0U1X6CspdlSFS2NPfrKmMIZs+GGyTNBAIgI5//RbMu9En4
+KC3Em5iPfTDkZWffm+zxBRfIBfWVSwE=
+-----END CERTIFICATE-----
+";
+
+    fn write_temp(contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("vibe-agent-ca-{}.pem", Uuid::new_v4()));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_ca_file_is_loaded_into_root_store() {
+        let path = write_temp(TEST_CA);
+        let store = load_root_store(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(store.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_ca_file_without_certificates_is_rejected() {
+        let path = write_temp("not a certificate\n");
+        let store = load_root_store(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(store.is_err());
+    }
+
+    #[test]
+    fn test_native_verification_keeps_default_connector() {
+        assert!(TlsVerification::Native.connector().unwrap().is_none());
+    }
+}
@@ -0,0 +1,179 @@
+//! Where the relay connection token comes from.
+//!
+//! `--token` leaks into shell history and `ps` output, so the token can also
+//! come from a file or the OS keyring. Sources are tried in the order
+//! flag > file > keyring > `VIBE_TOKEN`; later ones are only consulted when
+//! earlier ones are absent. The token value itself is never logged.
+
+use std::{fmt, path::Path};
+
+use anyhow::Context;
+
+/// Keyring service the token is stored under
+pub const KEYRING_SERVICE: &str = "vibe-kanban";
+/// Keyring account the token is stored under
+pub const KEYRING_USER: &str = "agent-token";
+/// Environment variable consulted last
+pub const TOKEN_ENV: &str = "VIBE_TOKEN";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenSource {
+    Flag,
+    File,
+    Keyring,
+    Env,
+}
+
+impl fmt::Display for TokenSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TokenSource::Flag => "--token",
+            TokenSource::File => "--token-file",
+            TokenSource::Keyring => "the OS keyring",
+            TokenSource::Env => TOKEN_ENV,
+        })
+    }
+}
+
+/// Pick the token from the first source that has one. A token file that was
+/// named but can't be read is an error rather than a fall-through.
+pub fn resolve_token(
+    flag: Option<String>,
+    file: Option<&Path>,
+    keyring: impl FnOnce() -> Option<String>,
+    env: impl FnOnce() -> Option<String>,
+) -> anyhow::Result<(String, TokenSource)> {
+    if let Some(token) = non_empty(flag) {
+        return Ok((token, TokenSource::Flag));
+    }
+    if let Some(path) = file {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read token file {}", path.display()))?;
+        let token = non_empty(Some(contents))
+            .with_context(|| format!("Token file {} is empty", path.display()))?;
+        return Ok((token, TokenSource::File));
+    }
+    if let Some(token) = non_empty(keyring()) {
+        return Ok((token, TokenSource::Keyring));
+    }
+    if let Some(token) = non_empty(env()) {
+        return Ok((token, TokenSource::Env));
+    }
+    anyhow::bail!(
+        "No connection token: pass --token or --token-file, store one in the OS keyring, or set {TOKEN_ENV}"
+    )
+}
+
+fn non_empty(token: Option<String>) -> Option<String> {
+    token
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty())
+}
+
+fn keyring_entry() -> keyring::Result<keyring::Entry> {
+    keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)
+}
+
+/// The token stored in the OS keyring, if any. An unavailable keyring is
+/// treated as empty.
+pub fn keyring_token() -> Option<String> {
+    match keyring_entry().and_then(|entry| entry.get_password()) {
+        Ok(token) => Some(token),
+        Err(keyring::Error::NoEntry) => None,
+        Err(e) => {
+            tracing::warn!("Could not read token from the OS keyring: {}", e);
+            None
+        }
+    }
+}
+
+/// Save the token in the OS keyring for later runs
+pub fn store_keyring_token(token: &str) -> anyhow::Result<()> {
+    keyring_entry()
+        .and_then(|entry| entry.set_password(token))
+        .context("Failed to store token in the OS keyring")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn write_temp(contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("vibe-agent-token-{}", Uuid::new_v4()));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn some(token: &str) -> impl FnOnce() -> Option<String> {
+        let token = token.to_string();
+        move || Some(token)
+    }
+
+    fn never_consulted() -> impl FnOnce() -> Option<String> {
+        || panic!("lower-precedence source should not be consulted")
+    }
+
+    #[test]
+    fn test_flag_beats_every_other_source() {
+        let file = write_temp("from-file\n");
+        let resolved = resolve_token(
+            Some("from-flag".to_string()),
+            Some(&file),
+            never_consulted(),
+            never_consulted(),
+        );
+        std::fs::remove_file(&file).unwrap();
+
+        assert_eq!(
+            resolved.unwrap(),
+            ("from-flag".to_string(), TokenSource::Flag)
+        );
+    }
+
+    #[test]
+    fn test_file_beats_keyring_and_env_and_is_trimmed() {
+        let file = write_temp("  from-file\r\n");
+        let resolved = resolve_token(None, Some(&file), never_consulted(), never_consulted());
+        std::fs::remove_file(&file).unwrap();
+
+        assert_eq!(
+            resolved.unwrap(),
+            ("from-file".to_string(), TokenSource::File)
+        );
+    }
+
+    #[test]
+    fn test_keyring_beats_env() {
+        let resolved = resolve_token(None, None, some("from-keyring"), never_consulted());
+        assert_eq!(
+            resolved.unwrap(),
+            ("from-keyring".to_string(), TokenSource::Keyring)
+        );
+    }
+
+    #[test]
+    fn test_env_is_the_last_resort() {
+        let resolved = resolve_token(Some("  ".to_string()), None, || None, some("from-env"));
+        assert_eq!(
+            resolved.unwrap(),
+            ("from-env".to_string(), TokenSource::Env)
+        );
+
+        assert!(resolve_token(None, None, || None, || None).is_err());
+    }
+
+    #[test]
+    fn test_unreadable_or_empty_token_file_is_an_error() {
+        let missing = std::env::temp_dir().join(format!("vibe-agent-token-{}", Uuid::new_v4()));
+        assert!(resolve_token(None, Some(&missing), never_consulted(), never_consulted()).is_err());
+
+        let empty = write_temp("\n");
+        let resolved = resolve_token(None, Some(&empty), never_consulted(), never_consulted());
+        std::fs::remove_file(&empty).unwrap();
+        assert!(resolved.is_err());
+    }
+}
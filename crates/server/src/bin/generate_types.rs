@@ -55,6 +55,7 @@ fn generate_types_content() -> String {
         db::models::role::CreateRole::decl(),
         db::models::role::UpdateRole::decl(),
         db::models::permission::Permission::decl(),
+        db::models::permission::PermissionCategory::decl(),
         db::models::workspace_member::WorkspaceMember::decl(),
         db::models::workspace_member::WorkspaceMemberWithRole::decl(),
         db::models::workspace_member::CreateWorkspaceMember::decl(),
@@ -110,12 +111,25 @@ fn generate_types_content() -> String {
         utils::api::workspaces::WorkspaceInvitation::decl(),
         utils::api::workspaces::InviteWorkspaceMemberRequest::decl(),
         utils::api::workspaces::InviteWorkspaceMemberResponse::decl(),
+        utils::api::workspaces::BulkInviteWorkspaceMembersRequest::decl(),
+        utils::api::workspaces::BulkInviteOutcome::decl(),
+        utils::api::workspaces::BulkInviteResult::decl(),
+        utils::api::workspaces::BulkInviteWorkspaceMembersResponse::decl(),
         utils::api::workspaces::UpdateWorkspaceMemberRoleRequest::decl(),
         utils::api::workspaces::UpdateWorkspaceMemberRoleResponse::decl(),
+        utils::api::workspaces::TransferWorkspaceOwnershipRequest::decl(),
+        utils::api::workspaces::TransferWorkspaceOwnershipResponse::decl(),
         utils::api::workspaces::GetWorkspaceInvitationResponse::decl(),
         utils::api::workspaces::AcceptWorkspaceInvitationResponse::decl(),
+        utils::api::workspaces::PendingWorkspaceInvitation::decl(),
+        utils::api::workspaces::ListPendingWorkspaceInvitationsResponse::decl(),
         utils::api::workspaces::RevokeWorkspaceInvitationRequest::decl(),
+        utils::api::workspaces::RevokeInvitationsByInviterRequest::decl(),
+        utils::api::workspaces::RevokeInvitationsByInviterResponse::decl(),
+        utils::api::workspaces::ExtendWorkspaceInvitationRequest::decl(),
         utils::api::workspaces::ListWorkspaceInvitationsResponse::decl(),
+        utils::api::workspaces::WorkspaceSettings::decl(),
+        utils::api::workspaces::UpdateWorkspaceSettingsRequest::decl(),
         utils::api::projects::RemoteProject::decl(),
         utils::api::projects::ListProjectsResponse::decl(),
         utils::api::projects::RemoteProjectMembersResponse::decl(),
@@ -137,7 +151,20 @@ fn generate_types_content() -> String {
         server::routes::cf_auth::AuthMeResponse::decl(),
         server::routes::cf_auth::UserResponse::decl(),
         server::routes::cf_auth::SessionResponse::decl(),
+        server::routes::cf_auth::ActiveSessionResponse::decl(),
+        server::routes::health::DependencyState::decl(),
+        server::routes::health::DependencyStatus::decl(),
+        server::routes::health::ReadinessResponse::decl(),
+        server::routes::users::UserSearchResult::decl(),
+        server::routes::roles::RoleWithPermissions::decl(),
+        server::routes::workspace_teams::MyWorkspace::decl(),
+        services::services::workspace_team::PermissionExplanation::decl(),
+        services::services::workspace_team::PermissionDiff::decl(),
+        server::agent::protocol::AgentMessage::decl(),
+        server::agent::protocol::ExecutePayload::decl(),
+        server::agent::protocol::ExecuteRepo::decl(),
         server::routes::cf_auth::LogoutResponse::decl(),
+        server::routes::admin::ForceLogoutResponse::decl(),
         server::routes::sessions::CreateFollowUpAttempt::decl(),
         server::routes::task_attempts::ChangeTargetBranchRequest::decl(),
         server::routes::task_attempts::ChangeTargetBranchResponse::decl(),
@@ -209,6 +236,8 @@ fn generate_types_content() -> String {
         executors::profile::ExecutorProfileId::decl(),
         executors::profile::ExecutorConfig::decl(),
         executors::profile::ExecutorConfigs::decl(),
+        executors::profile::ExecutorInfo::decl(),
+        executors::profile::ExecutorVariantInfo::decl(),
         executors::executors::BaseAgentCapability::decl(),
         executors::executors::claude::ClaudeCode::decl(),
         executors::executors::gemini::Gemini::decl(),
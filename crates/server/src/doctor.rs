@@ -0,0 +1,240 @@
+//! `doctor` self-check for self-hosted installs.
+//!
+//! Each check reports pass, fail or skip. A failed required check fails the
+//! whole run; a failed optional one is shown as a warning. Nothing here
+//! starts the HTTP server.
+
+use std::fmt::Write as _;
+
+use deployment::Deployment;
+use remote::{config::FilesR2Config, files::FilesService};
+
+use crate::DeploymentImpl;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckOutcome {
+    Pass(String),
+    Fail(String),
+    /// The feature isn't configured, so there is nothing to check
+    Skip(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub required: bool,
+    pub outcome: CheckOutcome,
+}
+
+impl CheckResult {
+    fn label(&self) -> &'static str {
+        match (&self.outcome, self.required) {
+            (CheckOutcome::Pass(_), _) => "PASS",
+            (CheckOutcome::Fail(_), true) => "FAIL",
+            (CheckOutcome::Fail(_), false) => "WARN",
+            (CheckOutcome::Skip(_), _) => "SKIP",
+        }
+    }
+
+    fn detail(&self) -> &str {
+        match &self.outcome {
+            CheckOutcome::Pass(detail)
+            | CheckOutcome::Fail(detail)
+            | CheckOutcome::Skip(detail) => detail,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DoctorReport {
+    pub checks: Vec<CheckResult>,
+}
+
+impl DoctorReport {
+    fn push(&mut self, name: &'static str, required: bool, outcome: CheckOutcome) {
+        self.checks.push(CheckResult {
+            name,
+            required,
+            outcome,
+        });
+    }
+
+    /// True unless a required check failed
+    pub fn passed(&self) -> bool {
+        !self
+            .checks
+            .iter()
+            .any(|check| check.required && matches!(check.outcome, CheckOutcome::Fail(_)))
+    }
+
+    /// One row per check, padded into columns
+    pub fn render(&self) -> String {
+        let name_width = self
+            .checks
+            .iter()
+            .map(|check| check.name.len())
+            .max()
+            .unwrap_or(0)
+            .max("CHECK".len());
+
+        let mut out = String::new();
+        let _ = writeln!(out, "{:<name_width$}  STATUS  DETAIL", "CHECK");
+        for check in &self.checks {
+            let _ = writeln!(
+                out,
+                "{:<name_width$}  {:<6}  {}",
+                check.name,
+                check.label(),
+                check.detail()
+            );
+        }
+        out
+    }
+}
+
+/// Run every check against the current environment
+pub async fn run() -> DoctorReport {
+    let mut report = DoctorReport::default();
+
+    match DeploymentImpl::new().await {
+        Ok(deployment) => {
+            report.push(
+                "deployment",
+                true,
+                CheckOutcome::Pass("initialized".to_string()),
+            );
+            let outcome = match sqlx::query("SELECT 1").execute(&deployment.db().pool).await {
+                Ok(_) => CheckOutcome::Pass("reachable".to_string()),
+                Err(e) => CheckOutcome::Fail(e.to_string()),
+            };
+            report.push("database", true, outcome);
+        }
+        Err(e) => {
+            report.push("deployment", true, CheckOutcome::Fail(e.to_string()));
+            report.push(
+                "database",
+                true,
+                CheckOutcome::Fail("skipped: deployment failed to initialize".to_string()),
+            );
+        }
+    }
+
+    let (required, outcome) = check_files_storage().await;
+    report.push("files storage (R2)", required, outcome);
+
+    let (required, outcome) = check_cf_access(
+        std::env::var("CF_ACCESS_TEAM").ok().as_deref(),
+        std::env::var("CF_ACCESS_AUD").ok().as_deref(),
+    );
+    report.push("Cloudflare Access", required, outcome);
+
+    report
+}
+
+/// Files storage is optional, but once any of it is configured the bucket
+/// has to be reachable
+async fn check_files_storage() -> (bool, CheckOutcome) {
+    match FilesR2Config::from_env() {
+        Ok(None) => (
+            false,
+            CheckOutcome::Skip("R2_FILES_ACCESS_KEY_ID not set".to_string()),
+        ),
+        Ok(Some(config)) => match FilesService::new(&config).check_bucket().await {
+            Ok(()) => (
+                true,
+                CheckOutcome::Pass(format!("bucket {} reachable", config.bucket)),
+            ),
+            Err(e) => (true, CheckOutcome::Fail(e.to_string())),
+        },
+        Err(e) => (true, CheckOutcome::Fail(e.to_string())),
+    }
+}
+
+/// Cloudflare Access needs both the team and the audience, or neither
+fn check_cf_access(team: Option<&str>, aud: Option<&str>) -> (bool, CheckOutcome) {
+    let team = team.filter(|v| !v.trim().is_empty());
+    let aud = aud.filter(|v| !v.trim().is_empty());
+    match (team, aud) {
+        (Some(team), Some(_)) => (true, CheckOutcome::Pass(format!("team {team}"))),
+        (None, None) => (
+            false,
+            CheckOutcome::Skip("CF_ACCESS_TEAM and CF_ACCESS_AUD not set".to_string()),
+        ),
+        (Some(_), None) => (
+            true,
+            CheckOutcome::Fail("CF_ACCESS_TEAM is set but CF_ACCESS_AUD is not".to_string()),
+        ),
+        (None, Some(_)) => (
+            true,
+            CheckOutcome::Fail("CF_ACCESS_AUD is set but CF_ACCESS_TEAM is not".to_string()),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(checks: &[(bool, CheckOutcome)]) -> DoctorReport {
+        let mut report = DoctorReport::default();
+        for (required, outcome) in checks {
+            report.push("check", *required, outcome.clone());
+        }
+        report
+    }
+
+    #[test]
+    fn test_only_required_failures_fail_the_report() {
+        let pass = CheckOutcome::Pass(String::new());
+        let fail = CheckOutcome::Fail(String::new());
+        let skip = CheckOutcome::Skip(String::new());
+
+        assert!(report(&[]).passed());
+        assert!(report(&[(true, pass.clone()), (false, skip.clone())]).passed());
+        assert!(report(&[(true, pass.clone()), (false, fail.clone())]).passed());
+        assert!(!report(&[(true, pass), (true, fail), (false, skip)]).passed());
+    }
+
+    #[test]
+    fn test_render_labels_optional_failures_as_warnings() {
+        let mut report = DoctorReport::default();
+        report.push("database", true, CheckOutcome::Pass("reachable".into()));
+        report.push("storage", false, CheckOutcome::Fail("timed out".into()));
+        report.push("access", true, CheckOutcome::Fail("missing aud".into()));
+
+        let lines: Vec<String> = report
+            .render()
+            .lines()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "CHECK STATUS DETAIL",
+                "database PASS reachable",
+                "storage WARN timed out",
+                "access FAIL missing aud",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cf_access_requires_team_and_audience_together() {
+        assert!(matches!(
+            check_cf_access(None, None),
+            (false, CheckOutcome::Skip(_))
+        ));
+        assert!(matches!(
+            check_cf_access(Some("acme"), Some("aud")),
+            (true, CheckOutcome::Pass(_))
+        ));
+        assert!(matches!(
+            check_cf_access(Some("acme"), Some(" ")),
+            (true, CheckOutcome::Fail(_))
+        ));
+        assert!(matches!(
+            check_cf_access(None, Some("aud")),
+            (true, CheckOutcome::Fail(_))
+        ));
+    }
+}
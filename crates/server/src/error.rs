@@ -22,6 +22,7 @@ use services::services::{
     remote_client::RemoteClientError,
     repo::RepoError as RepoServiceError,
     share::ShareError,
+    workspace_team::WorkspaceTeamServiceError,
     worktree_manager::WorktreeError,
 };
 use thiserror::Error;
@@ -76,6 +77,8 @@ pub enum ApiError {
     Conflict(String),
     #[error("Forbidden: {0}")]
     Forbidden(String),
+    #[error("Not found: {0}")]
+    NotFound(String),
     #[error("Internal error: {0}")]
     Internal(String),
 }
@@ -179,6 +182,7 @@ impl IntoResponse for ApiError {
             ApiError::BadRequest(_) => (StatusCode::BAD_REQUEST, "BadRequest"),
             ApiError::Conflict(_) => (StatusCode::CONFLICT, "ConflictError"),
             ApiError::Forbidden(_) => (StatusCode::FORBIDDEN, "ForbiddenError"),
+            ApiError::NotFound(_) => (StatusCode::NOT_FOUND, "NotFoundError"),
             ApiError::Internal(_) => (StatusCode::INTERNAL_SERVER_ERROR, "InternalError"),
         };
 
@@ -256,6 +260,7 @@ impl IntoResponse for ApiError {
             ApiError::BadRequest(msg) => msg.clone(),
             ApiError::Conflict(msg) => msg.clone(),
             ApiError::Forbidden(msg) => msg.clone(),
+            ApiError::NotFound(msg) => msg.clone(),
             ApiError::Internal(msg) => msg.clone(),
             _ => format!("{}: {}", error_type, self),
         };
@@ -393,3 +398,26 @@ impl From<ProjectRepoError> for ApiError {
         }
     }
 }
+
+impl From<WorkspaceTeamServiceError> for ApiError {
+    fn from(err: WorkspaceTeamServiceError) -> Self {
+        match err {
+            WorkspaceTeamServiceError::Database(db_err) => ApiError::Database(db_err),
+            WorkspaceTeamServiceError::TeamNotFound => ApiError::NotFound(err.to_string()),
+            WorkspaceTeamServiceError::MemberNotFound
+            | WorkspaceTeamServiceError::RoleNotFound
+            | WorkspaceTeamServiceError::PermissionNotFound(_) => {
+                ApiError::BadRequest(err.to_string())
+            }
+            WorkspaceTeamServiceError::AlreadyMember
+            | WorkspaceTeamServiceError::LastOwner
+            | WorkspaceTeamServiceError::LastOwnerRoleChange
+            | WorkspaceTeamServiceError::DuplicateRoleName(_) => {
+                ApiError::Conflict(err.to_string())
+            }
+            WorkspaceTeamServiceError::PermissionDenied(_)
+            | WorkspaceTeamServiceError::SystemRoleDelete
+            | WorkspaceTeamServiceError::SystemRoleModify => ApiError::Forbidden(err.to_string()),
+        }
+    }
+}
@@ -1,7 +1,11 @@
+pub mod agent;
+pub mod doctor;
 pub mod error;
 pub mod mcp;
 pub mod middleware;
 pub mod routes;
+pub mod shutdown;
+pub mod status;
 
 // #[cfg(feature = "cloud")]
 // type DeploymentImpl = vibe_kanban_cloud::deployment::CloudDeployment;
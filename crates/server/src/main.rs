@@ -1,15 +1,25 @@
+use std::{path::PathBuf, time::Duration};
+
 use anyhow::{self, Error as AnyhowError};
-use axum::http::HeaderValue;
 use clap::{Parser, Subcommand};
+use db::{
+    DBService,
+    rbac_seed::{self, SeedMode},
+};
 use deployment::{Deployment, DeploymentError};
-use futures_util::{SinkExt, StreamExt};
-use server::{DeploymentImpl, routes};
-use services::services::container::ContainerService;
+use server::{
+    DeploymentImpl,
+    agent::{self, LivenessConfig, TcpConfig, tls::TlsVerification, token},
+    doctor, routes,
+    shutdown::shutdown_signal,
+    status,
+};
+use services::services::{container::ContainerService, session_cleanup::SessionCleanupService};
 use sqlx::Error as SqlxError;
 use strip_ansi_escapes::strip;
 use thiserror::Error;
-use tokio_tungstenite::{connect_async, tungstenite::{protocol::Message, client::IntoClientRequest}};
 use tracing_subscriber::{EnvFilter, prelude::*};
+use url::{Host, Url};
 use utils::{
     assets::asset_dir,
     browser::open_browser,
@@ -27,6 +37,13 @@ pub enum VibeKanbanError {
     Deployment(#[from] DeploymentError),
     #[error(transparent)]
     Other(#[from] AnyhowError),
+    #[error(
+        "Invalid --url {url:?}: {reason}. Expected a WebSocket URL such as \
+         wss://vibe-kanban.pages.dev/api/v1/agents/local/ws"
+    )]
+    InvalidConnectUrl { url: String, reason: String },
+    #[error("Connection token is empty")]
+    EmptyToken,
 }
 
 #[derive(Parser)]
@@ -34,21 +51,111 @@ pub enum VibeKanbanError {
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
+
+    /// Report missing system roles and permissions without writing, exiting
+    /// non-zero if the database needs seeding
+    #[arg(long, global = true, env = "VIBE_CHECK_SEED")]
+    check_seed: bool,
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Start the server (default)
     Server,
+    /// Report running servers, their health and the agent connection,
+    /// exiting non-zero unless a healthy server is found
+    Status,
+    /// Check the database, files storage and Cloudflare Access settings,
+    /// exiting non-zero if a required check fails
+    Doctor,
     /// Connect to the remote dashboard
     Connect {
-        /// Connection token
-        #[arg(short, long, env = "VIBE_TOKEN")]
-        token: String,
-        
+        /// Connection token. Prefer --token-file or the OS keyring, since a
+        /// flag shows up in shell history and `ps`; VIBE_TOKEN is used last.
+        #[arg(short, long)]
+        token: Option<String>,
+
+        /// File holding the connection token; surrounding whitespace is ignored
+        #[arg(long, env = "VIBE_TOKEN_FILE")]
+        token_file: Option<PathBuf>,
+
+        /// Save the resolved token in the OS keyring so later runs can omit it
+        #[arg(long)]
+        store_token: bool,
+
         /// Remote API URL (WebSocket endpoint)
         #[arg(long, default_value = "wss://vibe-kanban.pages.dev/api/v1/agents/local/ws")]
         url: String,
+
+        /// Seconds of idle time before TCP keep-alive probes are sent
+        #[arg(
+            long,
+            env = "VIBE_TCP_KEEPALIVE_IDLE_SECS",
+            default_value_t = agent::TCP_KEEPALIVE_IDLE.as_secs()
+        )]
+        tcp_keepalive_idle_secs: u64,
+
+        /// Seconds between TCP keep-alive probes
+        #[arg(
+            long,
+            env = "VIBE_TCP_KEEPALIVE_INTERVAL_SECS",
+            default_value_t = agent::TCP_KEEPALIVE_INTERVAL.as_secs()
+        )]
+        tcp_keepalive_interval_secs: u64,
+
+        /// Unanswered keep-alive probes before the connection is dropped
+        #[arg(
+            long,
+            env = "VIBE_TCP_KEEPALIVE_RETRIES",
+            default_value_t = agent::TCP_KEEPALIVE_RETRIES
+        )]
+        tcp_keepalive_retries: u32,
+
+        /// Disable Nagle's algorithm on the agent socket
+        #[arg(
+            long,
+            env = "VIBE_TCP_NODELAY",
+            default_value_t = true,
+            action = clap::ArgAction::Set
+        )]
+        tcp_nodelay: bool,
+
+        /// PEM file with the CA that signed a self-hosted dashboard's certificate
+        #[arg(
+            long,
+            env = "VIBE_CA_CERT",
+            conflicts_with = "insecure_skip_tls_verify"
+        )]
+        ca_cert: Option<PathBuf>,
+
+        /// Accept any TLS certificate from the dashboard (testing only)
+        #[arg(long, env = "VIBE_INSECURE_SKIP_TLS_VERIFY")]
+        insecure_skip_tls_verify: bool,
+
+        /// Heartbeat intervals without any frame from the remote before the
+        /// connection is treated as dead and re-established
+        #[arg(
+            long,
+            env = "VIBE_HEARTBEAT_TIMEOUT_MULTIPLIER",
+            default_value_t = agent::INBOUND_TIMEOUT_MULTIPLIER
+        )]
+        heartbeat_timeout_multiplier: u32,
+
+        /// Seconds between application heartbeats, clamped to 5-120
+        #[arg(
+            long,
+            env = "VIBE_HEARTBEAT_SECS",
+            default_value_t = agent::APP_HEARTBEAT_INTERVAL.as_secs()
+        )]
+        heartbeat_secs: u64,
+
+        /// Task executions the agent runs at once; further requests are rejected
+        #[arg(
+            long,
+            env = "VIBE_MAX_CONCURRENT_EXECUTIONS",
+            default_value_t = agent::MAX_CONCURRENT_EXECUTIONS
+        )]
+        max_concurrent_executions: usize,
     },
 }
 
@@ -79,15 +186,76 @@ async fn main() -> Result<(), VibeKanbanError> {
 
     let cli = Cli::parse();
 
+    if cli.check_seed {
+        return run_seed_check().await;
+    }
+
     match cli.command.unwrap_or(Commands::Server) {
         Commands::Server => run_server().await,
-        Commands::Connect { token, url } => run_connect(token, url).await,
+        Commands::Doctor => run_doctor().await,
+        Commands::Status => run_status().await,
+        Commands::Connect {
+            token,
+            token_file,
+            store_token,
+            url,
+            tcp_keepalive_idle_secs,
+            tcp_keepalive_interval_secs,
+            tcp_keepalive_retries,
+            tcp_nodelay,
+            ca_cert,
+            insecure_skip_tls_verify,
+            heartbeat_timeout_multiplier,
+            heartbeat_secs,
+            max_concurrent_executions,
+        } => {
+            let tcp_config = TcpConfig {
+                keepalive_idle: Duration::from_secs(tcp_keepalive_idle_secs),
+                keepalive_interval: Duration::from_secs(tcp_keepalive_interval_secs),
+                keepalive_retries: tcp_keepalive_retries,
+                nodelay: tcp_nodelay,
+            };
+            let liveness = LivenessConfig {
+                app_heartbeat_interval: LivenessConfig::heartbeat_interval_from_secs(
+                    heartbeat_secs,
+                ),
+                inbound_timeout_multiplier: heartbeat_timeout_multiplier,
+                ..LivenessConfig::default()
+            };
+            let tls = match (insecure_skip_tls_verify, ca_cert) {
+                (true, _) => TlsVerification::Insecure,
+                (false, Some(path)) => TlsVerification::CustomCa(path),
+                (false, None) => TlsVerification::Native,
+            };
+            let (token, source) =
+                token::resolve_token(token, token_file.as_deref(), token::keyring_token, || {
+                    std::env::var(token::TOKEN_ENV).ok()
+                })?;
+            tracing::info!("Using connection token from {}", source);
+            if store_token && source != token::TokenSource::Keyring {
+                token::store_keyring_token(&token)?;
+                tracing::info!("Stored connection token in the OS keyring");
+            }
+            run_connect(
+                token,
+                url,
+                tcp_config,
+                tls,
+                liveness,
+                max_concurrent_executions,
+            )
+            .await
+        }
     }
 }
 
 async fn run_server() -> Result<(), VibeKanbanError> {
     let deployment = DeploymentImpl::new().await?;
     deployment.update_sentry_scope().await?;
+    let seeded = rbac_seed::seed_system_role_permissions(&deployment.db().pool).await?;
+    for change in seeded.describe() {
+        tracing::info!("Seeded {}", change);
+    }
     deployment
         .container()
         .cleanup_orphan_executions()
@@ -104,6 +272,7 @@ async fn run_server() -> Result<(), VibeKanbanError> {
         .await
         .map_err(DeploymentError::from)?;
     deployment.spawn_pr_monitor_service().await;
+    SessionCleanupService::spawn(deployment.db().clone()).await;
     deployment
         .track_if_analytics_allowed("session_start", serde_json::json!({}))
         .await;
@@ -131,26 +300,20 @@ async fn run_server() -> Result<(), VibeKanbanError> {
 
     let app_router = routes::router(deployment.clone());
 
-    let port = std::env::var("BACKEND_PORT")
+    let port_var = std::env::var("BACKEND_PORT")
         .or_else(|_| std::env::var("PORT"))
-        .ok()
-        .and_then(|s| {
-            // remove any ANSI codes, then turn into String
-            let cleaned =
-                String::from_utf8(strip(s.as_bytes())).expect("UTF-8 after stripping ANSI");
-            cleaned.trim().parse::<u16>().ok()
-        })
-        .unwrap_or_else(|| {
-            tracing::info!("No PORT environment variable set, using port 0 for auto-assignment");
-            0
-        }); // Use 0 to find free port if no specific port provided
+        .ok();
+    if port_var.is_none() {
+        tracing::info!("No PORT environment variable set, using port 0 for auto-assignment");
+    }
+    let port = parse_port(port_var.as_deref()).inspect_err(|e| tracing::error!("{}", e))?;
 
     let host = std::env::var("HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
     let listener = tokio::net::TcpListener::bind(format!("{host}:{port}")).await?;
     let actual_port = listener.local_addr()?.port(); // get → 53427 (example)
 
     // Write port file for discovery if prod, warn on fail
-    if let Err(e) = write_port_file(actual_port).await {
+    if let Err(e) = write_port_file(&host, actual_port).await {
         tracing::warn!("Failed to write port file: {}", e);
     }
 
@@ -178,110 +341,131 @@ async fn run_server() -> Result<(), VibeKanbanError> {
     Ok(())
 }
 
-async fn run_connect(token: String, url: String) -> Result<(), VibeKanbanError> {
-    tracing::info!("Initializing local agent environment...");
-    let _deployment = DeploymentImpl::new().await?;
-    
-    tracing::info!("Connecting to {}...", url);
-
-    // Construct the request with Authorization header
-    let mut request = url.into_client_request()
-        .map_err(|e| VibeKanbanError::Other(anyhow::anyhow!(e)))?;
-    
-    request.headers_mut().insert(
-        "Authorization", 
-        HeaderValue::from_str(&format!("Bearer {}", token))
-            .map_err(|e| VibeKanbanError::Other(anyhow::anyhow!(e)))?
-    );
+/// Port from `BACKEND_PORT`/`PORT`, with any ANSI codes stripped. Unset
+/// means 0 so the OS picks a free port; a value that isn't a port number is
+/// an error rather than silently falling back to a random one.
+fn parse_port(value: Option<&str>) -> anyhow::Result<u16> {
+    let Some(value) = value else {
+        return Ok(0);
+    };
+    let cleaned = String::from_utf8(strip(value.as_bytes())).expect("UTF-8 after stripping ANSI");
+    let cleaned = cleaned.trim();
+    cleaned.parse::<u16>().map_err(|_| {
+        anyhow::anyhow!(
+            "Invalid BACKEND_PORT/PORT value {cleaned:?}: expected a number between 0 and 65535"
+        )
+    })
+}
 
-    let (ws_stream, _) = connect_async(request).await
-        .map_err(|e| VibeKanbanError::Other(anyhow::anyhow!("Failed to connect: {}", e)))?;
-    
-    tracing::info!("Connected to remote dashboard");
-    let (mut write, mut read) = ws_stream.split();
+async fn run_seed_check() -> Result<(), VibeKanbanError> {
+    let db = DBService::new_read_only().await?;
+    let plan = rbac_seed::sync(&db.pool, SeedMode::Check).await?;
 
-    // Send initial heartbeat
-    write.send(Message::Text(serde_json::json!({ "type": "HEARTBEAT" }).to_string()))
-        .await
-        .map_err(|e| VibeKanbanError::Other(anyhow::anyhow!(e)))?;
+    if plan.is_empty() {
+        tracing::info!("Roles and permissions are fully seeded");
+        return Ok(());
+    }
 
-    // Heartbeat loop
-    let mut interval = tokio::time::interval(std::time::Duration::from_secs(15));
+    for change in plan.describe() {
+        tracing::warn!("Missing {}", change);
+    }
+    std::process::exit(1);
+}
 
-    loop {
-        tokio::select! {
-            _ = interval.tick() => {
-                 write.send(Message::Text(serde_json::json!({ "type": "HEARTBEAT" }).to_string()))
-                    .await
-                    .map_err(|e| VibeKanbanError::Other(anyhow::anyhow!(e)))?;
-            }
-            Some(message) = read.next() => {
-                match message {
-                    Ok(msg) => {
-                        if let Message::Text(text) = msg {
-                            tracing::debug!("Received: {}", text);
-                            if let Ok(data) = serde_json::from_str::<serde_json::Value>(&text) {
-                                if data["type"] == "EXECUTE" {
-                                    tracing::info!("Received execution task: {:?}", data["payload"]);
-                                    
-                                    // Ack reception
-                                    write.send(Message::Text(serde_json::json!({ 
-                                        "type": "EXECUTION_STARTED",
-                                        "taskId": data["payload"]["taskId"]
-                                    }).to_string())).await.ok();
-                                    
-                                    // TODO: Implement actual execution logic calling executors
-                                    // For now we log it.
-                                    // We would use deployment.container().create_execution(...)
-                                }
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        tracing::error!("WebSocket error: {}", e);
-                        break;
-                    }
-                }
-            }
-        }
+async fn run_status() -> Result<(), VibeKanbanError> {
+    let report = status::collect().await?;
+    print!("{}", report.render());
+
+    if !report.healthy() {
+        std::process::exit(1);
     }
+    Ok(())
+}
 
+async fn run_doctor() -> Result<(), VibeKanbanError> {
+    let report = doctor::run().await;
+    print!("{}", report.render());
+
+    if !report.passed() {
+        std::process::exit(1);
+    }
     Ok(())
 }
 
-pub async fn shutdown_signal() {
-    // Always wait for Ctrl+C
-    let ctrl_c = async {
-        if let Err(e) = tokio::signal::ctrl_c().await {
-            tracing::error!("Failed to install Ctrl+C handler: {e}");
-        }
-    };
+async fn run_connect(
+    token: String,
+    url: String,
+    tcp_config: TcpConfig,
+    tls: TlsVerification,
+    liveness: LivenessConfig,
+    max_concurrent_executions: usize,
+) -> Result<(), VibeKanbanError> {
+    if token.trim().is_empty() {
+        return Err(VibeKanbanError::EmptyToken);
+    }
+    let parsed = check_connect_url(&url)?;
+    if !is_secure_or_local(&parsed) {
+        tracing::warn!(
+            "Connecting to {} over unencrypted ws://; the token is sent in cleartext. Use wss://",
+            url
+        );
+    }
 
-    #[cfg(unix)]
-    {
-        use tokio::signal::unix::{SignalKind, signal};
-
-        // Try to install SIGTERM handler, but don't panic if it fails
-        let terminate = async {
-            if let Ok(mut sigterm) = signal(SignalKind::terminate()) {
-                sigterm.recv().await;
-            } else {
-                tracing::error!("Failed to install SIGTERM handler");
-                // Fallback: never resolves
-                std::future::pending::<()>().await;
-            }
-        };
+    tracing::info!("Initializing local agent environment...");
+    let deployment = DeploymentImpl::new().await?;
 
-        tokio::select! {
-            _ = ctrl_c => {},
-            _ = terminate => {},
+    agent::run(
+        token,
+        url,
+        tcp_config,
+        tls,
+        liveness,
+        max_concurrent_executions,
+        deployment.clone(),
+    )
+    .await?;
+    perform_cleanup_actions(&deployment).await;
+
+    Ok(())
+}
+
+/// Parse `--url` and require a `ws`/`wss` URL with a host
+fn check_connect_url(url: &str) -> Result<Url, VibeKanbanError> {
+    let invalid = |reason: String| VibeKanbanError::InvalidConnectUrl {
+        url: url.to_string(),
+        reason,
+    };
+    let parsed = Url::parse(url.trim()).map_err(|e| invalid(e.to_string()))?;
+    match parsed.scheme() {
+        "ws" | "wss" => {}
+        "http" => {
+            return Err(invalid(
+                "http:// is not a WebSocket scheme, use ws:// instead".into(),
+            ));
         }
+        "https" => {
+            return Err(invalid(
+                "https:// is not a WebSocket scheme, use wss:// instead".into(),
+            ));
+        }
+        other => return Err(invalid(format!("unsupported scheme {other:?}"))),
+    }
+    if parsed.host().is_none() {
+        return Err(invalid("missing host".to_string()));
     }
+    Ok(parsed)
+}
 
-    #[cfg(not(unix))]
-    {
-        // Only ctrl_c is available, so just await it
-        ctrl_c.await;
+/// `wss://` anywhere, or `ws://` to this machine
+fn is_secure_or_local(url: &Url) -> bool {
+    if url.scheme() == "wss" {
+        return true;
+    }
+    match url.host() {
+        Some(Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+        Some(Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
     }
 }
 
@@ -291,4 +475,69 @@ pub async fn perform_cleanup_actions(deployment: &DeploymentImpl) {
         .kill_all_running_processes()
         .await
         .expect("Failed to cleanly kill running execution processes");
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unset_port_picks_any_free_port() {
+        assert_eq!(parse_port(None).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_numeric_port_is_used() {
+        assert_eq!(parse_port(Some("8080")).unwrap(), 8080);
+        assert_eq!(parse_port(Some(" \x1b[32m8080\x1b[0m\n")).unwrap(), 8080);
+    }
+
+    #[test]
+    fn test_non_numeric_port_is_rejected() {
+        let err = parse_port(Some("abc")).unwrap_err();
+        assert!(err.to_string().contains("\"abc\""));
+        assert!(parse_port(Some("70000")).is_err());
+    }
+
+    #[test]
+    fn test_websocket_urls_are_accepted() {
+        assert!(check_connect_url("wss://vibe-kanban.pages.dev/api/v1/agents/local/ws").is_ok());
+        assert!(check_connect_url("ws://127.0.0.1:8787/ws").is_ok());
+    }
+
+    #[test]
+    fn test_http_urls_suggest_the_websocket_scheme() {
+        let err = check_connect_url("https://vibe-kanban.pages.dev/ws").unwrap_err();
+        assert!(err.to_string().contains("use wss:// instead"));
+
+        let err = check_connect_url("http://localhost:8787/ws").unwrap_err();
+        assert!(err.to_string().contains("use ws:// instead"));
+    }
+
+    #[test]
+    fn test_malformed_urls_are_rejected() {
+        assert!(matches!(
+            check_connect_url("vibe-kanban.pages.dev/ws"),
+            Err(VibeKanbanError::InvalidConnectUrl { .. })
+        ));
+        assert!(matches!(
+            check_connect_url("ftp://example.com/ws"),
+            Err(VibeKanbanError::InvalidConnectUrl { .. })
+        ));
+        assert!(matches!(
+            check_connect_url(""),
+            Err(VibeKanbanError::InvalidConnectUrl { .. })
+        ));
+    }
+
+    #[test]
+    fn test_plain_ws_is_only_trusted_locally() {
+        let secure = |url: &str| is_secure_or_local(&check_connect_url(url).unwrap());
+        assert!(secure("wss://example.com/ws"));
+        assert!(secure("ws://localhost:8787/ws"));
+        assert!(secure("ws://127.0.0.1:8787/ws"));
+        assert!(secure("ws://[::1]:8787/ws"));
+        assert!(!secure("ws://example.com/ws"));
+        assert!(!secure("ws://10.0.0.5/ws"));
+    }
+}
@@ -0,0 +1,106 @@
+//! Non-blocking audit event recording.
+//!
+//! Events are pushed onto a bounded channel and persisted by a background
+//! task, so request handling never waits on (or fails because of) the
+//! audit write.
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicU64, Ordering},
+};
+
+use db::models::audit::{AuditEvent, CreateAuditEvent};
+use sqlx::SqlitePool;
+use tokio::sync::mpsc;
+
+const AUDIT_CHANNEL_CAPACITY: usize = 1024;
+
+/// Handle for submitting audit events, inserted into request extensions.
+#[derive(Debug, Clone)]
+pub struct AuditSink {
+    tx: mpsc::Sender<CreateAuditEvent>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl AuditSink {
+    /// Spawn the background writer and return a sink that feeds it.
+    pub fn spawn(pool: SqlitePool) -> Self {
+        let (tx, mut rx) = mpsc::channel::<CreateAuditEvent>(AUDIT_CHANNEL_CAPACITY);
+
+        tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                if let Err(e) = AuditEvent::record(&pool, &event).await {
+                    tracing::warn!(
+                        error = %e,
+                        event_type = %event.event_type,
+                        "Failed to record audit event, dropping"
+                    );
+                }
+            }
+        });
+
+        Self {
+            tx,
+            dropped: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Queue an event for recording. Drops the event if the writer is
+    /// saturated or gone.
+    pub fn record(&self, event: CreateAuditEvent) {
+        if let Err(e) = self.tx.try_send(event) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            tracing::warn!(error = %e, "Audit channel unavailable, dropping event");
+        }
+    }
+
+    /// Events dropped because the channel was full or closed.
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_record_tolerates_db_errors() {
+        // No migrations have run, so every insert fails.
+        let pool = SqlitePool::connect_lazy("sqlite::memory:").unwrap();
+        let sink = AuditSink::spawn(pool);
+
+        for _ in 0..3 {
+            sink.record(CreateAuditEvent::permission_denied(
+                None,
+                "task_read".to_string(),
+                "/api/tasks".to_string(),
+            ));
+        }
+
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        assert!(!sink.tx.is_closed());
+    }
+
+    #[tokio::test]
+    async fn test_record_does_not_block_when_full() {
+        let (tx, _rx) = mpsc::channel(1);
+        let sink = AuditSink {
+            tx,
+            dropped: Arc::new(AtomicU64::new(0)),
+        };
+
+        let started = std::time::Instant::now();
+        for _ in 0..10 {
+            sink.record(CreateAuditEvent::permission_denied(
+                None,
+                "task_read".to_string(),
+                "/api/tasks".to_string(),
+            ));
+        }
+
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+        // The first event fills the channel; nothing drains it
+        assert_eq!(sink.dropped_count(), 9);
+    }
+}
@@ -8,24 +8,26 @@
 //! - "Own*" permission logic for task/attempt ownership
 //! - Helper functions: `has_permission`, `can_access_task`, `can_edit_task`
 
+use std::{collections::HashSet, sync::Arc};
+
 use axum::{
-    extract::{Path, Request, State},
+    extract::{OriginalUri, Path, Request, State},
     http::{HeaderMap, StatusCode},
     middleware::Next,
     response::Response,
 };
-use db::models::{task::Task, workspace::Workspace};
+use db::models::{audit::CreateAuditEvent, session::Session, task::Task, workspace::Workspace};
 use deployment::Deployment;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
 use ts_rs::TS;
 use uuid::Uuid;
 
-use crate::DeploymentImpl;
+use crate::{DeploymentImpl, middleware::audit::AuditSink};
 
 /// Permission keys for authorization checks.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, TS)]
 #[serde(rename_all = "snake_case")]
+#[ts(export)]
 pub enum Permission {
     // Task permissions
     TaskRead,
@@ -67,6 +69,7 @@ pub enum Permission {
 /// Role definitions with associated permissions.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
 #[serde(rename_all = "snake_case")]
+#[ts(export)]
 pub enum Role {
     /// Full access to all resources
     Admin,
@@ -149,21 +152,23 @@ impl Role {
 }
 
 /// User context for authorization, extracted from request.
+///
+/// The role's permission set is resolved once at construction and shared
+/// across clones, so repeated checks within a request don't rebuild it. The
+/// role is only readable through [`AuthContext::role`], so it can't be
+/// changed without the permissions following.
 #[derive(Debug, Clone)]
 pub struct AuthContext {
     pub user_id: Option<Uuid>,
-    pub role: Role,
+    role: Role,
     pub workspace_id: Option<Uuid>,
+    perms: Arc<HashSet<Permission>>,
 }
 
 impl Default for AuthContext {
     fn default() -> Self {
-        Self {
-            user_id: None,
-            // Default to Admin for local deployment (no auth required by default)
-            role: Role::Admin,
-            workspace_id: None,
-        }
+        // Default to Admin for local deployment (no auth required by default)
+        Self::new(None, Role::Admin)
     }
 }
 
@@ -174,9 +179,20 @@ impl AuthContext {
             user_id,
             role,
             workspace_id: None,
+            perms: Arc::new(role.permissions()),
         }
     }
 
+    /// The role this context was created with.
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+    /// The permission set granted by this context's role.
+    pub fn permissions(&self) -> &HashSet<Permission> {
+        &self.perms
+    }
+
     /// Set the workspace context.
     pub fn with_workspace(mut self, workspace_id: Option<Uuid>) -> Self {
         self.workspace_id = workspace_id;
@@ -185,19 +201,17 @@ impl AuthContext {
 
     /// Check if the user has the given permission.
     pub fn has_permission(&self, permission: Permission) -> bool {
-        self.role.permissions().contains(&permission)
+        self.perms.contains(&permission)
     }
 
     /// Check if the user has any of the given permissions.
     pub fn has_any_permission(&self, permissions: &[Permission]) -> bool {
-        let user_perms = self.role.permissions();
-        permissions.iter().any(|p| user_perms.contains(p))
+        permissions.iter().any(|p| self.perms.contains(p))
     }
 
     /// Check if the user has all of the given permissions.
     pub fn has_all_permissions(&self, permissions: &[Permission]) -> bool {
-        let user_perms = self.role.permissions();
-        permissions.iter().all(|p| user_perms.contains(p))
+        permissions.iter().all(|p| self.perms.contains(p))
     }
 }
 
@@ -261,15 +275,34 @@ async fn require_permission_inner(
     if !auth_context.has_permission(permission) {
         tracing::warn!(
             ?permission,
-            role = ?auth_context.role,
+            role = ?auth_context.role(),
             "Permission denied"
         );
+        if let Some(sink) = request.extensions().get::<AuditSink>() {
+            sink.record(CreateAuditEvent::permission_denied(
+                auth_context.user_id,
+                permission_key(permission),
+                request
+                    .extensions()
+                    .get::<OriginalUri>()
+                    .map_or_else(|| request.uri().path(), |uri| uri.path())
+                    .to_string(),
+            ));
+        }
         return Err(StatusCode::FORBIDDEN);
     }
 
     Ok(next.run(request).await)
 }
 
+/// Snake-case key for a permission, matching its serialized form.
+fn permission_key(permission: Permission) -> String {
+    serde_json::to_value(permission)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_else(|| format!("{permission:?}"))
+}
+
 /// Check if a user has a specific permission.
 pub fn has_permission(auth_context: &AuthContext, permission: Permission) -> bool {
     auth_context.has_permission(permission)
@@ -404,14 +437,27 @@ pub async fn can_access_session(
     auth_context: &AuthContext,
     session_id: Uuid,
 ) -> bool {
+    session_read_allowed(auth_context.permissions(), || {
+        is_session_owner(pool, auth_context, session_id)
+    })
+    .await
+}
+
+/// Resolve session read access from an effective permission set.
+/// The ownership check is only run when the set lacks blanket `SessionRead`.
+async fn session_read_allowed<F, Fut>(permissions: &HashSet<Permission>, is_owner: F) -> bool
+where
+    F: FnOnce() -> Fut,
+    Fut: std::future::Future<Output = bool>,
+{
     // Admin and users with SessionRead can access any session
-    if auth_context.has_permission(Permission::SessionRead) {
+    if permissions.contains(&Permission::SessionRead) {
         return true;
     }
 
     // Check own session access
-    if auth_context.has_permission(Permission::OwnSessionRead) {
-        return is_session_owner(pool, auth_context, session_id).await;
+    if permissions.contains(&Permission::OwnSessionRead) {
+        return is_owner().await;
     }
 
     false
@@ -547,8 +593,58 @@ pub async fn load_task_auth_context(
     Ok(next.run(request).await)
 }
 
+/// Middleware that loads session context and validates access.
+pub async fn load_session_auth_context(
+    State(pool): State<sqlx::SqlitePool>,
+    Path(session_id): Path<Uuid>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    // Load the session from the database
+    let session = match Session::find_by_id(&pool, session_id).await {
+        Ok(Some(s)) => s,
+        Ok(None) => {
+            tracing::warn!("Session {} not found", session_id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch Session {}: {}", session_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    // Get auth context
+    let auth_context = request
+        .extensions()
+        .get::<AuthContext>()
+        .cloned()
+        .unwrap_or_default();
+
+    // Check session access
+    if !can_access_session(&pool, &auth_context, session_id).await {
+        tracing::warn!("Access denied for session {}", session_id);
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    request.extensions_mut().insert(session);
+
+    Ok(next.run(request).await)
+}
+
 #[cfg(test)]
 mod tests {
+    use axum::{
+        Extension, Router, body::Body, http::Request, middleware::from_fn_with_state, routing::get,
+    };
+    use db::models::{
+        project::{CreateProject, Project},
+        session::CreateSession,
+        task::CreateTask,
+        workspace::CreateWorkspace,
+    };
+    use sqlx::{SqlitePool, sqlite::SqlitePoolOptions};
+    use tower::ServiceExt;
+
     use super::*;
 
     #[test]
@@ -605,10 +701,168 @@ mod tests {
         assert!(!viewer.has_all_permissions(&[Permission::TaskRead, Permission::TaskCreate]));
     }
 
+    #[test]
+    fn test_clones_share_cached_permissions() {
+        let auth = AuthContext::new(Some(Uuid::new_v4()), Role::Member);
+        let cloned = auth.clone();
+        assert!(Arc::ptr_eq(&auth.perms, &cloned.perms));
+
+        // The role is only set on construction, so the workspace builder
+        // keeps the cached set in step with it
+        let scoped = cloned.with_workspace(Some(Uuid::new_v4()));
+        assert_eq!(scoped.role(), Role::Member);
+        assert!(Arc::ptr_eq(&auth.perms, &scoped.perms));
+        assert_eq!(scoped.permissions(), &Role::Member.permissions());
+    }
+
+    #[test]
+    fn test_cached_permissions_match_role() {
+        for role in [Role::Admin, Role::Member, Role::Viewer] {
+            let auth = AuthContext::new(None, role);
+            assert_eq!(auth.role(), role);
+            assert_eq!(auth.permissions(), &role.permissions());
+        }
+    }
+
+    #[test]
+    fn test_permission_key_is_snake_case() {
+        assert_eq!(permission_key(Permission::TaskRead), "task_read");
+        assert_eq!(permission_key(Permission::AdminAccess), "admin_access");
+    }
+
+    #[test]
+    fn test_ts_declaration_lists_every_permission() {
+        let decl = Permission::decl();
+        // Admins hold every permission, so this covers all variants
+        for permission in Role::Admin.permissions() {
+            let key = format!("\"{}\"", permission_key(permission));
+            assert!(decl.contains(&key), "{key} missing from {decl}");
+        }
+        assert!(decl.contains("\"own_task_read\""));
+        assert!(!decl.contains("OwnTaskRead"));
+
+        let roles = Role::decl();
+        for role in ["\"admin\"", "\"member\"", "\"viewer\""] {
+            assert!(roles.contains(role), "{role} missing from {roles}");
+        }
+    }
+
     #[test]
     fn test_workspace_context() {
         let workspace_id = Uuid::new_v4();
         let auth = AuthContext::default().with_workspace(Some(workspace_id));
         assert_eq!(auth.workspace_id, Some(workspace_id));
     }
+
+    #[tokio::test]
+    async fn test_viewer_can_access_session() {
+        let pool = sqlx::SqlitePool::connect_lazy("sqlite::memory:").unwrap();
+        let viewer = AuthContext::new(Some(Uuid::new_v4()), Role::Viewer);
+        assert!(viewer.has_permission(Permission::SessionRead));
+        assert!(can_access_session(&pool, &viewer, Uuid::new_v4()).await);
+    }
+
+    #[tokio::test]
+    async fn test_session_access_rejected_without_permissions() {
+        let no_permissions = HashSet::new();
+        assert!(!session_read_allowed(&no_permissions, || async { true }).await);
+
+        let own_only = HashSet::from([Permission::OwnSessionRead]);
+        assert!(session_read_allowed(&own_only, || async { true }).await);
+        assert!(!session_read_allowed(&own_only, || async { false }).await);
+    }
+
+    async fn session_pool() -> (SqlitePool, Uuid) {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("../db/migrations").run(&pool).await.unwrap();
+
+        let project = Project::create(
+            &pool,
+            &CreateProject {
+                name: "Project".to_string(),
+                repositories: Vec::new(),
+            },
+            Uuid::new_v4(),
+        )
+        .await
+        .unwrap();
+        let task = Task::create(
+            &pool,
+            &CreateTask::from_title_description(project.id, "Task".to_string(), None),
+            Uuid::new_v4(),
+        )
+        .await
+        .unwrap();
+        let workspace = Workspace::create(
+            &pool,
+            &CreateWorkspace {
+                branch: "main".to_string(),
+                agent_working_dir: None,
+            },
+            Uuid::new_v4(),
+            task.id,
+        )
+        .await
+        .unwrap();
+        let session = Session::create(
+            &pool,
+            &CreateSession { executor: None },
+            Uuid::new_v4(),
+            workspace.id,
+        )
+        .await
+        .unwrap();
+        (pool, session.id)
+    }
+
+    /// Status of `GET /sessions/{session_id}` behind the session loader,
+    /// answered by a handler that needs the loaded session
+    async fn get_session(pool: &SqlitePool, auth: AuthContext, session_id: Uuid) -> StatusCode {
+        let app = Router::new()
+            .route(
+                "/sessions/{session_id}",
+                get(|Extension(session): Extension<Session>| async move { session.id.to_string() }),
+            )
+            .layer(from_fn_with_state(pool.clone(), load_session_auth_context))
+            .layer(Extension(auth));
+        app.oneshot(
+            Request::get(format!("/sessions/{session_id}"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+        .status()
+    }
+
+    #[tokio::test]
+    async fn test_session_routes_enforce_session_access() {
+        let (pool, session_id) = session_pool().await;
+
+        let viewer = AuthContext::new(Some(Uuid::new_v4()), Role::Viewer);
+        assert_eq!(
+            get_session(&pool, viewer.clone(), session_id).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            get_session(&pool, viewer, Uuid::new_v4()).await,
+            StatusCode::NOT_FOUND
+        );
+
+        // Every role grants some session access, so build an empty context
+        let unauthenticated = AuthContext {
+            user_id: None,
+            role: Role::Viewer,
+            workspace_id: None,
+            perms: Arc::default(),
+        };
+        assert_eq!(
+            get_session(&pool, unauthenticated, session_id).await,
+            StatusCode::FORBIDDEN
+        );
+    }
 }
@@ -7,17 +7,18 @@
 use axum::{
     body::Body,
     extract::State,
-    http::{Request, StatusCode},
+    http::{Request, StatusCode, header::USER_AGENT},
     middleware::Next,
     response::{IntoResponse, Response},
 };
 use chrono::{DateTime, TimeZone, Utc};
 use db::models::{
-    user::{UpsertUser, User},
-    user_session::UserSession,
+    user::{UpsertUser, User, UserError, normalize_email},
+    user_session::{DEFAULT_SESSION_DURATION, UserSession, UserSessionError},
 };
 use deployment::Deployment;
 use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
 use thiserror::Error;
 use tracing::warn;
 
@@ -26,6 +27,9 @@ use crate::DeploymentImpl;
 /// Header name for CF Access JWT assertion
 pub const CF_ACCESS_JWT_HEADER: &str = "CF-Access-JWT-Assertion";
 
+/// Header carrying the original client IP when proxied through Cloudflare
+pub const CF_CONNECTING_IP_HEADER: &str = "CF-Connecting-IP";
+
 /// Header name for CF Access client ID (service token auth)
 pub const CF_ACCESS_CLIENT_ID_HEADER: &str = "CF-Access-Client-Id";
 
@@ -212,22 +216,27 @@ pub async fn require_cf_access_auth(
 
     // Sync user from CF Access identity
     let user_data = UpsertUser {
-        email: claims.email.clone(),
+        email: normalize_email(&claims.email),
         name: claims.display_name(),
         avatar_url: None,
         cf_access_id: Some(claims.sub.clone()),
     };
 
-    let user = match User::upsert(pool, &user_data).await {
+    let mut user = match User::upsert(pool, &user_data).await {
         Ok(user) => user,
+        Err(UserError::Deactivated) => {
+            warn!(email = %claims.email, "Rejected sign-in for deactivated user");
+            return StatusCode::FORBIDDEN.into_response();
+        }
         Err(e) => {
             warn!(?e, "Failed to upsert user from CF Access");
             return StatusCode::INTERNAL_SERVER_ERROR.into_response();
         }
     };
 
-    // Create or update session
-    let session = match UserSession::create(pool, user.id, Some(&claims.sub), None).await {
+    // Reuse and extend the current session, or start a new one
+    let client = SessionClient::from_request(&req);
+    let session = match resolve_session(pool, &mut user, &claims.sub, &client).await {
         Ok(session) => session,
         Err(e) => {
             warn!(?e, "Failed to create session");
@@ -245,6 +254,75 @@ pub async fn require_cf_access_auth(
     next.run(req).await
 }
 
+/// Client details recorded on a session
+#[derive(Debug)]
+struct SessionClient {
+    user_agent: Option<String>,
+    ip_address: Option<String>,
+}
+
+impl SessionClient {
+    fn from_request(req: &Request<Body>) -> Self {
+        let header = |name: &str| {
+            req.headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(String::from)
+        };
+
+        Self {
+            user_agent: header(USER_AGENT.as_str()),
+            ip_address: header(CF_CONNECTING_IP_HEADER),
+        }
+    }
+}
+
+/// Reuse the user's latest active session from the same client, sliding its
+/// expiry forward, or create a new one if none is usable. Only a new session
+/// counts as a login, and `user` is refreshed to reflect it.
+async fn resolve_session(
+    pool: &SqlitePool,
+    user: &mut User,
+    cf_access_id: &str,
+    client: &SessionClient,
+) -> Result<UserSession, UserSessionError> {
+    let existing = UserSession::find_latest_valid_for_user(
+        pool,
+        user.id,
+        Some(cf_access_id),
+        client.user_agent.as_deref(),
+    )
+    .await?
+    .filter(|session| !session.is_inactive());
+
+    match existing {
+        Some(session) => {
+            UserSession::touch_and_extend(pool, session.id, DEFAULT_SESSION_DURATION).await
+        }
+        None => {
+            let session = UserSession::create(
+                pool,
+                user.id,
+                Some(cf_access_id),
+                None,
+                client.user_agent.as_deref(),
+                client.ip_address.as_deref(),
+            )
+            .await?;
+
+            // Login tracking is best-effort and must not block sign-in
+            match User::record_login(pool, user.id).await {
+                Ok(updated) => *user = updated,
+                Err(e) => warn!(?e, user_id = %user.id, "Failed to record login"),
+            }
+
+            Ok(session)
+        }
+    }
+}
+
 /// Optional middleware that extracts CF Access auth if present but doesn't require it.
 /// Useful for routes that work with or without authentication.
 pub async fn optional_cf_access_auth(
@@ -252,6 +330,8 @@ pub async fn optional_cf_access_auth(
     mut req: Request<Body>,
     next: Next,
 ) -> Response {
+    let client = SessionClient::from_request(&req);
+
     // Try to extract JWT from header
     if let Some(jwt_header) = req.headers().get(CF_ACCESS_JWT_HEADER) {
         if let Ok(jwt) = jwt_header.to_str() {
@@ -260,15 +340,15 @@ pub async fn optional_cf_access_auth(
 
                 // Sync user from CF Access identity
                 let user_data = UpsertUser {
-                    email: claims.email.clone(),
+                    email: normalize_email(&claims.email),
                     name: claims.display_name(),
                     avatar_url: None,
                     cf_access_id: Some(claims.sub.clone()),
                 };
 
-                if let Ok(user) = User::upsert(pool, &user_data).await {
+                if let Ok(mut user) = User::upsert(pool, &user_data).await {
                     if let Ok(session) =
-                        UserSession::create(pool, user.id, Some(&claims.sub), None).await
+                        resolve_session(pool, &mut user, &claims.sub, &client).await
                     {
                         req.extensions_mut().insert(AuthContext {
                             user,
@@ -288,6 +368,22 @@ pub async fn optional_cf_access_auth(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_session_client_from_request() {
+        let req = Request::builder()
+            .header(USER_AGENT, "curl/8.7.1")
+            .header(CF_CONNECTING_IP_HEADER, "203.0.113.7")
+            .body(Body::empty())
+            .unwrap();
+        let client = SessionClient::from_request(&req);
+        assert_eq!(client.user_agent.as_deref(), Some("curl/8.7.1"));
+        assert_eq!(client.ip_address.as_deref(), Some("203.0.113.7"));
+
+        let client = SessionClient::from_request(&Request::new(Body::empty()));
+        assert!(client.user_agent.is_none());
+        assert!(client.ip_address.is_none());
+    }
+
     #[test]
     fn test_base64_url_decode() {
         // Test basic decoding
@@ -0,0 +1,73 @@
+//! Request metrics.
+//!
+//! Each request is counted and timed under its matched route template, so
+//! paths with ids don't explode label cardinality.
+
+use std::time::Instant;
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use services::services::metrics::MetricsService;
+
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+pub async fn record_http_metrics(
+    State(metrics): State<MetricsService>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or(UNMATCHED_ROUTE, MatchedPath::as_str)
+        .to_string();
+    let started = Instant::now();
+
+    let response = next.run(request).await;
+
+    metrics.observe_http_request(
+        &method,
+        &route,
+        response.status().as_u16(),
+        started.elapsed(),
+    );
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router, body::Body, http::StatusCode, middleware::from_fn_with_state, routing::get,
+    };
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_requests_are_counted_by_route() {
+        let metrics = MetricsService::new();
+        let app = Router::new()
+            .route("/items/{id}", get(|| async { "ok" }))
+            .layer(from_fn_with_state(metrics.clone(), record_http_metrics));
+
+        for id in ["1", "2"] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .uri(format!("/items/{id}"))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        assert_eq!(metrics.http_requests_total("GET", "/items/{id}", 200), 2);
+    }
+}
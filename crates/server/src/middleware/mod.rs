@@ -1,7 +1,11 @@
+pub mod audit;
 pub mod authorization;
 pub mod cf_access;
+pub mod metrics;
 pub mod model_loaders;
 
+pub use audit::*;
 pub use authorization::*;
 pub use cf_access::*;
+pub use metrics::*;
 pub use model_loaders::*;
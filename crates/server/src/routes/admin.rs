@@ -0,0 +1,103 @@
+use axum::{
+    Extension, Router,
+    extract::{Path, State},
+    middleware::from_fn,
+    response::Json as ResponseJson,
+    routing::post,
+};
+use db::models::user_session::UserSession;
+use deployment::Deployment;
+use serde::Serialize;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    middleware::{Permission, authorization::AuthContext, require_auth, require_permission},
+};
+
+/// Response for POST /api/admin/users/{user_id}/logout-all
+#[derive(Debug, Serialize, TS)]
+#[ts(export)]
+pub struct ForceLogoutResponse {
+    /// Number of sessions that were invalidated
+    pub sessions_revoked: u64,
+}
+
+/// POST /api/admin/users/{user_id}/logout-all - Invalidate every session of
+/// a user (admin only)
+///
+/// Cloudflare Access starts a fresh session on the user's next request for as
+/// long as their Access login is valid, so to lock a compromised account out
+/// the user must also be revoked in Cloudflare Access.
+pub async fn force_logout(
+    State(deployment): State<DeploymentImpl>,
+    Extension(auth): Extension<AuthContext>,
+    Path(user_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<ForceLogoutResponse>>, ApiError> {
+    let count = UserSession::delete_all_for_user(&deployment.db().pool, user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!(?e, "Failed to delete all sessions");
+            ApiError::Internal(e.to_string())
+        })?;
+
+    tracing::info!(
+        %user_id,
+        admin_id = ?auth.user_id,
+        sessions_revoked = count,
+        "Admin logged out user"
+    );
+    Ok(ResponseJson(ApiResponse::success(ForceLogoutResponse {
+        sessions_revoked: count,
+    })))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    admin_only(Router::new().route("/admin/users/{user_id}/logout-all", post(force_logout)))
+        .layer(from_fn(require_auth))
+}
+
+fn admin_only<S>(router: Router<S>) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    router.layer(from_fn(require_permission(Permission::AdminAccess)))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::middleware::Role;
+
+    async fn post_as(role: Role) -> StatusCode {
+        let app = admin_only(
+            Router::new().route("/admin/users/{user_id}/logout-all", post(|| async { "ok" })),
+        )
+        .layer(Extension(AuthContext::new(Some(Uuid::new_v4()), role)));
+
+        app.oneshot(
+            Request::post(format!("/admin/users/{}/logout-all", Uuid::new_v4()))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+        .status()
+    }
+
+    #[tokio::test]
+    async fn test_force_logout_requires_admin() {
+        assert_eq!(post_as(Role::Member).await, StatusCode::FORBIDDEN);
+        assert_eq!(post_as(Role::Viewer).await, StatusCode::FORBIDDEN);
+        assert_eq!(post_as(Role::Admin).await, StatusCode::OK);
+    }
+}
@@ -0,0 +1,45 @@
+use axum::{
+    Router,
+    extract::{Query, State},
+    middleware::from_fn,
+    response::Json as ResponseJson,
+    routing::get,
+};
+use db::models::audit::AuditEvent;
+use deployment::Deployment;
+use serde::Deserialize;
+use utils::response::ApiResponse;
+
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    middleware::{Permission, require_auth, require_permission},
+};
+
+const DEFAULT_AUDIT_LIMIT: i64 = 100;
+const MAX_AUDIT_LIMIT: i64 = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct AuditEventsQuery {
+    pub limit: Option<i64>,
+}
+
+/// GET /api/audit/events - List recent audit events (admin only)
+pub async fn list_audit_events(
+    State(deployment): State<DeploymentImpl>,
+    Query(query): Query<AuditEventsQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<AuditEvent>>>, ApiError> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_AUDIT_LIMIT)
+        .clamp(1, MAX_AUDIT_LIMIT);
+    let events = AuditEvent::list_recent(&deployment.db().pool, limit).await?;
+    Ok(ResponseJson(ApiResponse::success(events)))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new()
+        .route("/audit/events", get(list_audit_events))
+        .layer(from_fn(require_permission(Permission::AdminAccess)))
+        .layer(from_fn(require_auth))
+}
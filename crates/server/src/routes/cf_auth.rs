@@ -3,7 +3,12 @@
 //! These routes handle user authentication status and logout functionality
 //! when using Cloudflare Access Zero Trust authentication.
 
-use axum::{Extension, Json, Router, extract::State, routing::{get, post}};
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{delete, get, post},
+};
 use chrono::{DateTime, Utc};
 use db::models::user_session::UserSession;
 use deployment::Deployment;
@@ -31,6 +36,8 @@ pub struct UserResponse {
     pub avatar_url: Option<String>,
     #[ts(type = "Date")]
     pub created_at: DateTime<Utc>,
+    #[ts(type = "Date | null")]
+    pub last_login_at: Option<DateTime<Utc>>,
 }
 
 /// Session information in auth response
@@ -44,6 +51,23 @@ pub struct SessionResponse {
     pub created_at: DateTime<Utc>,
 }
 
+/// An active session in GET /api/auth/sessions
+#[derive(Debug, Serialize, TS)]
+#[ts(export)]
+pub struct ActiveSessionResponse {
+    pub id: Uuid,
+    #[ts(type = "Date")]
+    pub created_at: DateTime<Utc>,
+    #[ts(type = "Date")]
+    pub last_used_at: DateTime<Utc>,
+    #[ts(type = "Date")]
+    pub expires_at: DateTime<Utc>,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    /// Whether this is the session making the request
+    pub current: bool,
+}
+
 /// Response for logout operation
 #[derive(Debug, Serialize, TS)]
 #[ts(export)]
@@ -64,6 +88,8 @@ pub fn router() -> Router<DeploymentImpl> {
     Router::new()
         .route("/auth/me", get(get_me))
         .route("/auth/logout", post(logout))
+        .route("/auth/sessions", get(list_sessions))
+        .route("/auth/sessions/{session_id}", delete(revoke_session))
 }
 
 /// GET /api/auth/me - Get current authenticated user
@@ -81,6 +107,7 @@ async fn get_me(
             name: auth.user.name,
             avatar_url: auth.user.avatar_url,
             created_at: auth.user.created_at,
+            last_login_at: auth.user.last_login_at,
         },
         session: SessionResponse {
             id: auth.session.id,
@@ -131,6 +158,60 @@ async fn logout(
     }
 }
 
+/// GET /api/auth/sessions - List the current user's active sessions
+///
+/// Sessions are ordered by most recent use.
+#[axum::debug_handler]
+async fn list_sessions(
+    State(deployment): State<DeploymentImpl>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<Json<Vec<ActiveSessionResponse>>, ApiError> {
+    let pool = &deployment.db().pool;
+
+    let sessions = UserSession::list_active_for_user(pool, auth.user.id)
+        .await
+        .map_err(|e| {
+            tracing::error!(?e, "Failed to list sessions");
+            ApiError::Internal(e.to_string())
+        })?;
+
+    Ok(Json(
+        sessions
+            .into_iter()
+            .map(|session| ActiveSessionResponse {
+                current: session.id == auth.session.id,
+                id: session.id,
+                created_at: session.created_at,
+                last_used_at: session.last_used_at,
+                expires_at: session.expires_at,
+                user_agent: session.user_agent,
+                ip_address: session.ip_address,
+            })
+            .collect(),
+    ))
+}
+
+/// DELETE /api/auth/sessions/{session_id} - Revoke one of the current user's sessions
+///
+/// Returns 404 if the session does not exist or belongs to another user.
+#[axum::debug_handler]
+async fn revoke_session(
+    State(deployment): State<DeploymentImpl>,
+    Extension(auth): Extension<AuthContext>,
+    Path(session_id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    let pool = &deployment.db().pool;
+
+    match UserSession::delete_for_user(pool, session_id, auth.user.id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!(?e, "Failed to revoke session");
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -14,7 +14,7 @@ use executors::{
         AvailabilityInfo, BaseAgentCapability, BaseCodingAgent, StandardCodingAgentExecutor,
     },
     mcp_config::{McpConfig, read_agent_config, write_agent_config},
-    profile::{ExecutorConfigs, ExecutorProfileId},
+    profile::{ExecutorConfigs, ExecutorInfo, ExecutorProfileId},
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -36,6 +36,7 @@ pub fn router() -> Router<DeploymentImpl> {
         .route("/sounds/{sound}", get(get_sound))
         .route("/mcp-config", get(get_mcp_servers).post(update_mcp_servers))
         .route("/profiles", get(get_profiles).put(update_profiles))
+        .route("/executors", get(list_executors))
         .route(
             "/editors/check-availability",
             get(check_editor_availability),
@@ -385,6 +386,13 @@ pub struct ProfilesContent {
     pub path: String,
 }
 
+/// Executors this deployment can run, with their variants and capabilities
+async fn list_executors() -> ResponseJson<ApiResponse<Vec<ExecutorInfo>>> {
+    ResponseJson(ApiResponse::success(
+        ExecutorConfigs::get_cached().executor_infos(),
+    ))
+}
+
 async fn get_profiles(
     State(_deployment): State<DeploymentImpl>,
 ) -> ResponseJson<ApiResponse<ProfilesContent>> {
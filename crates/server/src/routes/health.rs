@@ -1,6 +1,154 @@
-use axum::response::Json;
+use std::time::Duration;
+
+use axum::{extract::State, http::StatusCode, response::Json};
+use deployment::Deployment;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use ts_rs::TS;
 use utils::response::ApiResponse;
 
+use crate::DeploymentImpl;
+
+/// Upper bound on each readiness check, so a hung dependency fails the probe
+/// instead of hanging it.
+const READINESS_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum DependencyState {
+    Up,
+    Down,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct DependencyStatus {
+    pub name: String,
+    pub status: DependencyState,
+    /// Whether the service is unready while this dependency is down
+    pub required: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ReadinessResponse {
+    pub ready: bool,
+    pub dependencies: Vec<DependencyStatus>,
+}
+
+/// Liveness: the process is up and serving requests.
 pub async fn health_check() -> Json<ApiResponse<String>> {
     Json(ApiResponse::success("OK".to_string()))
 }
+
+/// Readiness: every required dependency is reachable. Responds 503 otherwise.
+pub async fn readiness_check(
+    State(deployment): State<DeploymentImpl>,
+) -> (StatusCode, Json<ReadinessResponse>) {
+    let dependencies = vec![check_database(&deployment.db().pool).await];
+    let response = readiness(dependencies);
+    let status = if response.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(response))
+}
+
+async fn check_database(pool: &SqlitePool) -> DependencyStatus {
+    let result = tokio::time::timeout(
+        READINESS_CHECK_TIMEOUT,
+        sqlx::query("SELECT 1").execute(pool),
+    )
+    .await;
+
+    let error = match result {
+        Ok(Ok(_)) => None,
+        Ok(Err(e)) => Some(e.to_string()),
+        Err(_) => Some(format!(
+            "timed out after {}ms",
+            READINESS_CHECK_TIMEOUT.as_millis()
+        )),
+    };
+    if let Some(error) = &error {
+        tracing::warn!("Readiness check failed for database: {}", error);
+    }
+
+    DependencyStatus {
+        name: "database".to_string(),
+        status: if error.is_none() {
+            DependencyState::Up
+        } else {
+            DependencyState::Down
+        },
+        required: true,
+        error,
+    }
+}
+
+fn readiness(dependencies: Vec<DependencyStatus>) -> ReadinessResponse {
+    let ready = dependencies
+        .iter()
+        .all(|dep| !dep.required || dep.status == DependencyState::Up);
+    ReadinessResponse {
+        ready,
+        dependencies,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+
+    async fn memory_pool() -> SqlitePool {
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_readiness_all_healthy() {
+        let pool = memory_pool().await;
+
+        let response = readiness(vec![check_database(&pool).await]);
+
+        assert!(response.ready);
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["ready"], true);
+        assert_eq!(json["dependencies"][0]["name"], "database");
+        assert_eq!(json["dependencies"][0]["status"], "up");
+        assert!(json["dependencies"][0]["error"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_readiness_database_down() {
+        let pool = memory_pool().await;
+        pool.close().await;
+
+        let response = readiness(vec![check_database(&pool).await]);
+
+        assert!(!response.ready);
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["ready"], false);
+        assert_eq!(json["dependencies"][0]["status"], "down");
+        assert!(json["dependencies"][0]["error"].is_string());
+    }
+
+    #[test]
+    fn test_optional_dependency_does_not_block_readiness() {
+        let response = readiness(vec![DependencyStatus {
+            name: "storage".to_string(),
+            status: DependencyState::Down,
+            required: false,
+            error: Some("unreachable".to_string()),
+        }]);
+
+        assert!(response.ready);
+    }
+}
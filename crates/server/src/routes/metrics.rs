@@ -0,0 +1,55 @@
+use axum::{
+    Router, extract::State, http::header, middleware::from_fn, response::IntoResponse, routing::get,
+};
+use db::models::user_session::UserSession;
+use deployment::Deployment;
+
+use crate::{
+    DeploymentImpl,
+    middleware::{Permission, require_auth, require_permission},
+};
+
+/// Set to restrict `/api/metrics` to admins.
+pub const METRICS_REQUIRE_ADMIN_ENV: &str = "VIBE_METRICS_REQUIRE_ADMIN";
+
+/// GET /api/metrics - Prometheus text-format metrics
+pub async fn get_metrics(State(deployment): State<DeploymentImpl>) -> impl IntoResponse {
+    match UserSession::count_active(&deployment.db().pool).await {
+        Ok(count) => deployment.metrics().set_active_user_sessions(count),
+        Err(e) => tracing::warn!("Failed to count active sessions for metrics: {}", e),
+    }
+
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        deployment.metrics().encode(),
+    )
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    let router = Router::new().route("/metrics", get(get_metrics));
+    if metrics_require_admin(std::env::var(METRICS_REQUIRE_ADMIN_ENV).ok().as_deref()) {
+        router
+            .layer(from_fn(require_permission(Permission::AdminAccess)))
+            .layer(from_fn(require_auth))
+    } else {
+        router
+    }
+}
+
+fn metrics_require_admin(raw: Option<&str>) -> bool {
+    raw.is_some_and(|value| matches!(value.trim(), "1" | "true" | "yes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_require_admin_flag() {
+        assert!(!metrics_require_admin(None));
+        assert!(!metrics_require_admin(Some("0")));
+        assert!(!metrics_require_admin(Some("false")));
+        assert!(metrics_require_admin(Some("1")));
+        assert!(metrics_require_admin(Some("true")));
+    }
+}
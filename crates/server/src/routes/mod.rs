@@ -1,11 +1,18 @@
 use axum::{
-    Router,
+    Extension, Router,
+    middleware::from_fn_with_state,
     routing::{IntoMakeService, get},
 };
+use deployment::Deployment;
 
-use crate::DeploymentImpl;
+use crate::{
+    DeploymentImpl,
+    middleware::{AuditSink, record_http_metrics},
+};
 
+pub mod admin;
 pub mod approvals;
+pub mod audit;
 pub mod cf_auth;
 pub mod config;
 pub mod containers;
@@ -16,21 +23,28 @@ pub mod execution_processes;
 pub mod frontend;
 pub mod health;
 pub mod images;
+pub mod metrics;
 pub mod oauth;
 pub mod organizations;
 pub mod projects;
 pub mod repo;
+pub mod roles;
 pub mod scratch;
 pub mod sessions;
 pub mod shared_tasks;
 pub mod tags;
 pub mod task_attempts;
 pub mod tasks;
+pub mod users;
+pub mod workspace_teams;
 
 pub fn router(deployment: DeploymentImpl) -> IntoMakeService<Router> {
+    let audit_sink = AuditSink::spawn(deployment.db().pool.clone());
+
     // Create routers with different middleware layers
     let base_routes = Router::new()
         .route("/health", get(health::health_check))
+        .route("/ready", get(health::readiness_check))
         .merge(config::router())
         .merge(containers::router(&deployment))
         .merge(projects::router(&deployment))
@@ -48,7 +62,18 @@ pub fn router(deployment: DeploymentImpl) -> IntoMakeService<Router> {
         .merge(scratch::router(&deployment))
         .merge(sessions::router(&deployment))
         .merge(cf_auth::router())
+        .merge(audit::router())
+        .merge(admin::router())
+        .merge(metrics::router())
+        .merge(users::router())
+        .merge(roles::router())
+        .merge(workspace_teams::router(&deployment))
         .nest("/images", images::routes())
+        .layer(Extension(audit_sink))
+        .layer(from_fn_with_state(
+            deployment.metrics().clone(),
+            record_http_metrics,
+        ))
         .with_state(deployment);
 
     Router::new()
@@ -0,0 +1,84 @@
+use axum::{
+    Router,
+    extract::{Path, Query, State},
+    middleware::from_fn,
+    response::Json as ResponseJson,
+    routing::get,
+};
+use db::models::{permission::PermissionCategory, role::Role};
+use deployment::Deployment;
+use serde::{Deserialize, Serialize};
+use services::services::workspace_team::{PermissionExplanation, WorkspaceTeamService};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    middleware::{Permission, require_auth, require_permission},
+};
+
+/// A role as listed by GET /api/roles, with its granted permission keys.
+#[derive(Debug, Serialize, TS)]
+#[ts(export)]
+pub struct RoleWithPermissions {
+    pub role: Role,
+    pub permissions: Vec<String>,
+}
+
+/// GET /api/roles - List every role with its permissions (admin only)
+pub async fn list_roles(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<RoleWithPermissions>>>, ApiError> {
+    let roles = WorkspaceTeamService::new()
+        .list_roles_with_permissions(&deployment.db().pool)
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(
+        roles
+            .into_iter()
+            .map(|(role, permissions)| RoleWithPermissions { role, permissions })
+            .collect(),
+    )))
+}
+
+/// GET /api/permissions/categories - All permissions grouped by key prefix
+pub async fn list_permission_categories(
+    State(deployment): State<DeploymentImpl>,
+) -> Result<ResponseJson<ApiResponse<Vec<PermissionCategory>>>, ApiError> {
+    let categories = WorkspaceTeamService::new()
+        .list_permission_categories(&deployment.db().pool)
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(categories)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExplainPermissionQuery {
+    pub user_id: String,
+    pub key: String,
+}
+
+/// GET /api/workspace-teams/{team_id}/permissions/explain?user_id=&key= -
+/// Show why a member does or doesn't hold a permission (admin only)
+pub async fn explain_permission(
+    State(deployment): State<DeploymentImpl>,
+    Path(team_id): Path<Uuid>,
+    Query(query): Query<ExplainPermissionQuery>,
+) -> Result<ResponseJson<ApiResponse<PermissionExplanation>>, ApiError> {
+    let explanation = WorkspaceTeamService::new()
+        .explain_permission(&deployment.db().pool, team_id, &query.user_id, &query.key)
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(explanation)))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new()
+        .route("/roles", get(list_roles))
+        .route("/permissions/categories", get(list_permission_categories))
+        .route(
+            "/workspace-teams/{team_id}/permissions/explain",
+            get(explain_permission),
+        )
+        .layer(from_fn(require_permission(Permission::AdminAccess)))
+        .layer(from_fn(require_auth))
+}
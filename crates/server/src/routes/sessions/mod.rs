@@ -32,7 +32,7 @@ use utils::response::ApiResponse;
 use uuid::Uuid;
 
 use crate::{
-    DeploymentImpl, error::ApiError, middleware::load_session_middleware,
+    DeploymentImpl, error::ApiError, middleware::load_session_auth_context,
     routes::task_attempts::util::restore_worktrees_to_process,
 };
 
@@ -242,8 +242,8 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
         .route("/follow-up", post(follow_up))
         .route("/review", post(review::start_review))
         .layer(from_fn_with_state(
-            deployment.clone(),
-            load_session_middleware,
+            deployment.db().pool.clone(),
+            load_session_auth_context,
         ));
 
     let sessions_router = Router::new()
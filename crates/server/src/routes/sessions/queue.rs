@@ -9,7 +9,7 @@ use services::services::queued_message::QueueStatus;
 use ts_rs::TS;
 use utils::response::ApiResponse;
 
-use crate::{DeploymentImpl, error::ApiError, middleware::load_session_middleware};
+use crate::{DeploymentImpl, error::ApiError, middleware::load_session_auth_context};
 
 /// Request body for queueing a follow-up message
 #[derive(Debug, Deserialize, TS)]
@@ -89,7 +89,7 @@ pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
                 .delete(cancel_queued_message),
         )
         .layer(from_fn_with_state(
-            deployment.clone(),
-            load_session_middleware,
+            deployment.db().pool.clone(),
+            load_session_auth_context,
         ))
 }
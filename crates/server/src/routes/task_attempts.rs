@@ -165,22 +165,56 @@ pub async fn create_task_attempt(
 ) -> Result<ResponseJson<ApiResponse<Workspace>>, ApiError> {
     let executor_profile_id = payload.executor_profile_id.clone();
 
-    if payload.repos.is_empty() {
+    let task = Task::find_by_id(&deployment.db().pool, payload.task_id)
+        .await?
+        .ok_or(SqlxError::RowNotFound)?;
+
+    let workspace = create_workspace_for_task(&deployment, &task, &payload.repos).await?;
+    if let Err(err) = deployment
+        .container()
+        .start_workspace(&workspace, executor_profile_id.clone())
+        .await
+    {
+        tracing::error!("Failed to start task attempt: {}", err);
+    }
+
+    deployment
+        .track_if_analytics_allowed(
+            "task_attempt_started",
+            serde_json::json!({
+                "task_id": workspace.task_id.to_string(),
+                "variant": &executor_profile_id.variant,
+                "executor": &executor_profile_id.executor,
+                "workspace_id": workspace.id.to_string(),
+                "repository_count": payload.repos.len(),
+            }),
+        )
+        .await;
+
+    tracing::info!("Created attempt for task {}", task.id);
+
+    Ok(ResponseJson(ApiResponse::success(workspace)))
+}
+
+/// Create a workspace and its repo links for a task, without starting it.
+pub(crate) async fn create_workspace_for_task(
+    deployment: &DeploymentImpl,
+    task: &Task,
+    repos: &[WorkspaceRepoInput],
+) -> Result<Workspace, ApiError> {
+    if repos.is_empty() {
         return Err(ApiError::BadRequest(
             "At least one repository is required".to_string(),
         ));
     }
 
     let pool = &deployment.db().pool;
-    let task = Task::find_by_id(&deployment.db().pool, payload.task_id)
-        .await?
-        .ok_or(SqlxError::RowNotFound)?;
 
     // Compute agent_working_dir based on repo count:
     // - Single repo: use repo name as working dir (agent runs in repo directory)
     // - Multiple repos: use None (agent runs in workspace root)
-    let agent_working_dir = if payload.repos.len() == 1 {
-        let repo = Repo::find_by_id(pool, payload.repos[0].repo_id)
+    let agent_working_dir = if repos.len() == 1 {
+        let repo = Repo::find_by_id(pool, repos[0].repo_id)
             .await?
             .ok_or(RepoError::NotFound)?;
         Some(repo.name)
@@ -201,12 +235,11 @@ pub async fn create_task_attempt(
             agent_working_dir,
         },
         attempt_id,
-        payload.task_id,
+        task.id,
     )
     .await?;
 
-    let workspace_repos: Vec<CreateWorkspaceRepo> = payload
-        .repos
+    let workspace_repos: Vec<CreateWorkspaceRepo> = repos
         .iter()
         .map(|r| CreateWorkspaceRepo {
             repo_id: r.repo_id,
@@ -215,30 +248,8 @@ pub async fn create_task_attempt(
         .collect();
 
     WorkspaceRepo::create_many(pool, workspace.id, &workspace_repos).await?;
-    if let Err(err) = deployment
-        .container()
-        .start_workspace(&workspace, executor_profile_id.clone())
-        .await
-    {
-        tracing::error!("Failed to start task attempt: {}", err);
-    }
-
-    deployment
-        .track_if_analytics_allowed(
-            "task_attempt_started",
-            serde_json::json!({
-                "task_id": workspace.task_id.to_string(),
-                "variant": &executor_profile_id.variant,
-                "executor": &executor_profile_id.executor,
-                "workspace_id": workspace.id.to_string(),
-                "repository_count": payload.repos.len(),
-            }),
-        )
-        .await;
 
-    tracing::info!("Created attempt for task {}", task.id);
-
-    Ok(ResponseJson(ApiResponse::success(workspace)))
+    Ok(workspace)
 }
 
 #[axum::debug_handler]
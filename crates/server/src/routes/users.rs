@@ -0,0 +1,111 @@
+use axum::{
+    Extension, Router,
+    extract::{Query, State},
+    middleware::from_fn,
+    response::Json as ResponseJson,
+    routing::get,
+};
+use db::models::user::User;
+use deployment::Deployment;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    middleware::{Permission, authorization::AuthContext, require_auth, require_permission},
+};
+
+const DEFAULT_USER_SEARCH_LIMIT: i64 = 10;
+
+#[derive(Debug, Deserialize)]
+pub struct UserSearchQuery {
+    pub q: String,
+    pub limit: Option<i64>,
+}
+
+/// A user matched by GET /api/users/search. `email` is only included for
+/// admins; everyone else sees the display name.
+#[derive(Debug, Serialize, TS)]
+#[ts(export)]
+pub struct UserSearchResult {
+    pub id: Uuid,
+    pub name: String,
+    pub email: Option<String>,
+    pub avatar_url: Option<String>,
+}
+
+impl UserSearchResult {
+    fn from_user(user: User, include_email: bool) -> Self {
+        Self {
+            id: user.id,
+            name: user.name,
+            email: include_email.then_some(user.email),
+            avatar_url: user.avatar_url,
+        }
+    }
+}
+
+/// GET /api/users/search?q= - Find users by partial name or email
+pub async fn search_users(
+    State(deployment): State<DeploymentImpl>,
+    Extension(auth): Extension<AuthContext>,
+    Query(query): Query<UserSearchQuery>,
+) -> Result<ResponseJson<ApiResponse<Vec<UserSearchResult>>>, ApiError> {
+    let limit = query.limit.unwrap_or(DEFAULT_USER_SEARCH_LIMIT);
+    let users = User::search(&deployment.db().pool, &query.q, limit)
+        .await
+        .map_err(|e| {
+            tracing::error!(?e, "Failed to search users");
+            ApiError::Internal(e.to_string())
+        })?;
+
+    let include_email = auth.has_permission(Permission::AdminAccess);
+    Ok(ResponseJson(ApiResponse::success(
+        users
+            .into_iter()
+            .map(|user| UserSearchResult::from_user(user, include_email))
+            .collect(),
+    )))
+}
+
+pub fn router() -> Router<DeploymentImpl> {
+    Router::new()
+        .route("/users/search", get(search_users))
+        .layer(from_fn(require_permission(Permission::WorkspaceRead)))
+        .layer(from_fn(require_auth))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+
+    fn user() -> User {
+        User {
+            id: Uuid::new_v4(),
+            email: "dana@example.com".to_string(),
+            name: "Dana Scully".to_string(),
+            avatar_url: None,
+            cf_access_id: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            deleted_at: None,
+            last_login_at: None,
+            login_count: 0,
+        }
+    }
+
+    #[test]
+    fn test_email_redacted_for_non_admins() {
+        let result = UserSearchResult::from_user(user(), false);
+        assert_eq!(result.name, "Dana Scully");
+        assert!(result.email.is_none());
+
+        let result = UserSearchResult::from_user(user(), true);
+        assert_eq!(result.email.as_deref(), Some("dana@example.com"));
+    }
+}
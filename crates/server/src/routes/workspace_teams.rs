@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+
+use axum::{
+    Extension, Router,
+    extract::{Path, State},
+    middleware::{from_fn, from_fn_with_state},
+    response::Json as ResponseJson,
+    routing::get,
+};
+use db::models::{role::Role, workspace_team::WorkspaceTeam};
+use deployment::Deployment;
+use serde::Serialize;
+use services::services::workspace_team::WorkspaceTeamService;
+use sqlx::SqlitePool;
+use ts_rs::TS;
+use utils::response::ApiResponse;
+use uuid::Uuid;
+
+use crate::{
+    DeploymentImpl,
+    error::ApiError,
+    middleware::{
+        Permission, cf_access::AuthContext, require_auth, require_cf_access_auth,
+        require_permission,
+    },
+};
+
+/// A team the current user belongs to, as listed by GET /api/me/workspaces,
+/// with their role there and the permission keys it grants.
+#[derive(Debug, Serialize, TS)]
+#[ts(export)]
+pub struct MyWorkspace {
+    pub team: WorkspaceTeam,
+    pub role: Role,
+    pub permissions: Vec<String>,
+}
+
+/// GET /api/me/workspaces - Teams the current user belongs to with their
+/// role and effective permissions in each
+pub async fn list_my_workspaces(
+    State(pool): State<SqlitePool>,
+    Extension(auth): Extension<AuthContext>,
+) -> Result<ResponseJson<ApiResponse<Vec<MyWorkspace>>>, ApiError> {
+    let teams = WorkspaceTeamService::new()
+        .find_teams_with_role_for_user(&pool, &auth.user.id.to_string())
+        .await?;
+    Ok(ResponseJson(ApiResponse::success(
+        teams
+            .into_iter()
+            .map(|(team, role, permissions)| MyWorkspace {
+                team,
+                role,
+                permissions,
+            })
+            .collect(),
+    )))
+}
+
+/// GET /api/workspace-teams/{team_id}/role-counts - Members per role ID,
+/// with 0 for roles nobody in the team holds. Only the team's members may
+/// read them.
+pub async fn get_role_member_counts(
+    State(pool): State<SqlitePool>,
+    Extension(auth): Extension<AuthContext>,
+    Path(team_id): Path<Uuid>,
+) -> Result<ResponseJson<ApiResponse<HashMap<Uuid, i64>>>, ApiError> {
+    let service = WorkspaceTeamService::new();
+    service.get_team(&pool, team_id).await?;
+    if service
+        .get_member_role(&pool, team_id, &auth.user.id.to_string())
+        .await?
+        .is_none()
+    {
+        return Err(ApiError::Forbidden("Not a member of this team".to_string()));
+    }
+
+    let counts = service.role_member_counts(&pool, team_id).await?;
+    Ok(ResponseJson(ApiResponse::success(counts)))
+}
+
+pub fn router(deployment: &DeploymentImpl) -> Router<DeploymentImpl> {
+    team_routes(deployment.db().pool.clone()).layer(from_fn_with_state(
+        deployment.clone(),
+        require_cf_access_auth,
+    ))
+}
+
+/// The routes served from the pool alone, so tests can call them without a
+/// deployment. The caller must already carry a CF Access [`AuthContext`].
+fn team_routes<S>(pool: SqlitePool) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let team_routes = Router::new()
+        .route(
+            "/workspace-teams/{team_id}/role-counts",
+            get(get_role_member_counts),
+        )
+        .layer(from_fn(require_permission(Permission::WorkspaceRead)))
+        .layer(from_fn(require_auth));
+
+    // Listing your own teams needs only a signed-in user
+    Router::new()
+        .route("/me/workspaces", get(list_my_workspaces))
+        .merge(team_routes)
+        .with_state(pool)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::{Body, to_bytes},
+        http::{Request, StatusCode},
+    };
+    use chrono::Utc;
+    use db::models::{
+        user::{UpsertUser, User},
+        user_session::UserSession,
+        workspace_team::CreateWorkspaceTeam,
+    };
+    use sqlx::sqlite::SqlitePoolOptions;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::middleware::cf_access::CfAccessClaims;
+
+    async fn test_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("../db/migrations").run(&pool).await.unwrap();
+        pool
+    }
+
+    /// What `require_cf_access_auth` attaches for a signed-in user
+    async fn signed_in(pool: &SqlitePool, email: &str) -> AuthContext {
+        let user = User::upsert(
+            pool,
+            &UpsertUser {
+                email: email.to_string(),
+                name: email.to_string(),
+                avatar_url: None,
+                cf_access_id: Some(email.to_string()),
+            },
+        )
+        .await
+        .unwrap();
+        let session = UserSession::create(pool, user.id, Some(email), None, None, None)
+            .await
+            .unwrap();
+        let now = Utc::now().timestamp();
+        AuthContext {
+            user,
+            session,
+            claims: CfAccessClaims {
+                sub: email.to_string(),
+                email: email.to_string(),
+                token_type: Some("app".to_string()),
+                iat: now,
+                exp: now + 3600,
+                iss: None,
+                aud: None,
+                name: None,
+                identity_nonce: None,
+                custom: None,
+            },
+        }
+    }
+
+    async fn create_team(pool: &SqlitePool, name: &str, owner: &AuthContext) -> WorkspaceTeam {
+        WorkspaceTeamService::new()
+            .create_team(
+                pool,
+                CreateWorkspaceTeam {
+                    name: name.to_string(),
+                    description: None,
+                },
+                &owner.user.id.to_string(),
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_list_my_workspaces_returns_callers_teams() {
+        let pool = test_pool().await;
+        let alice = signed_in(&pool, "alice@example.com").await;
+        let bob = signed_in(&pool, "bob@example.com").await;
+        let team = create_team(&pool, "Platform", &alice).await;
+        create_team(&pool, "Design", &bob).await;
+
+        let response = team_routes::<()>(pool)
+            .layer(Extension(alice))
+            .oneshot(Request::get("/me/workspaces").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: serde_json::Value =
+            serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap())
+                .unwrap();
+        let teams = body["data"].as_array().unwrap();
+        assert_eq!(teams.len(), 1);
+        assert_eq!(teams[0]["team"]["id"], team.id.to_string());
+        assert_eq!(teams[0]["role"]["name"], "Owner");
+        assert!(!teams[0]["permissions"].as_array().unwrap().is_empty());
+    }
+
+    async fn get_role_counts(pool: SqlitePool, caller: AuthContext, team_id: Uuid) -> StatusCode {
+        team_routes::<()>(pool)
+            .layer(Extension(caller))
+            .oneshot(
+                Request::get(format!("/workspace-teams/{team_id}/role-counts"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_role_counts_require_team_membership() {
+        let pool = test_pool().await;
+        let alice = signed_in(&pool, "alice@example.com").await;
+        let bob = signed_in(&pool, "bob@example.com").await;
+        let team = create_team(&pool, "Platform", &alice).await;
+
+        assert_eq!(
+            get_role_counts(pool.clone(), alice.clone(), team.id).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            get_role_counts(pool.clone(), bob, team.id).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            get_role_counts(pool, alice, Uuid::new_v4()).await,
+            StatusCode::NOT_FOUND
+        );
+    }
+}
@@ -0,0 +1,37 @@
+/// Resolves on Ctrl+C, or on SIGTERM on Unix, which is how service managers
+/// and container runtimes stop the process.
+pub async fn shutdown_signal() {
+    // Always wait for Ctrl+C
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to install Ctrl+C handler: {e}");
+        }
+    };
+
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        // Try to install SIGTERM handler, but don't panic if it fails
+        let terminate = async {
+            if let Ok(mut sigterm) = signal(SignalKind::terminate()) {
+                sigterm.recv().await;
+            } else {
+                tracing::error!("Failed to install SIGTERM handler");
+                // Fallback: never resolves
+                std::future::pending::<()>().await;
+            }
+        };
+
+        tokio::select! {
+            _ = ctrl_c => {},
+            _ = terminate => {},
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        // Only ctrl_c is available, so just await it
+        ctrl_c.await;
+    }
+}
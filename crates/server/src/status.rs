@@ -0,0 +1,214 @@
+//! `status` report: which servers are running on this machine, whether they
+//! answer `/api/health`, and whether a `connect` agent is attached to the
+//! dashboard.
+
+use std::{fmt::Write as _, time::Duration};
+
+use reqwest::StatusCode;
+use serde_json::Value;
+use utils::port_file::{AgentStatus, RunningInstance, list_running_instances, read_agent_status};
+
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerHealth {
+    Healthy,
+    Unhealthy(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct ServerStatus {
+    pub instance: RunningInstance,
+    pub health: ServerHealth,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StatusReport {
+    pub servers: Vec<ServerStatus>,
+    /// `None` when no agent is running
+    pub agent: Option<AgentStatus>,
+}
+
+impl StatusReport {
+    /// True when at least one server answered its health check
+    pub fn healthy(&self) -> bool {
+        self.servers
+            .iter()
+            .any(|server| server.health == ServerHealth::Healthy)
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        if self.servers.is_empty() {
+            let _ = writeln!(out, "Server: not running");
+        }
+        for server in &self.servers {
+            let health = match &server.health {
+                ServerHealth::Healthy => "healthy".to_string(),
+                ServerHealth::Unhealthy(reason) => format!("unhealthy ({reason})"),
+            };
+            let _ = writeln!(
+                out,
+                "Server: {} (pid {}) {}",
+                server.instance.base_url(),
+                server.instance.pid,
+                health
+            );
+        }
+
+        let _ = match &self.agent {
+            None => writeln!(out, "Agent: not running"),
+            Some(agent) if agent.connected => writeln!(
+                out,
+                "Agent: connected to {} (pid {}, since {})",
+                agent.url,
+                agent.pid,
+                agent.updated_at.to_rfc3339()
+            ),
+            Some(agent) => writeln!(
+                out,
+                "Agent: disconnected from {} (pid {}, since {})",
+                agent.url,
+                agent.pid,
+                agent.updated_at.to_rfc3339()
+            ),
+        };
+        out
+    }
+}
+
+/// Interpret a `/api/health` response
+pub fn health_from_response(status: StatusCode, body: &str) -> ServerHealth {
+    if !status.is_success() {
+        return ServerHealth::Unhealthy(format!("HTTP {}", status.as_u16()));
+    }
+    match serde_json::from_str::<Value>(body) {
+        Ok(json) if json.get("success") == Some(&Value::Bool(true)) => ServerHealth::Healthy,
+        Ok(_) => ServerHealth::Unhealthy("health check reported failure".to_string()),
+        Err(e) => ServerHealth::Unhealthy(format!("unexpected response: {e}")),
+    }
+}
+
+async fn check_health(client: &reqwest::Client, instance: &RunningInstance) -> ServerHealth {
+    let url = format!("{}/api/health", instance.base_url());
+    let response = match client.get(&url).send().await {
+        Ok(response) => response,
+        Err(e) => return ServerHealth::Unhealthy(e.to_string()),
+    };
+    let status = response.status();
+    match response.text().await {
+        Ok(body) => health_from_response(status, &body),
+        Err(e) => ServerHealth::Unhealthy(e.to_string()),
+    }
+}
+
+/// Whether the process that wrote a status file is still around
+fn process_alive(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        use nix::{errno::Errno, sys::signal::kill, unistd::Pid};
+
+        // EPERM means the process exists but belongs to another user
+        i32::try_from(pid)
+            .is_ok_and(|pid| matches!(kill(Pid::from_raw(pid), None), Ok(()) | Err(Errno::EPERM)))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = pid;
+        true
+    }
+}
+
+/// Gather the report for servers and the agent on this machine
+pub async fn collect() -> std::io::Result<StatusReport> {
+    let client = reqwest::Client::builder()
+        .timeout(HEALTH_TIMEOUT)
+        .build()
+        .map_err(std::io::Error::other)?;
+
+    let mut servers = Vec::new();
+    for instance in list_running_instances("vibe-kanban").await? {
+        let health = check_health(&client, &instance).await;
+        servers.push(ServerStatus { instance, health });
+    }
+
+    let agent = read_agent_status("vibe-kanban")
+        .await?
+        .filter(|agent| process_alive(agent.pid));
+
+    Ok(StatusReport { servers, agent })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    fn server(health: ServerHealth) -> ServerStatus {
+        ServerStatus {
+            instance: RunningInstance {
+                pid: 42,
+                host: "127.0.0.1".to_string(),
+                port: 3001,
+            },
+            health,
+        }
+    }
+
+    #[test]
+    fn test_health_response_is_interpreted() {
+        let ok = r#"{"success":true,"data":"OK","error_data":null,"message":null}"#;
+        assert_eq!(
+            health_from_response(StatusCode::OK, ok),
+            ServerHealth::Healthy
+        );
+        assert_eq!(
+            health_from_response(StatusCode::SERVICE_UNAVAILABLE, ""),
+            ServerHealth::Unhealthy("HTTP 503".to_string())
+        );
+        assert!(matches!(
+            health_from_response(StatusCode::OK, "<html>"),
+            ServerHealth::Unhealthy(_)
+        ));
+    }
+
+    #[test]
+    fn test_report_from_mocked_health_response() {
+        let ok = r#"{"success":true,"data":"OK","error_data":null,"message":null}"#;
+        let report = StatusReport {
+            servers: vec![server(health_from_response(StatusCode::OK, ok))],
+            agent: Some(AgentStatus {
+                pid: 7,
+                url: "wss://dash.example.com/ws".to_string(),
+                connected: true,
+                updated_at: Utc.with_ymd_and_hms(2026, 1, 2, 3, 4, 5).unwrap(),
+            }),
+        };
+
+        assert!(report.healthy());
+        assert_eq!(
+            report.render(),
+            "Server: http://127.0.0.1:3001 (pid 42) healthy\n\
+             Agent: connected to wss://dash.example.com/ws (pid 7, since 2026-01-02T03:04:05+00:00)\n"
+        );
+    }
+
+    #[test]
+    fn test_report_without_healthy_server_is_unhealthy() {
+        let empty = StatusReport::default();
+        assert!(!empty.healthy());
+        assert_eq!(empty.render(), "Server: not running\nAgent: not running\n");
+
+        let failing = StatusReport {
+            servers: vec![server(ServerHealth::Unhealthy("HTTP 500".to_string()))],
+            agent: None,
+        };
+        assert!(!failing.healthy());
+        assert!(
+            failing
+                .render()
+                .starts_with("Server: http://127.0.0.1:3001 (pid 42) unhealthy (HTTP 500)\n")
+        );
+    }
+}
@@ -125,6 +125,31 @@ pub trait ContainerService {
         Ok(false)
     }
 
+    /// Kill every running execution process for a task, returning the ids
+    /// of the processes that were stopped
+    async fn stop_task_executions(&self, task_id: Uuid) -> Result<Vec<Uuid>, ContainerError> {
+        let workspaces = Workspace::fetch_all(&self.db().pool, Some(task_id)).await?;
+        let mut stopped = Vec::new();
+
+        for workspace in workspaces {
+            let sessions = Session::find_by_workspace_id(&self.db().pool, workspace.id).await?;
+            for session in sessions {
+                let processes =
+                    ExecutionProcess::find_by_session_id(&self.db().pool, session.id, false)
+                        .await?;
+                for process in processes {
+                    if process.status == ExecutionProcessStatus::Running {
+                        self.stop_execution(&process, ExecutionProcessStatus::Killed)
+                            .await?;
+                        stopped.push(process.id);
+                    }
+                }
+            }
+        }
+
+        Ok(stopped)
+    }
+
     /// A context is finalized when
     /// - Always when the execution process has failed or been killed
     /// - Never when the run reason is DevServer
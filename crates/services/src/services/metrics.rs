@@ -0,0 +1,117 @@
+//! Prometheus metrics for the HTTP API.
+//!
+//! Request counters and latencies are recorded by the server's metrics
+//! layer; gauges backed by the database are refreshed when `/api/metrics` is
+//! scraped. Everything is rendered in the Prometheus text format.
+
+use std::time::Duration;
+
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry, TextEncoder,
+};
+
+#[derive(Clone)]
+pub struct MetricsService {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_request_duration: HistogramVec,
+    active_user_sessions: IntGauge,
+}
+
+impl MetricsService {
+    pub fn new() -> Self {
+        let registry = Registry::new_custom(Some("vibe_kanban".to_string()), None)
+            .expect("metrics prefix is valid");
+
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests by route and status"),
+            &["method", "route", "status"],
+        )
+        .expect("http_requests_total is a valid metric");
+        let http_request_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "HTTP request latency by route",
+            ),
+            &["method", "route"],
+        )
+        .expect("http_request_duration_seconds is a valid metric");
+        let active_user_sessions =
+            IntGauge::new("active_user_sessions", "Non-expired user sessions")
+                .expect("active_user_sessions is a valid metric");
+
+        for collector in [
+            Box::new(http_requests.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(http_request_duration.clone()),
+            Box::new(active_user_sessions.clone()),
+        ] {
+            registry
+                .register(collector)
+                .expect("metrics are registered once");
+        }
+
+        Self {
+            registry,
+            http_requests,
+            http_request_duration,
+            active_user_sessions,
+        }
+    }
+
+    /// Record a finished HTTP request. `route` should be the matched route
+    /// template, not the raw path, to keep label cardinality bounded.
+    pub fn observe_http_request(&self, method: &str, route: &str, status: u16, elapsed: Duration) {
+        self.http_requests
+            .with_label_values(&[method, route, &status.to_string()])
+            .inc();
+        self.http_request_duration
+            .with_label_values(&[method, route])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    pub fn set_active_user_sessions(&self, count: i64) {
+        self.active_user_sessions.set(count);
+    }
+
+    /// Requests recorded so far for a method, route and status.
+    pub fn http_requests_total(&self, method: &str, route: &str, status: u16) -> u64 {
+        self.http_requests
+            .with_label_values(&[method, route, &status.to_string()])
+            .get()
+    }
+
+    /// Render every metric in the Prometheus text exposition format.
+    pub fn encode(&self) -> String {
+        let mut buffer = Vec::new();
+        if let Err(e) = TextEncoder::new().encode(&self.registry.gather(), &mut buffer) {
+            tracing::error!("Failed to encode metrics: {}", e);
+        }
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+}
+
+impl Default for MetricsService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_includes_recorded_requests() {
+        let metrics = MetricsService::new();
+        metrics.observe_http_request("GET", "/api/tasks", 200, Duration::from_millis(5));
+        metrics.set_active_user_sessions(3);
+
+        let text = metrics.encode();
+
+        assert!(text.contains(
+            r#"vibe_kanban_http_requests_total{method="GET",route="/api/tasks",status="200"} 1"#
+        ));
+        assert!(text.contains("vibe_kanban_http_request_duration_seconds_bucket"));
+        assert!(text.contains("vibe_kanban_active_user_sessions 3"));
+    }
+}
@@ -12,6 +12,7 @@ pub mod filesystem_watcher;
 pub mod git;
 pub mod git_host;
 pub mod image;
+pub mod metrics;
 pub mod notification;
 pub mod oauth_credentials;
 pub mod pr_monitor;
@@ -21,6 +22,7 @@ pub mod qa_repos;
 pub mod queued_message;
 pub mod remote_client;
 pub mod repo;
+pub mod session_cleanup;
 pub mod share;
 pub mod workspace_manager;
 pub mod workspace_team;
@@ -97,7 +97,23 @@ fn map_error_code(code: Option<&str>) -> HandoffErrorCode {
 
 #[derive(Deserialize)]
 struct ApiErrorResponse {
-    error: String,
+    error: ApiErrorBody,
+}
+
+/// OAuth routes send a bare code; other routes send `{ code, message }`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ApiErrorBody {
+    Code(String),
+    Detailed { code: String },
+}
+
+impl ApiErrorBody {
+    fn code(&self) -> &str {
+        match self {
+            ApiErrorBody::Code(code) | ApiErrorBody::Detailed { code } => code,
+        }
+    }
 }
 
 /// HTTP client for the remote OAuth server with automatic retries.
@@ -392,7 +408,7 @@ impl RemoteClient {
         if let RemoteClientError::Http { body, .. } = &err
             && let Ok(api_err) = serde_json::from_str::<ApiErrorResponse>(body)
         {
-            return RemoteClientError::Api(map_error_code(Some(&api_err.error)));
+            return RemoteClientError::Api(map_error_code(Some(api_err.error.code())));
         }
         err
     }
@@ -512,13 +528,31 @@ impl RemoteClient {
         .await
     }
 
-    /// Lists members of an organization.
+    /// Lists all members of an organization, following pagination cursors.
     pub async fn list_members(
         &self,
         org_id: Uuid,
     ) -> Result<ListMembersResponse, RemoteClientError> {
-        self.get_authed(&format!("/v1/organizations/{org_id}/members"))
-            .await
+        let mut members = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let path = match &cursor {
+                // Cursors are URL-safe base64
+                Some(cursor) => format!("/v1/organizations/{org_id}/members?cursor={cursor}"),
+                None => format!("/v1/organizations/{org_id}/members"),
+            };
+            let page: ListMembersResponse = self.get_authed(&path).await?;
+            members.extend(page.members);
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+
+        Ok(ListMembersResponse {
+            members,
+            next_cursor: None,
+        })
     }
 
     /// Removes a member from an organization.
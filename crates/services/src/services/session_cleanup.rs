@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+use db::{DBService, models::user_session::UserSession};
+use tokio::time::{MissedTickBehavior, interval};
+use tracing::{debug, error, info, warn};
+
+/// Environment variable overriding how often expired sessions are purged
+pub const SESSION_CLEANUP_INTERVAL_ENV: &str = "VIBE_SESSION_CLEANUP_INTERVAL_SECS";
+
+const DEFAULT_CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Service that periodically deletes expired user sessions
+pub struct SessionCleanupService {
+    db: DBService,
+    cleanup_interval: Duration,
+}
+
+impl SessionCleanupService {
+    pub async fn spawn(db: DBService) -> tokio::task::JoinHandle<()> {
+        let service = Self {
+            db,
+            cleanup_interval: cleanup_interval_from_env(),
+        };
+        tokio::spawn(async move {
+            service.start().await;
+        })
+    }
+
+    async fn start(&self) {
+        info!(
+            "Starting session cleanup service with interval {:?}",
+            self.cleanup_interval
+        );
+
+        // The first tick completes immediately, so cleanup also runs at startup.
+        // Runs happen sequentially in this loop and missed ticks are skipped, so
+        // a slow cleanup never overlaps with the next one.
+        let mut interval = interval(self.cleanup_interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+        loop {
+            interval.tick().await;
+            match UserSession::cleanup_expired(&self.db.pool).await {
+                Ok(0) => debug!("No expired sessions to clean up"),
+                Ok(removed) => info!("Removed {} expired session(s)", removed),
+                Err(e) => error!("Error cleaning up expired sessions: {}", e),
+            }
+        }
+    }
+}
+
+fn cleanup_interval_from_env() -> Duration {
+    parse_cleanup_interval(std::env::var(SESSION_CLEANUP_INTERVAL_ENV).ok().as_deref())
+}
+
+fn parse_cleanup_interval(value: Option<&str>) -> Duration {
+    let Some(value) = value else {
+        return DEFAULT_CLEANUP_INTERVAL;
+    };
+
+    match value.trim().parse::<u64>() {
+        Ok(secs) if secs > 0 => Duration::from_secs(secs),
+        _ => {
+            warn!(
+                "Invalid {}={:?}, using default of {:?}",
+                SESSION_CLEANUP_INTERVAL_ENV, value, DEFAULT_CLEANUP_INTERVAL
+            );
+            DEFAULT_CLEANUP_INTERVAL
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cleanup_interval_defaults_to_hourly() {
+        assert_eq!(parse_cleanup_interval(None), Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn cleanup_interval_parses_seconds() {
+        assert_eq!(
+            parse_cleanup_interval(Some("300")),
+            Duration::from_secs(300)
+        );
+    }
+
+    #[test]
+    fn invalid_cleanup_interval_falls_back_to_default() {
+        assert_eq!(parse_cleanup_interval(Some("0")), DEFAULT_CLEANUP_INTERVAL);
+        assert_eq!(
+            parse_cleanup_interval(Some("soon")),
+            DEFAULT_CLEANUP_INTERVAL
+        );
+    }
+}
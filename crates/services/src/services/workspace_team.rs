@@ -1,11 +1,15 @@
+use std::collections::{BTreeSet, HashMap};
+
 use db::models::{
-    permission::{self, Permission},
-    role::{system_roles, Role},
+    permission::{self, Permission, PermissionCategory},
+    role::{CreateRole, Role, UpdateRole, system_roles},
     workspace_member::{CreateWorkspaceMember, WorkspaceMember, WorkspaceMemberWithRole},
     workspace_team::{CreateWorkspaceTeam, UpdateWorkspaceTeam, WorkspaceTeam},
 };
+use serde::Serialize;
 use sqlx::SqlitePool;
 use thiserror::Error;
+use ts_rs::TS;
 use uuid::Uuid;
 
 #[derive(Debug, Error)]
@@ -28,10 +32,36 @@ pub enum WorkspaceTeamServiceError {
     PermissionDenied(String),
     #[error("Cannot delete system role")]
     SystemRoleDelete,
+    #[error("Cannot modify permissions of system role")]
+    SystemRoleModify,
+    #[error("Permission not found: {0}")]
+    PermissionNotFound(String),
+    #[error("A role named '{0}' already exists")]
+    DuplicateRoleName(String),
 }
 
 pub type Result<T> = std::result::Result<T, WorkspaceTeamServiceError>;
 
+/// Why a permission check passes or fails for a team member
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct PermissionExplanation {
+    pub permission_key: String,
+    /// False when no permission has this key, which usually means a typo
+    pub key_exists: bool,
+    /// The user's role in the team, `None` if they aren't a member
+    pub role_name: Option<String>,
+    pub granted_permissions: Vec<String>,
+    pub granted: bool,
+}
+
+/// How a desired permission set differs from a role's current one
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+pub struct PermissionDiff {
+    pub to_add: Vec<String>,
+    pub to_remove: Vec<String>,
+    pub unchanged: Vec<String>,
+}
+
 #[derive(Clone, Default)]
 pub struct WorkspaceTeamService;
 
@@ -87,12 +117,19 @@ impl WorkspaceTeamService {
         Ok(WorkspaceTeam::update(pool, team_id, &data).await?)
     }
 
-    /// Delete a workspace team
+    /// Delete a workspace team along with its memberships
     pub async fn delete_team(&self, pool: &SqlitePool, team_id: Uuid) -> Result<()> {
-        let rows = WorkspaceTeam::delete(pool, team_id).await?;
+        let mut tx = pool.begin().await?;
+
+        // Don't rely on the foreign key cascade, which SQLite only honours
+        // when foreign key enforcement is on for the connection
+        WorkspaceMember::delete_all_for_team(&mut *tx, team_id).await?;
+        let rows = WorkspaceTeam::delete(&mut *tx, team_id).await?;
         if rows == 0 {
             return Err(WorkspaceTeamServiceError::TeamNotFound);
         }
+
+        tx.commit().await?;
         Ok(())
     }
 
@@ -105,6 +142,16 @@ impl WorkspaceTeamService {
         Ok(WorkspaceTeam::find_by_user_id(pool, user_id).await?)
     }
 
+    /// Find all workspace teams a user belongs to, each with the user's role
+    /// there and that role's permission keys
+    pub async fn find_teams_with_role_for_user(
+        &self,
+        pool: &SqlitePool,
+        user_id: &str,
+    ) -> Result<Vec<(WorkspaceTeam, Role, Vec<String>)>> {
+        Ok(WorkspaceTeam::find_teams_with_role_for_user(pool, user_id).await?)
+    }
+
     // ==================== Member Management ====================
 
     /// List all members of a workspace team
@@ -206,6 +253,18 @@ impl WorkspaceTeamService {
         Ok(())
     }
 
+    /// Number of members holding each role in a team; unused roles map to 0
+    pub async fn role_member_counts(
+        &self,
+        pool: &SqlitePool,
+        team_id: Uuid,
+    ) -> Result<HashMap<Uuid, i64>> {
+        // Verify team exists
+        let _ = self.get_team(pool, team_id).await?;
+
+        Ok(WorkspaceMember::count_all_roles(pool, team_id).await?)
+    }
+
     /// Get member's role in a team
     pub async fn get_member_role(
         &self,
@@ -258,6 +317,31 @@ impl WorkspaceTeamService {
         Ok(())
     }
 
+    /// Explain the outcome of a permission check, for admin debugging.
+    /// Unlike `require_permission` this never fails on a missing grant.
+    pub async fn explain_permission(
+        &self,
+        pool: &SqlitePool,
+        team_id: Uuid,
+        user_id: &str,
+        permission_key: &str,
+    ) -> Result<PermissionExplanation> {
+        let key_exists = Permission::find_by_key(pool, permission_key)
+            .await?
+            .is_some();
+        let role = WorkspaceMember::get_role(pool, team_id, user_id).await?;
+        let granted_permissions = WorkspaceMember::get_permissions(pool, team_id, user_id).await?;
+        let granted = granted_permissions.iter().any(|key| key == permission_key);
+
+        Ok(PermissionExplanation {
+            permission_key: permission_key.to_string(),
+            key_exists,
+            role_name: role.map(|role| role.name),
+            granted_permissions,
+            granted,
+        })
+    }
+
     // ==================== Role Management ====================
 
     /// List all roles
@@ -265,6 +349,14 @@ impl WorkspaceTeamService {
         Ok(Role::find_all(pool).await?)
     }
 
+    /// List all roles with their permission keys in one query
+    pub async fn list_roles_with_permissions(
+        &self,
+        pool: &SqlitePool,
+    ) -> Result<Vec<(Role, Vec<String>)>> {
+        Ok(Role::find_all_with_permissions(pool).await?)
+    }
+
     /// Get a role by ID
     pub async fn get_role(&self, pool: &SqlitePool, role_id: Uuid) -> Result<Role> {
         Role::find_by_id(pool, role_id)
@@ -272,6 +364,52 @@ impl WorkspaceTeamService {
             .ok_or(WorkspaceTeamServiceError::RoleNotFound)
     }
 
+    /// Create a custom role, rejecting names already in use
+    pub async fn create_role(&self, pool: &SqlitePool, data: CreateRole) -> Result<Role> {
+        self.ensure_role_name_available(pool, &data.name, None)
+            .await?;
+
+        Role::create(pool, &data)
+            .await
+            .map_err(|e| map_role_name_conflict(e, &data.name))
+    }
+
+    /// Update a custom role, rejecting renames onto an existing role name
+    pub async fn update_role(
+        &self,
+        pool: &SqlitePool,
+        role_id: Uuid,
+        data: UpdateRole,
+    ) -> Result<Role> {
+        let role = self.get_role(pool, role_id).await?;
+        if role.is_system {
+            return Err(WorkspaceTeamServiceError::SystemRoleModify);
+        }
+
+        if let Some(name) = &data.name {
+            self.ensure_role_name_available(pool, name, Some(role_id))
+                .await?;
+        }
+
+        Role::update(pool, role_id, &data)
+            .await
+            .map_err(|e| map_role_name_conflict(e, data.name.as_deref().unwrap_or(&role.name)))
+    }
+
+    async fn ensure_role_name_available(
+        &self,
+        pool: &SqlitePool,
+        name: &str,
+        exclude_role_id: Option<Uuid>,
+    ) -> Result<()> {
+        match Role::find_by_name(pool, name).await? {
+            Some(existing) if Some(existing.id) != exclude_role_id => Err(
+                WorkspaceTeamServiceError::DuplicateRoleName(name.to_string()),
+            ),
+            _ => Ok(()),
+        }
+    }
+
     /// Get permissions for a role
     pub async fn get_role_permissions(
         &self,
@@ -284,6 +422,99 @@ impl WorkspaceTeamService {
         Ok(Role::get_permissions(pool, role_id).await?)
     }
 
+    /// Grant a permission to a custom role
+    pub async fn grant_permission(
+        &self,
+        pool: &SqlitePool,
+        role_id: Uuid,
+        permission_key: &str,
+    ) -> Result<()> {
+        self.get_custom_role(pool, role_id).await?;
+        let permission = self.get_permission_by_key(pool, permission_key).await?;
+
+        Role::add_permission(pool, role_id, permission.id).await?;
+        Ok(())
+    }
+
+    /// Revoke a permission from a custom role
+    pub async fn revoke_permission(
+        &self,
+        pool: &SqlitePool,
+        role_id: Uuid,
+        permission_key: &str,
+    ) -> Result<()> {
+        self.get_custom_role(pool, role_id).await?;
+        let permission = self.get_permission_by_key(pool, permission_key).await?;
+
+        Role::remove_permission(pool, role_id, permission.id).await?;
+        Ok(())
+    }
+
+    /// Compare `desired_keys` against the role's current permissions. Keys in
+    /// each bucket are sorted and deduplicated.
+    pub async fn diff_role_permissions(
+        &self,
+        pool: &SqlitePool,
+        role_id: Uuid,
+        desired_keys: &[String],
+    ) -> Result<PermissionDiff> {
+        let current: BTreeSet<String> = self
+            .get_role_permissions(pool, role_id)
+            .await?
+            .into_iter()
+            .collect();
+        let desired: BTreeSet<String> = desired_keys.iter().cloned().collect();
+
+        Ok(PermissionDiff {
+            to_add: desired.difference(&current).cloned().collect(),
+            to_remove: current.difference(&desired).cloned().collect(),
+            unchanged: current.intersection(&desired).cloned().collect(),
+        })
+    }
+
+    /// Replace the full permission set of a custom role, touching only the
+    /// permissions that actually change
+    pub async fn set_permissions(
+        &self,
+        pool: &SqlitePool,
+        role_id: Uuid,
+        permission_keys: &[String],
+    ) -> Result<PermissionDiff> {
+        self.get_custom_role(pool, role_id).await?;
+        let diff = self
+            .diff_role_permissions(pool, role_id, permission_keys)
+            .await?;
+
+        // Resolve every key up front so an unknown key leaves the role untouched.
+        // Removed keys are already attached, so only added keys can be unknown.
+        let mut add_ids = Vec::with_capacity(diff.to_add.len());
+        for key in &diff.to_add {
+            add_ids.push(self.get_permission_by_key(pool, key).await?.id);
+        }
+        let mut remove_ids = Vec::with_capacity(diff.to_remove.len());
+        for key in &diff.to_remove {
+            remove_ids.push(self.get_permission_by_key(pool, key).await?.id);
+        }
+
+        Role::update_permissions(pool, role_id, &add_ids, &remove_ids).await?;
+        Ok(diff)
+    }
+
+    /// Get a role, rejecting system roles whose permissions are fixed
+    async fn get_custom_role(&self, pool: &SqlitePool, role_id: Uuid) -> Result<Role> {
+        let role = self.get_role(pool, role_id).await?;
+        if role.is_system {
+            return Err(WorkspaceTeamServiceError::SystemRoleModify);
+        }
+        Ok(role)
+    }
+
+    async fn get_permission_by_key(&self, pool: &SqlitePool, key: &str) -> Result<Permission> {
+        Permission::find_by_key(pool, key)
+            .await?
+            .ok_or_else(|| WorkspaceTeamServiceError::PermissionNotFound(key.to_string()))
+    }
+
     // ==================== Permission Listing ====================
 
     /// List all available permissions
@@ -291,6 +522,26 @@ impl WorkspaceTeamService {
         Ok(Permission::find_all(pool).await?)
     }
 
+    /// List all permissions grouped by category, in key order
+    pub async fn list_permission_categories(
+        &self,
+        pool: &SqlitePool,
+    ) -> Result<Vec<PermissionCategory>> {
+        let mut categories: Vec<PermissionCategory> = Vec::new();
+        for permission in Permission::find_all(pool).await? {
+            match categories.last_mut() {
+                Some(category) if category.name == permission.category() => {
+                    category.permissions.push(permission)
+                }
+                _ => categories.push(PermissionCategory {
+                    name: permission.category().to_string(),
+                    permissions: vec![permission],
+                }),
+            }
+        }
+        Ok(categories)
+    }
+
     /// List permissions by category prefix (e.g., "workspace.", "task.")
     pub async fn list_permissions_by_category(
         &self,
@@ -301,5 +552,532 @@ impl WorkspaceTeamService {
     }
 }
 
+/// Surface the `roles.name` unique constraint as a typed error, covering a
+/// concurrent insert that slips past the `find_by_name` check.
+fn map_role_name_conflict(err: sqlx::Error, name: &str) -> WorkspaceTeamServiceError {
+    match &err {
+        sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+            WorkspaceTeamServiceError::DuplicateRoleName(name.to_string())
+        }
+        _ => WorkspaceTeamServiceError::Database(err),
+    }
+}
+
 /// Re-export permission keys for easy access
 pub use permission::keys as permission_keys;
+
+#[cfg(test)]
+mod tests {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    use super::*;
+
+    async fn test_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("../db/migrations").run(&pool).await.unwrap();
+        pool
+    }
+
+    async fn custom_role(pool: &SqlitePool, name: &str) -> Role {
+        Role::create(
+            pool,
+            &CreateRole {
+                name: name.to_string(),
+                description: None,
+            },
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn grant_and_revoke_permission() {
+        let pool = test_pool().await;
+        let service = WorkspaceTeamService::new();
+        let role = custom_role(&pool, "Reviewer").await;
+
+        service
+            .grant_permission(&pool, role.id, permission_keys::TASK_VIEW)
+            .await
+            .unwrap();
+        // Granting twice is a no-op
+        service
+            .grant_permission(&pool, role.id, permission_keys::TASK_VIEW)
+            .await
+            .unwrap();
+        assert_eq!(
+            service.get_role_permissions(&pool, role.id).await.unwrap(),
+            vec![permission_keys::TASK_VIEW.to_string()]
+        );
+
+        service
+            .revoke_permission(&pool, role.id, permission_keys::TASK_VIEW)
+            .await
+            .unwrap();
+        assert!(
+            service
+                .get_role_permissions(&pool, role.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn set_permissions_replaces_existing_set() {
+        let pool = test_pool().await;
+        let service = WorkspaceTeamService::new();
+        let role = custom_role(&pool, "Reviewer").await;
+
+        service
+            .grant_permission(&pool, role.id, permission_keys::TASK_VIEW)
+            .await
+            .unwrap();
+        let keys = vec![
+            permission_keys::TASK_CREATE.to_string(),
+            permission_keys::TASK_EDIT.to_string(),
+        ];
+        service
+            .set_permissions(&pool, role.id, &keys)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            service.get_role_permissions(&pool, role.id).await.unwrap(),
+            keys
+        );
+    }
+
+    #[tokio::test]
+    async fn diff_role_permissions_partitions_overlapping_sets() {
+        let pool = test_pool().await;
+        let service = WorkspaceTeamService::new();
+        let role = custom_role(&pool, "Reviewer").await;
+
+        let current = vec![
+            permission_keys::TASK_VIEW.to_string(),
+            permission_keys::TASK_EDIT.to_string(),
+        ];
+        service
+            .set_permissions(&pool, role.id, &current)
+            .await
+            .unwrap();
+
+        let desired = vec![
+            permission_keys::TASK_EDIT.to_string(),
+            permission_keys::TASK_CREATE.to_string(),
+        ];
+        let diff = service
+            .diff_role_permissions(&pool, role.id, &desired)
+            .await
+            .unwrap();
+        assert_eq!(
+            diff,
+            PermissionDiff {
+                to_add: vec![permission_keys::TASK_CREATE.to_string()],
+                to_remove: vec![permission_keys::TASK_VIEW.to_string()],
+                unchanged: vec![permission_keys::TASK_EDIT.to_string()],
+            }
+        );
+
+        let applied = service
+            .set_permissions(&pool, role.id, &desired)
+            .await
+            .unwrap();
+        assert_eq!(applied, diff);
+        let mut expected = desired.clone();
+        expected.sort();
+        assert_eq!(
+            service.get_role_permissions(&pool, role.id).await.unwrap(),
+            expected
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_permission_leaves_role_untouched() {
+        let pool = test_pool().await;
+        let service = WorkspaceTeamService::new();
+        let role = custom_role(&pool, "Reviewer").await;
+
+        service
+            .grant_permission(&pool, role.id, permission_keys::TASK_VIEW)
+            .await
+            .unwrap();
+        let keys = vec![
+            permission_keys::TASK_CREATE.to_string(),
+            "task.nonexistent".to_string(),
+        ];
+        let err = service
+            .set_permissions(&pool, role.id, &keys)
+            .await
+            .unwrap_err();
+
+        assert!(
+            matches!(err, WorkspaceTeamServiceError::PermissionNotFound(k) if k == "task.nonexistent")
+        );
+        assert_eq!(
+            service.get_role_permissions(&pool, role.id).await.unwrap(),
+            vec![permission_keys::TASK_VIEW.to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn list_roles_with_permissions_groups_keys_per_role() {
+        let pool = test_pool().await;
+        let service = WorkspaceTeamService::new();
+        let role = custom_role(&pool, "Reviewer").await;
+        custom_role(&pool, "Auditor").await;
+
+        service
+            .grant_permission(&pool, role.id, permission_keys::TASK_VIEW)
+            .await
+            .unwrap();
+        service
+            .grant_permission(&pool, role.id, permission_keys::PROJECT_VIEW)
+            .await
+            .unwrap();
+
+        let roles = service.list_roles_with_permissions(&pool).await.unwrap();
+
+        // Same order as list_roles: system roles first, then by name
+        let names: Vec<_> = roles.iter().map(|(role, _)| role.name.clone()).collect();
+        let expected: Vec<_> = service
+            .list_roles(&pool)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|role| role.name)
+            .collect();
+        assert_eq!(names, expected);
+
+        let (_, keys) = roles.iter().find(|(r, _)| r.id == role.id).unwrap();
+        assert_eq!(
+            keys,
+            &vec![
+                permission_keys::PROJECT_VIEW.to_string(),
+                permission_keys::TASK_VIEW.to_string(),
+            ]
+        );
+        let (_, auditor_keys) = roles.iter().find(|(r, _)| r.name == "Auditor").unwrap();
+        assert!(auditor_keys.is_empty());
+    }
+
+    #[tokio::test]
+    async fn role_member_counts_include_unused_roles() {
+        let pool = test_pool().await;
+        let service = WorkspaceTeamService::new();
+        let team = service
+            .create_team(
+                &pool,
+                CreateWorkspaceTeam {
+                    name: "Platform".to_string(),
+                    description: None,
+                },
+                "owner",
+            )
+            .await
+            .unwrap();
+        for user in ["alice", "bob"] {
+            service
+                .add_member(&pool, team.id, user, system_roles::MEMBER, None)
+                .await
+                .unwrap();
+        }
+
+        let counts = service.role_member_counts(&pool, team.id).await.unwrap();
+
+        assert_eq!(counts[&system_roles::OWNER], 1);
+        assert_eq!(counts[&system_roles::MEMBER], 2);
+        assert_eq!(counts[&system_roles::VIEWER], 0);
+    }
+
+    #[tokio::test]
+    async fn teams_with_role_resolve_permissions_per_team() {
+        let pool = test_pool().await;
+        let service = WorkspaceTeamService::new();
+        let mut teams = Vec::new();
+        for name in ["Platform", "Design"] {
+            let team = service
+                .create_team(
+                    &pool,
+                    CreateWorkspaceTeam {
+                        name: name.to_string(),
+                        description: None,
+                    },
+                    "owner",
+                )
+                .await
+                .unwrap();
+            teams.push(team);
+        }
+        service
+            .add_member(&pool, teams[0].id, "alice", system_roles::ADMIN, None)
+            .await
+            .unwrap();
+        service
+            .add_member(&pool, teams[1].id, "alice", system_roles::VIEWER, None)
+            .await
+            .unwrap();
+
+        let memberships = service
+            .find_teams_with_role_for_user(&pool, "alice")
+            .await
+            .unwrap();
+        assert_eq!(memberships.len(), 2);
+
+        for (team, role_id) in [
+            (&teams[0], system_roles::ADMIN),
+            (&teams[1], system_roles::VIEWER),
+        ] {
+            let (_, role, keys) = memberships
+                .iter()
+                .find(|(t, _, _)| t.id == team.id)
+                .unwrap();
+            assert_eq!(role.id, role_id);
+            assert_eq!(
+                keys,
+                &service.get_role_permissions(&pool, role_id).await.unwrap()
+            );
+            assert_eq!(
+                keys,
+                &service
+                    .get_user_permissions(&pool, team.id, "alice")
+                    .await
+                    .unwrap()
+            );
+        }
+
+        let (_, _, viewer_keys) = memberships
+            .iter()
+            .find(|(t, _, _)| t.id == teams[1].id)
+            .unwrap();
+        assert!(viewer_keys.contains(&permission_keys::TASK_VIEW.to_string()));
+        assert!(!viewer_keys.contains(&permission_keys::MEMBER_INVITE.to_string()));
+    }
+
+    #[tokio::test]
+    async fn delete_team_removes_memberships() {
+        let pool = test_pool().await;
+        let service = WorkspaceTeamService::new();
+        let team = service
+            .create_team(
+                &pool,
+                CreateWorkspaceTeam {
+                    name: "Platform".to_string(),
+                    description: None,
+                },
+                "owner",
+            )
+            .await
+            .unwrap();
+        service
+            .add_member(&pool, team.id, "alice", system_roles::MEMBER, None)
+            .await
+            .unwrap();
+
+        service.delete_team(&pool, team.id).await.unwrap();
+
+        assert!(
+            WorkspaceMember::find_all_for_team(&pool, team.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(matches!(
+            service.get_team(&pool, team.id).await.unwrap_err(),
+            WorkspaceTeamServiceError::TeamNotFound
+        ));
+        assert!(matches!(
+            service.delete_team(&pool, team.id).await.unwrap_err(),
+            WorkspaceTeamServiceError::TeamNotFound
+        ));
+    }
+
+    #[tokio::test]
+    async fn permission_categories_nest_their_keys() {
+        let pool = test_pool().await;
+        let service = WorkspaceTeamService::new();
+
+        let categories = service.list_permission_categories(&pool).await.unwrap();
+        let keys_of = |name: &str| -> Vec<String> {
+            categories
+                .iter()
+                .find(|c| c.name == name)
+                .unwrap_or_else(|| panic!("missing category {name}"))
+                .permissions
+                .iter()
+                .map(|p| p.key.clone())
+                .collect()
+        };
+
+        assert_eq!(
+            keys_of("workspace"),
+            vec![
+                permission_keys::WORKSPACE_DELETE,
+                permission_keys::WORKSPACE_EDIT,
+                permission_keys::WORKSPACE_TRANSFER,
+                permission_keys::WORKSPACE_VIEW,
+            ]
+        );
+        assert_eq!(
+            keys_of("member"),
+            vec![
+                permission_keys::MEMBER_INVITE,
+                permission_keys::MEMBER_REMOVE,
+                permission_keys::MEMBER_ROLE_ASSIGN,
+                permission_keys::MEMBER_VIEW,
+            ]
+        );
+        assert!(keys_of("task").contains(&permission_keys::TASK_STATUS_CHANGE.to_string()));
+        assert_eq!(keys_of("project").len(), 4);
+        assert_eq!(categories.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn explain_permission_distinguishes_unknown_from_ungranted() {
+        let pool = test_pool().await;
+        let service = WorkspaceTeamService::new();
+        let team = service
+            .create_team(
+                &pool,
+                CreateWorkspaceTeam {
+                    name: "Platform".to_string(),
+                    description: None,
+                },
+                "owner",
+            )
+            .await
+            .unwrap();
+        service
+            .add_member(&pool, team.id, "alice", system_roles::VIEWER, None)
+            .await
+            .unwrap();
+
+        let typo = service
+            .explain_permission(&pool, team.id, "alice", "task.veiw")
+            .await
+            .unwrap();
+        assert!(!typo.key_exists);
+        assert!(!typo.granted);
+
+        let ungranted = service
+            .explain_permission(&pool, team.id, "alice", permission_keys::TASK_DELETE)
+            .await
+            .unwrap();
+        assert!(ungranted.key_exists);
+        assert!(!ungranted.granted);
+        assert_eq!(ungranted.role_name.as_deref(), Some("Viewer"));
+        assert!(
+            ungranted
+                .granted_permissions
+                .contains(&permission_keys::TASK_VIEW.to_string())
+        );
+
+        let granted = service
+            .explain_permission(&pool, team.id, "alice", permission_keys::TASK_VIEW)
+            .await
+            .unwrap();
+        assert!(granted.key_exists && granted.granted);
+    }
+
+    #[tokio::test]
+    async fn system_role_permissions_are_immutable() {
+        let pool = test_pool().await;
+        let service = WorkspaceTeamService::new();
+
+        let err = service
+            .revoke_permission(&pool, system_roles::VIEWER, permission_keys::TASK_VIEW)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, WorkspaceTeamServiceError::SystemRoleModify));
+
+        let err = service
+            .set_permissions(&pool, system_roles::OWNER, &[])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, WorkspaceTeamServiceError::SystemRoleModify));
+    }
+
+    #[tokio::test]
+    async fn duplicate_role_name_is_rejected() {
+        let pool = test_pool().await;
+        let service = WorkspaceTeamService::new();
+        let data = CreateRole {
+            name: "Reviewer".to_string(),
+            description: None,
+        };
+
+        service.create_role(&pool, data.clone()).await.unwrap();
+        let err = service.create_role(&pool, data).await.unwrap_err();
+
+        assert!(matches!(err, WorkspaceTeamServiceError::DuplicateRoleName(n) if n == "Reviewer"));
+    }
+
+    #[tokio::test]
+    async fn rename_onto_existing_role_name_is_rejected() {
+        let pool = test_pool().await;
+        let service = WorkspaceTeamService::new();
+        custom_role(&pool, "Reviewer").await;
+        let role = custom_role(&pool, "Triager").await;
+
+        let err = service
+            .update_role(
+                &pool,
+                role.id,
+                UpdateRole {
+                    name: Some("Reviewer".to_string()),
+                    description: None,
+                },
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            WorkspaceTeamServiceError::DuplicateRoleName(_)
+        ));
+
+        // Keeping the role's own name is not a conflict
+        let updated = service
+            .update_role(
+                &pool,
+                role.id,
+                UpdateRole {
+                    name: Some("Triager".to_string()),
+                    description: Some("Sorts incoming tasks".to_string()),
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(updated.description.as_deref(), Some("Sorts incoming tasks"));
+    }
+
+    #[tokio::test]
+    async fn unique_violation_maps_to_duplicate_role_name() {
+        let pool = test_pool().await;
+        custom_role(&pool, "Reviewer").await;
+
+        // Bypass the service pre-check, as a concurrent insert would
+        let err = Role::create(
+            &pool,
+            &CreateRole {
+                name: "Reviewer".to_string(),
+                description: None,
+            },
+        )
+        .await
+        .unwrap_err();
+
+        assert!(matches!(
+            map_role_name_conflict(err, "Reviewer"),
+            WorkspaceTeamServiceError::DuplicateRoleName(_)
+        ));
+        assert!(matches!(
+            map_role_name_conflict(sqlx::Error::RowNotFound, "Reviewer"),
+            WorkspaceTeamServiceError::Database(_)
+        ));
+    }
+}
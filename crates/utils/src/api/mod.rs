@@ -1,4 +1,5 @@
 pub mod oauth;
 pub mod organizations;
 pub mod projects;
+pub mod users;
 pub mod workspaces;
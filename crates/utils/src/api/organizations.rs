@@ -25,6 +25,7 @@ pub enum InvitationStatus {
     Accepted,
     Declined,
     Expired,
+    Revoked,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, TS)]
@@ -149,7 +150,7 @@ pub struct OrganizationMember {
     pub joined_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, TS)]
 #[ts(export)]
 pub struct OrganizationMemberWithProfile {
     pub user_id: Uuid,
@@ -166,6 +167,8 @@ pub struct OrganizationMemberWithProfile {
 #[ts(export)]
 pub struct ListMembersResponse {
     pub members: Vec<OrganizationMemberWithProfile>,
+    /// Pass as `cursor` to fetch the next page; absent on the last page
+    pub next_cursor: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
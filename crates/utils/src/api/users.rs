@@ -0,0 +1,118 @@
+//! The user profile exchanged between the local server and the remote one.
+//!
+//! The two sides store users differently, and both map to and from
+//! [`UserProfile`]:
+//!
+//! | local (`db::models::user::User`) | `UserProfile`             | remote (`remote::db::users::User`) |
+//! |----------------------------------|---------------------------|------------------------------------|
+//! | `id`                             | `id`                      | `id`                               |
+//! | `email`                          | `email`                   | `email`                            |
+//! | `name`                           | `first_name`, `last_name` | `first_name`, `last_name`          |
+//! | —                                | `username`                | `username`                         |
+//! | `avatar_url`                     | `avatar_url`              | `avatar_url`                       |
+//! | `cf_access_id`, `deleted_at`     | —                         | —                                  |
+//!
+//! A local `name` is split at its first run of whitespace: the first word
+//! becomes `first_name` and the rest `last_name`. Going the other way,
+//! [`UserProfile::display_name`] joins them back.
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub struct UserProfile {
+    pub id: Uuid,
+    pub email: String,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub username: Option<String>,
+    pub avatar_url: Option<String>,
+}
+
+impl UserProfile {
+    /// Single-field name for the local model: first and last name joined,
+    /// else the username, else the local part of the email.
+    pub fn display_name(&self) -> String {
+        let full_name = [self.first_name.as_deref(), self.last_name.as_deref()]
+            .into_iter()
+            .flatten()
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        if !full_name.is_empty() {
+            return full_name;
+        }
+        if let Some(username) = self.username.as_deref().map(str::trim)
+            && !username.is_empty()
+        {
+            return username.to_string();
+        }
+        self.email.split('@').next().unwrap_or_default().to_string()
+    }
+}
+
+/// Split a single-field name into `(first_name, last_name)`.
+pub fn split_name(name: &str) -> (Option<String>, Option<String>) {
+    let name = name.trim();
+    if name.is_empty() {
+        return (None, None);
+    }
+    match name.split_once(char::is_whitespace) {
+        Some((first, last)) => (Some(first.to_string()), Some(last.trim().to_string())),
+        None => (Some(name.to_string()), None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(first: Option<&str>, last: Option<&str>, username: Option<&str>) -> UserProfile {
+        UserProfile {
+            id: Uuid::new_v4(),
+            email: "dana.scully@example.com".to_string(),
+            first_name: first.map(str::to_string),
+            last_name: last.map(str::to_string),
+            username: username.map(str::to_string),
+            avatar_url: None,
+        }
+    }
+
+    #[test]
+    fn test_split_name() {
+        assert_eq!(
+            split_name("Dana Scully"),
+            (Some("Dana".to_string()), Some("Scully".to_string()))
+        );
+        assert_eq!(
+            split_name("  Dana  Katherine Scully "),
+            (
+                Some("Dana".to_string()),
+                Some("Katherine Scully".to_string())
+            )
+        );
+        assert_eq!(split_name("Cher"), (Some("Cher".to_string()), None));
+        assert_eq!(split_name("   "), (None, None));
+    }
+
+    #[test]
+    fn test_display_name_joins_split_name() {
+        for name in ["Dana Scully", "Dana Katherine Scully", "Cher"] {
+            let (first, last) = split_name(name);
+            let profile = profile(first.as_deref(), last.as_deref(), None);
+            assert_eq!(profile.display_name(), name);
+        }
+    }
+
+    #[test]
+    fn test_display_name_falls_back_to_username_then_email() {
+        assert_eq!(
+            profile(None, None, Some("dscully")).display_name(),
+            "dscully"
+        );
+        assert_eq!(profile(None, Some("Scully"), None).display_name(), "Scully");
+        assert_eq!(profile(None, None, Some(" ")).display_name(), "dana.scully");
+    }
+}
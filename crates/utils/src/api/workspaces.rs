@@ -13,6 +13,9 @@ use super::organizations::{InvitationStatus, MemberRole};
 #[ts(export)]
 #[ts(use_ts_enum)]
 pub enum WorkspacePermission {
+    #[serde(rename = "member.view")]
+    #[sqlx(rename = "member.view")]
+    MemberView,
     #[serde(rename = "member.invite")]
     #[sqlx(rename = "member.invite")]
     MemberInvite,
@@ -24,6 +27,26 @@ pub enum WorkspacePermission {
     MemberRoleChange,
 }
 
+impl WorkspacePermission {
+    /// Every variant
+    pub const ALL: [Self; 4] = [
+        Self::MemberView,
+        Self::MemberInvite,
+        Self::MemberRemove,
+        Self::MemberRoleChange,
+    ];
+
+    /// The permission key, as stored in the database and sent over the API
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::MemberView => "member.view",
+            Self::MemberInvite => "member.invite",
+            Self::MemberRemove => "member.remove",
+            Self::MemberRoleChange => "member.role.change",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[ts(export)]
 pub struct WorkspaceMember {
@@ -53,6 +76,8 @@ pub struct WorkspaceMemberWithProfile {
 #[ts(export)]
 pub struct ListWorkspaceMembersResponse {
     pub members: Vec<WorkspaceMemberWithProfile>,
+    /// Number of members matching the search, across all pages
+    pub total: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -67,6 +92,9 @@ pub struct WorkspaceInvitation {
     pub token: String,
     pub created_at: DateTime<Utc>,
     pub expires_at: DateTime<Utc>,
+    /// When and by whom the invitation was revoked, if it was
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub revoked_by_user_id: Option<Uuid>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -74,6 +102,10 @@ pub struct WorkspaceInvitation {
 pub struct InviteWorkspaceMemberRequest {
     pub email: String,
     pub role: MemberRole,
+    /// Overrides the server's default invitation lifetime, within its limit
+    #[serde(default)]
+    #[ts(optional)]
+    pub expires_in_days: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -82,6 +114,40 @@ pub struct InviteWorkspaceMemberResponse {
     pub invitation: WorkspaceInvitation,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct BulkInviteWorkspaceMembersRequest {
+    pub emails: Vec<String>,
+    pub role: MemberRole,
+    /// Overrides the server's default invitation lifetime, within its limit
+    #[serde(default)]
+    #[ts(optional)]
+    pub expires_in_days: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum BulkInviteOutcome {
+    Created,
+    Invalid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct BulkInviteResult {
+    pub email: String,
+    pub outcome: BulkInviteOutcome,
+    pub invitation: Option<WorkspaceInvitation>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct BulkInviteWorkspaceMembersResponse {
+    pub results: Vec<BulkInviteResult>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[ts(export)]
 pub struct UpdateWorkspaceMemberRoleRequest {
@@ -95,6 +161,24 @@ pub struct UpdateWorkspaceMemberRoleResponse {
     pub role: MemberRole,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TransferWorkspaceOwnershipRequest {
+    pub new_owner_user_id: Uuid,
+    /// Step down to a regular member once the new owner is an admin
+    #[serde(default)]
+    #[ts(optional)]
+    pub demote_self: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TransferWorkspaceOwnershipResponse {
+    pub workspace_id: Uuid,
+    pub new_owner_user_id: Uuid,
+    pub previous_owner_role: MemberRole,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[ts(export)]
 pub struct GetWorkspaceInvitationResponse {
@@ -102,6 +186,9 @@ pub struct GetWorkspaceInvitationResponse {
     pub workspace_id: Uuid,
     pub role: MemberRole,
     pub expires_at: DateTime<Utc>,
+    /// Inviter's full name, or username if they have none. `None` when the
+    /// inviting account no longer exists.
+    pub invited_by_name: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -111,13 +198,69 @@ pub struct AcceptWorkspaceInvitationResponse {
     pub role: MemberRole,
 }
 
+/// An invitation addressed to the current user. Workspace names are not
+/// stored on the remote server, so clients resolve `workspace_id` locally.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct PendingWorkspaceInvitation {
+    pub id: Uuid,
+    pub workspace_id: Uuid,
+    pub role: MemberRole,
+    pub token: String,
+    pub invited_by_username: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ListPendingWorkspaceInvitationsResponse {
+    pub invitations: Vec<PendingWorkspaceInvitation>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 pub struct RevokeWorkspaceInvitationRequest {
     pub invitation_id: Uuid,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RevokeInvitationsByInviterRequest {
+    pub inviter_user_id: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RevokeInvitationsByInviterResponse {
+    /// Pending invitations that were revoked
+    pub revoked: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ExtendWorkspaceInvitationRequest {
+    /// Days added to the invitation's current expiry
+    pub additional_days: i64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[ts(export)]
 pub struct ListWorkspaceInvitationsResponse {
     pub invitations: Vec<WorkspaceInvitation>,
+    /// Number of invitations with the requested status, across all pages
+    pub total: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct WorkspaceSettings {
+    pub workspace_id: Uuid,
+    /// Seconds after creation before an invitation can be accepted
+    pub invitation_activation_delay_secs: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct UpdateWorkspaceSettingsRequest {
+    pub invitation_activation_delay_secs: i64,
 }
@@ -11,6 +11,7 @@ pub mod git;
 pub mod jwt;
 pub mod log_msg;
 pub mod msg_store;
+pub mod pagination;
 pub mod path;
 pub mod port_file;
 pub mod response;
@@ -0,0 +1,223 @@
+//! Cursor (keyset) pagination for list endpoints.
+//!
+//! Offset pagination repeats or skips rows when rows are inserted while a
+//! client is paging. A cursor instead records the sort key and id of the last
+//! row returned, and the next page starts strictly after that pair. Queries
+//! must order by `(sort_key, id)` ascending for the cursor to be stable.
+
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use thiserror::Error;
+use uuid::Uuid;
+
+pub const DEFAULT_PAGE_SIZE: i64 = 50;
+pub const MAX_PAGE_SIZE: i64 = 200;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("Invalid pagination cursor")]
+pub struct InvalidCursor;
+
+/// Position of the last row of a page. The id breaks ties between rows that
+/// share a sort key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cursor<K> {
+    pub sort_key: K,
+    pub id: Uuid,
+}
+
+impl<K: Serialize + DeserializeOwned> Cursor<K> {
+    pub fn new(sort_key: K, id: Uuid) -> Self {
+        Self { sort_key, id }
+    }
+
+    /// Opaque, URL-safe token handed to clients.
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_vec(&(&self.sort_key, self.id))
+            .expect("cursor sort keys serialize to JSON");
+        URL_SAFE_NO_PAD.encode(json)
+    }
+
+    pub fn decode(token: &str) -> Result<Self, InvalidCursor> {
+        let json = URL_SAFE_NO_PAD.decode(token).map_err(|_| InvalidCursor)?;
+        let (sort_key, id) = serde_json::from_slice(&json).map_err(|_| InvalidCursor)?;
+        Ok(Self { sort_key, id })
+    }
+}
+
+/// `?limit=&cursor=` query parameters shared by paginated endpoints.
+#[derive(Debug, Default, Deserialize)]
+pub struct PageQuery {
+    pub limit: Option<i64>,
+    pub cursor: Option<String>,
+}
+
+impl PageQuery {
+    /// Requested page size, clamped to `1..=MAX_PAGE_SIZE`.
+    pub fn limit(&self) -> i64 {
+        self.limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE)
+    }
+
+    pub fn cursor<K: Serialize + DeserializeOwned>(
+        &self,
+    ) -> Result<Option<Cursor<K>>, InvalidCursor> {
+        self.cursor.as_deref().map(Cursor::decode).transpose()
+    }
+}
+
+/// SQL predicate selecting rows after a cursor, with the cursor's sort key
+/// and id bound at `$first_param` and the placeholder after it. Postgres and
+/// SQLite both compare row values lexicographically.
+pub fn after_clause(sort_column: &str, id_column: &str, first_param: usize) -> String {
+    format!(
+        "({sort_column}, {id_column}) > (${first_param}, ${})",
+        first_param + 1
+    )
+}
+
+/// Trim rows fetched with `LIMIT limit + 1` down to `limit`, returning the
+/// cursor for the next page when the extra row shows there is one.
+pub fn split_page<T, K>(
+    mut rows: Vec<T>,
+    limit: i64,
+    cursor_for: impl Fn(&T) -> Cursor<K>,
+) -> (Vec<T>, Option<String>)
+where
+    K: Serialize + DeserializeOwned,
+{
+    let limit = usize::try_from(limit.max(0)).unwrap_or(usize::MAX);
+    if rows.len() <= limit {
+        return (rows, None);
+    }
+    rows.truncate(limit);
+    let next = rows.last().map(|row| cursor_for(row).encode());
+    (rows, next)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Row {
+        joined_at: DateTime<Utc>,
+        id: Uuid,
+    }
+
+    fn cursor_for(row: &Row) -> Cursor<DateTime<Utc>> {
+        Cursor::new(row.joined_at, row.id)
+    }
+
+    /// In-memory stand-in for `WHERE (joined_at, id) > ($n, $m) ORDER BY
+    /// joined_at, id LIMIT limit + 1`.
+    fn fetch_page(table: &[Row], query: &PageQuery) -> (Vec<Row>, Option<String>) {
+        let after = query.cursor::<DateTime<Utc>>().unwrap();
+        let mut rows: Vec<Row> = table
+            .iter()
+            .filter(|row| {
+                after
+                    .as_ref()
+                    .is_none_or(|c| (row.joined_at, row.id) > (c.sort_key, c.id))
+            })
+            .cloned()
+            .collect();
+        rows.sort_by_key(|row| (row.joined_at, row.id));
+        rows.truncate(query.limit() as usize + 1);
+        split_page(rows, query.limit(), cursor_for)
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = Cursor::new(
+            Utc.with_ymd_and_hms(2026, 1, 18, 9, 30, 0).unwrap() + Duration::microseconds(123),
+            Uuid::new_v4(),
+        );
+        let token = cursor.encode();
+        assert!(
+            token
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        );
+        assert_eq!(Cursor::decode(&token), Ok(cursor));
+
+        let cursor = Cursor::new("alice@example.com".to_string(), Uuid::new_v4());
+        assert_eq!(Cursor::decode(&cursor.encode()), Ok(cursor));
+    }
+
+    #[test]
+    fn test_decode_rejects_garbage() {
+        assert_eq!(Cursor::<i64>::decode("not a cursor!"), Err(InvalidCursor));
+        let wrong_shape = URL_SAFE_NO_PAD.encode(br#"{"id":1}"#);
+        assert_eq!(Cursor::<i64>::decode(&wrong_shape), Err(InvalidCursor));
+    }
+
+    #[test]
+    fn test_after_clause() {
+        assert_eq!(
+            after_clause("omm.joined_at", "omm.user_id", 3),
+            "(omm.joined_at, omm.user_id) > ($3, $4)"
+        );
+    }
+
+    #[test]
+    fn test_page_limit_is_clamped() {
+        let query = |limit| PageQuery {
+            limit,
+            cursor: None,
+        };
+        assert_eq!(query(None).limit(), DEFAULT_PAGE_SIZE);
+        assert_eq!(query(Some(0)).limit(), 1);
+        assert_eq!(query(Some(10_000)).limit(), MAX_PAGE_SIZE);
+    }
+
+    #[test]
+    fn test_pages_are_stable_across_inserts() {
+        let start = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let row = |minutes| Row {
+            joined_at: start + Duration::minutes(minutes),
+            id: Uuid::new_v4(),
+        };
+        let mut table: Vec<Row> = (0..5).map(row).collect();
+        // Same sort key as the second row, so only the id orders them
+        table.push(Row {
+            joined_at: table[1].joined_at,
+            id: Uuid::new_v4(),
+        });
+        let mut expected = table.clone();
+        expected.sort_by_key(|row| (row.joined_at, row.id));
+
+        let (first, next) = fetch_page(
+            &table,
+            &PageQuery {
+                limit: Some(2),
+                cursor: None,
+            },
+        );
+        assert_eq!(first, expected[..2]);
+
+        // Rows inserted before the cursor must not shift later pages
+        table.push(row(-10));
+        table.push(Row {
+            joined_at: first[1].joined_at,
+            id: Uuid::nil(),
+        });
+
+        let mut seen = first;
+        let mut cursor = next;
+        while let Some(token) = cursor {
+            let (page, next) = fetch_page(
+                &table,
+                &PageQuery {
+                    limit: Some(2),
+                    cursor: Some(token),
+                },
+            );
+            seen.extend(page);
+            cursor = next;
+        }
+        assert_eq!(seen, expected);
+    }
+}
@@ -1,13 +1,87 @@
-use std::{env, path::PathBuf};
+use std::{
+    env,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
-use tokio::fs;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::{fs, net::TcpStream};
 
-pub async fn write_port_file(port: u16) -> std::io::Result<PathBuf> {
+/// How long to wait for an instance's port to accept a connection before
+/// treating its port file as stale
+const LIVENESS_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// A server instance as recorded in its port file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunningInstance {
+    pub pid: u32,
+    pub host: String,
+    pub port: u16,
+}
+
+impl RunningInstance {
+    /// Host to dial from this machine. A server bound to every interface is
+    /// reachable on loopback.
+    fn connect_host(&self) -> String {
+        match self.host.parse::<IpAddr>() {
+            Ok(IpAddr::V4(ip)) if ip.is_unspecified() => Ipv4Addr::LOCALHOST.to_string(),
+            Ok(IpAddr::V6(ip)) if ip.is_unspecified() => Ipv6Addr::LOCALHOST.to_string(),
+            _ => self.host.clone(),
+        }
+    }
+
+    /// Base URL for reaching the instance from this machine
+    pub fn base_url(&self) -> String {
+        let host = self.connect_host();
+        if host.contains(':') {
+            format!("http://[{host}]:{}", self.port)
+        } else {
+            format!("http://{host}:{}", self.port)
+        }
+    }
+}
+
+/// Connection state of a `connect` agent, as it last recorded it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentStatus {
+    pub pid: u32,
+    pub url: String,
+    pub connected: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+fn agent_status_path(app_name: &str) -> PathBuf {
+    env::temp_dir().join(app_name).join("agent.json")
+}
+
+fn instances_dir(app_name: &str) -> PathBuf {
+    env::temp_dir().join(app_name).join("instances")
+}
+
+/// Record the port for the MCP server and other single-instance readers, and
+/// this process's host and port for [`list_running_instances`].
+pub async fn write_port_file(host: &str, port: u16) -> std::io::Result<PathBuf> {
     let dir = env::temp_dir().join("vibe-kanban");
     let path = dir.join("vibe-kanban.port");
     tracing::debug!("Writing port {} to {:?}", port, path);
     fs::create_dir_all(&dir).await?;
     fs::write(&path, port.to_string()).await?;
+
+    let instance = RunningInstance {
+        pid: std::process::id(),
+        host: host.to_string(),
+        port,
+    };
+    let instances = instances_dir("vibe-kanban");
+    fs::create_dir_all(&instances).await?;
+    fs::write(
+        instances.join(format!("{}.json", instance.pid)),
+        serde_json::to_vec(&instance)?,
+    )
+    .await?;
+
     Ok(path)
 }
 
@@ -24,3 +98,158 @@ pub async fn read_port_file(app_name: &str) -> std::io::Result<u16> {
 
     Ok(port)
 }
+
+/// Instances whose port files are present and whose port still accepts
+/// connections, ordered by port. Stale and unreadable files are skipped.
+pub async fn list_running_instances(app_name: &str) -> std::io::Result<Vec<RunningInstance>> {
+    list_instances_in(&instances_dir(app_name)).await
+}
+
+async fn list_instances_in(dir: &Path) -> std::io::Result<Vec<RunningInstance>> {
+    let mut entries = match fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut instances = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        let instance = match read_instance(&path).await {
+            Ok(instance) => instance,
+            Err(e) => {
+                tracing::debug!("Ignoring unreadable port file {:?}: {}", path, e);
+                continue;
+            }
+        };
+
+        if is_listening(&instance).await {
+            instances.push(instance);
+        } else {
+            tracing::debug!("Ignoring stale port file {:?}", path);
+        }
+    }
+
+    instances.sort_by_key(|instance| instance.port);
+    Ok(instances)
+}
+
+async fn read_instance(path: &Path) -> std::io::Result<RunningInstance> {
+    let bytes = fs::read(path).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+async fn is_listening(instance: &RunningInstance) -> bool {
+    let host = instance.connect_host();
+    matches!(
+        tokio::time::timeout(
+            LIVENESS_TIMEOUT,
+            TcpStream::connect((host.as_str(), instance.port))
+        )
+        .await,
+        Ok(Ok(_))
+    )
+}
+
+/// Record the agent's connection state for `status`
+pub async fn write_agent_status(app_name: &str, status: &AgentStatus) -> std::io::Result<()> {
+    let path = agent_status_path(app_name);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).await?;
+    }
+    fs::write(&path, serde_json::to_vec(status)?).await
+}
+
+/// The last recorded agent state, if an agent has run since the temp
+/// directory was cleared. The agent may have exited without cleaning up.
+pub async fn read_agent_status(app_name: &str) -> std::io::Result<Option<AgentStatus>> {
+    match fs::read(agent_status_path(app_name)).await {
+        Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Forget the agent's state once it shuts down
+pub async fn remove_agent_status(app_name: &str) -> std::io::Result<()> {
+    match fs::remove_file(agent_status_path(app_name)).await {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    async fn scratch_dir() -> PathBuf {
+        let dir = env::temp_dir().join(format!("vk-port-file-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).await.unwrap();
+        dir
+    }
+
+    async fn write_instance(dir: &Path, instance: &RunningInstance) {
+        fs::write(
+            dir.join(format!("{}.json", instance.pid)),
+            serde_json::to_vec(instance).unwrap(),
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_live_instance_is_listed() {
+        let dir = scratch_dir().await;
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let live = RunningInstance {
+            pid: 1,
+            host: "127.0.0.1".to_string(),
+            port: listener.local_addr().unwrap().port(),
+        };
+        write_instance(&dir, &live).await;
+
+        assert_eq!(list_instances_in(&dir).await.unwrap(), vec![live]);
+        fs::remove_dir_all(&dir).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_stale_and_unreadable_files_are_ignored() {
+        let dir = scratch_dir().await;
+
+        // Bind then drop, so the port is known to be free
+        let stale_port = {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            listener.local_addr().unwrap().port()
+        };
+        let stale = RunningInstance {
+            pid: 2,
+            host: "127.0.0.1".to_string(),
+            port: stale_port,
+        };
+        write_instance(&dir, &stale).await;
+        fs::write(dir.join("3.json"), "not json").await.unwrap();
+
+        assert!(list_instances_in(&dir).await.unwrap().is_empty());
+        fs::remove_dir_all(&dir).await.unwrap();
+    }
+
+    #[test]
+    fn test_base_url_dials_loopback_for_unspecified_hosts() {
+        let instance = |host: &str| RunningInstance {
+            pid: 1,
+            host: host.to_string(),
+            port: 8080,
+        };
+        assert_eq!(instance("0.0.0.0").base_url(), "http://127.0.0.1:8080");
+        assert_eq!(instance("::").base_url(), "http://[::1]:8080");
+        assert_eq!(instance("localhost").base_url(), "http://localhost:8080");
+    }
+
+    #[tokio::test]
+    async fn test_missing_directory_lists_nothing() {
+        let dir = env::temp_dir().join(format!("vk-port-file-test-{}", uuid::Uuid::new_v4()));
+        assert!(list_instances_in(&dir).await.unwrap().is_empty());
+    }
+}
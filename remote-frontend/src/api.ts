@@ -50,7 +50,12 @@ export type OrganizationMemberWithProfile = {
   avatar_url: string | null;
 };
 
-export type InvitationStatus = "PENDING" | "ACCEPTED" | "DECLINED" | "EXPIRED";
+export type InvitationStatus =
+  | "PENDING"
+  | "ACCEPTED"
+  | "DECLINED"
+  | "EXPIRED"
+  | "REVOKED";
 
 export type OrganizationInvitation = {
   id: string;
@@ -380,17 +385,30 @@ export async function deleteOrganization(orgId: string): Promise<void> {
 }
 
 // Organization Members APIs
+type ListMembersResponse = {
+  members: OrganizationMemberWithProfile[];
+  next_cursor: string | null;
+};
+
+// The server returns members a page at a time; follow the cursor to the end
 export async function listMembers(
   orgId: string,
 ): Promise<OrganizationMemberWithProfile[]> {
-  const res = await authenticatedFetch(
-    `${API_BASE}/v1/organizations/${orgId}/members`,
-  );
-  if (!res.ok) {
-    throw new Error(`Failed to fetch members (${res.status})`);
-  }
-  const data = await res.json();
-  return data.members;
+  const members: OrganizationMemberWithProfile[] = [];
+  let cursor: string | null = null;
+  do {
+    const query: string = cursor ? `?cursor=${encodeURIComponent(cursor)}` : "";
+    const res = await authenticatedFetch(
+      `${API_BASE}/v1/organizations/${orgId}/members${query}`,
+    );
+    if (!res.ok) {
+      throw new Error(`Failed to fetch members (${res.status})`);
+    }
+    const data: ListMembersResponse = await res.json();
+    members.push(...data.members);
+    cursor = data.next_cursor;
+  } while (cursor);
+  return members;
 }
 
 export async function removeMember(
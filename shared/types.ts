@@ -10,7 +10,7 @@ export type AssigneesQuery = { project_id: string, };
 
 export type SharedTask = { id: string, organization_id: string, project_id: string, creator_user_id: string | null, assignee_user_id: string | null, deleted_by_user_id: string | null, title: string, description: string | null, status: TaskStatus, deleted_at: string | null, shared_at: string | null, created_at: string, updated_at: string, };
 
-export type UserData = { user_id: string, first_name: string | null, last_name: string | null, username: string | null, };
+export type UserData = { user_id: string, first_name: string | null, last_name: string | null, username: string | null, avatar_url: string | null, };
 
 export type Project = { id: string, name: string, default_agent_working_dir: string | null, remote_project_id: string | null, created_at: Date, updated_at: Date, };
 
@@ -96,6 +96,8 @@ export type UpdateRole = { name: string | null, description: string | null, };
 
 export type Permission = { id: string, key: string, description: string | null, created_at: Date, };
 
+export type PermissionCategory = { name: string, permissions: Array<Permission>, };
+
 export type WorkspaceMember = { id: string, workspace_team_id: string, user_id: string, role_id: string, invited_by: string | null, joined_at: Date, created_at: Date, updated_at: Date, };
 
 export type WorkspaceMemberWithRole = { role_name: string, id: string, workspace_team_id: string, user_id: string, role_id: string, invited_by: string | null, joined_at: Date, created_at: Date, updated_at: Date, };
@@ -130,9 +132,17 @@ export type MergeStatus = "open" | "merged" | "closed" | "unknown";
 
 export type PullRequestInfo = { number: bigint, url: string, status: MergeStatus, merged_at: string | null, merge_commit_sha: string | null, };
 
-export type User = { id: string, email: string, name: string, avatar_url: string | null, cf_access_id: string | null, created_at: Date, updated_at: Date, };
+export type User = { id: string, email: string, name: string, avatar_url: string | null, cf_access_id: string | null, created_at: Date, updated_at: Date, 
+/**
+ * Set when the account is deactivated; such users are hidden from lookups
+ */
+deleted_at: Date | null, 
+/**
+ * When the user last started a new session
+ */
+last_login_at: Date | null, login_count: bigint, };
 
-export type UserSession = { id: string, user_id: string, cf_access_jwt_id: string | null, expires_at: Date, created_at: Date, last_used_at: Date, };
+export type UserSession = { id: string, user_id: string, cf_access_jwt_id: string | null, user_agent: string | null, ip_address: string | null, expires_at: Date, created_at: Date, last_used_at: Date, };
 
 export type ApprovalStatus = { "status": "pending" } | { "status": "approved" } | { "status": "denied", reason?: string, } | { "status": "timed_out" };
 
@@ -164,7 +174,7 @@ export type StatusResponse = { logged_in: boolean, profile: ProfileResponse | nu
 
 export enum MemberRole { ADMIN = "ADMIN", MEMBER = "MEMBER" }
 
-export enum InvitationStatus { PENDING = "PENDING", ACCEPTED = "ACCEPTED", DECLINED = "DECLINED", EXPIRED = "EXPIRED" }
+export enum InvitationStatus { PENDING = "PENDING", ACCEPTED = "ACCEPTED", DECLINED = "DECLINED", EXPIRED = "EXPIRED", REVOKED = "REVOKED" }
 
 export type Organization = { id: string, name: string, slug: string, is_personal: boolean, created_at: string, updated_at: string, };
 
@@ -198,37 +208,108 @@ export type OrganizationMember = { user_id: string, role: MemberRole, joined_at:
 
 export type OrganizationMemberWithProfile = { user_id: string, role: MemberRole, joined_at: string, first_name: string | null, last_name: string | null, username: string | null, email: string | null, avatar_url: string | null, };
 
-export type ListMembersResponse = { members: Array<OrganizationMemberWithProfile>, };
+export type ListMembersResponse = { members: Array<OrganizationMemberWithProfile>, 
+/**
+ * Pass as `cursor` to fetch the next page; absent on the last page
+ */
+next_cursor: string | null, };
 
 export type UpdateMemberRoleRequest = { role: MemberRole, };
 
 export type UpdateMemberRoleResponse = { user_id: string, role: MemberRole, };
 
-export enum WorkspacePermission { member.invite = "member.invite", member.remove = "member.remove", member.role.change = "member.role.change" }
+export enum WorkspacePermission { member.view = "member.view", member.invite = "member.invite", member.remove = "member.remove", member.role.change = "member.role.change" }
 
 export type WorkspaceMember = { workspace_id: string, user_id: string, role: MemberRole, permissions: Array<WorkspacePermission>, joined_at: string, };
 
 export type WorkspaceMemberWithProfile = { workspace_id: string, user_id: string, role: MemberRole, permissions: Array<WorkspacePermission>, joined_at: string, first_name: string | null, last_name: string | null, username: string | null, email: string | null, avatar_url: string | null, };
 
-export type ListWorkspaceMembersResponse = { members: Array<WorkspaceMemberWithProfile>, };
+export type ListWorkspaceMembersResponse = { members: Array<WorkspaceMemberWithProfile>, 
+/**
+ * Number of members matching the search, across all pages
+ */
+total: bigint, };
 
-export type WorkspaceInvitation = { id: string, workspace_id: string, invited_by_user_id: string | null, email: string, role: MemberRole, status: InvitationStatus, token: string, created_at: string, expires_at: string, };
+export type WorkspaceInvitation = { id: string, workspace_id: string, invited_by_user_id: string | null, email: string, role: MemberRole, status: InvitationStatus, token: string, created_at: string, expires_at: string, 
+/**
+ * When and by whom the invitation was revoked, if it was
+ */
+revoked_at: string | null, revoked_by_user_id: string | null, };
 
-export type InviteWorkspaceMemberRequest = { email: string, role: MemberRole, };
+export type InviteWorkspaceMemberRequest = { email: string, role: MemberRole, 
+/**
+ * Overrides the server's default invitation lifetime, within its limit
+ */
+expires_in_days?: bigint, };
 
 export type InviteWorkspaceMemberResponse = { invitation: WorkspaceInvitation, };
 
+export type BulkInviteWorkspaceMembersRequest = { emails: Array<string>, role: MemberRole, 
+/**
+ * Overrides the server's default invitation lifetime, within its limit
+ */
+expires_in_days?: bigint, };
+
+export type BulkInviteOutcome = "created" | "invalid";
+
+export type BulkInviteResult = { email: string, outcome: BulkInviteOutcome, invitation: WorkspaceInvitation | null, error: string | null, };
+
+export type BulkInviteWorkspaceMembersResponse = { results: Array<BulkInviteResult>, };
+
 export type UpdateWorkspaceMemberRoleRequest = { role: MemberRole, };
 
 export type UpdateWorkspaceMemberRoleResponse = { user_id: string, role: MemberRole, };
 
-export type GetWorkspaceInvitationResponse = { id: string, workspace_id: string, role: MemberRole, expires_at: string, };
+export type TransferWorkspaceOwnershipRequest = { new_owner_user_id: string, 
+/**
+ * Step down to a regular member once the new owner is an admin
+ */
+demote_self?: boolean, };
+
+export type TransferWorkspaceOwnershipResponse = { workspace_id: string, new_owner_user_id: string, previous_owner_role: MemberRole, };
+
+export type GetWorkspaceInvitationResponse = { id: string, workspace_id: string, role: MemberRole, expires_at: string, 
+/**
+ * Inviter's full name, or username if they have none. `None` when the
+ * inviting account no longer exists.
+ */
+invited_by_name: string | null, };
 
 export type AcceptWorkspaceInvitationResponse = { workspace_id: string, role: MemberRole, };
 
+export type PendingWorkspaceInvitation = { id: string, workspace_id: string, role: MemberRole, token: string, invited_by_username: string | null, created_at: string, expires_at: string, };
+
+export type ListPendingWorkspaceInvitationsResponse = { invitations: Array<PendingWorkspaceInvitation>, };
+
 export type RevokeWorkspaceInvitationRequest = { invitation_id: string, };
 
-export type ListWorkspaceInvitationsResponse = { invitations: Array<WorkspaceInvitation>, };
+export type RevokeInvitationsByInviterRequest = { inviter_user_id: string, };
+
+export type RevokeInvitationsByInviterResponse = { 
+/**
+ * Pending invitations that were revoked
+ */
+revoked: bigint, };
+
+export type ExtendWorkspaceInvitationRequest = { 
+/**
+ * Days added to the invitation's current expiry
+ */
+additional_days: bigint, };
+
+export type ListWorkspaceInvitationsResponse = { invitations: Array<WorkspaceInvitation>, 
+/**
+ * Number of invitations with the requested status, across all pages
+ */
+total: bigint, };
+
+export type WorkspaceSettings = { workspace_id: string, 
+/**
+ * Seconds after creation before an invitation can be accepted
+ */
+invitation_activation_delay_secs: bigint, };
+
+export type UpdateWorkspaceSettingsRequest = { invitation_activation_delay_secs: bigint, };
 
 export type RemoteProject = { id: string, organization_id: string, name: string, metadata: Record<string, unknown>, created_at: string, };
 
@@ -272,12 +353,67 @@ export type CurrentUserResponse = { user_id: string, };
 
 export type AuthMeResponse = { user: UserResponse, session: SessionResponse, };
 
-export type UserResponse = { id: string, email: string, name: string, avatar_url: string | null, created_at: Date, };
+export type UserResponse = { id: string, email: string, name: string, avatar_url: string | null, created_at: Date, last_login_at: Date | null, };
 
 export type SessionResponse = { id: string, expires_at: Date, created_at: Date, };
 
+export type ActiveSessionResponse = { id: string, created_at: Date, last_used_at: Date, expires_at: Date, user_agent: string | null, ip_address: string | null, 
+/**
+ * Whether this is the session making the request
+ */
+current: boolean, };
+
+export type DependencyState = "up" | "down";
+
+export type DependencyStatus = { name: string, status: DependencyState, 
+/**
+ * Whether the service is unready while this dependency is down
+ */
+required: boolean, error: string | null, };
+
+export type ReadinessResponse = { ready: boolean, dependencies: Array<DependencyStatus>, };
+
+export type UserSearchResult = { id: string, name: string, email: string | null, avatar_url: string | null, };
+
+export type RoleWithPermissions = { role: Role, permissions: Array<string>, };
+
+export type MyWorkspace = { team: WorkspaceTeam, role: Role, permissions: Array<string>, };
+
+export type PermissionExplanation = { permission_key: string, 
+/**
+ * False when no permission has this key, which usually means a typo
+ */
+key_exists: boolean, 
+/**
+ * The user's role in the team, `None` if they aren't a member
+ */
+role_name: string | null, granted_permissions: Array<string>, granted: boolean, };
+
+export type PermissionDiff = { to_add: Array<string>, to_remove: Array<string>, unchanged: Array<string>, };
+
+export type AgentMessage = { "type": "REGISTER", agentVersion: string, hostname: string, availableExecutors: Array<BaseCodingAgent>, os: string, arch: string, 
+/**
+ * Seconds between `Heartbeat` frames, so the relay can size its
+ * liveness timeout
+ */
+heartbeatIntervalSecs: bigint, } | { "type": "HEARTBEAT" } | { "type": "HEARTBEAT_ACK" } | { "type": "GOODBYE" } | { "type": "EXECUTE", taskId: string, payload: ExecutePayload, } | { "type": "EXECUTION_STARTED", taskId: string, workspaceId: string, executionProcessId: string, } | { "type": "EXECUTION_COMPLETED", taskId: string, executionProcessId: string, } | { "type": "EXECUTION_FAILED", taskId: string | null, executionProcessId: string | null, error: string, } | { "type": "CANCEL", taskId: string, } | { "type": "EXECUTION_CANCELLED", taskId: string, executionProcessIds: Array<string>, } | { "type": "CANCEL_NOOP", taskId: string, };
+
+export type ExecutePayload = { 
+/**
+ * Falls back to the executor profile in the local config
+ */
+executorProfileId?: ExecutorProfileId, repos: Array<ExecuteRepo>, };
+
+export type ExecuteRepo = { repoId: string, targetBranch: string, };
+
 export type LogoutResponse = { success: boolean, message: string, };
 
+export type ForceLogoutResponse = { 
+/**
+ * Number of sessions that were invalidated
+ */
+sessions_revoked: bigint, };
+
 export type CreateFollowUpAttempt = { prompt: string, variant: string | null, retry_process_id: string | null, force_when_dirty: boolean | null, perform_git_reset: boolean | null, };
 
 export type ChangeTargetBranchRequest = { repo_id: string, new_target_branch: string, };
@@ -517,6 +653,22 @@ export type ExecutorConfig = { [key in string]?: { "CLAUDE_CODE": ClaudeCode } |
 
 export type ExecutorConfigs = { executors: { [key in BaseCodingAgent]?: ExecutorConfig }, };
 
+export type ExecutorInfo = { executor: BaseCodingAgent, display_name: string, 
+/**
+ * `DEFAULT` first, then the other variants by name
+ */
+variants: Array<ExecutorVariantInfo>, 
+/**
+ * Capabilities of the default variant
+ */
+capabilities: Array<BaseAgentCapability>, };
+
+export type ExecutorVariantInfo = { name: string, 
+/**
+ * Model the variant pins, or `None` for the agent's own default
+ */
+model: string | null, };
+
 export enum BaseAgentCapability { SESSION_FORK = "SESSION_FORK", SETUP_HELPER = "SETUP_HELPER" }
 
 export type ClaudeCode = { append_prompt: AppendPrompt, claude_code_router?: boolean | null, plan?: boolean | null, approvals?: boolean | null, model?: string | null, dangerously_skip_permissions?: boolean | null, disable_api_key?: boolean | null, base_command_override?: string | null, additional_params?: Array<string> | null, env?: { [key in string]?: string } | null, };
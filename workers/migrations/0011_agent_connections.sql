@@ -0,0 +1,17 @@
+-- Migration: Agent Connections
+-- Durable record of local agents seen by the relay, so liveness survives a
+-- relay restart
+
+CREATE TABLE agent_connections (
+  agent_id TEXT PRIMARY KEY,
+  user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+  hostname TEXT NOT NULL, -- From the agent's REGISTER frame
+  agent_version TEXT,
+  status TEXT NOT NULL DEFAULT 'online' CHECK (status IN ('online', 'offline')),
+  connected_at TEXT NOT NULL,
+  last_heartbeat_at TEXT NOT NULL,
+  disconnected_at TEXT,
+  UNIQUE (user_id, hostname)
+);
+
+CREATE INDEX idx_agent_connections_user ON agent_connections(user_id);
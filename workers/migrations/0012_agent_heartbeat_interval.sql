@@ -0,0 +1,5 @@
+-- Migration: Agent Heartbeat Interval
+-- Heartbeat interval each agent advertised at REGISTER, so its heartbeat
+-- timeout can be sized to match
+
+ALTER TABLE agent_connections ADD COLUMN heartbeat_interval_secs INTEGER;
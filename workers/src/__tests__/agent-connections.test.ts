@@ -0,0 +1,125 @@
+import { describe, it, expect } from 'vitest';
+import {
+  HEARTBEAT_TIMEOUT_MS,
+  applyHeartbeat,
+  applyOffline,
+  heartbeatTimeoutMs,
+  isHeartbeatExpired,
+  type AgentIdentity,
+} from '../services/agent-connections';
+
+describe('Agent Connection Transitions', () => {
+  const identity: AgentIdentity = {
+    user_id: 'user-1',
+    hostname: 'build-box',
+    agent_version: '0.0.149',
+    heartbeat_interval_secs: null,
+  };
+
+  const T0 = '2026-01-16T10:00:00.000Z';
+  const T1 = '2026-01-16T10:00:15.000Z';
+  const T2 = '2026-01-16T10:00:30.000Z';
+
+  describe('Upsert on heartbeat', () => {
+    it('creates an online connection on first sight', () => {
+      const connection = applyHeartbeat(null, identity, T0, true);
+
+      expect(connection.agent_id).toBeDefined();
+      expect(connection.user_id).toBe('user-1');
+      expect(connection.hostname).toBe('build-box');
+      expect(connection.status).toBe('online');
+      expect(connection.connected_at).toBe(T0);
+      expect(connection.last_heartbeat_at).toBe(T0);
+      expect(connection.disconnected_at).toBeNull();
+    });
+
+    it('keeps the agent id and connection time across heartbeats', () => {
+      const registered = applyHeartbeat(null, identity, T0, true);
+      const updated = applyHeartbeat(registered, identity, T1);
+
+      expect(updated.agent_id).toBe(registered.agent_id);
+      expect(updated.connected_at).toBe(T0);
+      expect(updated.last_heartbeat_at).toBe(T1);
+    });
+
+    it('restarts the connection time on a new registration', () => {
+      const registered = applyHeartbeat(null, identity, T0, true);
+      const reregistered = applyHeartbeat(registered, identity, T1, true);
+
+      expect(reregistered.agent_id).toBe(registered.agent_id);
+      expect(reregistered.connected_at).toBe(T1);
+    });
+
+    it('brings an offline agent back online', () => {
+      const offline = applyOffline(applyHeartbeat(null, identity, T0, true), T1);
+      const resumed = applyHeartbeat(offline, identity, T2);
+
+      expect(resumed.status).toBe('online');
+      expect(resumed.connected_at).toBe(T2);
+      expect(resumed.disconnected_at).toBeNull();
+    });
+
+    it('keeps the known version when a frame omits it', () => {
+      const registered = applyHeartbeat(null, identity, T0, true);
+      const updated = applyHeartbeat(registered, { ...identity, agent_version: null }, T1);
+
+      expect(updated.agent_version).toBe('0.0.149');
+    });
+
+    it('keeps the advertised heartbeat interval across heartbeats', () => {
+      const registered = applyHeartbeat(
+        null,
+        { ...identity, heartbeat_interval_secs: 60 },
+        T0,
+        true
+      );
+      const updated = applyHeartbeat(registered, identity, T1);
+
+      expect(updated.heartbeat_interval_secs).toBe(60);
+    });
+  });
+
+  describe('Mark offline', () => {
+    it('records when an online agent disconnected', () => {
+      const offline = applyOffline(applyHeartbeat(null, identity, T0, true), T1);
+
+      expect(offline.status).toBe('offline');
+      expect(offline.disconnected_at).toBe(T1);
+      expect(offline.last_heartbeat_at).toBe(T0);
+    });
+
+    it('leaves an already offline agent untouched', () => {
+      const offline = applyOffline(applyHeartbeat(null, identity, T0, true), T1);
+
+      expect(applyOffline(offline, T2)).toBe(offline);
+    });
+  });
+
+  describe('Heartbeat timeout', () => {
+    const online = applyHeartbeat(null, identity, T0, true);
+    const heartbeatMs = Date.parse(T0);
+
+    it('keeps an agent online within the timeout', () => {
+      expect(isHeartbeatExpired(online, heartbeatMs)).toBe(false);
+      expect(isHeartbeatExpired(online, heartbeatMs + HEARTBEAT_TIMEOUT_MS)).toBe(false);
+    });
+
+    it('expires an agent after the timeout', () => {
+      expect(isHeartbeatExpired(online, heartbeatMs + HEARTBEAT_TIMEOUT_MS + 1)).toBe(true);
+    });
+
+    it('sizes the timeout to the advertised heartbeat interval', () => {
+      const slow = applyHeartbeat(null, { ...identity, heartbeat_interval_secs: 60 }, T0, true);
+
+      expect(heartbeatTimeoutMs(60)).toBe(120000);
+      expect(isHeartbeatExpired(slow, heartbeatMs + HEARTBEAT_TIMEOUT_MS + 1)).toBe(false);
+      expect(isHeartbeatExpired(slow, heartbeatMs + heartbeatTimeoutMs(60))).toBe(false);
+      expect(isHeartbeatExpired(slow, heartbeatMs + heartbeatTimeoutMs(60) + 1)).toBe(true);
+    });
+
+    it('never expires an agent that is already offline', () => {
+      const offline = applyOffline(online, T1);
+      expect(isHeartbeatExpired(offline, heartbeatMs + 10 * HEARTBEAT_TIMEOUT_MS)).toBe(false);
+    });
+  });
+});
@@ -1,9 +1,24 @@
 import { DurableObject } from 'cloudflare:workers';
 import type { Env } from '../types/env';
+import {
+  heartbeatTimeoutMs,
+  recordAgentHeartbeat,
+  recordAgentOffline,
+  type AgentIdentity,
+} from '../services/agent-connections';
+
+/** Header the agents route uses to tell the relay which user it belongs to */
+export const RELAY_USER_HEADER = 'X-Relay-User-Id';
 
 export class LocalAgentRelay extends DurableObject {
   private sessions: Set<WebSocket> = new Set();
   private lastHeartbeat: number = 0;
+  // Heartbeat interval the latest REGISTER frame advertised, if any
+  private heartbeatIntervalSecs: number | null = null;
+  // Latest REGISTER frame: agent version, hostname, executors, os and arch
+  private registration: Record<string, unknown> | null = null;
+  // Persisted identity of each session's agent, known once it registers
+  private agents: Map<WebSocket, AgentIdentity> = new Map();
   public env: Env;
 
   constructor(ctx: DurableObjectState, env: Env) {
@@ -26,7 +41,7 @@ export class LocalAgentRelay extends DurableObject {
       const pair = new WebSocketPair();
       const [client, server] = Object.values(pair);
 
-      this.handleSession(server);
+      this.handleSession(server, request.headers.get(RELAY_USER_HEADER));
 
       return new Response(null, {
         status: 101,
@@ -41,7 +56,7 @@ export class LocalAgentRelay extends DurableObject {
       }
       
       try {
-        const payload = await request.json();
+        const payload = await request.json() as { taskId?: string };
         
         if (this.sessions.size === 0) {
             return new Response('No local agent connected', { status: 503 });
@@ -50,6 +65,7 @@ export class LocalAgentRelay extends DurableObject {
         // Broadcast execute command to all connected local agents (usually just one)
         this.broadcast(JSON.stringify({ 
             type: 'EXECUTE', 
+            taskId: payload.taskId,
             payload 
         }));
         
@@ -65,14 +81,16 @@ export class LocalAgentRelay extends DurableObject {
     // Status endpoint
     if (url.pathname.endsWith('/status')) {
          const isConnected = this.sessions.size > 0;
-         // Check if heartbeat is recent (e.g., within 30 seconds)
-         const isLive = isConnected && (Date.now() - this.lastHeartbeat < 30000);
+         // Check if heartbeat is recent
+         const isLive = isConnected
+             && (Date.now() - this.lastHeartbeat < heartbeatTimeoutMs(this.heartbeatIntervalSecs));
 
          return new Response(JSON.stringify({ 
              connected: isConnected,
              isLive, 
              lastHeartbeat: this.lastHeartbeat,
-             sessions: this.sessions.size 
+             sessions: this.sessions.size,
+             registration: this.registration,
          }), { 
              headers: { 'Content-Type': 'application/json' } 
          });
@@ -81,7 +99,7 @@ export class LocalAgentRelay extends DurableObject {
     return new Response('Not found', { status: 404 });
   }
 
-  handleSession(webSocket: WebSocket) {
+  handleSession(webSocket: WebSocket, userId: string | null) {
     this.sessions.add(webSocket);
     webSocket.accept();
     this.lastHeartbeat = Date.now();
@@ -90,9 +108,37 @@ export class LocalAgentRelay extends DurableObject {
       try {
         const data = JSON.parse(event.data as string);
         
+        if (data.type === 'REGISTER') {
+            this.registration = data;
+            this.heartbeatIntervalSecs =
+                typeof data.heartbeatIntervalSecs === 'number' && data.heartbeatIntervalSecs > 0
+                    ? data.heartbeatIntervalSecs
+                    : null;
+            if (userId) {
+                const identity: AgentIdentity = {
+                    user_id: userId,
+                    hostname: typeof data.hostname === 'string' ? data.hostname : 'unknown',
+                    agent_version: typeof data.agentVersion === 'string' ? data.agentVersion : null,
+                    heartbeat_interval_secs: this.heartbeatIntervalSecs,
+                };
+                this.agents.set(webSocket, identity);
+                await recordAgentHeartbeat(this.env.DB, identity, true);
+            }
+        }
+
+        if (data.type === 'GOODBYE') {
+            // Clean shutdown: report offline now instead of waiting for the heartbeat to lapse
+            this.lastHeartbeat = 0;
+            await this.markOffline(webSocket);
+        }
+
         if (data.type === 'HEARTBEAT') {
             this.lastHeartbeat = Date.now();
             webSocket.send(JSON.stringify({ type: 'HEARTBEAT_ACK' }));
+            const identity = this.agents.get(webSocket);
+            if (identity) {
+                await recordAgentHeartbeat(this.env.DB, identity);
+            }
         }
         
         // Handle log streaming or task updates from local agent
@@ -110,12 +156,27 @@ export class LocalAgentRelay extends DurableObject {
 
     webSocket.addEventListener('close', () => {
       this.sessions.delete(webSocket);
+      this.ctx.waitUntil(this.markOffline(webSocket));
     });
     
     webSocket.addEventListener('error', () => {
         this.sessions.delete(webSocket);
+        this.ctx.waitUntil(this.markOffline(webSocket));
     });
   }
+
+  private async markOffline(webSocket: WebSocket) {
+      const identity = this.agents.get(webSocket);
+      if (!identity) {
+          return;
+      }
+      this.agents.delete(webSocket);
+      try {
+          await recordAgentOffline(this.env.DB, identity.user_id, identity.hostname);
+      } catch (e) {
+          console.error('Failed to record agent disconnect:', e);
+      }
+  }
   
   broadcast(message: string) {
       this.sessions.forEach(session => {
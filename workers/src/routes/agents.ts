@@ -10,6 +10,8 @@ import { ApiError, ErrorCodes } from '../middleware/error-handler';
 import { ClaudeAdapter, ToolExecutor } from '../services/claude-adapter';
 import { AGENT_TOOLS, type CloudAgentType } from '../services/agent-types';
 import { ExecutionStream, createSSEStream } from '../services/execution-stream';
+import { listAgentConnections } from '../services/agent-connections';
+import { RELAY_USER_HEADER } from '../durable_objects/LocalAgentRelay';
 
 export const agentsRoutes = new Hono<{ Bindings: Env; Variables: Variables }>();
 
@@ -305,6 +307,15 @@ agentsRoutes.get('/executions/:id/logs', async (c) => {
   });
 });
 
+/**
+ * GET /agents - List the current user's local agents and their connection state
+ */
+agentsRoutes.get('/', async (c) => {
+  const user = c.get('user')!;
+  const agents = await listAgentConnections(c.env.DB, user.id);
+  return c.json({ agents });
+});
+
 /**
  * GET /agents/types - List available agent types
  */
@@ -386,7 +397,11 @@ agentsRoutes.get('/local/ws', async (c) => {
     const id = c.env.LOCAL_AGENT_RELAY.idFromName(userId);
     const stub = c.env.LOCAL_AGENT_RELAY.get(id);
 
-    return stub.fetch(c.req.raw);
+    // The relay records connection state against this user
+    const headers = new Headers(c.req.raw.headers);
+    headers.set(RELAY_USER_HEADER, userId);
+
+    return stub.fetch(new Request(c.req.raw, { headers }));
 });
 
 /**
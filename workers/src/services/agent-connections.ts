@@ -0,0 +1,186 @@
+/**
+ * Agent Connections Service
+ * Persists local agent liveness reported over the relay websocket
+ */
+
+import type { D1Database } from '@cloudflare/workers-types';
+
+/**
+ * An online agent with no heartbeat for this long is reported offline, when
+ * it didn't advertise its heartbeat interval
+ */
+export const HEARTBEAT_TIMEOUT_MS = 30000;
+
+/** Heartbeats an agent may miss in a row before it is reported offline */
+const MISSED_HEARTBEATS = 2;
+
+/** How long to wait for a heartbeat from an agent sending one every `intervalSecs` */
+export function heartbeatTimeoutMs(intervalSecs: number | null): number {
+  return intervalSecs ? MISSED_HEARTBEATS * intervalSecs * 1000 : HEARTBEAT_TIMEOUT_MS;
+}
+
+export type AgentConnectionStatus = 'online' | 'offline';
+
+export interface AgentConnection {
+  agent_id: string;
+  user_id: string;
+  hostname: string;
+  agent_version: string | null;
+  heartbeat_interval_secs: number | null;
+  status: AgentConnectionStatus;
+  connected_at: string;
+  last_heartbeat_at: string;
+  disconnected_at: string | null;
+}
+
+/** Who a relay session belongs to, taken from its REGISTER frame */
+export interface AgentIdentity {
+  user_id: string;
+  hostname: string;
+  agent_version: string | null;
+  /** Seconds between the agent's heartbeats, as advertised at REGISTER */
+  heartbeat_interval_secs: number | null;
+}
+
+/**
+ * State after a REGISTER or HEARTBEAT frame. A new connection (REGISTER, or
+ * a heartbeat from an agent last seen offline) restarts `connected_at`.
+ */
+export function applyHeartbeat(
+  existing: AgentConnection | null,
+  identity: AgentIdentity,
+  now: string,
+  isNewConnection = false
+): AgentConnection {
+  if (!existing) {
+    return {
+      agent_id: crypto.randomUUID(),
+      ...identity,
+      status: 'online',
+      connected_at: now,
+      last_heartbeat_at: now,
+      disconnected_at: null,
+    };
+  }
+
+  const reconnected = isNewConnection || existing.status === 'offline';
+  return {
+    ...existing,
+    agent_version: identity.agent_version ?? existing.agent_version,
+    heartbeat_interval_secs:
+      identity.heartbeat_interval_secs ?? existing.heartbeat_interval_secs,
+    status: 'online',
+    connected_at: reconnected ? now : existing.connected_at,
+    last_heartbeat_at: now,
+    disconnected_at: null,
+  };
+}
+
+/** State after a disconnect or heartbeat timeout */
+export function applyOffline(existing: AgentConnection, now: string): AgentConnection {
+  if (existing.status === 'offline') {
+    return existing;
+  }
+  return { ...existing, status: 'offline', disconnected_at: now };
+}
+
+export function isHeartbeatExpired(
+  connection: AgentConnection,
+  nowMs: number,
+  timeoutMs = heartbeatTimeoutMs(connection.heartbeat_interval_secs)
+): boolean {
+  return (
+    connection.status === 'online' &&
+    nowMs - Date.parse(connection.last_heartbeat_at) > timeoutMs
+  );
+}
+
+async function findAgentConnection(
+  db: D1Database,
+  userId: string,
+  hostname: string
+): Promise<AgentConnection | null> {
+  return db.prepare(
+    'SELECT * FROM agent_connections WHERE user_id = ? AND hostname = ?'
+  ).bind(userId, hostname).first<AgentConnection>();
+}
+
+async function saveAgentConnection(db: D1Database, connection: AgentConnection): Promise<void> {
+  await db.prepare(`
+    INSERT INTO agent_connections (
+      agent_id, user_id, hostname, agent_version, heartbeat_interval_secs, status,
+      connected_at, last_heartbeat_at, disconnected_at
+    )
+    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+    ON CONFLICT (user_id, hostname) DO UPDATE SET
+      agent_version = excluded.agent_version,
+      heartbeat_interval_secs = excluded.heartbeat_interval_secs,
+      status = excluded.status,
+      connected_at = excluded.connected_at,
+      last_heartbeat_at = excluded.last_heartbeat_at,
+      disconnected_at = excluded.disconnected_at
+  `).bind(
+    connection.agent_id,
+    connection.user_id,
+    connection.hostname,
+    connection.agent_version,
+    connection.heartbeat_interval_secs,
+    connection.status,
+    connection.connected_at,
+    connection.last_heartbeat_at,
+    connection.disconnected_at
+  ).run();
+}
+
+/** Record a REGISTER or HEARTBEAT frame, creating the row on first sight */
+export async function recordAgentHeartbeat(
+  db: D1Database,
+  identity: AgentIdentity,
+  isNewConnection = false,
+  now = new Date().toISOString()
+): Promise<AgentConnection> {
+  const existing = await findAgentConnection(db, identity.user_id, identity.hostname);
+  const connection = applyHeartbeat(existing, identity, now, isNewConnection);
+  await saveAgentConnection(db, connection);
+  return connection;
+}
+
+/** Mark an agent offline after its socket closed */
+export async function recordAgentOffline(
+  db: D1Database,
+  userId: string,
+  hostname: string,
+  now = new Date().toISOString()
+): Promise<void> {
+  const existing = await findAgentConnection(db, userId, hostname);
+  if (existing && existing.status === 'online') {
+    await saveAgentConnection(db, applyOffline(existing, now));
+  }
+}
+
+/**
+ * A user's agents, most recently seen first. Agents whose heartbeat has
+ * lapsed are marked offline on the way out, which also covers a relay that
+ * was evicted before it could record the disconnect.
+ */
+export async function listAgentConnections(
+  db: D1Database,
+  userId: string,
+  nowMs = Date.now()
+): Promise<AgentConnection[]> {
+  const { results } = await db.prepare(
+    'SELECT * FROM agent_connections WHERE user_id = ? ORDER BY last_heartbeat_at DESC'
+  ).bind(userId).all<AgentConnection>();
+
+  const now = new Date(nowMs).toISOString();
+  return Promise.all(
+    results.map(async (connection) => {
+      if (!isHeartbeatExpired(connection, nowMs)) {
+        return connection;
+      }
+      const offline = applyOffline(connection, now);
+      await saveAgentConnection(db, offline);
+      return offline;
+    })
+  );
+}